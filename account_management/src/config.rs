@@ -1,16 +1,27 @@
+use lightspeed_core::model::language::Language;
+use secrecy::SecretString;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct AMConfig {
     /// Determines the activation token validity minutes
     pub activation_token_validity_minutes: u32,
+    /// Key of the HMAC-SHA256 with which the tokens are stored. Changing it
+    /// invalidates every pending token. It is required: the module cannot
+    /// be built if it is shorter than 32 bytes.
+    pub token_hash_secret: SecretString,
 
     /// Determines the maximum session validity minutes.
     /// Once the session expires it is not possible to refresh it
     /// and the user needs to reenter his credentials.
     pub auth_session_max_validity_minutes: u32,
 
+    /// Minimum seconds between two updates of the last activity of a
+    /// session, to not write it at every request
+    pub session_activity_update_seconds: u32,
+
     /// Argon2id memory cost, in KiB. Must be >= 8 * `argon2_parallelism`.
     pub argon2_memory_kib: u32,
     /// Argon2id time cost (number of iterations). Must be >= 1.
@@ -24,23 +35,272 @@ pub struct AMConfig {
     /// and the user is required to set a new password. `None` disables the
     /// check.
     pub password_expiration_seconds: Option<u32>,
+
+    /// Number of most recent passwords, the current one included, that
+    /// cannot be reused when the password is changed or reset. 0 disables
+    /// the check.
+    pub password_history_size: u32,
+
+    /// Path of a local file of breached password hashes in the "Have I Been
+    /// Pwned" format ordered by hash. `None` disables the check.
+    pub breached_passwords_file: Option<String>,
+    /// Minimum number of breaches in which a password must appear to be
+    /// rejected
+    pub breached_password_min_count: u64,
+
+    /// Consecutive failed logins after which the account is temporarily
+    /// locked. `None` disables the lockout.
+    pub max_failed_login_attempts: Option<u32>,
+    /// Seconds an account stays locked once `max_failed_login_attempts` is
+    /// reached. Every further failed login doubles them.
+    pub login_lockout_seconds: u32,
+    /// Upper bound of the seconds an account stays locked
+    pub max_login_lockout_seconds: u32,
+
+    /// Issuer shown by the authenticator apps for the TOTP accounts
+    pub totp_issuer: String,
+
+    /// Validity seconds of the token returned by a login to an account with
+    /// MFA enabled, within which the second factor must be provided
+    pub mfa_pending_token_validity_seconds: u32,
+
+    /// Seconds an anonymized account is kept before
+    /// `purge_anonymized_accounts` deletes it
+    pub anonymized_account_grace_period_seconds: u32,
+
+    /// Seconds after its creation a never activated account is deleted by
+    /// `purge_pending_accounts`
+    pub pending_account_max_age_seconds: u32,
+
+    /// Validity seconds of the magic link sent to log in without password
+    pub magic_login_token_validity_seconds: u32,
+
+    /// OpenID Connect providers the users can log in with
+    pub oidc_providers: Vec<OidcProviderConfig>,
+
+    /// Normalization of the usernames and emails for their lookups and
+    /// uniqueness
+    pub identifier_normalization: IdentifierNormalizationConfig,
+
+    /// Maximum number of profile attributes of an account
+    pub max_profile_attributes: usize,
+    /// Maximum length, in characters, of the value of a profile attribute
+    pub max_profile_attribute_length: usize,
+
+    /// The emails sent to the users by `LsAMEmailService`
+    pub emails: AMEmailConfig,
+
+    /// The cron expressions of the maintenance tasks registered by
+    /// `register_scheduled_tasks`
+    pub schedules: AMScheduleConfig,
 }
 
 impl Default for AMConfig {
     fn default() -> Self {
         Self {
             activation_token_validity_minutes: 120,
+            token_hash_secret: SecretString::from(""),
             auth_session_max_validity_minutes: 240,
+            session_activity_update_seconds: 60,
             // OWASP-recommended Argon2id settings (m=19 MiB, t=2, p=1).
             argon2_memory_kib: 19_456,
             argon2_iterations: 2,
             argon2_parallelism: 1,
             default_roles_on_account_creation: vec![],
             password_expiration_seconds: None,
+            password_history_size: 5,
+            breached_passwords_file: None,
+            breached_password_min_count: 1,
+            max_failed_login_attempts: Some(5),
+            login_lockout_seconds: 60,
+            max_login_lockout_seconds: 3600,
+            totp_issuer: "LightSpeed".to_owned(),
+            mfa_pending_token_validity_seconds: 300,
+            // 30 days
+            anonymized_account_grace_period_seconds: 2_592_000,
+            // 7 days
+            pending_account_max_age_seconds: 604_800,
+            magic_login_token_validity_seconds: 900,
+            oidc_providers: vec![],
+            identifier_normalization: IdentifierNormalizationConfig::default(),
+            max_profile_attributes: 50,
+            max_profile_attribute_length: 1024,
+            emails: AMEmailConfig::default(),
+            schedules: AMScheduleConfig::default(),
+        }
+    }
+}
+
+/// The steps applied, in this order, to a username or email to get the form
+/// with which it is looked up and kept unique. The form entered by the user
+/// is kept for display.
+///
+/// Changing them requires `LsAMAccountService::normalize_identifiers` to
+/// rewrite the normalized forms of the existing accounts.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IdentifierNormalizationConfig {
+    /// Removes the leading and trailing whitespaces
+    pub trim: bool,
+    /// Unicode compatibility normalization (NFKC), so that the visually
+    /// identical names are the same
+    pub unicode_nfkc: bool,
+    /// Unicode case folding, so that "Alice" and "alice" are the same
+    pub case_fold: bool,
+}
+
+impl Default for IdentifierNormalizationConfig {
+    fn default() -> Self {
+        Self { trim: true, unicode_nfkc: true, case_fold: true }
+    }
+}
+
+/// Cron expressions, seconds included, of the maintenance tasks. `None`
+/// leaves the task out of the scheduler.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AMScheduleConfig {
+    pub purge_anonymized_accounts: Option<String>,
+    pub purge_pending_accounts: Option<String>,
+    pub purge_expired_tokens: Option<String>,
+    pub lift_expired_suspensions: Option<String>,
+}
+
+impl Default for AMScheduleConfig {
+    fn default() -> Self {
+        Self {
+            purge_anonymized_accounts: Some("0 0 3 * * *".to_owned()),
+            purge_pending_accounts: Some("0 30 3 * * *".to_owned()),
+            purge_expired_tokens: Some("0 0 * * * *".to_owned()),
+            lift_expired_suspensions: Some("0 */5 * * * *".to_owned()),
+        }
+    }
+}
+
+/// An OpenID Connect provider, registered with this application as client
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OidcProviderConfig {
+    /// Name used to select the provider
+    pub name: String,
+    /// The discovery document is loaded from
+    /// `{issuer_url}/.well-known/openid-configuration`
+    pub issuer_url: String,
+    pub client_id: String,
+    /// Sent to the token endpoint, if set; public clients rely on PKCE only
+    pub client_secret: Option<SecretString>,
+    /// URL of this application to which the provider redirects the user
+    pub redirect_uri: String,
+    pub scopes: Vec<String>,
+    /// Creates an account, with `default_roles_on_account_creation`, at the
+    /// first login of an identity not linked to any account
+    pub jit_provisioning: bool,
+}
+
+impl Default for OidcProviderConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            issuer_url: String::new(),
+            client_id: String::new(),
+            client_secret: None,
+            redirect_uri: String::new(),
+            scopes: vec!["openid".to_owned(), "email".to_owned(), "profile".to_owned()],
+            jit_provisioning: false,
+        }
+    }
+}
+
+/// The emails sent on the account lifecycle events.
+///
+/// In the links and in the templates, `{username}`, `{email}`, `{token}` and
+/// `{link}` are replaced with the values of the account and of the event.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AMEmailConfig {
+    /// Sender of the emails
+    pub from: String,
+    /// Link of the page of the application that activates an account
+    pub activation_link: String,
+    /// Link of the page of the application that resets a password
+    pub reset_password_link: String,
+    /// Language of the emails to the users without a preferred language
+    pub default_language: Language,
+    /// Templates by language. The emails to the users whose language has no
+    /// templates use those of `default_language`, if any, or the built-in
+    /// English ones.
+    pub templates: HashMap<Language, EmailTemplates>,
+}
+
+impl Default for AMEmailConfig {
+    fn default() -> Self {
+        Self {
+            from: "no-reply@localhost".to_owned(),
+            activation_link: "http://localhost/activate?token={token}".to_owned(),
+            reset_password_link: "http://localhost/reset-password?token={token}".to_owned(),
+            default_language: Language::En,
+            templates: HashMap::new(),
         }
     }
 }
 
+/// The templates of the emails in one language
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EmailTemplates {
+    pub activation: EmailTemplate,
+    /// Sent with a new activation token, when the first one was lost or
+    /// expired
+    pub resend_activation: EmailTemplate,
+    pub password_reset: EmailTemplate,
+    /// Notifies the user that the password was changed or reset
+    pub password_changed: EmailTemplate,
+    pub account_disabled: EmailTemplate,
+}
+
+impl Default for EmailTemplates {
+    fn default() -> Self {
+        Self {
+            activation: EmailTemplate::new(
+                "Activate your account",
+                "Hello {username},\n\nplease activate your account by opening this link:\n{link}",
+            ),
+            resend_activation: EmailTemplate::new(
+                "Your new activation link",
+                "Hello {username},\n\nhere is your new link to activate your account:\n{link}",
+            ),
+            password_reset: EmailTemplate::new(
+                "Reset your password",
+                "Hello {username},\n\nyou can set a new password by opening this link:\n{link}\n\nIf you did not ask to reset your password, please ignore this email.",
+            ),
+            password_changed: EmailTemplate::new(
+                "Your password was changed",
+                "Hello {username},\n\nthe password of your account was changed. If it was not you, please contact us immediately.",
+            ),
+            account_disabled: EmailTemplate::new(
+                "Your account was disabled",
+                "Hello {username},\n\nyour account was disabled. Please contact us for more information.",
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub text: String,
+    /// HTML body sent together with `text`, if set. The replaced values are
+    /// HTML-escaped.
+    pub html: Option<String>,
+}
+
+impl EmailTemplate {
+    pub fn new(subject: &str, text: &str) -> Self {
+        Self { subject: subject.to_owned(), text: text.to_owned(), html: None }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
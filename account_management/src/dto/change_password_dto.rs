@@ -3,6 +3,7 @@ use lightspeed_validator::Validable;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Validable)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[validate(fields_match(new_password, new_password_confirm, attach_to_fields = true))]
 pub struct ChangePasswordDto {
     pub user_id: i64,
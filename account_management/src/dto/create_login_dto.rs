@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 #[derive(Clone, Serialize, Deserialize, Validable)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[validate(fields_match(password, password_confirm, attach_to_fields = true))]
 pub struct CreateLoginDto {
     pub username: Option<String>,
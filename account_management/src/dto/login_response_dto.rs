@@ -3,6 +3,7 @@ use lightspeed_core::service::auth::Auth;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct LoginResponseDto {
     pub auth: Auth,
     pub token: TokenDto,
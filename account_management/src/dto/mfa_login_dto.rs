@@ -0,0 +1,17 @@
+use crate::dto::token_dto::TokenDto;
+use serde::{Deserialize, Serialize};
+
+/// Returned by a login to an account with MFA enabled
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MfaRequiredDto {
+    pub mfa_token: TokenDto,
+}
+
+/// Completes a login with a TOTP or recovery code
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MfaLoginDto {
+    pub mfa_token: String,
+    pub code: String,
+}
@@ -2,6 +2,7 @@ use lightspeed_validator::Validable;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Validable)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 #[validate(fields_match(password, password_confirm, attach_to_fields = true))]
 pub struct ResetPasswordDto {
     pub token: String,
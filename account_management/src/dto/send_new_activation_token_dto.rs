@@ -2,12 +2,14 @@ use lightspeed_core::model::language::Language;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SendNewActivationTokenDto {
     pub token: String,
     pub language: Language,
 }
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SendNewActivationTokenByUsernameAndEmailDto {
     pub username: String,
     pub email: String,
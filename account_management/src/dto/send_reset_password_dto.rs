@@ -2,6 +2,7 @@ use lightspeed_core::model::language::Language;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct SendResetPasswordDto {
     pub email: String,
     pub language: Language,
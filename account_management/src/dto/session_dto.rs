@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+/// A login session of the user
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SessionDto {
+    pub session_id: String,
+    pub created_date_epoch_seconds: i64,
+    pub last_activity_epoch_seconds: i64,
+    pub expire_at_epoch_seconds: i64,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    /// Whether it is the session of the request
+    pub current: bool,
+}
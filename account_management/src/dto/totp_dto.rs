@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+
+/// A TOTP secret to be registered in an authenticator app, either by
+/// scanning the `otpauth_uri` as QR code or by typing the `secret`
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TotpEnrollmentDto {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ConfirmTotpDto {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct DisableTotpDto {
+    pub password: String,
+}
+
+/// One-time codes that replace a TOTP code when the authenticator app is lost
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RecoveryCodesDto {
+    pub recovery_codes: Vec<String>,
+}
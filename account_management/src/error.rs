@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,12 +9,21 @@ pub enum LsAccountManagementError {
     #[error("BadRequest: {message} - {code}")]
     BadRequest { message: String, code: &'static str },
 
+    /// The input is not valid; `field_errors` maps each invalid field to
+    /// the descriptions of its errors.
+    #[error("ValidationError: {field_errors:?}")]
+    ValidationError { field_errors: BTreeMap<String, Vec<String>> },
+
     #[error("TokenExpired")]
     TokenExpired,
 
     #[error("TokenNotValid")]
     TokenNotValid,
 
+    /// A token could not be hashed or sealed with the `token_hash_secret`
+    #[error("TokenSealingError: {message}")]
+    TokenSealingError { message: String },
+
     #[error("UsernameAlreadyUsed")]
     UsernameAlreadyUsed,
 
@@ -32,18 +42,106 @@ pub enum LsAccountManagementError {
         source: c3p0::sqlx::Error,
     },
 
+    #[error("LsError: {source:?}")]
+    LsError {
+        #[from]
+        source: lightspeed_core::error::LsError,
+    },
+
+    #[error("EventError: {source:?}")]
+    EventError {
+        #[from]
+        source: lightspeed_event::error::LsEventError,
+    },
+
+    #[cfg(feature = "email")]
+    #[error("EmailError: {source:?}")]
+    EmailError {
+        #[from]
+        source: lightspeed_email::error::LsEmailError,
+    },
+
     #[error("InactiveUser: {0}")]
     InactiveUser(String),
 
     #[error("NotDisabledUser: {0}")]
     NotDisabledUser(String),
 
+    /// The personal data of the account with the given id were scrubbed;
+    /// the account cannot be used anymore
+    #[error("AnonymizedUser: {0}")]
+    AnonymizedUser(i64),
+
+    /// The account can only log in with a magic link
+    #[error("PasswordLoginDisabled for user {0}")]
+    PasswordLoginDisabled(String),
+
     #[error("ExpiredPassword for user {0}")]
     ExpiredPassword(String),
 
+    /// The new password matches one of the most recent passwords of the user
+    #[error("PasswordReused")]
+    PasswordReused,
+
+    /// The password appears in known data breaches at least
+    /// `breached_password_min_count` times
+    #[error("BreachedPassword")]
+    BreachedPassword,
+
+    #[error("BreachedPasswordCheckError: {message}")]
+    BreachedPasswordCheckError { message: String },
+
     #[error("WrongCredentials")]
     WrongCredentials,
 
+    /// Too many failed logins; the account is locked until the given epoch
+    /// seconds. Returned to authenticated callers, e.g. when disabling TOTP.
+    /// Every login path (password, magic link, external and MFA) answers
+    /// `WrongCredentials` to a locked account instead, as to an unknown
+    /// username, so that the lockout does not reveal registered accounts.
+    #[error("AccountLocked until {until}")]
+    AccountLocked { until: i64 },
+
+    /// The account is suspended until the given epoch seconds
+    #[error("AccountSuspended until {until}: {reason}")]
+    AccountSuspended { until: i64, reason: String },
+
+    /// The account was locked by an administrator, who must unlock it
+    #[error("AccountLockedByAdmin: {reason}")]
+    AccountLockedByAdmin { reason: String },
+
+    #[error("NotSuspendedUser: {0}")]
+    NotSuspendedUser(String),
+
     #[error("UserNotPendingActivation")]
     UserNotPendingActivation,
+
+    #[error("UnknownOidcProvider: {0}")]
+    UnknownOidcProvider(String),
+
+    /// The OpenID Connect provider could not be reached or answered with an
+    /// error
+    #[error("OidcProviderError: {message}")]
+    OidcProviderError { message: String },
+
+    /// The response of the OpenID Connect provider is not valid, e.g. the ID
+    /// token signature or the state do not match
+    #[error("OidcAuthenticationFailed: {message}")]
+    OidcAuthenticationFailed { message: String },
+
+    /// The external identity is not linked to any account and cannot be
+    /// provisioned
+    #[error("ExternalIdentityNotLinked")]
+    ExternalIdentityNotLinked,
+
+    #[error("ExternalIdentityAlreadyLinked")]
+    ExternalIdentityAlreadyLinked,
+
+    /// The session of the token was revoked, is expired or was never
+    /// recorded
+    #[error("SessionNotValid")]
+    SessionNotValid,
+
+    #[error("SessionNotFound")]
+    SessionNotFound,
 }
@@ -6,8 +6,18 @@
 use crate::config::AMConfig;
 use crate::repository::AMRepositoryManager;
 use crate::service::account::LsAMAccountService;
+use crate::service::acl::LsAMAclService;
+use crate::service::audit::LsAMAuditService;
+use crate::service::breached_password::{
+    BreachedPasswordChecker, HibpFileBreachedPasswordChecker, NoBreachedPasswordChecker,
+};
+use crate::service::lifecycle::AccountLifecycleListener;
+use crate::service::mfa::LsAMMfaService;
 use crate::service::password_codec::LsPasswordCodecService;
+use crate::service::session::LsAMSessionService;
 use lightspeed_core::error::LsError;
+use lightspeed_core::utils::current_epoch_seconds;
+use lightspeed_event::service::publisher::LsEventPublisher;
 use log::*;
 use std::sync::Arc;
 
@@ -15,8 +25,11 @@ pub mod config;
 pub mod dto;
 pub mod error;
 pub mod model;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod repository;
 pub mod service;
+pub mod web;
 
 #[derive(Clone)]
 pub struct LsAMModule<RepoManager: AMRepositoryManager> {
@@ -27,6 +40,14 @@ pub struct LsAMModule<RepoManager: AMRepositoryManager> {
     pub password_codec: Arc<service::password_codec::LsPasswordCodecService>,
     pub auth_account_service: Arc<service::account::LsAMAccountService<RepoManager>>,
     pub token_service: Arc<service::token::LsTokenService<RepoManager>>,
+    pub acl_service: Arc<service::acl::LsAMAclService<RepoManager>>,
+    pub audit_service: Arc<service::audit::LsAMAuditService<RepoManager>>,
+    pub mfa_service: Arc<service::mfa::LsAMMfaService<RepoManager>>,
+    #[cfg(feature = "oidc")]
+    pub oidc_service: Arc<service::oidc::LsAMOidcService<RepoManager>>,
+    pub session_service: Arc<service::session::LsAMSessionService<RepoManager>>,
+    pub breached_password_checker: Arc<dyn BreachedPasswordChecker>,
+    pub event_publisher: Arc<LsEventPublisher<RepoManager::OutboxRepo>>,
 }
 
 impl<RepoManager: AMRepositoryManager> LsAMModule<RepoManager> {
@@ -35,6 +56,8 @@ impl<RepoManager: AMRepositoryManager> LsAMModule<RepoManager> {
         println!("Creating LsAMModule");
         info!("Creating LsAMModule");
 
+        service::token::LsTokenService::<RepoManager>::check_token_hash_secret(&auth_config)?;
+
         let password_codec = Arc::new(LsPasswordCodecService::new(
             auth_config.argon2_memory_kib,
             auth_config.argon2_iterations,
@@ -44,15 +67,79 @@ impl<RepoManager: AMRepositoryManager> LsAMModule<RepoManager> {
         let token_service =
             Arc::new(service::token::LsTokenService::new(auth_config.clone(), repo_manager.token_repo()));
 
-        let auth_account_service = Arc::new(LsAMAccountService::new(
+        let audit_service = Arc::new(LsAMAuditService::new(repo_manager.c3p0().clone(), repo_manager.audit_repo()));
+
+        let event_publisher = Arc::new(LsEventPublisher::new(repo_manager.outbox_repo()));
+
+        let breached_password_checker: Arc<dyn BreachedPasswordChecker> = match &auth_config.breached_passwords_file {
+            Some(path) => Arc::new(HibpFileBreachedPasswordChecker::new(path)?),
+            None => Arc::new(NoBreachedPasswordChecker),
+        };
+
+        let auth_account_service = Arc::new(
+            LsAMAccountService::new(
+                &repo_manager,
+                auth_config.clone(),
+                token_service.clone(),
+                password_codec.clone(),
+                audit_service.clone(),
+                event_publisher.clone(),
+            )
+            .with_breached_password_checker(breached_password_checker.clone()),
+        );
+
+        let mfa_service = Arc::new(LsAMMfaService::new(
             repo_manager.c3p0().clone(),
             auth_config.clone(),
             token_service.clone(),
             password_codec.clone(),
             repo_manager.account_repo(),
+            audit_service.clone(),
+            Arc::new(current_epoch_seconds),
+        ));
+
+        let acl_service = Arc::new(LsAMAclService::new(repo_manager.c3p0().clone(), repo_manager.acl_repo()));
+
+        let session_service = Arc::new(LsAMSessionService::new(
+            repo_manager.c3p0().clone(),
+            auth_config.clone(),
+            repo_manager.session_repo(),
+            audit_service.clone(),
+        ));
+
+        #[cfg(feature = "oidc")]
+        let oidc_service = Arc::new(service::oidc::LsAMOidcService::new(
+            repo_manager.c3p0().clone(),
+            auth_config.clone(),
+            auth_account_service.clone(),
+            repo_manager.external_identity_repo(),
+            audit_service.clone(),
+            event_publisher.clone(),
         ));
 
-        Ok(LsAMModule { auth_config, repo_manager, password_codec, auth_account_service, token_service })
+        Ok(LsAMModule {
+            auth_config,
+            repo_manager,
+            password_codec,
+            auth_account_service,
+            token_service,
+            acl_service,
+            audit_service,
+            mfa_service,
+            #[cfg(feature = "oidc")]
+            oidc_service,
+            session_service,
+            breached_password_checker,
+            event_publisher,
+        })
+    }
+
+    /// Adds a listener notified of the changes of the accounts, in the
+    /// transactions of the changes: an error of a listener rolls the change
+    /// back.
+    pub fn with_lifecycle_listener<L: AccountLifecycleListener<RepoManager::DB>>(self, listener: L) -> Self {
+        self.auth_account_service.add_lifecycle_listener(listener);
+        self
     }
 }
 
@@ -0,0 +1,54 @@
+use c3p0::{Codec, DataType, Record};
+use lightspeed_core::service::auth::{AccessEntry, AccessLevel, Grantee};
+use serde::{Deserialize, Serialize};
+
+pub type AclEntryModel = Record<AclEntryData>;
+
+/// Grants `grantee` the `access_level` on the resource identified by
+/// `resource_type` and `resource_id`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AclEntryData {
+    pub resource_type: String,
+    pub resource_id: String,
+    pub grantee: Grantee,
+    pub access_level: AccessLevel,
+    pub created_date_epoch_seconds: i64,
+}
+
+impl AclEntryData {
+    pub fn to_access_entry(&self) -> AccessEntry {
+        AccessEntry { grantee: self.grantee.clone(), access_level: self.access_level }
+    }
+}
+
+impl DataType for AclEntryData {
+    const TABLE_NAME: &'static str = "LS_AM_ACL";
+    type CODEC = AclEntryDataCodec;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "_codec_tag")]
+pub enum AclEntryDataCodec {
+    V1(AclEntryData),
+}
+
+impl Codec<AclEntryData> for AclEntryDataCodec {
+    fn encode(data: AclEntryData) -> Self {
+        AclEntryDataCodec::V1(data)
+    }
+
+    fn decode(data: Self) -> AclEntryData {
+        match data {
+            AclEntryDataCodec::V1(data) => data,
+        }
+    }
+}
+
+/// Returns the values stored in the `grantee.type` and `grantee.id` JSON
+/// fields, as used by the repository queries.
+pub(crate) fn grantee_type_and_id(grantee: &Grantee) -> (&'static str, String) {
+    match grantee {
+        Grantee::User(id) => ("User", id.to_string()),
+        Grantee::Group(name) => ("Group", name.clone()),
+    }
+}
@@ -0,0 +1,85 @@
+use c3p0::{Codec, DataType, Record};
+use lightspeed_core::service::auth::Auth;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub type AuditEventModel = Record<AuditEventData>;
+
+/// A change applied to an entity. `before` and `after` only contain the
+/// fields that changed; they are `None` when the entity did not exist before
+/// (creation) or after (deletion) the change.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AuditEventData {
+    /// `None` for changes not triggered by an authenticated user
+    pub actor: Option<AuditActor>,
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_date_epoch_seconds: i64,
+}
+
+impl DataType for AuditEventData {
+    const TABLE_NAME: &'static str = "LS_AM_AUDIT_EVENT";
+    type CODEC = AuditEventDataCodec;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditActor {
+    pub id: i64,
+    pub username: String,
+}
+
+impl From<&Auth> for AuditActor {
+    fn from(auth: &Auth) -> Self {
+        AuditActor { id: auth.id, username: auth.username.clone() }
+    }
+}
+
+/// Filters of an audit events query. Unset filters match every event.
+/// Events are returned in insertion order, starting from `start_id`.
+#[derive(Debug, Clone)]
+pub struct AuditEventQuery {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub actor_id: Option<i64>,
+    /// Inclusive lower bound of `created_date_epoch_seconds`
+    pub from_epoch_seconds: Option<i64>,
+    /// Exclusive upper bound of `created_date_epoch_seconds`
+    pub to_epoch_seconds: Option<i64>,
+    pub start_id: i64,
+    pub limit: u32,
+}
+
+impl Default for AuditEventQuery {
+    fn default() -> Self {
+        Self {
+            entity_type: None,
+            entity_id: None,
+            actor_id: None,
+            from_epoch_seconds: None,
+            to_epoch_seconds: None,
+            start_id: 0,
+            limit: 100,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "_codec_tag")]
+pub enum AuditEventDataCodec {
+    V1(AuditEventData),
+}
+
+impl Codec<AuditEventData> for AuditEventDataCodec {
+    fn encode(data: AuditEventData) -> Self {
+        AuditEventDataCodec::V1(data)
+    }
+
+    fn decode(data: Self) -> AuditEventData {
+        match data {
+            AuditEventDataCodec::V1(data) => data,
+        }
+    }
+}
@@ -1,19 +1,67 @@
+use crate::model::token::TokenType;
 use c3p0::{Codec, DataType, Record};
+use lightspeed_core::model::language::Language;
 use serde::{Deserialize, Serialize};
-use strum::{AsRefStr, Display};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use strum::{AsRefStr, Display, EnumDiscriminants, EnumString};
 
 pub type AuthAccountModel = Record<AccountData>;
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AccountData {
+    /// The username as entered by the user
     pub username: String,
+    /// The email as entered by the user
     pub email: String,
+    /// Form of `username` with which it is looked up and kept unique; see
+    /// `IdentifierNormalizationConfig`
+    #[serde(default)]
+    pub normalized_username: String,
+    /// Form of `email` with which it is looked up and kept unique
+    #[serde(default)]
+    pub normalized_email: String,
     pub password: String,
     pub roles: Vec<String>,
     pub created_date_epoch_seconds: i64,
     /// Epoch seconds at which `password` was last set
     pub password_updated_date_epoch_seconds: i64,
+    /// Hashes of the previous passwords, the most recent first
+    #[serde(default)]
+    pub password_history: Vec<String>,
     pub status: AccountStatus,
+    /// Multi-factor authentication settings of the account
+    #[serde(default)]
+    pub mfa: MfaState,
+    /// Consecutive failed logins since the last successful one
+    #[serde(default)]
+    pub failed_login_attempts: u32,
+    /// Epoch seconds of the last failed login
+    #[serde(default)]
+    pub last_failed_login_epoch_seconds: Option<i64>,
+    /// New email requested by the user; it replaces `email` once confirmed
+    /// with the token sent to it
+    #[serde(default)]
+    pub pending_email: Option<String>,
+    /// Epoch seconds at which the personal data of the account were scrubbed.
+    /// The account is deleted once the grace period after it has passed.
+    #[serde(default)]
+    pub anonymized_date_epoch_seconds: Option<i64>,
+    /// When set, the user can only log in with a magic link
+    #[serde(default)]
+    pub password_login_disabled: bool,
+    /// Preferred language of the user; unknown for the accounts created
+    /// before it was collected
+    pub language: Option<Language>,
+    /// Free-form attributes of the user, e.g. the extra fields of the signup
+    /// form
+    pub profile: BTreeMap<String, String>,
+}
+
+impl AccountData {
+    pub fn is_anonymized(&self) -> bool {
+        self.anonymized_date_epoch_seconds.is_some()
+    }
 }
 
 impl DataType for AccountData {
@@ -21,27 +69,404 @@ impl DataType for AccountData {
     type CODEC = AccountDataToken;
 }
 
-#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, AsRefStr, Display)]
+/// Status of an account, stored as an object with the variant name in
+/// `kind`; the accounts written before `AccountDataToken::V3` store only
+/// the name.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize, AsRefStr, Display, EnumDiscriminants)]
+#[serde(tag = "kind")]
+#[strum_discriminants(name(AccountStatusKind), derive(Hash, AsRefStr, Display, EnumString))]
 pub enum AccountStatus {
     Active,
     PendingActivation,
     Disabled,
+    /// Temporarily suspended; the account is active again once
+    /// `until_epoch_seconds` has passed
+    Suspended {
+        until_epoch_seconds: i64,
+        reason: String,
+    },
+    /// Locked by an administrator, until it is unlocked
+    Locked {
+        reason: String,
+    },
+}
+
+impl AccountStatus {
+    pub fn kind(&self) -> AccountStatusKind {
+        self.into()
+    }
+}
+
+/// State of the TOTP (RFC 6238) second factor of an account.
+/// The secret is kept in clear as it is needed to compute the codes.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub enum MfaState {
+    #[default]
+    Disabled,
+    /// Enrollment started; `totp_secret` is enabled once a first code is confirmed
+    TotpPending { totp_secret: String },
+    TotpEnabled {
+        totp_secret: String,
+        /// SHA-256 hashes of the recovery codes not used yet
+        recovery_code_hashes: Vec<String>,
+        /// Time step of the last accepted code; older or equal steps are rejected
+        last_used_step: u64,
+    },
+}
+
+impl MfaState {
+    pub fn is_enabled(&self) -> bool {
+        matches!(self, MfaState::TotpEnabled { .. })
+    }
+}
+
+/// Filters of an accounts search. Unset filters match every account.
+#[derive(Debug, Clone, Default)]
+pub struct AccountFilter {
+    /// Case-insensitive prefix of the username or of the email
+    pub username_or_email_prefix: Option<String>,
+    pub role: Option<String>,
+    pub status: Option<AccountStatusKind>,
+    /// Inclusive lower bound of `created_date_epoch_seconds`
+    pub created_from_epoch_seconds: Option<i64>,
+    /// Exclusive upper bound of `created_date_epoch_seconds`
+    pub created_to_epoch_seconds: Option<i64>,
+}
+
+impl AccountFilter {
+    /// `username_or_email_prefix` as a lowercase `LIKE` pattern that uses `!`
+    /// as escape character
+    pub fn prefix_like_pattern(&self) -> Option<String> {
+        self.username_or_email_prefix.as_ref().map(|prefix| {
+            let escaped = prefix.to_lowercase().replace('!', "!!").replace('%', "!%").replace('_', "!_");
+            format!("{escaped}%")
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountSortField {
+    Id,
+    Username,
+    Email,
+    Status,
+    CreatedDate,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+/// A page of the accounts matching `filter`. The accounts are sorted by the
+/// `sort` keys, in order of priority, and then by id.
+#[derive(Debug, Clone)]
+pub struct AccountQuery {
+    pub filter: AccountFilter,
+    pub sort: Vec<(AccountSortField, SortOrder)>,
+    pub offset: u32,
+    pub limit: u32,
+}
+
+impl Default for AccountQuery {
+    fn default() -> Self {
+        Self { filter: AccountFilter::default(), sort: vec![], offset: 0, limit: 100 }
+    }
+}
+
+/// Result of an `AccountQuery`
+#[derive(Clone)]
+pub struct AccountPage {
+    pub accounts: Vec<AuthAccountModel>,
+    /// Number of accounts matching the filter, in all the pages
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountStats {
+    pub total: u64,
+    /// Number of accounts in each status; statuses without accounts are
+    /// not listed
+    pub count_by_status: Vec<(AccountStatusKind, u64)>,
+}
+
+/// Machine-readable copy of the personal data held for an account. The
+/// password hash and the token strings are never exported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountDataExport {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub pending_email: Option<String>,
+    pub roles: Vec<String>,
+    pub status: AccountStatus,
+    pub created_date_epoch_seconds: i64,
+    pub password_updated_date_epoch_seconds: i64,
+    pub mfa_enabled: bool,
+    pub language: Option<Language>,
+    pub profile: BTreeMap<String, String>,
+    pub tokens: Vec<TokenExport>,
+    /// Data contributed by the other modules, by section name
+    pub sections: BTreeMap<String, Value>,
+}
+
+/// The profile of an account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountProfile {
+    pub language: Option<Language>,
+    pub attributes: BTreeMap<String, String>,
+}
+
+/// Changes to apply to the profile of an account. Each attribute is set to
+/// its value, or removed when the value is `None`; the attributes not listed
+/// are left unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountProfilePatch {
+    /// The new preferred language, if it changes
+    pub language: Option<Language>,
+    pub attributes: BTreeMap<String, Option<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenExport {
+    pub token_type: TokenType,
+    pub expire_at_epoch_seconds: i64,
+}
+
+/// `AccountStatus` as stored, by name, before the suspended and the locked
+/// statuses were added
+#[derive(Clone, Serialize, Deserialize)]
+pub enum AccountStatusV1 {
+    Active,
+    PendingActivation,
+    Disabled,
+}
+
+impl From<AccountStatusV1> for AccountStatus {
+    fn from(status: AccountStatusV1) -> Self {
+        match status {
+            AccountStatusV1::Active => AccountStatus::Active,
+            AccountStatusV1::PendingActivation => AccountStatus::PendingActivation,
+            AccountStatusV1::Disabled => AccountStatus::Disabled,
+        }
+    }
+}
+
+/// `AccountData` as stored before the language and the profile attributes
+/// were added. Accounts are always written with the latest version.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AccountDataV1 {
+    pub username: String,
+    pub email: String,
+    #[serde(default)]
+    pub normalized_username: String,
+    #[serde(default)]
+    pub normalized_email: String,
+    pub password: String,
+    pub roles: Vec<String>,
+    pub created_date_epoch_seconds: i64,
+    pub password_updated_date_epoch_seconds: i64,
+    #[serde(default)]
+    pub password_history: Vec<String>,
+    pub status: AccountStatusV1,
+    #[serde(default)]
+    pub mfa: MfaState,
+    #[serde(default)]
+    pub failed_login_attempts: u32,
+    #[serde(default)]
+    pub last_failed_login_epoch_seconds: Option<i64>,
+    #[serde(default)]
+    pub pending_email: Option<String>,
+    #[serde(default)]
+    pub anonymized_date_epoch_seconds: Option<i64>,
+    #[serde(default)]
+    pub password_login_disabled: bool,
+}
+
+impl From<AccountDataV1> for AccountData {
+    fn from(data: AccountDataV1) -> Self {
+        AccountData {
+            username: data.username,
+            email: data.email,
+            normalized_username: data.normalized_username,
+            normalized_email: data.normalized_email,
+            password: data.password,
+            roles: data.roles,
+            created_date_epoch_seconds: data.created_date_epoch_seconds,
+            password_updated_date_epoch_seconds: data.password_updated_date_epoch_seconds,
+            password_history: data.password_history,
+            status: data.status.into(),
+            mfa: data.mfa,
+            failed_login_attempts: data.failed_login_attempts,
+            last_failed_login_epoch_seconds: data.last_failed_login_epoch_seconds,
+            pending_email: data.pending_email,
+            anonymized_date_epoch_seconds: data.anonymized_date_epoch_seconds,
+            password_login_disabled: data.password_login_disabled,
+            language: None,
+            profile: BTreeMap::new(),
+        }
+    }
+}
+
+/// `AccountData` as stored before the suspended and the locked statuses
+/// were added
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AccountDataV2 {
+    pub username: String,
+    pub email: String,
+    #[serde(default)]
+    pub normalized_username: String,
+    #[serde(default)]
+    pub normalized_email: String,
+    pub password: String,
+    pub roles: Vec<String>,
+    pub created_date_epoch_seconds: i64,
+    pub password_updated_date_epoch_seconds: i64,
+    #[serde(default)]
+    pub password_history: Vec<String>,
+    pub status: AccountStatusV1,
+    #[serde(default)]
+    pub mfa: MfaState,
+    #[serde(default)]
+    pub failed_login_attempts: u32,
+    #[serde(default)]
+    pub last_failed_login_epoch_seconds: Option<i64>,
+    #[serde(default)]
+    pub pending_email: Option<String>,
+    #[serde(default)]
+    pub anonymized_date_epoch_seconds: Option<i64>,
+    #[serde(default)]
+    pub password_login_disabled: bool,
+    pub language: Option<Language>,
+    pub profile: BTreeMap<String, String>,
+}
+
+impl From<AccountDataV2> for AccountData {
+    fn from(data: AccountDataV2) -> Self {
+        AccountData {
+            username: data.username,
+            email: data.email,
+            normalized_username: data.normalized_username,
+            normalized_email: data.normalized_email,
+            password: data.password,
+            roles: data.roles,
+            created_date_epoch_seconds: data.created_date_epoch_seconds,
+            password_updated_date_epoch_seconds: data.password_updated_date_epoch_seconds,
+            password_history: data.password_history,
+            status: data.status.into(),
+            mfa: data.mfa,
+            failed_login_attempts: data.failed_login_attempts,
+            last_failed_login_epoch_seconds: data.last_failed_login_epoch_seconds,
+            pending_email: data.pending_email,
+            anonymized_date_epoch_seconds: data.anonymized_date_epoch_seconds,
+            password_login_disabled: data.password_login_disabled,
+            language: data.language,
+            profile: data.profile,
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(tag = "_codec_tag")]
 pub enum AccountDataToken {
-    V1(AccountData),
+    V1(AccountDataV1),
+    V2(AccountDataV2),
+    V3(AccountData),
 }
 
 impl Codec<AccountData> for AccountDataToken {
     fn encode(data: AccountData) -> Self {
-        AccountDataToken::V1(data)
+        AccountDataToken::V3(data)
     }
 
     fn decode(data: Self) -> AccountData {
         match data {
-            AccountDataToken::V1(data) => data,
+            AccountDataToken::V1(data) => data.into(),
+            AccountDataToken::V2(data) => data.into(),
+            AccountDataToken::V3(data) => data,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn stored_v1() -> AccountDataToken {
+        serde_json::from_value(json!({
+            "_codec_tag": "V1",
+            "username": "user",
+            "email": "user@email.fake",
+            "password": "hash",
+            "roles": ["admin"],
+            "created_date_epoch_seconds": 1,
+            "password_updated_date_epoch_seconds": 2,
+            "status": "Active",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_decode_v1_account_data() {
+        let data: AccountData = AccountDataToken::decode(stored_v1());
+        assert_eq!("user", data.username);
+        assert_eq!(vec!["admin".to_owned()], data.roles);
+        assert_eq!(AccountStatus::Active, data.status);
+        assert_eq!(None, data.language);
+        assert!(data.profile.is_empty());
+    }
+
+    #[test]
+    fn should_decode_v2_account_data() {
+        let stored: AccountDataToken = serde_json::from_value(json!({
+            "_codec_tag": "V2",
+            "username": "user",
+            "email": "user@email.fake",
+            "password": "hash",
+            "roles": [],
+            "created_date_epoch_seconds": 1,
+            "password_updated_date_epoch_seconds": 2,
+            "status": "Disabled",
+            "language": "It",
+            "profile": { "city": "Rome" },
+        }))
+        .unwrap();
+        let data: AccountData = AccountDataToken::decode(stored);
+        assert_eq!(AccountStatus::Disabled, data.status);
+        assert_eq!(Some(Language::It), data.language);
+        assert_eq!(Some(&"Rome".to_owned()), data.profile.get("city"));
+    }
+
+    #[test]
+    fn should_encode_the_latest_version() {
+        let data: AccountData = AccountDataToken::decode(stored_v1());
+        let stored = serde_json::to_value(AccountDataToken::encode(data)).unwrap();
+        assert_eq!("V3", stored["_codec_tag"]);
+        assert_eq!(json!({}), stored["profile"]);
+        assert_eq!(json!({ "kind": "Active" }), stored["status"]);
+    }
+
+    #[test]
+    fn should_store_the_status_details() {
+        let mut data: AccountData = AccountDataToken::decode(stored_v1());
+        data.status = AccountStatus::Suspended { until_epoch_seconds: 10, reason: "spam".to_owned() };
+        let stored = serde_json::to_value(AccountDataToken::encode(data)).unwrap();
+        assert_eq!(json!({ "kind": "Suspended", "until_epoch_seconds": 10, "reason": "spam" }), stored["status"]);
+
+        let data: AccountData = AccountDataToken::decode(serde_json::from_value(stored).unwrap());
+        assert_eq!(AccountStatus::Suspended { until_epoch_seconds: 10, reason: "spam".to_owned() }, data.status);
+        assert_eq!(AccountStatusKind::Suspended, data.status.kind());
+        assert_eq!("Suspended", data.status.as_ref());
+    }
+}
@@ -0,0 +1,213 @@
+use lightspeed_event::model::DomainEvent;
+use serde::{Deserialize, Serialize};
+
+/// An account was created; it must be activated with the token sealed in
+/// `sealed_activation_token`.
+///
+/// The tokens of the events are sealed with `LsTokenService::seal_token`,
+/// so that the outbox holds no usable token, and are given back by
+/// `LsTokenService::open_token`. They are scrubbed from the outbox once the
+/// event is delivered or moved to the dead letters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountCreated {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub sealed_activation_token: String,
+}
+
+impl DomainEvent for AccountCreated {
+    const EVENT_TYPE: &'static str = "AM_ACCOUNT_CREATED";
+    const SCRUBBED_FIELDS: &'static [&'static str] = &["sealed_activation_token"];
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// A new activation token was generated for an account pending activation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivationTokenGenerated {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub sealed_activation_token: String,
+}
+
+impl DomainEvent for ActivationTokenGenerated {
+    const EVENT_TYPE: &'static str = "AM_ACTIVATION_TOKEN_GENERATED";
+    const SCRUBBED_FIELDS: &'static [&'static str] = &["sealed_activation_token"];
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountActivated {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+}
+
+impl DomainEvent for AccountActivated {
+    const EVENT_TYPE: &'static str = "AM_ACCOUNT_ACTIVATED";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// A user asked to reset the password; it can be reset with the token
+/// sealed in `sealed_reset_token`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordResetRequested {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub sealed_reset_token: String,
+}
+
+impl DomainEvent for PasswordResetRequested {
+    const EVENT_TYPE: &'static str = "AM_PASSWORD_RESET_REQUESTED";
+    const SCRUBBED_FIELDS: &'static [&'static str] = &["sealed_reset_token"];
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordReset {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+}
+
+impl DomainEvent for PasswordReset {
+    const EVENT_TYPE: &'static str = "AM_PASSWORD_RESET";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// A user changed the password, providing the current one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PasswordChanged {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+}
+
+impl DomainEvent for PasswordChanged {
+    const EVENT_TYPE: &'static str = "AM_PASSWORD_CHANGED";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountDisabled {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+}
+
+impl DomainEvent for AccountDisabled {
+    const EVENT_TYPE: &'static str = "AM_ACCOUNT_DISABLED";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// A user asked to change the email to `new_email`. The change must be
+/// confirmed with the token sealed in `sealed_change_token`, to be sent to
+/// `new_email`; the current `email` should be notified of the request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailChangeRequested {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub new_email: String,
+    pub sealed_change_token: String,
+}
+
+impl DomainEvent for EmailChangeRequested {
+    const EVENT_TYPE: &'static str = "AM_EMAIL_CHANGE_REQUESTED";
+    const SCRUBBED_FIELDS: &'static [&'static str] = &["sealed_change_token"];
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailChanged {
+    pub user_id: i64,
+    pub username: String,
+    pub old_email: String,
+    pub email: String,
+}
+
+impl DomainEvent for EmailChanged {
+    const EVENT_TYPE: &'static str = "AM_EMAIL_CHANGED";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// The personal data of an account were scrubbed; the modules holding data
+/// of the user should scrub them too.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountAnonymized {
+    pub user_id: i64,
+}
+
+impl DomainEvent for AccountAnonymized {
+    const EVENT_TYPE: &'static str = "AM_ACCOUNT_ANONYMIZED";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// A user asked to log in without password; the link sent to `email`
+/// carries the token sealed in `sealed_login_token`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MagicLoginRequested {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub sealed_login_token: String,
+}
+
+impl DomainEvent for MagicLoginRequested {
+    const EVENT_TYPE: &'static str = "AM_MAGIC_LOGIN_REQUESTED";
+    const SCRUBBED_FIELDS: &'static [&'static str] = &["sealed_login_token"];
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
+
+/// An account was created at the first login of an external identity; it is
+/// already active.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountProvisioned {
+    pub user_id: i64,
+    pub username: String,
+    pub email: String,
+    pub issuer: String,
+}
+
+impl DomainEvent for AccountProvisioned {
+    const EVENT_TYPE: &'static str = "AM_ACCOUNT_PROVISIONED";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.user_id.to_string())
+    }
+}
@@ -0,0 +1,40 @@
+use c3p0::{Codec, DataType, Record};
+use serde::{Deserialize, Serialize};
+
+pub type ExternalIdentityModel = Record<ExternalIdentityData>;
+
+/// Links the identity `subject` of the OpenID Connect provider `issuer` to
+/// the account `user_id`. An identity is linked to one account only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExternalIdentityData {
+    pub user_id: i64,
+    pub issuer: String,
+    pub subject: String,
+    /// Email claimed by the provider at the last login
+    pub email: Option<String>,
+    pub created_date_epoch_seconds: i64,
+    pub last_login_epoch_seconds: Option<i64>,
+}
+
+impl DataType for ExternalIdentityData {
+    const TABLE_NAME: &'static str = "LS_AM_EXTERNAL_IDENTITY";
+    type CODEC = ExternalIdentityDataCodec;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "_codec_tag")]
+pub enum ExternalIdentityDataCodec {
+    V1(ExternalIdentityData),
+}
+
+impl Codec<ExternalIdentityData> for ExternalIdentityDataCodec {
+    fn encode(data: ExternalIdentityData) -> Self {
+        ExternalIdentityDataCodec::V1(data)
+    }
+
+    fn decode(data: Self) -> ExternalIdentityData {
+        match data {
+            ExternalIdentityDataCodec::V1(data) => data,
+        }
+    }
+}
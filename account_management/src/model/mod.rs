@@ -1,2 +1,7 @@
+pub mod acl;
+pub mod audit;
 pub mod auth_account;
+pub mod event;
+pub mod external_identity;
+pub mod session;
 pub mod token;
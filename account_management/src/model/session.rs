@@ -0,0 +1,42 @@
+use c3p0::{Codec, DataType, Record};
+use serde::{Deserialize, Serialize};
+
+pub type SessionModel = Record<SessionData>;
+
+/// A login session, identified by the `session_id` of the `Auth` returned
+/// by the login. A session is valid as long as its record exists and it is
+/// not expired; revoking it deletes the record.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionData {
+    pub session_id: String,
+    pub user_id: i64,
+    pub created_date_epoch_seconds: i64,
+    pub last_activity_epoch_seconds: i64,
+    /// Expiration of the token of the session
+    pub expire_at_epoch_seconds: i64,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl DataType for SessionData {
+    const TABLE_NAME: &'static str = "LS_AM_SESSION";
+    type CODEC = SessionDataCodec;
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "_codec_tag")]
+pub enum SessionDataCodec {
+    V1(SessionData),
+}
+
+impl Codec<SessionData> for SessionDataCodec {
+    fn encode(data: SessionData) -> Self {
+        SessionDataCodec::V1(data)
+    }
+
+    fn decode(data: Self) -> SessionData {
+        match data {
+            SessionDataCodec::V1(data) => data,
+        }
+    }
+}
@@ -7,10 +7,16 @@ pub const ERR_TOKEN_EXPIRED: &str = "expired";
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct TokenData {
+    /// Keyed hash of the token; the token itself is never stored. See
+    /// `LsTokenService::hash_token`.
     pub token: String,
     pub username: String,
     pub token_type: TokenType,
     pub expire_at_epoch_seconds: i64,
+    /// Fingerprint of the device that requested the token; when set, the
+    /// token can only be used from the same device
+    #[serde(default)]
+    pub device_fingerprint: Option<String>,
 }
 
 impl DataType for TokenData {
@@ -22,6 +28,15 @@ impl DataType for TokenData {
 pub enum TokenType {
     AccountActivation,
     ResetPassword,
+    /// Issued by a login with correct password to an account with MFA
+    /// enabled; it is exchanged for a session with a valid code.
+    MfaPending,
+    /// Sent to the new email requested by a user; the email is changed once
+    /// it is confirmed.
+    EmailChange,
+    /// Sent by email to log in without password; it is exchanged once for a
+    /// session.
+    MagicLogin,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -0,0 +1,115 @@
+use crate::dto::activate_user_dto::ActivateUserDto;
+use crate::dto::auth_dto::AuthDto;
+use crate::dto::change_password_dto::ChangePasswordDto;
+use crate::dto::create_login_dto::CreateLoginDto;
+use crate::dto::error_response_dto::ErrorResponseDto;
+use crate::dto::login_dto::LoginDto;
+use crate::dto::login_response_dto::LoginResponseDto;
+use crate::dto::mfa_login_dto::{MfaLoginDto, MfaRequiredDto};
+use crate::dto::reset_password_dto::ResetPasswordDto;
+use crate::dto::send_new_activation_token_dto::{
+    SendNewActivationTokenByUsernameAndEmailDto, SendNewActivationTokenDto,
+};
+use crate::dto::send_reset_password_dto::SendResetPasswordDto;
+use crate::dto::session_dto::SessionDto;
+use crate::dto::token_dto::TokenDto;
+use crate::dto::totp_dto::{ConfirmTotpDto, DisableTotpDto, RecoveryCodesDto, TotpEnrollmentDto};
+use lightspeed_core::web::openapi::LsOpenApi;
+use lightspeed_validator::openapi::constrained_schema;
+use utoipa::openapi::{ComponentsBuilder, OpenApi, OpenApiBuilder};
+use utoipa::{PartialSchema, ToSchema};
+
+/// OpenAPI fragment with the account management DTOs. The schemas of the
+/// `Validable` DTOs carry the constraints declared by their validators.
+///
+/// With the `axum` feature it also contains the paths of all the endpoints
+/// of `LsAMRouter`, relative to the prefix the router is nested under.
+pub struct LsAMOpenApi;
+
+impl LsOpenApi for LsAMOpenApi {
+    fn openapi() -> OpenApi {
+        let (create_login_name, create_login_schema) = constrained_schema::<CreateLoginDto>();
+        let (change_password_name, change_password_schema) = constrained_schema::<ChangePasswordDto>();
+        let (reset_password_name, reset_password_schema) = constrained_schema::<ResetPasswordDto>();
+
+        #[cfg(feature = "axum")]
+        let paths = crate::web::axum::openapi_paths();
+        #[cfg(not(feature = "axum"))]
+        let paths = utoipa::openapi::Paths::new();
+
+        OpenApiBuilder::new()
+            .paths(paths)
+            .components(Some(
+                ComponentsBuilder::new()
+                    .schema(ActivateUserDto::name(), ActivateUserDto::schema())
+                    .schema(AuthDto::name(), AuthDto::schema())
+                    .schema(change_password_name, change_password_schema)
+                    .schema(ConfirmTotpDto::name(), ConfirmTotpDto::schema())
+                    .schema(create_login_name, create_login_schema)
+                    .schema(DisableTotpDto::name(), DisableTotpDto::schema())
+                    .schema(ErrorResponseDto::name(), ErrorResponseDto::schema())
+                    .schema(LoginDto::name(), LoginDto::schema())
+                    .schema(LoginResponseDto::name(), LoginResponseDto::schema())
+                    .schema(MfaLoginDto::name(), MfaLoginDto::schema())
+                    .schema(MfaRequiredDto::name(), MfaRequiredDto::schema())
+                    .schema(RecoveryCodesDto::name(), RecoveryCodesDto::schema())
+                    .schema(reset_password_name, reset_password_schema)
+                    .schema(SendNewActivationTokenDto::name(), SendNewActivationTokenDto::schema())
+                    .schema(
+                        SendNewActivationTokenByUsernameAndEmailDto::name(),
+                        SendNewActivationTokenByUsernameAndEmailDto::schema(),
+                    )
+                    .schema(SendResetPasswordDto::name(), SendResetPasswordDto::schema())
+                    .schema(SessionDto::name(), SessionDto::schema())
+                    .schema(TokenDto::name(), TokenDto::schema())
+                    .schema(TotpEnrollmentDto::name(), TotpEnrollmentDto::schema())
+                    .build(),
+            ))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use lightspeed_core::web::openapi::build_openapi;
+
+    #[test]
+    fn should_expose_validation_constraints_in_dto_schemas() {
+        let spec = build_openapi("am", "1.0.0", [LsAMOpenApi::openapi()]);
+        let json = serde_json::to_value(&spec).unwrap();
+        let schemas = &json["components"]["schemas"];
+
+        let create_login = &schemas["CreateLoginDto"]["properties"];
+        assert_eq!("email", create_login["email"]["format"]);
+        assert_eq!("password", create_login["password"]["format"]);
+        assert_eq!(8, create_login["password"]["minLength"]);
+
+        assert_eq!(8, schemas["ChangePasswordDto"]["properties"]["new_password"]["minLength"]);
+        assert_eq!(8, schemas["ResetPasswordDto"]["properties"]["password"]["minLength"]);
+        assert!(schemas["LoginResponseDto"].is_object());
+        assert!(schemas["Language"].is_object());
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn should_expose_the_router_paths() {
+        let spec = build_openapi("am", "1.0.0", [LsAMOpenApi::openapi()]);
+        let json = serde_json::to_value(&spec).unwrap();
+        let paths = &json["paths"];
+
+        let login = &paths["/login"]["post"];
+        assert_eq!(
+            "#/components/schemas/LoginDto",
+            login["requestBody"]["content"]["application/json"]["schema"]["$ref"]
+        );
+        assert!(login["responses"]["202"].is_object());
+        assert!(paths["/signup"]["post"]["responses"]["202"].is_object());
+        assert!(paths["/sessions"]["get"].is_object());
+        assert!(paths["/sessions"]["delete"].is_object());
+        assert!(paths["/sessions/{session_id}"]["delete"]["security"][0]["bearer_auth"].is_array());
+        assert!(paths["/mfa/totp/confirm"]["post"].is_object());
+        assert!(paths["/password/reset"]["post"].is_object());
+    }
+}
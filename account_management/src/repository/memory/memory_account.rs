@@ -0,0 +1,203 @@
+use crate::error::LsAccountManagementError;
+use crate::model::auth_account::{
+    AccountData, AccountFilter, AccountQuery, AccountSortField, AccountStatusKind, AuthAccountModel, SortOrder,
+};
+use crate::repository::AccountRepository;
+use crate::repository::memory::memory_db::{MemoryConnection, MemoryDb};
+use crate::repository::memory::memory_table::{MemoryRecords, MemoryTable};
+use c3p0::*;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+#[derive(Clone)]
+pub struct MemoryAccountRepository {
+    accounts: MemoryTable<AccountData>,
+}
+
+impl MemoryAccountRepository {
+    pub fn new(accounts: MemoryTable<AccountData>) -> Self {
+        Self { accounts }
+    }
+
+    fn fetch_first(&self, predicate: impl Fn(&AccountData) -> bool) -> Option<AuthAccountModel> {
+        self.accounts.read(|accounts| accounts.iter().find(|account| predicate(&account.data)).cloned())
+    }
+}
+
+fn matches(filter: &AccountFilter, data: &AccountData) -> bool {
+    filter.username_or_email_prefix.as_ref().is_none_or(|prefix| {
+        let prefix = prefix.to_lowercase();
+        data.username.to_lowercase().starts_with(&prefix) || data.email.to_lowercase().starts_with(&prefix)
+    }) && filter.role.as_ref().is_none_or(|role| data.roles.contains(role))
+        && filter.status.is_none_or(|status| data.status.kind() == status)
+        && filter.created_from_epoch_seconds.is_none_or(|from| data.created_date_epoch_seconds >= from)
+        && filter.created_to_epoch_seconds.is_none_or(|to| data.created_date_epoch_seconds < to)
+}
+
+fn compare(field: AccountSortField, first: &AuthAccountModel, second: &AuthAccountModel) -> Ordering {
+    match field {
+        AccountSortField::Id => first.id.cmp(&second.id),
+        AccountSortField::Username => first.data.username.cmp(&second.data.username),
+        AccountSortField::Email => first.data.email.cmp(&second.data.email),
+        AccountSortField::Status => first.data.status.as_ref().cmp(second.data.status.as_ref()),
+        AccountSortField::CreatedDate => {
+            first.data.created_date_epoch_seconds.cmp(&second.data.created_date_epoch_seconds)
+        }
+    }
+}
+
+/// Fails if another account has the same normalized username or email, as
+/// the unique indexes of the SQL repositories do
+fn check_unique(
+    accounts: &MemoryRecords<AccountData>,
+    id: Option<i64>,
+    data: &AccountData,
+) -> Result<(), LsAccountManagementError> {
+    for account in accounts.iter().filter(|account| Some(account.id) != id) {
+        if account.data.normalized_username == data.normalized_username {
+            return Err(LsAccountManagementError::UsernameAlreadyUsed);
+        }
+        if account.data.normalized_email == data.normalized_email {
+            return Err(LsAccountManagementError::EmailAlreadyUsed);
+        }
+    }
+    Ok(())
+}
+
+impl AccountRepository for MemoryAccountRepository {
+    type DB = MemoryDb;
+
+    async fn search(
+        &self,
+        _tx: &mut MemoryConnection,
+        query: &AccountQuery,
+    ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
+        let mut accounts = self.accounts.read(|accounts| {
+            accounts.iter().filter(|account| matches(&query.filter, &account.data)).cloned().collect::<Vec<_>>()
+        });
+        accounts.sort_by(|first, second| {
+            query
+                .sort
+                .iter()
+                .map(|(field, order)| match order {
+                    SortOrder::Asc => compare(*field, first, second),
+                    SortOrder::Desc => compare(*field, second, first),
+                })
+                .fold(Ordering::Equal, Ordering::then)
+                .then(first.id.cmp(&second.id))
+        });
+        Ok(accounts.into_iter().skip(query.offset as usize).take(query.limit as usize).collect())
+    }
+
+    async fn count(&self, _tx: &mut MemoryConnection, filter: &AccountFilter) -> Result<u64, LsAccountManagementError> {
+        Ok(self.accounts.read(|accounts| accounts.iter().filter(|account| matches(filter, &account.data)).count())
+            as u64)
+    }
+
+    async fn count_by_status(
+        &self,
+        _tx: &mut MemoryConnection,
+    ) -> Result<Vec<(String, u64)>, LsAccountManagementError> {
+        let mut counts = BTreeMap::<String, u64>::new();
+        self.accounts.read(|accounts| {
+            for account in accounts.iter() {
+                *counts.entry(account.data.status.as_ref().to_owned()).or_default() += 1;
+            }
+        });
+        Ok(counts.into_iter().collect())
+    }
+
+    async fn fetch_all_by_status(
+        &self,
+        _tx: &mut MemoryConnection,
+        status: AccountStatusKind,
+        start_user_id: i64,
+        limit: u32,
+    ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
+        Ok(self.accounts.read(|accounts| {
+            accounts
+                .iter_from(start_user_id)
+                .filter(|account| account.data.status.kind() == status)
+                .take(limit as usize)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn fetch_by_id(
+        &self,
+        _tx: &mut MemoryConnection,
+        user_id: i64,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        Ok(self.accounts.read(|accounts| accounts.fetch_by_id(user_id))?)
+    }
+
+    async fn fetch_by_id_optional(
+        &self,
+        _tx: &mut MemoryConnection,
+        user_id: i64,
+    ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+        Ok(self.accounts.read(|accounts| accounts.fetch_by_id_optional(user_id)))
+    }
+
+    async fn fetch_by_username(
+        &self,
+        tx: &mut MemoryConnection,
+        username: &str,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.fetch_by_username_optional(tx, username).await?.ok_or_else(|| LsAccountManagementError::BadRequest {
+            message: format!("No user found with username [{username}]"),
+            code: "",
+        })
+    }
+
+    async fn fetch_by_username_optional(
+        &self,
+        _tx: &mut MemoryConnection,
+        username: &str,
+    ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+        Ok(self.fetch_first(|data| data.normalized_username == username))
+    }
+
+    async fn fetch_by_email_optional(
+        &self,
+        _tx: &mut MemoryConnection,
+        email: &str,
+    ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+        Ok(self.fetch_first(|data| data.normalized_email == email))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: NewRecord<AccountData>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.accounts.write(|accounts| {
+            check_unique(accounts, None, &model.data)?;
+            Ok(accounts.save(model))
+        })
+    }
+
+    async fn update(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: AuthAccountModel,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.accounts.write(|accounts| {
+            check_unique(accounts, Some(model.id), &model.data)?;
+            Ok(accounts.update(model)?)
+        })
+    }
+
+    async fn delete(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: AuthAccountModel,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        Ok(self.accounts.write(|accounts| accounts.delete(model))?)
+    }
+
+    async fn delete_by_id(&self, _tx: &mut MemoryConnection, user_id: i64) -> Result<u64, LsAccountManagementError> {
+        Ok(self.accounts.write(|accounts| accounts.delete_where(|account| account.id == user_id)))
+    }
+}
@@ -0,0 +1,140 @@
+use crate::error::LsAccountManagementError;
+use crate::model::acl::{AclEntryData, AclEntryModel, grantee_type_and_id};
+use crate::repository::AclRepository;
+use crate::repository::memory::memory_db::{MemoryConnection, MemoryDb};
+use crate::repository::memory::memory_table::{MemoryRecords, MemoryTable};
+use c3p0::*;
+use lightspeed_core::service::auth::Grantee;
+
+#[derive(Clone)]
+pub struct MemoryAclRepository {
+    acl_entries: MemoryTable<AclEntryData>,
+}
+
+impl MemoryAclRepository {
+    pub fn new(acl_entries: MemoryTable<AclEntryData>) -> Self {
+        Self { acl_entries }
+    }
+
+    fn fetch_where(&self, predicate: impl Fn(&AclEntryData) -> bool) -> Vec<AclEntryModel> {
+        self.acl_entries
+            .read(|acl_entries| acl_entries.iter().filter(|entry| predicate(&entry.data)).cloned().collect())
+    }
+}
+
+fn same_grantee(first: &Grantee, second: &Grantee) -> bool {
+    grantee_type_and_id(first) == grantee_type_and_id(second)
+}
+
+/// Fails if another entry has the same resource and grantee, as the unique
+/// index of the SQL repositories does
+fn check_unique(
+    acl_entries: &MemoryRecords<AclEntryData>,
+    id: Option<i64>,
+    data: &AclEntryData,
+) -> Result<(), LsAccountManagementError> {
+    let duplicated = acl_entries.iter().filter(|entry| Some(entry.id) != id).any(|entry| {
+        entry.data.resource_type == data.resource_type
+            && entry.data.resource_id == data.resource_id
+            && same_grantee(&entry.data.grantee, &data.grantee)
+    });
+    if duplicated {
+        return Err(C3p0Error::Other {
+            cause: format!("Duplicated resource and grantee in table [{}]", AclEntryData::TABLE_NAME),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl AclRepository for MemoryAclRepository {
+    type DB = MemoryDb;
+
+    async fn fetch_all_by_resource(
+        &self,
+        _tx: &mut MemoryConnection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        Ok(self.fetch_where(|data| data.resource_type == resource_type && data.resource_id == resource_id))
+    }
+
+    async fn fetch_by_resource_and_grantee_optional(
+        &self,
+        _tx: &mut MemoryConnection,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: &Grantee,
+    ) -> Result<Option<AclEntryModel>, LsAccountManagementError> {
+        Ok(self
+            .fetch_where(|data| {
+                data.resource_type == resource_type
+                    && data.resource_id == resource_id
+                    && same_grantee(&data.grantee, grantee)
+            })
+            .into_iter()
+            .next())
+    }
+
+    async fn fetch_all_by_grantee(
+        &self,
+        _tx: &mut MemoryConnection,
+        resource_type: &str,
+        grantee: &Grantee,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        Ok(self.fetch_where(|data| data.resource_type == resource_type && same_grantee(&data.grantee, grantee)))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: NewRecord<AclEntryData>,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        self.acl_entries.write(|acl_entries| {
+            check_unique(acl_entries, None, &model.data)?;
+            Ok(acl_entries.save(model))
+        })
+    }
+
+    async fn update(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: AclEntryModel,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        self.acl_entries.write(|acl_entries| {
+            check_unique(acl_entries, Some(model.id), &model.data)?;
+            Ok(acl_entries.update(model)?)
+        })
+    }
+
+    async fn delete(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: AclEntryModel,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        Ok(self.acl_entries.write(|acl_entries| acl_entries.delete(model))?)
+    }
+
+    async fn delete_all_by_resource(
+        &self,
+        _tx: &mut MemoryConnection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<u64, LsAccountManagementError> {
+        Ok(self.acl_entries.write(|acl_entries| {
+            acl_entries.delete_where(|entry| {
+                entry.data.resource_type == resource_type && entry.data.resource_id == resource_id
+            })
+        }))
+    }
+
+    async fn delete_all_by_grantee(
+        &self,
+        _tx: &mut MemoryConnection,
+        grantee: &Grantee,
+    ) -> Result<u64, LsAccountManagementError> {
+        Ok(self
+            .acl_entries
+            .write(|acl_entries| acl_entries.delete_where(|entry| same_grantee(&entry.data.grantee, grantee))))
+    }
+}
@@ -0,0 +1,60 @@
+use crate::error::LsAccountManagementError;
+use crate::model::audit::{AuditEventData, AuditEventModel, AuditEventQuery};
+use crate::repository::AuditRepository;
+use crate::repository::memory::memory_db::{MemoryConnection, MemoryDb};
+use crate::repository::memory::memory_table::MemoryTable;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct MemoryAuditRepository {
+    audit_events: MemoryTable<AuditEventData>,
+}
+
+impl MemoryAuditRepository {
+    pub fn new(audit_events: MemoryTable<AuditEventData>) -> Self {
+        Self { audit_events }
+    }
+}
+
+fn matches(query: &AuditEventQuery, data: &AuditEventData) -> bool {
+    query.entity_type.as_ref().is_none_or(|entity_type| &data.entity_type == entity_type)
+        && query.entity_id.as_ref().is_none_or(|entity_id| &data.entity_id == entity_id)
+        && query.actor_id.is_none_or(|actor_id| data.actor.as_ref().is_some_and(|actor| actor.id == actor_id))
+        && query.from_epoch_seconds.is_none_or(|from| data.created_date_epoch_seconds >= from)
+        && query.to_epoch_seconds.is_none_or(|to| data.created_date_epoch_seconds < to)
+}
+
+impl AuditRepository for MemoryAuditRepository {
+    type DB = MemoryDb;
+
+    async fn fetch_all(
+        &self,
+        _tx: &mut MemoryConnection,
+        query: &AuditEventQuery,
+    ) -> Result<Vec<AuditEventModel>, LsAccountManagementError> {
+        Ok(self.audit_events.read(|audit_events| {
+            audit_events
+                .iter_from(query.start_id)
+                .filter(|event| matches(query, &event.data))
+                .take(query.limit as usize)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: NewRecord<AuditEventData>,
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        Ok(self.audit_events.write(|audit_events| audit_events.save(model)))
+    }
+
+    async fn update(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: AuditEventModel,
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        Ok(self.audit_events.write(|audit_events| audit_events.update(model))?)
+    }
+}
@@ -0,0 +1,326 @@
+//! The transaction type of the in-memory repositories.
+//!
+//! The repository traits receive the connection of a sqlx transaction, so
+//! [`MemoryDb`] implements [`Database`] without a server: its connection is
+//! a no-op handle, and the rows, values and statements cannot be built
+//! because the in-memory repositories run no queries.
+
+use c3p0::sqlx::error::BoxDynError;
+use c3p0::sqlx::query::{Query, QueryAs, QueryScalar};
+use c3p0::sqlx::{
+    Arguments, Column, ColumnIndex, ConnectOptions, Connection, Database, Either, Encode, Error, FromRow,
+    IntoArguments, Row, SqlStr, Statement, Transaction, Type, TypeInfo, Value, ValueRef,
+};
+use c3p0::{C3p0Error, C3p0Pool};
+use log::LevelFilter;
+use sqlx_core::Url;
+use sqlx_core::transaction::TransactionManager;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A database that keeps no data: the in-memory repositories store it
+#[derive(Debug)]
+pub struct MemoryDb;
+
+impl Database for MemoryDb {
+    type Connection = MemoryConnection;
+    type TransactionManager = MemoryTransactionManager;
+    type Row = Unsupported;
+    type QueryResult = MemoryQueryResult;
+    type Column = Unsupported;
+    type TypeInfo = Unsupported;
+    type Value = Unsupported;
+    type ValueRef<'r> = Unsupported;
+    type Arguments = MemoryArguments;
+    type ArgumentBuffer = ();
+    type Statement = Unsupported;
+
+    const NAME: &'static str = "Memory";
+    const URL_SCHEMES: &'static [&'static str] = &["memory"];
+}
+
+/// [`C3p0Pool`] whose transactions are no-ops: the changes of the in-memory
+/// repositories are applied immediately and are kept when a transaction
+/// fails.
+#[derive(Clone, Default)]
+pub struct MemoryC3p0Pool;
+
+impl C3p0Pool for MemoryC3p0Pool {
+    type DB = MemoryDb;
+
+    async fn transaction<
+        T: Send,
+        E: Send + From<C3p0Error>,
+        F: Send + AsyncFnOnce(&mut MemoryConnection) -> Result<T, E>,
+    >(
+        &self,
+        tx: F,
+    ) -> Result<T, E> {
+        tx(&mut MemoryConnection).await
+    }
+}
+
+/// The connection, and transaction, of [`MemoryDb`]
+#[derive(Debug, Default)]
+pub struct MemoryConnection;
+
+impl Connection for MemoryConnection {
+    type Database = MemoryDb;
+    type Options = MemoryConnectOptions;
+
+    async fn close(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn close_hard(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn ping(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn begin(&mut self) -> impl Future<Output = Result<Transaction<'_, MemoryDb>, Error>> + Send + '_ {
+        Transaction::begin(self, None)
+    }
+
+    fn shrink_buffers(&mut self) {}
+
+    async fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn should_flush(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryConnectOptions;
+
+impl FromStr for MemoryConnectOptions {
+    type Err = Error;
+
+    fn from_str(_url: &str) -> Result<Self, Error> {
+        Ok(MemoryConnectOptions)
+    }
+}
+
+impl ConnectOptions for MemoryConnectOptions {
+    type Connection = MemoryConnection;
+
+    fn from_url(_url: &Url) -> Result<Self, Error> {
+        Ok(MemoryConnectOptions)
+    }
+
+    async fn connect(&self) -> Result<MemoryConnection, Error> {
+        Ok(MemoryConnection)
+    }
+
+    fn log_statements(self, _level: LevelFilter) -> Self {
+        self
+    }
+
+    fn log_slow_statements(self, _level: LevelFilter, _duration: Duration) -> Self {
+        self
+    }
+}
+
+pub struct MemoryTransactionManager;
+
+impl TransactionManager for MemoryTransactionManager {
+    type Database = MemoryDb;
+
+    async fn begin(_conn: &mut MemoryConnection, _statement: Option<SqlStr>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn commit(_conn: &mut MemoryConnection) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn rollback(_conn: &mut MemoryConnection) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn start_rollback(_conn: &mut MemoryConnection) {}
+
+    fn get_transaction_depth(_conn: &MemoryConnection) -> usize {
+        0
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryQueryResult;
+
+impl Extend<MemoryQueryResult> for MemoryQueryResult {
+    fn extend<I: IntoIterator<Item = MemoryQueryResult>>(&mut self, _iter: I) {}
+}
+
+/// The arguments of the queries, that [`MemoryDb`] cannot bind
+#[derive(Default)]
+pub struct MemoryArguments;
+
+impl Arguments for MemoryArguments {
+    type Database = MemoryDb;
+
+    fn reserve(&mut self, _additional: usize, _size: usize) {}
+
+    fn add<'t, T>(&mut self, _value: T) -> Result<(), BoxDynError>
+    where
+        T: Encode<'t, MemoryDb> + Type<MemoryDb>,
+    {
+        Err(format!("{} does not run queries", MemoryDb::NAME).into())
+    }
+
+    fn len(&self) -> usize {
+        0
+    }
+}
+
+/// The rows, columns, types, values and statements of [`MemoryDb`], that
+/// has none
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unsupported {}
+
+impl Display for Unsupported {
+    fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {}
+    }
+}
+
+impl TypeInfo for Unsupported {
+    fn is_null(&self) -> bool {
+        match *self {}
+    }
+
+    fn name(&self) -> &str {
+        match *self {}
+    }
+}
+
+impl Column for Unsupported {
+    type Database = MemoryDb;
+
+    fn ordinal(&self) -> usize {
+        match *self {}
+    }
+
+    fn name(&self) -> &str {
+        match *self {}
+    }
+
+    fn type_info(&self) -> &Unsupported {
+        self
+    }
+}
+
+impl Row for Unsupported {
+    type Database = MemoryDb;
+
+    fn columns(&self) -> &[Unsupported] {
+        match *self {}
+    }
+
+    fn try_get_raw<I>(&self, _index: I) -> Result<Unsupported, Error>
+    where
+        I: ColumnIndex<Self>,
+    {
+        match *self {}
+    }
+}
+
+impl Value for Unsupported {
+    type Database = MemoryDb;
+
+    fn as_ref(&self) -> Unsupported {
+        match *self {}
+    }
+
+    fn type_info(&self) -> Cow<'_, Unsupported> {
+        match *self {}
+    }
+
+    fn is_null(&self) -> bool {
+        match *self {}
+    }
+}
+
+impl ValueRef<'_> for Unsupported {
+    type Database = MemoryDb;
+
+    fn to_owned(&self) -> Unsupported {
+        match *self {}
+    }
+
+    fn type_info(&self) -> Cow<'_, Unsupported> {
+        match *self {}
+    }
+
+    fn is_null(&self) -> bool {
+        match *self {}
+    }
+}
+
+impl Statement for Unsupported {
+    type Database = MemoryDb;
+
+    fn into_sql(self) -> SqlStr {
+        match self {}
+    }
+
+    fn sql(&self) -> &SqlStr {
+        match *self {}
+    }
+
+    fn parameters(&self) -> Option<Either<&[Unsupported], usize>> {
+        match *self {}
+    }
+
+    fn columns(&self) -> &[Unsupported] {
+        match *self {}
+    }
+
+    fn query(&self) -> Query<'_, MemoryDb, MemoryArguments> {
+        match *self {}
+    }
+
+    fn query_with<A>(&self, _arguments: A) -> Query<'_, MemoryDb, A>
+    where
+        A: IntoArguments<MemoryDb>,
+    {
+        match *self {}
+    }
+
+    fn query_as<O>(&self) -> QueryAs<'_, MemoryDb, O, MemoryArguments>
+    where
+        O: for<'r> FromRow<'r, Unsupported>,
+    {
+        match *self {}
+    }
+
+    fn query_as_with<'s, O, A>(&'s self, _arguments: A) -> QueryAs<'s, MemoryDb, O, A>
+    where
+        O: for<'r> FromRow<'r, Unsupported>,
+        A: IntoArguments<MemoryDb>,
+    {
+        match *self {}
+    }
+
+    fn query_scalar<O>(&self) -> QueryScalar<'_, MemoryDb, O, MemoryArguments>
+    where
+        (O,): for<'r> FromRow<'r, Unsupported>,
+    {
+        match *self {}
+    }
+
+    fn query_scalar_with<'s, O, A>(&'s self, _arguments: A) -> QueryScalar<'s, MemoryDb, O, A>
+    where
+        (O,): for<'r> FromRow<'r, Unsupported>,
+        A: IntoArguments<MemoryDb>,
+    {
+        match *self {}
+    }
+}
@@ -0,0 +1,95 @@
+use crate::error::LsAccountManagementError;
+use crate::model::external_identity::{ExternalIdentityData, ExternalIdentityModel};
+use crate::repository::ExternalIdentityRepository;
+use crate::repository::memory::memory_db::{MemoryConnection, MemoryDb};
+use crate::repository::memory::memory_table::{MemoryRecords, MemoryTable};
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct MemoryExternalIdentityRepository {
+    external_identities: MemoryTable<ExternalIdentityData>,
+}
+
+impl MemoryExternalIdentityRepository {
+    pub fn new(external_identities: MemoryTable<ExternalIdentityData>) -> Self {
+        Self { external_identities }
+    }
+}
+
+/// Fails if another identity has the same issuer and subject, as the unique
+/// index of the SQL repositories does
+fn check_unique(
+    external_identities: &MemoryRecords<ExternalIdentityData>,
+    id: Option<i64>,
+    data: &ExternalIdentityData,
+) -> Result<(), LsAccountManagementError> {
+    let duplicated = external_identities
+        .iter()
+        .filter(|identity| Some(identity.id) != id)
+        .any(|identity| identity.data.issuer == data.issuer && identity.data.subject == data.subject);
+    if duplicated {
+        return Err(C3p0Error::Other {
+            cause: format!("Duplicated issuer and subject in table [{}]", ExternalIdentityData::TABLE_NAME),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl ExternalIdentityRepository for MemoryExternalIdentityRepository {
+    type DB = MemoryDb;
+
+    async fn fetch_by_issuer_and_subject_optional(
+        &self,
+        _tx: &mut MemoryConnection,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentityModel>, LsAccountManagementError> {
+        Ok(self.external_identities.read(|external_identities| {
+            external_identities
+                .iter()
+                .find(|identity| identity.data.issuer == issuer && identity.data.subject == subject)
+                .cloned()
+        }))
+    }
+
+    async fn fetch_all_by_user_id(
+        &self,
+        _tx: &mut MemoryConnection,
+        user_id: i64,
+    ) -> Result<Vec<ExternalIdentityModel>, LsAccountManagementError> {
+        Ok(self.external_identities.read(|external_identities| {
+            external_identities.iter().filter(|identity| identity.data.user_id == user_id).cloned().collect()
+        }))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: NewRecord<ExternalIdentityData>,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        self.external_identities.write(|external_identities| {
+            check_unique(external_identities, None, &model.data)?;
+            Ok(external_identities.save(model))
+        })
+    }
+
+    async fn update(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: ExternalIdentityModel,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        self.external_identities.write(|external_identities| {
+            check_unique(external_identities, Some(model.id), &model.data)?;
+            Ok(external_identities.update(model)?)
+        })
+    }
+
+    async fn delete(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: ExternalIdentityModel,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(self.external_identities.write(|external_identities| external_identities.delete(model))?)
+    }
+}
@@ -0,0 +1,91 @@
+use crate::error::LsAccountManagementError;
+use crate::model::session::{SessionData, SessionModel};
+use crate::repository::SessionRepository;
+use crate::repository::memory::memory_db::{MemoryConnection, MemoryDb};
+use crate::repository::memory::memory_table::{MemoryRecords, MemoryTable};
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct MemorySessionRepository {
+    sessions: MemoryTable<SessionData>,
+}
+
+impl MemorySessionRepository {
+    pub fn new(sessions: MemoryTable<SessionData>) -> Self {
+        Self { sessions }
+    }
+}
+
+/// Fails if another session has the same session id, as the unique index
+/// of the SQL repositories does
+fn check_unique(
+    sessions: &MemoryRecords<SessionData>,
+    id: Option<i64>,
+    data: &SessionData,
+) -> Result<(), LsAccountManagementError> {
+    let duplicated = sessions
+        .iter()
+        .filter(|session| Some(session.id) != id)
+        .any(|session| session.data.session_id == data.session_id);
+    if duplicated {
+        return Err(C3p0Error::Other {
+            cause: format!("Duplicated session_id in table [{}]", SessionData::TABLE_NAME),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+impl SessionRepository for MemorySessionRepository {
+    type DB = MemoryDb;
+
+    async fn fetch_by_session_id_optional(
+        &self,
+        _tx: &mut MemoryConnection,
+        session_id: &str,
+    ) -> Result<Option<SessionModel>, LsAccountManagementError> {
+        Ok(self
+            .sessions
+            .read(|sessions| sessions.iter().find(|session| session.data.session_id == session_id).cloned()))
+    }
+
+    async fn fetch_all_by_user_id(
+        &self,
+        _tx: &mut MemoryConnection,
+        user_id: i64,
+    ) -> Result<Vec<SessionModel>, LsAccountManagementError> {
+        Ok(self
+            .sessions
+            .read(|sessions| sessions.iter().filter(|session| session.data.user_id == user_id).cloned().collect()))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: NewRecord<SessionData>,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        self.sessions.write(|sessions| {
+            check_unique(sessions, None, &model.data)?;
+            Ok(sessions.save(model))
+        })
+    }
+
+    async fn update(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: SessionModel,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        self.sessions.write(|sessions| {
+            check_unique(sessions, Some(model.id), &model.data)?;
+            Ok(sessions.update(model)?)
+        })
+    }
+
+    async fn delete(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: SessionModel,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        Ok(self.sessions.write(|sessions| sessions.delete(model))?)
+    }
+}
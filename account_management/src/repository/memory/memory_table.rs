@@ -0,0 +1,119 @@
+use c3p0::*;
+use chrono::Utc;
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// The records of a [`MemoryTable`], ordered by id
+pub struct MemoryRecords<DATA: DataType> {
+    last_id: i64,
+    records: BTreeMap<i64, Record<DATA>>,
+}
+
+impl<DATA: DataType + Clone> Clone for MemoryRecords<DATA> {
+    fn clone(&self) -> Self {
+        Self { last_id: self.last_id, records: self.records.clone() }
+    }
+}
+
+impl<DATA: DataType> Default for MemoryRecords<DATA> {
+    fn default() -> Self {
+        Self { last_id: 0, records: BTreeMap::new() }
+    }
+}
+
+impl<DATA: DataType + Clone> MemoryRecords<DATA> {
+    pub fn iter(&self) -> impl Iterator<Item = &Record<DATA>> {
+        self.records.values()
+    }
+
+    /// The records with id greater than or equal to `start_id`
+    pub fn iter_from(&self, start_id: i64) -> impl Iterator<Item = &Record<DATA>> {
+        self.records.range(start_id..).map(|(_, record)| record)
+    }
+
+    pub fn fetch_by_id_optional(&self, id: i64) -> Option<Record<DATA>> {
+        self.records.get(&id).cloned()
+    }
+
+    /// Fails with `RowNotFound`, like the SQL repositories
+    pub fn fetch_by_id(&self, id: i64) -> Result<Record<DATA>, C3p0Error> {
+        self.fetch_by_id_optional(id).ok_or(C3p0Error::SqlxError(c3p0::sqlx::Error::RowNotFound))
+    }
+
+    pub fn save(&mut self, model: NewRecord<DATA>) -> Record<DATA> {
+        self.last_id += 1;
+        let now = Utc::now();
+        let record = Record { id: self.last_id, version: 0, create_time: now, update_time: now, data: model.data };
+        self.records.insert(record.id, record.clone());
+        record
+    }
+
+    /// Fails with `OptimisticLockError` if the stored version is not the one
+    /// of `model`
+    pub fn update(&mut self, mut model: Record<DATA>) -> Result<Record<DATA>, C3p0Error> {
+        let stored = self.stored_version(&model, "update")?;
+        model.version += 1;
+        model.update_time = Utc::now();
+        *stored = model.clone();
+        Ok(model)
+    }
+
+    /// Fails with `OptimisticLockError` if the stored version is not the one
+    /// of `model`
+    pub fn delete(&mut self, model: Record<DATA>) -> Result<Record<DATA>, C3p0Error> {
+        self.stored_version(&model, "delete")?;
+        self.records.remove(&model.id);
+        Ok(model)
+    }
+
+    /// Deletes the records matching `predicate` and returns their number
+    pub fn delete_where(&mut self, predicate: impl Fn(&Record<DATA>) -> bool) -> u64 {
+        let count = self.records.len();
+        self.records.retain(|_, record| !predicate(record));
+        (count - self.records.len()) as u64
+    }
+
+    fn stored_version(&mut self, model: &Record<DATA>, operation: &str) -> Result<&mut Record<DATA>, C3p0Error> {
+        match self.records.get_mut(&model.id) {
+            Some(stored) if stored.version == model.version => Ok(stored),
+            _ => Err(C3p0Error::OptimisticLockError {
+                cause: format!(
+                    "Cannot {operation} data in table [{}] with id [{:?}], version [{}]: data was changed!",
+                    DATA::TABLE_NAME,
+                    model.id,
+                    model.version
+                ),
+            }),
+        }
+    }
+}
+
+/// A table kept in memory; the clones share the same records.
+pub struct MemoryTable<DATA: DataType> {
+    records: Arc<Mutex<MemoryRecords<DATA>>>,
+}
+
+impl<DATA: DataType> Clone for MemoryTable<DATA> {
+    fn clone(&self) -> Self {
+        Self { records: self.records.clone() }
+    }
+}
+
+impl<DATA: DataType> Default for MemoryTable<DATA> {
+    fn default() -> Self {
+        Self { records: Default::default() }
+    }
+}
+
+impl<DATA: DataType> MemoryTable<DATA> {
+    pub fn read<R>(&self, read: impl FnOnce(&MemoryRecords<DATA>) -> R) -> R {
+        read(&self.records.lock())
+    }
+
+    /// Applies `write` atomically, so it can check constraints before changing
+    /// the records
+    pub fn write<R>(&self, write: impl FnOnce(&mut MemoryRecords<DATA>) -> R) -> R {
+        write(&mut self.records.lock())
+    }
+}
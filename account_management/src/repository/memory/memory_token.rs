@@ -0,0 +1,73 @@
+use crate::error::LsAccountManagementError;
+use crate::model::token::{TokenData, TokenModel};
+use crate::repository::TokenRepository;
+use crate::repository::memory::memory_db::{MemoryConnection, MemoryDb};
+use crate::repository::memory::memory_table::MemoryTable;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct MemoryTokenRepository {
+    tokens: MemoryTable<TokenData>,
+}
+
+impl MemoryTokenRepository {
+    pub fn new(tokens: MemoryTable<TokenData>) -> Self {
+        Self { tokens }
+    }
+}
+
+impl TokenRepository for MemoryTokenRepository {
+    type DB = MemoryDb;
+
+    async fn fetch_by_token(
+        &self,
+        _tx: &mut MemoryConnection,
+        token_hash: &str,
+    ) -> Result<TokenModel, LsAccountManagementError> {
+        self.tokens
+            .read(|tokens| tokens.iter().find(|token| token.data.token == token_hash).cloned())
+            .ok_or(LsAccountManagementError::SqlxError { source: c3p0::sqlx::Error::RowNotFound })
+    }
+
+    async fn fetch_by_username(
+        &self,
+        _tx: &mut MemoryConnection,
+        username: &str,
+    ) -> Result<Vec<TokenModel>, LsAccountManagementError> {
+        Ok(self.tokens.read(|tokens| tokens.iter().filter(|token| token.data.username == username).cloned().collect()))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: NewRecord<TokenData>,
+    ) -> Result<TokenModel, LsAccountManagementError> {
+        self.tokens.write(|tokens| {
+            if tokens.iter().any(|token| token.data.token == model.data.token) {
+                return Err(C3p0Error::Other {
+                    cause: format!("Duplicated token in table [{}]", TokenData::TABLE_NAME),
+                }
+                .into());
+            }
+            Ok(tokens.save(model))
+        })
+    }
+
+    async fn delete(
+        &self,
+        _tx: &mut MemoryConnection,
+        model: TokenModel,
+    ) -> Result<TokenModel, LsAccountManagementError> {
+        Ok(self.tokens.write(|tokens| tokens.delete(model))?)
+    }
+
+    async fn delete_expired(
+        &self,
+        _tx: &mut MemoryConnection,
+        threshold_epoch_seconds: i64,
+    ) -> Result<u64, LsAccountManagementError> {
+        Ok(self
+            .tokens
+            .write(|tokens| tokens.delete_where(|token| token.data.expire_at_epoch_seconds < threshold_epoch_seconds)))
+    }
+}
@@ -0,0 +1,100 @@
+use crate::model::acl::AclEntryData;
+use crate::model::audit::AuditEventData;
+use crate::model::auth_account::AccountData;
+use crate::model::external_identity::ExternalIdentityData;
+use crate::model::session::SessionData;
+use crate::model::token::TokenData;
+use crate::repository::AMRepositoryManager;
+use lightspeed_core::error::LsError;
+use lightspeed_event::repository::memory::MemoryOutboxRepository;
+use memory_account::MemoryAccountRepository;
+use memory_acl::MemoryAclRepository;
+use memory_audit::MemoryAuditRepository;
+use memory_db::{MemoryC3p0Pool, MemoryDb};
+use memory_external_identity::MemoryExternalIdentityRepository;
+use memory_session::MemorySessionRepository;
+use memory_table::MemoryTable;
+use memory_token::MemoryTokenRepository;
+
+pub mod memory_account;
+pub mod memory_acl;
+pub mod memory_audit;
+pub mod memory_db;
+pub mod memory_external_identity;
+pub mod memory_session;
+pub mod memory_table;
+pub mod memory_token;
+
+/// [`AMRepositoryManager`] that keeps the data in memory, to test the code
+/// using the services without a database.
+///
+/// The transactions of [`MemoryC3p0Pool`] are no-ops: the changes are
+/// applied immediately and are not undone when a transaction fails.
+/// Uniqueness of username, email, token, ACL grantee, external identity and
+/// session id and the optimistic `version` checks are enforced as in the SQL
+/// repositories.
+#[derive(Clone, Default)]
+pub struct MemoryAMRepositoryManager {
+    c3p0: MemoryC3p0Pool,
+    accounts: MemoryTable<AccountData>,
+    tokens: MemoryTable<TokenData>,
+    acl_entries: MemoryTable<AclEntryData>,
+    audit_events: MemoryTable<AuditEventData>,
+    external_identities: MemoryTable<ExternalIdentityData>,
+    sessions: MemoryTable<SessionData>,
+    outbox_repo: MemoryOutboxRepository<MemoryDb>,
+}
+
+impl MemoryAMRepositoryManager {
+    pub fn new() -> MemoryAMRepositoryManager {
+        Self::default()
+    }
+}
+
+impl AMRepositoryManager for MemoryAMRepositoryManager {
+    type DB = MemoryDb;
+    type C3P0 = MemoryC3p0Pool;
+    type AccountRepo = MemoryAccountRepository;
+    type TokenRepo = MemoryTokenRepository;
+    type AclRepo = MemoryAclRepository;
+    type AuditRepo = MemoryAuditRepository;
+    type ExternalIdentityRepo = MemoryExternalIdentityRepository;
+    type SessionRepo = MemorySessionRepository;
+    type OutboxRepo = MemoryOutboxRepository<MemoryDb>;
+
+    fn c3p0(&self) -> &Self::C3P0 {
+        &self.c3p0
+    }
+
+    async fn start(&self) -> Result<(), LsError> {
+        Ok(())
+    }
+
+    fn account_repo(&self) -> Self::AccountRepo {
+        MemoryAccountRepository::new(self.accounts.clone())
+    }
+
+    fn token_repo(&self) -> Self::TokenRepo {
+        MemoryTokenRepository::new(self.tokens.clone())
+    }
+
+    fn acl_repo(&self) -> Self::AclRepo {
+        MemoryAclRepository::new(self.acl_entries.clone())
+    }
+
+    fn audit_repo(&self) -> Self::AuditRepo {
+        MemoryAuditRepository::new(self.audit_events.clone())
+    }
+
+    fn external_identity_repo(&self) -> Self::ExternalIdentityRepo {
+        MemoryExternalIdentityRepository::new(self.external_identities.clone())
+    }
+
+    fn session_repo(&self) -> Self::SessionRepo {
+        MemorySessionRepository::new(self.sessions.clone())
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        self.outbox_repo.clone()
+    }
+}
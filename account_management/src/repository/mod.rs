@@ -1,11 +1,19 @@
 use std::future::Future;
 
 use crate::error::LsAccountManagementError;
-use crate::model::auth_account::{AccountData, AccountStatus, AuthAccountModel};
+use crate::model::acl::{AclEntryData, AclEntryModel};
+use crate::model::audit::{AuditEventData, AuditEventModel, AuditEventQuery};
+use crate::model::auth_account::{AccountData, AccountFilter, AccountQuery, AccountStatusKind, AuthAccountModel};
+use crate::model::external_identity::{ExternalIdentityData, ExternalIdentityModel};
+use crate::model::session::{SessionData, SessionModel};
 use crate::model::token::{TokenData, TokenModel};
 use c3p0::sqlx::Database;
 use c3p0::*;
 use lightspeed_core::error::LsError;
+use lightspeed_core::service::auth::Grantee;
+use lightspeed_event::repository::OutboxRepository;
+
+pub mod memory;
 
 #[cfg(feature = "mysql")]
 pub mod mysql;
@@ -21,11 +29,21 @@ pub trait AMRepositoryManager: Clone + Send + Sync {
     type C3P0: C3p0Pool<DB = Self::DB>;
     type AccountRepo: for<'a> AccountRepository<DB = Self::DB>;
     type TokenRepo: for<'a> TokenRepository<DB = Self::DB>;
+    type AclRepo: for<'a> AclRepository<DB = Self::DB>;
+    type AuditRepo: for<'a> AuditRepository<DB = Self::DB>;
+    type ExternalIdentityRepo: for<'a> ExternalIdentityRepository<DB = Self::DB>;
+    type SessionRepo: for<'a> SessionRepository<DB = Self::DB>;
+    type OutboxRepo: OutboxRepository<DB = Self::DB>;
 
     fn c3p0(&self) -> &Self::C3P0;
     fn start(&self) -> impl Future<Output = Result<(), LsError>> + Send;
     fn account_repo(&self) -> Self::AccountRepo;
     fn token_repo(&self) -> Self::TokenRepo;
+    fn acl_repo(&self) -> Self::AclRepo;
+    fn audit_repo(&self) -> Self::AuditRepo;
+    fn external_identity_repo(&self) -> Self::ExternalIdentityRepo;
+    fn session_repo(&self) -> Self::SessionRepo;
+    fn outbox_repo(&self) -> Self::OutboxRepo;
 }
 
 pub trait AccountRepository: Clone + Send + Sync {
@@ -34,29 +52,56 @@ pub trait AccountRepository: Clone + Send + Sync {
     fn fetch_all_by_status(
         &self,
         tx: &mut <Self::DB as Database>::Connection,
-        status: AccountStatus,
+        status: AccountStatusKind,
         start_user_id: i64,
         limit: u32,
     ) -> impl Future<Output = Result<Vec<AuthAccountModel>, LsAccountManagementError>> + Send;
 
+    fn search(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        query: &AccountQuery,
+    ) -> impl Future<Output = Result<Vec<AuthAccountModel>, LsAccountManagementError>> + Send;
+
+    fn count(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        filter: &AccountFilter,
+    ) -> impl Future<Output = Result<u64, LsAccountManagementError>> + Send;
+
+    /// Returns the number of accounts for each status name stored
+    fn count_by_status(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+    ) -> impl Future<Output = Result<Vec<(String, u64)>, LsAccountManagementError>> + Send;
+
     fn fetch_by_id(
         &self,
         tx: &mut <Self::DB as Database>::Connection,
         user_id: i64,
     ) -> impl Future<Output = Result<AuthAccountModel, LsAccountManagementError>> + Send;
 
+    fn fetch_by_id_optional(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        user_id: i64,
+    ) -> impl Future<Output = Result<Option<AuthAccountModel>, LsAccountManagementError>> + Send;
+
+    /// Looks the account up by its `normalized_username`
     fn fetch_by_username(
         &self,
         tx: &mut <Self::DB as Database>::Connection,
         username: &str,
     ) -> impl Future<Output = Result<AuthAccountModel, LsAccountManagementError>> + Send;
 
+    /// Looks the account up by its `normalized_username`
     fn fetch_by_username_optional(
         &self,
         tx: &mut <Self::DB as Database>::Connection,
         username: &str,
     ) -> impl Future<Output = Result<Option<AuthAccountModel>, LsAccountManagementError>> + Send;
 
+    /// Looks the account up by its `normalized_email`
     fn fetch_by_email_optional(
         &self,
         tx: &mut <Self::DB as Database>::Connection,
@@ -91,10 +136,11 @@ pub trait AccountRepository: Clone + Send + Sync {
 pub trait TokenRepository: Clone + Send + Sync {
     type DB: Database;
 
+    /// Looks the token up by the hash stored in `TokenData::token`
     fn fetch_by_token(
         &self,
         tx: &mut <Self::DB as Database>::Connection,
-        token_string: &str,
+        token_hash: &str,
     ) -> impl Future<Output = Result<TokenModel, LsAccountManagementError>> + Send;
 
     fn fetch_by_username(
@@ -121,3 +167,151 @@ pub trait TokenRepository: Clone + Send + Sync {
         threshold_epoch_seconds: i64,
     ) -> impl Future<Output = Result<u64, LsAccountManagementError>> + Send;
 }
+
+pub trait AclRepository: Clone + Send + Sync {
+    type DB: Database;
+
+    fn fetch_all_by_resource(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> impl Future<Output = Result<Vec<AclEntryModel>, LsAccountManagementError>> + Send;
+
+    fn fetch_by_resource_and_grantee_optional(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: &Grantee,
+    ) -> impl Future<Output = Result<Option<AclEntryModel>, LsAccountManagementError>> + Send;
+
+    fn fetch_all_by_grantee(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        resource_type: &str,
+        grantee: &Grantee,
+    ) -> impl Future<Output = Result<Vec<AclEntryModel>, LsAccountManagementError>> + Send;
+
+    fn save(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: NewRecord<AclEntryData>,
+    ) -> impl Future<Output = Result<AclEntryModel, LsAccountManagementError>> + Send;
+
+    fn update(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: AclEntryModel,
+    ) -> impl Future<Output = Result<AclEntryModel, LsAccountManagementError>> + Send;
+
+    fn delete(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: AclEntryModel,
+    ) -> impl Future<Output = Result<AclEntryModel, LsAccountManagementError>> + Send;
+
+    fn delete_all_by_resource(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> impl Future<Output = Result<u64, LsAccountManagementError>> + Send;
+
+    fn delete_all_by_grantee(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        grantee: &Grantee,
+    ) -> impl Future<Output = Result<u64, LsAccountManagementError>> + Send;
+}
+
+pub trait AuditRepository: Clone + Send + Sync {
+    type DB: Database;
+
+    fn fetch_all(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        query: &AuditEventQuery,
+    ) -> impl Future<Output = Result<Vec<AuditEventModel>, LsAccountManagementError>> + Send;
+
+    fn save(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: NewRecord<AuditEventData>,
+    ) -> impl Future<Output = Result<AuditEventModel, LsAccountManagementError>> + Send;
+
+    fn update(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: AuditEventModel,
+    ) -> impl Future<Output = Result<AuditEventModel, LsAccountManagementError>> + Send;
+}
+
+pub trait ExternalIdentityRepository: Clone + Send + Sync {
+    type DB: Database;
+
+    fn fetch_by_issuer_and_subject_optional(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        issuer: &str,
+        subject: &str,
+    ) -> impl Future<Output = Result<Option<ExternalIdentityModel>, LsAccountManagementError>> + Send;
+
+    fn fetch_all_by_user_id(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        user_id: i64,
+    ) -> impl Future<Output = Result<Vec<ExternalIdentityModel>, LsAccountManagementError>> + Send;
+
+    fn save(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: NewRecord<ExternalIdentityData>,
+    ) -> impl Future<Output = Result<ExternalIdentityModel, LsAccountManagementError>> + Send;
+
+    fn update(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: ExternalIdentityModel,
+    ) -> impl Future<Output = Result<ExternalIdentityModel, LsAccountManagementError>> + Send;
+
+    fn delete(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: ExternalIdentityModel,
+    ) -> impl Future<Output = Result<ExternalIdentityModel, LsAccountManagementError>> + Send;
+}
+
+pub trait SessionRepository: Clone + Send + Sync {
+    type DB: Database;
+
+    fn fetch_by_session_id_optional(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        session_id: &str,
+    ) -> impl Future<Output = Result<Option<SessionModel>, LsAccountManagementError>> + Send;
+
+    fn fetch_all_by_user_id(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        user_id: i64,
+    ) -> impl Future<Output = Result<Vec<SessionModel>, LsAccountManagementError>> + Send;
+
+    fn save(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: NewRecord<SessionData>,
+    ) -> impl Future<Output = Result<SessionModel, LsAccountManagementError>> + Send;
+
+    fn update(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: SessionModel,
+    ) -> impl Future<Output = Result<SessionModel, LsAccountManagementError>> + Send;
+
+    fn delete(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: SessionModel,
+    ) -> impl Future<Output = Result<SessionModel, LsAccountManagementError>> + Send;
+}
@@ -2,10 +2,21 @@ use crate::repository::AMRepositoryManager;
 use c3p0::sqlx::{migrate::Migrator, *};
 use c3p0::*;
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::repository::mysql::MySqlEventRepositoryManager;
+use lightspeed_event::repository::mysql::mysql_outbox::MySqlOutboxRepository;
 use mysql_account::MySqlAccountRepository;
+use mysql_acl::MySqlAclRepository;
+use mysql_audit::MySqlAuditRepository;
+use mysql_external_identity::MySqlExternalIdentityRepository;
+use mysql_session::MySqlSessionRepository;
 use mysql_token::MySqlTokenRepository;
 
 pub mod mysql_account;
+pub mod mysql_acl;
+pub mod mysql_audit;
+pub mod mysql_external_identity;
+pub mod mysql_session;
 pub mod mysql_token;
 
 static MIGRATOR: Migrator = c3p0::sqlx::migrate!("src_resources/db/mysql/migrations");
@@ -26,12 +37,19 @@ impl AMRepositoryManager for MyAMSqlRepositoryManager {
     type C3P0 = MySqlC3p0Pool;
     type AccountRepo = MySqlAccountRepository;
     type TokenRepo = MySqlTokenRepository;
+    type AclRepo = MySqlAclRepository;
+    type AuditRepo = MySqlAuditRepository;
+    type ExternalIdentityRepo = MySqlExternalIdentityRepository;
+    type SessionRepo = MySqlSessionRepository;
+    type OutboxRepo = MySqlOutboxRepository;
 
     fn c3p0(&self) -> &Self::C3P0 {
         &self.c3p0
     }
 
     async fn start(&self) -> Result<(), LsError> {
+        // The migrations of the module update the events of the outbox
+        MySqlEventRepositoryManager::new(self.c3p0.clone()).start().await?;
         MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
             message: format!("MySqlAuthRepositoryManager - db migration failed: {err:?}"),
         })
@@ -44,4 +62,24 @@ impl AMRepositoryManager for MyAMSqlRepositoryManager {
     fn token_repo(&self) -> Self::TokenRepo {
         MySqlTokenRepository::new()
     }
+
+    fn acl_repo(&self) -> Self::AclRepo {
+        MySqlAclRepository::new()
+    }
+
+    fn audit_repo(&self) -> Self::AuditRepo {
+        MySqlAuditRepository::new()
+    }
+
+    fn external_identity_repo(&self) -> Self::ExternalIdentityRepo {
+        MySqlExternalIdentityRepository::new()
+    }
+
+    fn session_repo(&self) -> Self::SessionRepo {
+        MySqlSessionRepository::new()
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        MySqlOutboxRepository::new()
+    }
 }
@@ -1,9 +1,17 @@
 use crate::error::LsAccountManagementError;
-use crate::model::auth_account::{AccountData, AccountStatus, AuthAccountModel};
+use crate::model::auth_account::{
+    AccountData, AccountFilter, AccountQuery, AccountSortField, AccountStatusKind, AuthAccountModel,
+};
 use crate::repository::AccountRepository;
+use c3p0::sqlx::query::QueryAs;
 use c3p0::sqlx::*;
 use c3p0::*;
 
+/// Name of the status: the accounts stored before `AccountDataToken::V3`
+/// hold it in `status`, the others in `status.kind`
+const STATUS_SQL: &str = "coalesce(JSON_VALUE(data, '$.status.kind' RETURNING CHAR(255)), \
+     JSON_VALUE(data, '$.status' RETURNING CHAR(255)))";
+
 #[derive(Clone)]
 pub struct MySqlAccountRepository {}
 
@@ -19,28 +27,113 @@ impl MySqlAccountRepository {
     }
 }
 
+/// The `where` conditions of the filter, bound by `bind_filter`
+fn filter_conditions(filter: &AccountFilter) -> String {
+    let status_condition = format!("{STATUS_SQL} = ?");
+    let mut conditions = vec!["true"];
+    if filter.username_or_email_prefix.is_some() {
+        conditions.push(
+            "(lower(JSON_VALUE(data, '$.username' RETURNING CHAR(255))) like ? escape '!' \
+              or lower(JSON_VALUE(data, '$.email' RETURNING CHAR(255))) like ? escape '!')",
+        );
+    }
+    if filter.role.is_some() {
+        conditions.push("JSON_CONTAINS(JSON_EXTRACT(data, '$.roles'), JSON_QUOTE(?))");
+    }
+    if filter.status.is_some() {
+        conditions.push(&status_condition);
+    }
+    if filter.created_from_epoch_seconds.is_some() {
+        conditions.push("JSON_VALUE(data, '$.created_date_epoch_seconds' RETURNING SIGNED) >= ?");
+    }
+    if filter.created_to_epoch_seconds.is_some() {
+        conditions.push("JSON_VALUE(data, '$.created_date_epoch_seconds' RETURNING SIGNED) < ?");
+    }
+    conditions.join(" and ")
+}
+
+fn bind_filter<'q, O>(
+    mut sql_query: QueryAs<'q, MySql, O, <MySql as Database>::Arguments>,
+    filter: &AccountFilter,
+) -> QueryAs<'q, MySql, O, <MySql as Database>::Arguments> {
+    if let Some(pattern) = filter.prefix_like_pattern() {
+        sql_query = sql_query.bind(pattern.clone()).bind(pattern);
+    }
+    if let Some(role) = &filter.role {
+        sql_query = sql_query.bind(role.clone());
+    }
+    if let Some(status) = &filter.status {
+        sql_query = sql_query.bind(status.as_ref().to_owned());
+    }
+    if let Some(from_epoch_seconds) = filter.created_from_epoch_seconds {
+        sql_query = sql_query.bind(from_epoch_seconds);
+    }
+    if let Some(to_epoch_seconds) = filter.created_to_epoch_seconds {
+        sql_query = sql_query.bind(to_epoch_seconds);
+    }
+    sql_query
+}
+
+fn sort_column(field: AccountSortField) -> &'static str {
+    match field {
+        AccountSortField::Id => "id",
+        AccountSortField::Username => "JSON_VALUE(data, '$.username' RETURNING CHAR(255))",
+        AccountSortField::Email => "JSON_VALUE(data, '$.email' RETURNING CHAR(255))",
+        AccountSortField::Status => STATUS_SQL,
+        AccountSortField::CreatedDate => "JSON_VALUE(data, '$.created_date_epoch_seconds' RETURNING SIGNED)",
+    }
+}
+
 impl AccountRepository for MySqlAccountRepository {
     type DB = MySql;
 
+    async fn search(
+        &self,
+        tx: &mut MySqlConnection,
+        query: &AccountQuery,
+    ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
+        let order_by = query
+            .sort
+            .iter()
+            .map(|(field, order)| format!("{} {}", sort_column(*field), order.as_sql()))
+            .chain(std::iter::once("id asc".to_owned()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let tail = format!("where {} order by {order_by} limit ? offset ?", filter_conditions(&query.filter));
+
+        Ok(bind_filter(AuthAccountModel::query_with_tail(&tail), &query.filter)
+            .bind(query.limit as i64)
+            .bind(query.offset as i64)
+            .fetch_all(tx)
+            .await?)
+    }
+
+    async fn count(&self, tx: &mut MySqlConnection, filter: &AccountFilter) -> Result<u64, LsAccountManagementError> {
+        let sql = format!("select count(*) from {} where {}", AccountData::TABLE_NAME, filter_conditions(filter));
+        let (count,): (i64,) = bind_filter(query_as(AssertSqlSafe(sql)), filter).fetch_one(tx).await?;
+        Ok(count as u64)
+    }
+
+    async fn count_by_status(&self, tx: &mut MySqlConnection) -> Result<Vec<(String, u64)>, LsAccountManagementError> {
+        let sql = format!("select {STATUS_SQL}, count(*) from {} group by 1", AccountData::TABLE_NAME);
+        let counts: Vec<(String, i64)> = query_as(AssertSqlSafe(sql)).fetch_all(tx).await?;
+        Ok(counts.into_iter().map(|(status, count)| (status, count as u64)).collect())
+    }
+
     async fn fetch_all_by_status(
         &self,
         tx: &mut MySqlConnection,
-        status: AccountStatus,
+        status: AccountStatusKind,
         start_user_id: i64,
         limit: u32,
     ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
-        Ok(AuthAccountModel::query_with_tail(
-            r#"
-            where id >= ? and JSON_VALUE(DATA, '$.status' RETURNING CHAR(255)) = ?
-            order by id asc
-            limit ?
-        "#,
-        )
-        .bind(start_user_id)
-        .bind(status.as_ref())
-        .bind(limit as i64)
-        .fetch_all(tx)
-        .await?)
+        let tail = format!("where id >= ? and {STATUS_SQL} = ? order by id asc limit ?");
+        Ok(AuthAccountModel::query_with_tail(&tail)
+            .bind(start_user_id)
+            .bind(status.as_ref())
+            .bind(limit as i64)
+            .fetch_all(tx)
+            .await?)
     }
 
     async fn fetch_by_id(
@@ -51,6 +144,14 @@ impl AccountRepository for MySqlAccountRepository {
         Ok(tx.fetch_one_by_id::<AccountData>(user_id).await?)
     }
 
+    async fn fetch_by_id_optional(
+        &self,
+        tx: &mut MySqlConnection,
+        user_id: i64,
+    ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+        Ok(tx.fetch_one_optional_by_id::<AccountData>(user_id).await?)
+    }
+
     async fn fetch_by_username(
         &self,
         tx: &mut MySqlConnection,
@@ -69,7 +170,7 @@ impl AccountRepository for MySqlAccountRepository {
     ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
         Ok(AuthAccountModel::query_with_tail(
             r#"
-            where JSON_VALUE(DATA, '$.username' RETURNING CHAR(255)) = ?
+            where JSON_VALUE(DATA, '$.normalized_username' RETURNING CHAR(255)) = ?
             limit 1
         "#,
         )
@@ -85,7 +186,7 @@ impl AccountRepository for MySqlAccountRepository {
     ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
         Ok(AuthAccountModel::query_with_tail(
             r#"
-            where JSON_VALUE(DATA, '$.email' RETURNING CHAR(255)) = ?
+            where JSON_VALUE(DATA, '$.normalized_email' RETURNING CHAR(255)) = ?
             limit 1
         "#,
         )
@@ -0,0 +1,82 @@
+use crate::error::LsAccountManagementError;
+use crate::model::external_identity::{ExternalIdentityData, ExternalIdentityModel};
+use crate::repository::ExternalIdentityRepository;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct MySqlExternalIdentityRepository {}
+
+impl Default for MySqlExternalIdentityRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MySqlExternalIdentityRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ExternalIdentityRepository for MySqlExternalIdentityRepository {
+    type DB = MySql;
+
+    async fn fetch_by_issuer_and_subject_optional(
+        &self,
+        tx: &mut MySqlConnection,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentityModel>, LsAccountManagementError> {
+        Ok(ExternalIdentityModel::query_with_tail(
+            r#"
+            where JSON_VALUE(data, '$.issuer' RETURNING CHAR(255)) = ? and JSON_VALUE(data, '$.subject' RETURNING CHAR(255)) = ?
+            limit 1
+        "#,
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_user_id(
+        &self,
+        tx: &mut MySqlConnection,
+        user_id: i64,
+    ) -> Result<Vec<ExternalIdentityModel>, LsAccountManagementError> {
+        Ok(ExternalIdentityModel::query_with_tail(
+            r#"
+            where JSON_VALUE(data, '$.user_id' RETURNING SIGNED) = ?
+            order by id asc
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut MySqlConnection,
+        model: NewRecord<ExternalIdentityData>,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut MySqlConnection,
+        model: ExternalIdentityModel,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete(
+        &self,
+        tx: &mut MySqlConnection,
+        model: ExternalIdentityModel,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(tx.delete(model).await?)
+    }
+}
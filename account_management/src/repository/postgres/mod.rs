@@ -1,11 +1,22 @@
 use crate::repository::AMRepositoryManager;
 use crate::repository::postgres::pg_account::PgAccountRepository;
+use crate::repository::postgres::pg_acl::PgAclRepository;
+use crate::repository::postgres::pg_audit::PgAuditRepository;
+use crate::repository::postgres::pg_external_identity::PgExternalIdentityRepository;
+use crate::repository::postgres::pg_session::PgSessionRepository;
 use crate::repository::postgres::pg_token::PgTokenRepository;
 use c3p0::sqlx::{migrate::Migrator, *};
 use c3p0::*;
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::repository::postgres::PgEventRepositoryManager;
+use lightspeed_event::repository::postgres::pg_outbox::PgOutboxRepository;
 
 pub mod pg_account;
+pub mod pg_acl;
+pub mod pg_audit;
+pub mod pg_external_identity;
+pub mod pg_session;
 pub mod pg_token;
 
 static MIGRATOR: Migrator = c3p0::sqlx::migrate!("src_resources/db/postgres/migrations");
@@ -26,12 +37,19 @@ impl AMRepositoryManager for PgAMRepositoryManager {
     type C3P0 = PgC3p0Pool;
     type AccountRepo = PgAccountRepository;
     type TokenRepo = PgTokenRepository;
+    type AclRepo = PgAclRepository;
+    type AuditRepo = PgAuditRepository;
+    type ExternalIdentityRepo = PgExternalIdentityRepository;
+    type SessionRepo = PgSessionRepository;
+    type OutboxRepo = PgOutboxRepository;
 
     fn c3p0(&self) -> &Self::C3P0 {
         &self.c3p0
     }
 
     async fn start(&self) -> Result<(), LsError> {
+        // The migrations of the module update the events of the outbox
+        PgEventRepositoryManager::new(self.c3p0.clone()).start().await?;
         MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
             message: format!("PgAuthRepositoryManager - db migration failed: {err:?}"),
         })
@@ -44,4 +62,24 @@ impl AMRepositoryManager for PgAMRepositoryManager {
     fn token_repo(&self) -> Self::TokenRepo {
         PgTokenRepository::new()
     }
+
+    fn acl_repo(&self) -> Self::AclRepo {
+        PgAclRepository::new()
+    }
+
+    fn audit_repo(&self) -> Self::AuditRepo {
+        PgAuditRepository::new()
+    }
+
+    fn external_identity_repo(&self) -> Self::ExternalIdentityRepo {
+        PgExternalIdentityRepository::new()
+    }
+
+    fn session_repo(&self) -> Self::SessionRepo {
+        PgSessionRepository::new()
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        PgOutboxRepository::new()
+    }
 }
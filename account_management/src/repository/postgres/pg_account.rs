@@ -1,9 +1,16 @@
 use crate::error::LsAccountManagementError;
-use crate::model::auth_account::{AccountData, AccountStatus, AuthAccountModel};
+use crate::model::auth_account::{
+    AccountData, AccountFilter, AccountQuery, AccountSortField, AccountStatusKind, AuthAccountModel,
+};
 use crate::repository::AccountRepository;
+use c3p0::sqlx::query::QueryAs;
 use c3p0::sqlx::*;
 use c3p0::*;
 
+/// Name of the status: the accounts stored before `AccountDataToken::V3`
+/// hold it in `status`, the others in `status.kind`
+const STATUS_SQL: &str = "coalesce(data -> 'status' ->> 'kind', data ->> 'status')";
+
 #[derive(Clone)]
 pub struct PgAccountRepository {}
 
@@ -19,28 +26,120 @@ impl PgAccountRepository {
     }
 }
 
+/// The `where` conditions of the filter, bound by `bind_filter`. Returns
+/// them with the number of parameters they use.
+fn filter_conditions(filter: &AccountFilter) -> (String, usize) {
+    let mut conditions = vec!["true".to_owned()];
+    let mut params = 0;
+    let mut next_param = || {
+        params += 1;
+        params
+    };
+    if filter.username_or_email_prefix.is_some() {
+        let param = next_param();
+        conditions.push(format!(
+            "(lower(data ->> 'username') like ${param} escape '!' or lower(data ->> 'email') like ${param} escape '!')"
+        ));
+    }
+    if filter.role.is_some() {
+        conditions.push(format!("data -> 'roles' @> to_jsonb(${}::text)", next_param()));
+    }
+    if filter.status.is_some() {
+        conditions.push(format!("{STATUS_SQL} = ${}", next_param()));
+    }
+    if filter.created_from_epoch_seconds.is_some() {
+        conditions.push(format!("(data ->> 'created_date_epoch_seconds')::bigint >= ${}", next_param()));
+    }
+    if filter.created_to_epoch_seconds.is_some() {
+        conditions.push(format!("(data ->> 'created_date_epoch_seconds')::bigint < ${}", next_param()));
+    }
+    (conditions.join(" and "), params)
+}
+
+fn bind_filter<'q, O>(
+    mut sql_query: QueryAs<'q, Postgres, O, <Postgres as Database>::Arguments>,
+    filter: &AccountFilter,
+) -> QueryAs<'q, Postgres, O, <Postgres as Database>::Arguments> {
+    if let Some(pattern) = filter.prefix_like_pattern() {
+        sql_query = sql_query.bind(pattern);
+    }
+    if let Some(role) = &filter.role {
+        sql_query = sql_query.bind(role.clone());
+    }
+    if let Some(status) = &filter.status {
+        sql_query = sql_query.bind(status.as_ref().to_owned());
+    }
+    if let Some(from_epoch_seconds) = filter.created_from_epoch_seconds {
+        sql_query = sql_query.bind(from_epoch_seconds);
+    }
+    if let Some(to_epoch_seconds) = filter.created_to_epoch_seconds {
+        sql_query = sql_query.bind(to_epoch_seconds);
+    }
+    sql_query
+}
+
+fn sort_column(field: AccountSortField) -> &'static str {
+    match field {
+        AccountSortField::Id => "id",
+        AccountSortField::Username => "data ->> 'username'",
+        AccountSortField::Email => "data ->> 'email'",
+        AccountSortField::Status => STATUS_SQL,
+        AccountSortField::CreatedDate => "(data ->> 'created_date_epoch_seconds')::bigint",
+    }
+}
+
 impl AccountRepository for PgAccountRepository {
     type DB = Postgres;
 
+    async fn search(
+        &self,
+        tx: &mut PgConnection,
+        query: &AccountQuery,
+    ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
+        let order_by = query
+            .sort
+            .iter()
+            .map(|(field, order)| format!("{} {}", sort_column(*field), order.as_sql()))
+            .chain(std::iter::once("id asc".to_owned()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let (conditions, params) = filter_conditions(&query.filter);
+        let tail = format!("where {conditions} order by {order_by} limit ${} offset ${}", params + 1, params + 2);
+
+        Ok(bind_filter(AuthAccountModel::query_with_tail(&tail), &query.filter)
+            .bind(query.limit as i64)
+            .bind(query.offset as i64)
+            .fetch_all(tx)
+            .await?)
+    }
+
+    async fn count(&self, tx: &mut PgConnection, filter: &AccountFilter) -> Result<u64, LsAccountManagementError> {
+        let (conditions, _) = filter_conditions(filter);
+        let sql = format!("select count(*) from {} where {conditions}", AccountData::TABLE_NAME);
+        let (count,): (i64,) = bind_filter(query_as(AssertSqlSafe(sql)), filter).fetch_one(tx).await?;
+        Ok(count as u64)
+    }
+
+    async fn count_by_status(&self, tx: &mut PgConnection) -> Result<Vec<(String, u64)>, LsAccountManagementError> {
+        let sql = format!("select {STATUS_SQL}, count(*) from {} group by 1", AccountData::TABLE_NAME);
+        let counts: Vec<(String, i64)> = query_as(AssertSqlSafe(sql)).fetch_all(tx).await?;
+        Ok(counts.into_iter().map(|(status, count)| (status, count as u64)).collect())
+    }
+
     async fn fetch_all_by_status(
         &self,
         tx: &mut PgConnection,
-        status: AccountStatus,
+        status: AccountStatusKind,
         start_user_id: i64,
         limit: u32,
     ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
-        Ok(AuthAccountModel::query_with_tail(
-            r#"
-            where id >= $1 and DATA ->> 'status' = $2
-            order by id asc
-            limit $3
-        "#,
-        )
-        .bind(start_user_id)
-        .bind(status.as_ref())
-        .bind(limit as i64)
-        .fetch_all(tx)
-        .await?)
+        let tail = format!("where id >= $1 and {STATUS_SQL} = $2 order by id asc limit $3");
+        Ok(AuthAccountModel::query_with_tail(&tail)
+            .bind(start_user_id)
+            .bind(status.as_ref())
+            .bind(limit as i64)
+            .fetch_all(tx)
+            .await?)
     }
 
     async fn fetch_by_id(
@@ -51,6 +150,14 @@ impl AccountRepository for PgAccountRepository {
         Ok(tx.fetch_one_by_id::<AccountData>(user_id).await?)
     }
 
+    async fn fetch_by_id_optional(
+        &self,
+        tx: &mut PgConnection,
+        user_id: i64,
+    ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+        Ok(tx.fetch_one_optional_by_id::<AccountData>(user_id).await?)
+    }
+
     async fn fetch_by_username(
         &self,
         tx: &mut PgConnection,
@@ -69,7 +176,7 @@ impl AccountRepository for PgAccountRepository {
     ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
         Ok(AuthAccountModel::query_with_tail(
             r#"
-            where DATA ->> 'username' = $1
+            where DATA ->> 'normalized_username' = $1
             limit 1
         "#,
         )
@@ -85,7 +192,7 @@ impl AccountRepository for PgAccountRepository {
     ) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
         Ok(AuthAccountModel::query_with_tail(
             r#"
-            where DATA ->> 'email' = $1
+            where DATA ->> 'normalized_email' = $1
             limit 1
         "#,
         )
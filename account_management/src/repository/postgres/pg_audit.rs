@@ -0,0 +1,82 @@
+use crate::error::LsAccountManagementError;
+use crate::model::audit::{AuditEventData, AuditEventModel, AuditEventQuery};
+use crate::repository::AuditRepository;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct PgAuditRepository {}
+
+impl Default for PgAuditRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgAuditRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl AuditRepository for PgAuditRepository {
+    type DB = Postgres;
+
+    async fn fetch_all(
+        &self,
+        tx: &mut PgConnection,
+        query: &AuditEventQuery,
+    ) -> Result<Vec<AuditEventModel>, LsAccountManagementError> {
+        let mut conditions = vec!["id >= $1".to_owned()];
+        if query.entity_type.is_some() {
+            conditions.push(format!("data ->> 'entity_type' = ${}", conditions.len() + 1));
+        }
+        if query.entity_id.is_some() {
+            conditions.push(format!("data ->> 'entity_id' = ${}", conditions.len() + 1));
+        }
+        if query.actor_id.is_some() {
+            conditions.push(format!("(data -> 'actor' ->> 'id')::bigint = ${}", conditions.len() + 1));
+        }
+        if query.from_epoch_seconds.is_some() {
+            conditions.push(format!("(data ->> 'created_date_epoch_seconds')::bigint >= ${}", conditions.len() + 1));
+        }
+        if query.to_epoch_seconds.is_some() {
+            conditions.push(format!("(data ->> 'created_date_epoch_seconds')::bigint < ${}", conditions.len() + 1));
+        }
+        let tail = format!("where {} order by id asc limit ${}", conditions.join(" and "), conditions.len() + 1);
+
+        let mut sql_query = AuditEventModel::query_with_tail(&tail).bind(query.start_id);
+        if let Some(entity_type) = &query.entity_type {
+            sql_query = sql_query.bind(entity_type);
+        }
+        if let Some(entity_id) = &query.entity_id {
+            sql_query = sql_query.bind(entity_id);
+        }
+        if let Some(actor_id) = query.actor_id {
+            sql_query = sql_query.bind(actor_id);
+        }
+        if let Some(from_epoch_seconds) = query.from_epoch_seconds {
+            sql_query = sql_query.bind(from_epoch_seconds);
+        }
+        if let Some(to_epoch_seconds) = query.to_epoch_seconds {
+            sql_query = sql_query.bind(to_epoch_seconds);
+        }
+        Ok(sql_query.bind(query.limit as i64).fetch_all(tx).await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut PgConnection,
+        model: NewRecord<AuditEventData>,
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut PgConnection,
+        model: AuditEventModel,
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        Ok(tx.update(model).await?)
+    }
+}
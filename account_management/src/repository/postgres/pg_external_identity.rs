@@ -0,0 +1,82 @@
+use crate::error::LsAccountManagementError;
+use crate::model::external_identity::{ExternalIdentityData, ExternalIdentityModel};
+use crate::repository::ExternalIdentityRepository;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct PgExternalIdentityRepository {}
+
+impl Default for PgExternalIdentityRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgExternalIdentityRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ExternalIdentityRepository for PgExternalIdentityRepository {
+    type DB = Postgres;
+
+    async fn fetch_by_issuer_and_subject_optional(
+        &self,
+        tx: &mut PgConnection,
+        issuer: &str,
+        subject: &str,
+    ) -> Result<Option<ExternalIdentityModel>, LsAccountManagementError> {
+        Ok(ExternalIdentityModel::query_with_tail(
+            r#"
+            where data ->> 'issuer' = $1 and data ->> 'subject' = $2
+            limit 1
+        "#,
+        )
+        .bind(issuer)
+        .bind(subject)
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_user_id(
+        &self,
+        tx: &mut PgConnection,
+        user_id: i64,
+    ) -> Result<Vec<ExternalIdentityModel>, LsAccountManagementError> {
+        Ok(ExternalIdentityModel::query_with_tail(
+            r#"
+            where (data ->> 'user_id')::bigint = $1
+            order by id asc
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut PgConnection,
+        model: NewRecord<ExternalIdentityData>,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut PgConnection,
+        model: ExternalIdentityModel,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete(
+        &self,
+        tx: &mut PgConnection,
+        model: ExternalIdentityModel,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        Ok(tx.delete(model).await?)
+    }
+}
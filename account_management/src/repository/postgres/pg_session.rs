@@ -0,0 +1,80 @@
+use crate::error::LsAccountManagementError;
+use crate::model::session::{SessionData, SessionModel};
+use crate::repository::SessionRepository;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct PgSessionRepository {}
+
+impl Default for PgSessionRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgSessionRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl SessionRepository for PgSessionRepository {
+    type DB = Postgres;
+
+    async fn fetch_by_session_id_optional(
+        &self,
+        tx: &mut PgConnection,
+        session_id: &str,
+    ) -> Result<Option<SessionModel>, LsAccountManagementError> {
+        Ok(SessionModel::query_with_tail(
+            r#"
+            where data ->> 'session_id' = $1
+            limit 1
+        "#,
+        )
+        .bind(session_id)
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_user_id(
+        &self,
+        tx: &mut PgConnection,
+        user_id: i64,
+    ) -> Result<Vec<SessionModel>, LsAccountManagementError> {
+        Ok(SessionModel::query_with_tail(
+            r#"
+            where (data ->> 'user_id')::bigint = $1
+            order by id asc
+        "#,
+        )
+        .bind(user_id)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut PgConnection,
+        model: NewRecord<SessionData>,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut PgConnection,
+        model: SessionModel,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete(
+        &self,
+        tx: &mut PgConnection,
+        model: SessionModel,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        Ok(tx.delete(model).await?)
+    }
+}
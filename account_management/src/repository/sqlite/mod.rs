@@ -4,10 +4,21 @@ use c3p0::{
     sqlx::{migrate::Migrator, *},
 };
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::repository::sqlite::SqliteEventRepositoryManager;
+use lightspeed_event::repository::sqlite::sqlite_outbox::SqliteOutboxRepository;
 use sqlite_account::SqliteAccountRepository;
+use sqlite_acl::SqliteAclRepository;
+use sqlite_audit::SqliteAuditRepository;
+use sqlite_external_identity::SqliteExternalIdentityRepository;
+use sqlite_session::SqliteSessionRepository;
 use sqlite_token::SqliteTokenRepository;
 
 pub mod sqlite_account;
+pub mod sqlite_acl;
+pub mod sqlite_audit;
+pub mod sqlite_external_identity;
+pub mod sqlite_session;
 pub mod sqlite_token;
 
 static MIGRATOR: Migrator = ::sqlx::migrate!("src_resources/db/sqlite/migrations");
@@ -28,12 +39,19 @@ impl AMRepositoryManager for SqliteAMRepositoryManager {
     type C3P0 = SqliteC3p0Pool;
     type AccountRepo = SqliteAccountRepository;
     type TokenRepo = SqliteTokenRepository;
+    type AclRepo = SqliteAclRepository;
+    type AuditRepo = SqliteAuditRepository;
+    type ExternalIdentityRepo = SqliteExternalIdentityRepository;
+    type SessionRepo = SqliteSessionRepository;
+    type OutboxRepo = SqliteOutboxRepository;
 
     fn c3p0(&self) -> &Self::C3P0 {
         &self.c3p0
     }
 
     async fn start(&self) -> Result<(), LsError> {
+        // The migrations of the module update the events of the outbox
+        SqliteEventRepositoryManager::new(self.c3p0.clone()).start().await?;
         MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
             message: format!("SqliteAuthRepositoryManager - db migration failed: {err:?}"),
         })
@@ -46,4 +64,24 @@ impl AMRepositoryManager for SqliteAMRepositoryManager {
     fn token_repo(&self) -> Self::TokenRepo {
         SqliteTokenRepository::new()
     }
+
+    fn acl_repo(&self) -> Self::AclRepo {
+        SqliteAclRepository::new()
+    }
+
+    fn audit_repo(&self) -> Self::AuditRepo {
+        SqliteAuditRepository::new()
+    }
+
+    fn external_identity_repo(&self) -> Self::ExternalIdentityRepo {
+        SqliteExternalIdentityRepository::new()
+    }
+
+    fn session_repo(&self) -> Self::SessionRepo {
+        SqliteSessionRepository::new()
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        SqliteOutboxRepository::new()
+    }
 }
@@ -0,0 +1,139 @@
+use crate::error::LsAccountManagementError;
+use crate::model::acl::{AclEntryData, AclEntryModel, grantee_type_and_id};
+use crate::repository::AclRepository;
+use ::sqlx::AssertSqlSafe;
+use c3p0::sqlx::*;
+use c3p0::*;
+use lightspeed_core::service::auth::Grantee;
+
+#[derive(Clone)]
+pub struct SqliteAclRepository {}
+
+impl Default for SqliteAclRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqliteAclRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl AclRepository for SqliteAclRepository {
+    type DB = Sqlite;
+
+    async fn fetch_all_by_resource(
+        &self,
+        tx: &mut SqliteConnection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        Ok(AclEntryModel::query_with_tail(
+            r#"
+            where data ->> '$.resource_type' = ? and data ->> '$.resource_id' = ?
+            order by id asc
+        "#,
+        )
+        .bind(resource_type)
+        .bind(resource_id)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_by_resource_and_grantee_optional(
+        &self,
+        tx: &mut SqliteConnection,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: &Grantee,
+    ) -> Result<Option<AclEntryModel>, LsAccountManagementError> {
+        let (grantee_type, grantee_id) = grantee_type_and_id(grantee);
+        Ok(AclEntryModel::query_with_tail(
+            r#"
+            where data ->> '$.resource_type' = ? and data ->> '$.resource_id' = ?
+            and data ->> '$.grantee.type' = ? and CAST(data ->> '$.grantee.id' AS TEXT) = ?
+            limit 1
+        "#,
+        )
+        .bind(resource_type)
+        .bind(resource_id)
+        .bind(grantee_type)
+        .bind(grantee_id)
+        .fetch_optional(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_grantee(
+        &self,
+        tx: &mut SqliteConnection,
+        resource_type: &str,
+        grantee: &Grantee,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        let (grantee_type, grantee_id) = grantee_type_and_id(grantee);
+        Ok(AclEntryModel::query_with_tail(
+            r#"
+            where data ->> '$.resource_type' = ? and data ->> '$.grantee.type' = ? and CAST(data ->> '$.grantee.id' AS TEXT) = ?
+            order by id asc
+        "#,
+        )
+        .bind(resource_type)
+        .bind(grantee_type)
+        .bind(grantee_id)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut SqliteConnection,
+        model: NewRecord<AclEntryData>,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut SqliteConnection,
+        model: AclEntryModel,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete(
+        &self,
+        tx: &mut SqliteConnection,
+        model: AclEntryModel,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        Ok(tx.delete(model).await?)
+    }
+
+    async fn delete_all_by_resource(
+        &self,
+        tx: &mut SqliteConnection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<u64, LsAccountManagementError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE data ->> '$.resource_type' = ? AND data ->> '$.resource_id' = ?",
+            <AclEntryData as DataType>::TABLE_NAME
+        );
+        let res = query(AssertSqlSafe(sql)).bind(resource_type).bind(resource_id).execute(tx).await?;
+        Ok(res.rows_affected())
+    }
+
+    async fn delete_all_by_grantee(
+        &self,
+        tx: &mut SqliteConnection,
+        grantee: &Grantee,
+    ) -> Result<u64, LsAccountManagementError> {
+        let (grantee_type, grantee_id) = grantee_type_and_id(grantee);
+        let sql = format!(
+            "DELETE FROM {} WHERE data ->> '$.grantee.type' = ? AND CAST(data ->> '$.grantee.id' AS TEXT) = ?",
+            <AclEntryData as DataType>::TABLE_NAME
+        );
+        let res = query(AssertSqlSafe(sql)).bind(grantee_type).bind(grantee_id).execute(tx).await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,82 @@
+use crate::error::LsAccountManagementError;
+use crate::model::audit::{AuditEventData, AuditEventModel, AuditEventQuery};
+use crate::repository::AuditRepository;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct SqliteAuditRepository {}
+
+impl Default for SqliteAuditRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqliteAuditRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl AuditRepository for SqliteAuditRepository {
+    type DB = Sqlite;
+
+    async fn fetch_all(
+        &self,
+        tx: &mut SqliteConnection,
+        query: &AuditEventQuery,
+    ) -> Result<Vec<AuditEventModel>, LsAccountManagementError> {
+        let mut conditions = vec!["id >= ?".to_owned()];
+        if query.entity_type.is_some() {
+            conditions.push("data ->> '$.entity_type' = ?".to_owned());
+        }
+        if query.entity_id.is_some() {
+            conditions.push("data ->> '$.entity_id' = ?".to_owned());
+        }
+        if query.actor_id.is_some() {
+            conditions.push("data ->> '$.actor.id' = ?".to_owned());
+        }
+        if query.from_epoch_seconds.is_some() {
+            conditions.push("data ->> '$.created_date_epoch_seconds' >= ?".to_owned());
+        }
+        if query.to_epoch_seconds.is_some() {
+            conditions.push("data ->> '$.created_date_epoch_seconds' < ?".to_owned());
+        }
+        let tail = format!("where {} order by id asc limit ?", conditions.join(" and "));
+
+        let mut sql_query = AuditEventModel::query_with_tail(&tail).bind(query.start_id);
+        if let Some(entity_type) = &query.entity_type {
+            sql_query = sql_query.bind(entity_type);
+        }
+        if let Some(entity_id) = &query.entity_id {
+            sql_query = sql_query.bind(entity_id);
+        }
+        if let Some(actor_id) = query.actor_id {
+            sql_query = sql_query.bind(actor_id);
+        }
+        if let Some(from_epoch_seconds) = query.from_epoch_seconds {
+            sql_query = sql_query.bind(from_epoch_seconds);
+        }
+        if let Some(to_epoch_seconds) = query.to_epoch_seconds {
+            sql_query = sql_query.bind(to_epoch_seconds);
+        }
+        Ok(sql_query.bind(query.limit as i64).fetch_all(tx).await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut SqliteConnection,
+        model: NewRecord<AuditEventData>,
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut SqliteConnection,
+        model: AuditEventModel,
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        Ok(tx.update(model).await?)
+    }
+}
@@ -26,7 +26,7 @@ impl TokenRepository for SqliteTokenRepository {
     async fn fetch_by_token(
         &self,
         tx: &mut SqliteConnection,
-        token_string: &str,
+        token_hash: &str,
     ) -> Result<TokenModel, LsAccountManagementError> {
         Ok(TokenModel::query_with_tail(
             r#"
@@ -34,7 +34,7 @@ impl TokenRepository for SqliteTokenRepository {
             limit 1
         "#,
         )
-        .bind(token_string)
+        .bind(token_hash)
         .fetch_one(tx)
         .await?)
     }
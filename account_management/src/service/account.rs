@@ -3,89 +3,614 @@ use crate::dto::change_password_dto::ChangePasswordDto;
 use crate::dto::create_login_dto::CreateLoginDto;
 use crate::dto::reset_password_dto::ResetPasswordDto;
 use crate::error::LsAccountManagementError;
-use crate::model::auth_account::{AccountData, AccountStatus, AuthAccountModel};
+use crate::model::audit::AuditActor;
+use crate::model::auth_account::{
+    AccountData, AccountDataExport, AccountPage, AccountProfile, AccountProfilePatch, AccountQuery, AccountSortField,
+    AccountStats, AccountStatus, AccountStatusKind, AuthAccountModel, MfaState, SortOrder, TokenExport,
+};
+use crate::model::event::{
+    AccountActivated, AccountAnonymized, AccountCreated, AccountDisabled, ActivationTokenGenerated,
+    EmailChangeRequested, EmailChanged, MagicLoginRequested, PasswordChanged, PasswordReset, PasswordResetRequested,
+};
 use crate::model::token::{TokenModel, TokenType};
-use crate::repository::{AMRepositoryManager, AccountRepository};
+use crate::repository::{
+    AMRepositoryManager, AccountRepository, AclRepository, ExternalIdentityRepository, SessionRepository,
+};
+use crate::service::audit::LsAMAuditService;
+use crate::service::breached_password::{BreachedPasswordChecker, NoBreachedPasswordChecker};
+use crate::service::data_export::{AccountDataExporter, ErasedAccountDataExporter};
+use crate::service::identifier::{lookup_forms, normalize_identifier};
+use crate::service::lifecycle::{AccountLifecycleEvent, AccountLifecycleListener, ErasedAccountLifecycleListener};
 use crate::service::password_codec::LsPasswordCodecService;
 use crate::service::token::LsTokenService;
 use c3p0::sqlx::Database;
 use c3p0::*;
-use lightspeed_core::service::auth::Auth;
-use lightspeed_core::utils::current_epoch_seconds;
+use lightspeed_core::service::auth::{Auth, Grantee};
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use lightspeed_event::repository::OutboxRepository;
+use lightspeed_event::service::publisher::LsEventPublisher;
+use lightspeed_validator::{FieldValidator, ValidationError};
 use log::*;
+use parking_lot::RwLock;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use strum::{AsRefStr, Display};
 
 pub const WRONG_TYPE: &str = "WRONG_TYPE";
 
+/// Entity type of the audit events written for accounts
+pub const ACCOUNT_AUDIT_ENTITY_TYPE: &str = "ACCOUNT";
+
+/// Code of the error returned when different accounts have the same
+/// normalized username or email
+pub const IDENTIFIER_CONFLICT: &str = "IDENTIFIER_CONFLICT";
+
+/// Code of the error returned when an account is suspended until a time
+/// already passed
+pub const SUSPENSION_ALREADY_EXPIRED: &str = "SUSPENSION_ALREADY_EXPIRED";
+
+#[derive(Debug, Clone, Copy, PartialEq, AsRefStr, Display)]
+pub enum AccountAuditAction {
+    Created,
+    Activated,
+    PasswordReset,
+    PasswordChanged,
+    RolesAdded,
+    RolesDeleted,
+    DataChanged,
+    Disabled,
+    Reactivated,
+    Deleted,
+    MfaEnabled,
+    MfaDisabled,
+    Unlocked,
+    EmailChangeRequested,
+    EmailChanged,
+    Anonymized,
+    PasswordLoginEnabled,
+    PasswordLoginDisabled,
+    ExternalIdentityLinked,
+    ExternalIdentityUnlinked,
+    ProfileChanged,
+    SessionRevoked,
+    AllSessionsRevoked,
+    Suspended,
+    Unsuspended,
+    Locked,
+}
+
+/// Result of a login with correct credentials
+#[derive(Debug)]
+pub enum LoginOutcome {
+    Authenticated(Auth),
+    /// The account has MFA enabled: `mfa_token` must be exchanged, before it
+    /// expires, together with a valid code through `LsAMMfaService`.
+    MfaRequired {
+        mfa_token: String,
+        expire_at_epoch_seconds: i64,
+    },
+}
+
+/// A data exporter with the name of the section of its data
+type DataExporter = (String, Arc<dyn ErasedAccountDataExporter>);
+
+type LifecycleListener<RepoManager> = Arc<dyn ErasedAccountLifecycleListener<<RepoManager as AMRepositoryManager>::DB>>;
+
+/// Validates the values of a profile attribute
+pub type ProfileAttributeValidator = Arc<dyn FieldValidator<String, ValidationError, ()> + Send + Sync>;
+
+/// Prefix of the profile attributes in the keys of the validation errors,
+/// matching the `data` field of `CreateLoginDto`
+const PROFILE_FIELD_PREFIX: &str = "data";
+
+/// Prefix of the types of the events published by this module
+const AM_EVENT_TYPE_PREFIX: &str = "AM_";
+
+/// Fields of the event payloads holding the username or an email
+const EVENT_USERNAME_FIELDS: [&str; 1] = ["username"];
+const EVENT_EMAIL_FIELDS: [&str; 3] = ["email", "new_email", "old_email"];
+
 #[derive(Clone)]
 pub struct LsAMAccountService<RepoManager: AMRepositoryManager> {
     c3p0: RepoManager::C3P0,
     auth_config: AMConfig,
     auth_repo: RepoManager::AccountRepo,
+    acl_repo: RepoManager::AclRepo,
+    external_identity_repo: RepoManager::ExternalIdentityRepo,
+    session_repo: RepoManager::SessionRepo,
+    outbox_repo: RepoManager::OutboxRepo,
     password_service: Arc<LsPasswordCodecService>,
     token_service: Arc<LsTokenService<RepoManager>>,
+    audit_service: Arc<LsAMAuditService<RepoManager>>,
+    event_publisher: Arc<LsEventPublisher<RepoManager::OutboxRepo>>,
+    breached_password_checker: Arc<dyn BreachedPasswordChecker>,
+    data_exporters: Arc<RwLock<Vec<DataExporter>>>,
+    lifecycle_listeners: Arc<RwLock<Vec<LifecycleListener<RepoManager>>>>,
+    profile_validators: Arc<RwLock<BTreeMap<String, Vec<ProfileAttributeValidator>>>>,
 }
 
 impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
+    /// Builds the service on the repositories of `repo_manager`. Besides the
+    /// accounts, it deletes or scrubs the sessions, external identities,
+    /// ACL entries, audit events and outbox events of the accounts deleted
+    /// or anonymized.
     pub fn new(
-        c3p0: RepoManager::C3P0,
+        repo_manager: &RepoManager,
         auth_config: AMConfig,
         token_service: Arc<LsTokenService<RepoManager>>,
         password_service: Arc<LsPasswordCodecService>,
-        auth_repo: RepoManager::AccountRepo,
+        audit_service: Arc<LsAMAuditService<RepoManager>>,
+        event_publisher: Arc<LsEventPublisher<RepoManager::OutboxRepo>>,
     ) -> Self {
-        LsAMAccountService { c3p0, auth_config, auth_repo, password_service, token_service }
+        LsAMAccountService {
+            c3p0: repo_manager.c3p0().clone(),
+            auth_config,
+            auth_repo: repo_manager.account_repo(),
+            acl_repo: repo_manager.acl_repo(),
+            external_identity_repo: repo_manager.external_identity_repo(),
+            session_repo: repo_manager.session_repo(),
+            outbox_repo: repo_manager.outbox_repo(),
+            password_service,
+            token_service,
+            audit_service,
+            event_publisher,
+            breached_password_checker: Arc::new(NoBreachedPasswordChecker),
+            data_exporters: Default::default(),
+            lifecycle_listeners: Default::default(),
+            profile_validators: Default::default(),
+        }
+    }
+
+    /// Rejects the new passwords found in known data breaches by `checker`.
+    /// By default no password is considered breached.
+    pub fn with_breached_password_checker(mut self, checker: Arc<dyn BreachedPasswordChecker>) -> Self {
+        self.breached_password_checker = checker;
+        self
+    }
+
+    /// Adds the data returned by `exporter` to the `section` of every
+    /// account data export
+    pub fn add_data_exporter<X: AccountDataExporter>(&self, section: impl Into<String>, exporter: X) {
+        self.data_exporters.write().push((section.into(), Arc::new(exporter)));
+    }
+
+    /// Notifies `listener` of the changes of the accounts, in the
+    /// transactions of the changes. The listeners are invoked in the order
+    /// they are added.
+    pub fn add_lifecycle_listener<L: AccountLifecycleListener<RepoManager::DB>>(&self, listener: L) {
+        self.lifecycle_listeners.write().push(Arc::new(listener));
+    }
+
+    /// Validates with `validator` the values of the profile attribute `key`,
+    /// at signup and on every change. Attributes without validators accept
+    /// any value.
+    pub fn add_profile_validator<V>(&self, key: impl Into<String>, validator: V)
+    where
+        V: FieldValidator<String, ValidationError, ()> + Send + Sync + 'static,
+    {
+        self.profile_validators.write().entry(key.into()).or_default().push(Arc::new(validator));
+    }
+
+    /// Checks the new values of the profile attributes against the
+    /// validators and the limits of the configuration
+    fn validate_profile<'a>(
+        &self,
+        attributes: impl Iterator<Item = (&'a String, &'a String)>,
+    ) -> Result<(), LsAccountManagementError> {
+        let validators = self.profile_validators.read();
+        let mut field_errors = BTreeMap::new();
+        for (key, value) in attributes {
+            let mut errors = vec![];
+            if value.chars().count() > self.auth_config.max_profile_attribute_length {
+                errors
+                    .push(format!("Value longer than [{}] characters", self.auth_config.max_profile_attribute_length));
+            }
+            for validator in validators.get(key).into_iter().flatten() {
+                if let Err(err) = validator.validate(value, &()) {
+                    errors.push(err.to_string());
+                }
+            }
+            if !errors.is_empty() {
+                field_errors.insert(format!("{PROFILE_FIELD_PREFIX}.{key}"), errors);
+            }
+        }
+        if field_errors.is_empty() { Ok(()) } else { Err(LsAccountManagementError::ValidationError { field_errors }) }
+    }
+
+    fn check_profile_size(&self, profile: &BTreeMap<String, String>) -> Result<(), LsAccountManagementError> {
+        if profile.len() > self.auth_config.max_profile_attributes {
+            return Err(LsAccountManagementError::ValidationError {
+                field_errors: BTreeMap::from([(
+                    PROFILE_FIELD_PREFIX.to_owned(),
+                    vec![format!("More than [{}] attributes", self.auth_config.max_profile_attributes)],
+                )]),
+            });
+        }
+        Ok(())
+    }
+
+    fn normalize_identifier(&self, identifier: &str) -> String {
+        normalize_identifier(&self.auth_config.identifier_normalization, identifier)
+    }
+
+    /// Sets the normalized forms of the username and of the email
+    fn set_normalized_identifiers(&self, data: &mut AccountData) {
+        data.normalized_username = self.normalize_identifier(&data.username);
+        data.normalized_email = self.normalize_identifier(&data.email);
+    }
+
+    async fn notify_listeners_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        event: AccountLifecycleEvent,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        let listeners = self.lifecycle_listeners.read().clone();
+        for listener in listeners {
+            listener.notify(conn, event, account).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the account, active again if its suspension has expired, or
+    /// the error telling why it cannot log in
+    async fn active_for_login_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user: AuthAccountModel,
+        now: i64,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        let user = match &user.data.status {
+            AccountStatus::Suspended { until_epoch_seconds, .. } if *until_epoch_seconds <= now => {
+                info!("The suspension of user [{}] expired", user.data.username);
+                self.unsuspend_with_conn(conn, user, None).await?
+            }
+            _ => user,
+        };
+        match &user.data.status {
+            AccountStatus::Active => Ok(user),
+            AccountStatus::Suspended { until_epoch_seconds, reason } => {
+                Err(LsAccountManagementError::AccountSuspended { until: *until_epoch_seconds, reason: reason.clone() })
+            }
+            AccountStatus::Locked { reason } => {
+                Err(LsAccountManagementError::AccountLockedByAdmin { reason: reason.clone() })
+            }
+            AccountStatus::PendingActivation | AccountStatus::Disabled => {
+                Err(LsAccountManagementError::InactiveUser(user.data.username.to_string()))
+            }
+        }
+    }
+
+    async fn unsuspend_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        mut user: AuthAccountModel,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        let before = user.data.clone();
+        user.data.status = AccountStatus::Active;
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Unsuspended,
+            user.id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Reactivated, &user).await?;
+        Ok(user)
+    }
+
+    /// Writes the audit event of a change to an account. The password hash
+    /// is never part of the event.
+    async fn audit_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        actor: Option<AuditActor>,
+        action: AccountAuditAction,
+        user_id: i64,
+        before_and_after: (Option<&AccountData>, Option<&AccountData>),
+    ) -> Result<(), LsAccountManagementError> {
+        let (before, after) = before_and_after;
+        self.audit_service
+            .record_with_conn(
+                conn,
+                actor,
+                action.as_ref(),
+                ACCOUNT_AUDIT_ENTITY_TYPE,
+                &user_id.to_string(),
+                (before.map(audit_snapshot), after.map(audit_snapshot)),
+            )
+            .await?;
+        Ok(())
     }
 
-    pub async fn login(&self, username: &str, password: &str) -> Result<Auth, LsAccountManagementError> {
-        self.c3p0.transaction(async |conn| self.login_with_conn(conn, username, password).await).await
+    pub async fn login(&self, username: &str, password: &str) -> Result<LoginOutcome, LsAccountManagementError> {
+        // The failed attempt counted on a wrong password must be committed
+        // even though the login fails.
+        self.c3p0
+            .transaction(async |conn| match self.login_with_conn(conn, username, password).await {
+                Err(LsAccountManagementError::WrongCredentials) => Ok(Err(LsAccountManagementError::WrongCredentials)),
+                result => result.map(Ok),
+            })
+            .await?
     }
 
+    /// Verifies the credentials. A wrong password increments the failed
+    /// logins of the account, so the caller must commit the transaction also
+    /// when `WrongCredentials` is returned; after `max_failed_login_attempts`
+    /// consecutive failures the account is locked for a while. A locked
+    /// account answers `WrongCredentials` too, like an unknown username, so
+    /// that the lockout does not reveal which usernames are registered; the
+    /// magic link, external and MFA logins apply the same policy.
+    ///
+    /// On success, a password hash that `needs_rehash` is replaced by a new
+    /// one computed with the configured parameters.
     pub async fn login_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         username: &str,
         password: &str,
-    ) -> Result<Auth, LsAccountManagementError> {
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
         debug!("login attempt with username [{username}]");
-        let model = self.auth_repo.fetch_by_username_optional(conn, username).await?;
+        let now = current_epoch_seconds();
 
-        if let Some(user) = model
-            && self.password_service.verify_match(password, &user.data.password).await?
-        {
-            match &user.data.status {
-                AccountStatus::Active => {}
-                _ => {
-                    return Err(LsAccountManagementError::InactiveUser(username.to_string()));
-                }
-            };
+        // Unknown users, locked accounts and wrong passwords all cost one
+        // password verification, to prevent username enumeration via
+        // response time.
+        let Some(mut user) = fetch_by_username_optional(&self.auth_repo, conn, &self.auth_config, username).await?
+        else {
+            let _ = self.password_service.verify_match(password, self.password_service.dummy_hash()).await;
+            return Err(LsAccountManagementError::WrongCredentials);
+        };
 
-            let creation_ts_seconds = current_epoch_seconds();
+        if let Some(until) = locked_until(&self.auth_config, &user.data, now) {
+            let _ = self.password_service.verify_match(password, self.password_service.dummy_hash()).await;
+            debug!("login of username [{username}] rejected: account locked until [{until}]");
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
 
-            if let Some(expiration_secs) = self.auth_config.password_expiration_seconds {
-                let password_set_at = user.data.password_updated_date_epoch_seconds;
-                if creation_ts_seconds.saturating_sub(password_set_at) >= expiration_secs as i64 {
-                    return Err(LsAccountManagementError::ExpiredPassword(username.to_string()));
-                }
+        if !self.password_service.verify_match(password, &user.data.password).await? {
+            count_failed_login(&mut user.data, now);
+            self.auth_repo.update(conn, user).await?;
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
+
+        let mut user = self.active_for_login_with_conn(conn, user, now).await?;
+
+        if user.data.password_login_disabled {
+            return Err(LsAccountManagementError::PasswordLoginDisabled(username.to_string()));
+        }
+
+        if let Some(expiration_secs) = self.auth_config.password_expiration_seconds {
+            let password_set_at = user.data.password_updated_date_epoch_seconds;
+            if now.saturating_sub(password_set_at) >= expiration_secs as i64 {
+                return Err(LsAccountManagementError::ExpiredPassword(username.to_string()));
             }
+        }
 
-            let expiration_ts_seconds =
-                creation_ts_seconds + (self.auth_config.auth_session_max_validity_minutes as i64 * 60);
+        let reset_failed_logins = user.data.failed_login_attempts > 0;
+        if reset_failed_logins {
+            user.data.failed_login_attempts = 0;
+            user.data.last_failed_login_epoch_seconds = None;
+        }
 
-            return Ok(Auth::new(
-                user.id,
-                user.data.username,
-                user.data.roles,
-                creation_ts_seconds,
-                expiration_ts_seconds,
-            ));
-        } else {
-            // Even out timing between "no such user" and "wrong password" to
-            // prevent username enumeration via response time.
-            let _ = self.password_service.verify_match(password, self.password_service.dummy_hash()).await;
+        // Hashes of imported accounts or computed with weaker parameters are
+        // upgraded while the plain password is at hand
+        let rehash = self.password_service.needs_rehash(&user.data.password);
+        if rehash {
+            info!("Rehash the password of user [{username}]");
+            user.data.password = self.password_service.hash_password(password).await?;
+        }
+
+        if reset_failed_logins || rehash {
+            user = self.auth_repo.update(conn, user).await?;
+        }
+
+        self.login_outcome_with_conn(conn, user, now).await
+    }
+
+    /// Completes the login of an account whose first factor was verified
+    async fn login_outcome_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user: AuthAccountModel,
+        now: i64,
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
+        if user.data.mfa.is_enabled() {
+            debug!("login of username [{}] requires the second factor", user.data.username);
+            let token = self
+                .token_service
+                .generate_and_save_token_valid_for_with_conn(
+                    conn,
+                    &user.data.username,
+                    TokenType::MfaPending,
+                    self.auth_config.mfa_pending_token_validity_seconds as i64,
+                )
+                .await?;
+            return Ok(LoginOutcome::MfaRequired {
+                mfa_token: token.data.token,
+                expire_at_epoch_seconds: token.data.expire_at_epoch_seconds,
+            });
+        }
+
+        Ok(LoginOutcome::Authenticated(new_auth(&self.auth_config, user, now)))
+    }
+
+    /// Logs in `user_id` after its identity was verified by an external
+    /// provider. The account must be active; a locked account answers
+    /// `WrongCredentials`, as in `login_with_conn`.
+    #[cfg(feature = "oidc")]
+    pub(crate) async fn external_login_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
+        let user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        let now = current_epoch_seconds();
+        let user = self.active_for_login_with_conn(conn, user, now).await?;
+
+        if let Some(until) = locked_until(&self.auth_config, &user.data, now) {
+            debug!("external login of user_id [{user_id}] rejected: account locked until [{until}]");
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
+
+        self.login_outcome_with_conn(conn, user, now).await
+    }
+
+    pub async fn request_magic_login(
+        &self,
+        email: &str,
+        device_fingerprint: Option<&str>,
+    ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.request_magic_login_with_conn(conn, email, device_fingerprint).await)
+            .await
+    }
+
+    /// Issues a single-use token to log in the active account with the given
+    /// email, bound to the requesting device if `device_fingerprint` is set.
+    /// The token reaches the user through the `MagicLoginRequested` event.
+    ///
+    /// Like `generate_new_activation_token_by_username_and_email_with_conn`,
+    /// every not eligible request fails with the same `WrongCredentials`, so
+    /// it cannot be used to find out which accounts are registered.
+    pub async fn request_magic_login_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        email: &str,
+        device_fingerprint: Option<&str>,
+    ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
+        debug!("Request magic login for email [{email}]");
+        let generic_failure = || LsAccountManagementError::WrongCredentials;
+
+        let Some(user) = fetch_by_email_optional(&self.auth_repo, conn, &self.auth_config, email).await? else {
+            debug!("request_magic_login: email [{email}] not found");
+            return Err(generic_failure());
+        };
+
+        if !matches!(user.data.status, AccountStatus::Active) {
+            debug!("request_magic_login: username [{}] not Active", user.data.username);
+            return Err(generic_failure());
+        }
+
+        if locked_until(&self.auth_config, &user.data, current_epoch_seconds()).is_some() {
+            debug!("request_magic_login: username [{}] locked", user.data.username);
+            return Err(generic_failure());
+        }
+
+        // Only the last requested link can be used
+        let existing_tokens = self.token_service.fetch_all_by_username_with_conn(conn, &user.data.username).await?;
+        for token in existing_tokens.into_iter().filter(|t| t.data.token_type == TokenType::MagicLogin) {
+            self.token_service.delete_with_conn(conn, token).await?;
+        }
+
+        info!("Send magic login token to user [{}]", user.data.username);
+        let token = self
+            .token_service
+            .generate_and_save_device_token_with_conn(
+                conn,
+                &user.data.username,
+                TokenType::MagicLogin,
+                self.auth_config.magic_login_token_validity_seconds as i64,
+                device_fingerprint.map(str::to_owned),
+            )
+            .await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &MagicLoginRequested {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                    sealed_login_token: self.token_service.seal_token(&token.data.token)?,
+                },
+            )
+            .await?;
+        Ok((user, token))
+    }
+
+    pub async fn login_with_magic_token(
+        &self,
+        login_token: &str,
+        device_fingerprint: Option<&str>,
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| {
+                self.login_with_magic_token_with_conn(conn, login_token, device_fingerprint).await
+            })
+            .await
+    }
+
+    /// Exchanges a token issued by `request_magic_login_with_conn` for a
+    /// session. The token is consumed; a token bound to a device is only
+    /// accepted with the same `device_fingerprint`.
+    pub async fn login_with_magic_token_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        login_token: &str,
+        device_fingerprint: Option<&str>,
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
+        let token = self.token_service.fetch_by_token_with_conn(conn, login_token, true).await?;
+        debug!("Magic login called with token [{}] of user [{}]", token.id, token.data.username);
+        match &token.data.token_type {
+            TokenType::MagicLogin => {}
+            _ => return Err(LsAccountManagementError::TokenNotValid),
         };
+        if token.data.device_fingerprint.as_deref().is_some_and(|bound| Some(bound) != device_fingerprint) {
+            debug!("Magic login token of user [{}] used from another device", token.data.username);
+            return Err(LsAccountManagementError::TokenNotValid);
+        }
+
+        let user = fetch_by_username(&self.auth_repo, conn, &self.auth_config, &token.data.username).await?;
+        let now = current_epoch_seconds();
+        let user = self.active_for_login_with_conn(conn, user, now).await?;
+
+        if let Some(until) = locked_until(&self.auth_config, &user.data, now) {
+            debug!("Magic login of user [{}] rejected: account locked until [{until}]", token.data.username);
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
+
+        self.token_service.delete_with_conn(conn, token).await?;
+        self.login_outcome_with_conn(conn, user, now).await
+    }
+
+    pub async fn set_password_login_enabled(
+        &self,
+        user_id: i64,
+        enabled: bool,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.set_password_login_enabled_with_conn(conn, user_id, enabled, actor).await)
+            .await
+    }
+
+    /// Allows or forbids the login of the account with its password; the
+    /// magic link login is always allowed
+    pub async fn set_password_login_enabled_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        enabled: bool,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Set password login enabled [{enabled}] for user with user_id [{user_id:?}]");
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        if user.data.password_login_disabled != enabled {
+            return Ok(user);
+        }
 
-        Err(LsAccountManagementError::WrongCredentials)
+        let before = user.data.clone();
+        user.data.password_login_disabled = !enabled;
+        let user = self.auth_repo.update(conn, user).await?;
+        let action =
+            if enabled { AccountAuditAction::PasswordLoginEnabled } else { AccountAuditAction::PasswordLoginDisabled };
+        self.audit_with_conn(conn, actor.map(AuditActor::from), action, user_id, (Some(&before), Some(&user.data)))
+            .await?;
+        Ok(user)
     }
 
     pub async fn create_user(
@@ -104,6 +629,10 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             "Create login attempt with username [{:?}] and email [{}]",
             create_login_dto.username, create_login_dto.email
         );
+        let profile: BTreeMap<String, String> = create_login_dto.data.into_iter().collect();
+        self.check_profile_size(&profile)?;
+        self.validate_profile(profile.iter())?;
+        self.check_not_breached(&create_login_dto.password).await?;
         let hashed_password = self.password_service.hash_password(&create_login_dto.password).await?;
 
         let username = match &create_login_dto.username {
@@ -118,10 +647,16 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         }
         .clone();
 
-        if let Some(_existing_user) = self.auth_repo.fetch_by_username_optional(conn, &username).await? {
+        let normalized_username = self.normalize_identifier(&username);
+        let normalized_email = self.normalize_identifier(&create_login_dto.email);
+        if let Some(_existing_user) =
+            fetch_by_username_optional(&self.auth_repo, conn, &self.auth_config, &username).await?
+        {
             return Err(LsAccountManagementError::UsernameAlreadyUsed);
         }
-        if let Some(_existing_email) = self.auth_repo.fetch_by_email_optional(conn, &create_login_dto.email).await? {
+        if let Some(_existing_email) =
+            fetch_by_email_optional(&self.auth_repo, conn, &self.auth_config, &create_login_dto.email).await?
+        {
             return Err(LsAccountManagementError::EmailAlreadyUsed);
         }
 
@@ -133,19 +668,100 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
                 NewRecord::new(AccountData {
                     username,
                     email: create_login_dto.email,
+                    normalized_username,
+                    normalized_email,
                     password: hashed_password,
                     roles: self.auth_config.default_roles_on_account_creation.clone(),
                     created_date_epoch_seconds: now,
                     password_updated_date_epoch_seconds: now,
+                    password_history: vec![],
                     status: AccountStatus::PendingActivation,
+                    mfa: MfaState::Disabled,
+                    failed_login_attempts: 0,
+                    last_failed_login_epoch_seconds: None,
+                    pending_email: None,
+                    anonymized_date_epoch_seconds: None,
+                    password_login_disabled: false,
+                    language: Some(create_login_dto.language),
+                    profile,
                 }),
             )
             .await?;
+        self.audit_with_conn(
+            conn,
+            None,
+            AccountAuditAction::Created,
+            auth_account_model.id,
+            (None, Some(&auth_account_model.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Created, &auth_account_model).await?;
 
         let token = self.generate_activation_token_with_conn(conn, &auth_account_model.data.username).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &AccountCreated {
+                    user_id: auth_account_model.id,
+                    username: auth_account_model.data.username.clone(),
+                    email: auth_account_model.data.email.clone(),
+                    sealed_activation_token: self.token_service.seal_token(&token.data.token)?,
+                },
+            )
+            .await?;
         Ok((auth_account_model, token))
     }
 
+    /// Creates an active account for a user authenticated by an external
+    /// provider. The account cannot log in with a password, as it has none
+    /// the user knows.
+    #[cfg(feature = "oidc")]
+    pub(crate) async fn provision_account_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        email: &str,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Provision account for email [{email}]");
+        let normalized_email = self.normalize_identifier(email);
+        if fetch_by_username_optional(&self.auth_repo, conn, &self.auth_config, email).await?.is_some() {
+            return Err(LsAccountManagementError::UsernameAlreadyUsed);
+        }
+        if fetch_by_email_optional(&self.auth_repo, conn, &self.auth_config, email).await?.is_some() {
+            return Err(LsAccountManagementError::EmailAlreadyUsed);
+        }
+
+        let now = current_epoch_seconds();
+        let user = self
+            .auth_repo
+            .save(
+                conn,
+                NewRecord::new(AccountData {
+                    username: email.to_owned(),
+                    email: email.to_owned(),
+                    normalized_username: normalized_email.clone(),
+                    normalized_email,
+                    password: self.password_service.hash_password(&new_hyphenated_uuid()).await?,
+                    roles: self.auth_config.default_roles_on_account_creation.clone(),
+                    created_date_epoch_seconds: now,
+                    password_updated_date_epoch_seconds: now,
+                    password_history: vec![],
+                    status: AccountStatus::Active,
+                    mfa: MfaState::Disabled,
+                    failed_login_attempts: 0,
+                    last_failed_login_epoch_seconds: None,
+                    pending_email: None,
+                    anonymized_date_epoch_seconds: None,
+                    password_login_disabled: true,
+                    language: None,
+                    profile: BTreeMap::new(),
+                }),
+            )
+            .await?;
+        self.audit_with_conn(conn, None, AccountAuditAction::Created, user.id, (None, Some(&user.data))).await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Created, &user).await?;
+        Ok(user)
+    }
+
     async fn generate_activation_token_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
@@ -189,7 +805,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         // single opaque error to the caller.
         let generic_failure = || LsAccountManagementError::WrongCredentials;
 
-        let user = match self.auth_repo.fetch_by_username_optional(conn, username).await? {
+        let user = match fetch_by_username_optional(&self.auth_repo, conn, &self.auth_config, username).await? {
             Some(u) => u,
             None => {
                 debug!("generate_new_activation_token: username [{username}] not found");
@@ -197,7 +813,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             }
         };
 
-        if user.data.email != email {
+        if !lookup_forms(&self.auth_config.identifier_normalization, email).contains(&user.data.normalized_email) {
             debug!("generate_new_activation_token: email mismatch for username [{username}]");
             return Err(generic_failure());
         }
@@ -210,13 +826,24 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         // Invalidate every previous activation token so an attacker cannot
         // re-use one that leaked. Reset-password tokens belong to a different
         // lifecycle and are left untouched.
-        let existing_tokens = self.token_service.fetch_all_by_username_with_conn(conn, username).await?;
+        let existing_tokens = self.token_service.fetch_all_by_username_with_conn(conn, &user.data.username).await?;
         for token in existing_tokens.into_iter().filter(|t| t.data.token_type == TokenType::AccountActivation) {
             self.token_service.delete_with_conn(conn, token).await?;
         }
 
         info!("Send new activation token to user [{username}]");
         let token = self.generate_activation_token_with_conn(conn, &user.data.username).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &ActivationTokenGenerated {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                    sealed_activation_token: self.token_service.seal_token(&token.data.token)?,
+                },
+            )
+            .await?;
         Ok((user, token))
     }
 
@@ -229,9 +856,8 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         conn: &mut <RepoManager::DB as Database>::Connection,
         activation_token: &str,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
-        debug!("Activate user called with token [{activation_token}]");
-
         let token = self.token_service.fetch_by_token_with_conn(conn, activation_token, true).await?;
+        debug!("Activate user called with token [{}] of user [{}]", token.id, token.data.username);
 
         match &token.data.token_type {
             TokenType::AccountActivation => {}
@@ -240,7 +866,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
 
         info!("Activate user [{}]", token.data.username);
 
-        let mut user = self.auth_repo.fetch_by_username(conn, &token.data.username).await?;
+        let mut user = fetch_by_username(&self.auth_repo, conn, &self.auth_config, &token.data.username).await?;
 
         match &user.data.status {
             AccountStatus::PendingActivation => {}
@@ -251,8 +877,28 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
 
         self.token_service.delete_with_conn(conn, token).await?;
 
+        let before = user.data.clone();
         user.data.status = AccountStatus::Active;
         user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            Some(account_actor(&user)),
+            AccountAuditAction::Activated,
+            user.id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Activated, &user).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &AccountActivated {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                },
+            )
+            .await?;
         Ok(user)
     }
 
@@ -270,7 +916,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
     ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
         info!("Generate reset password token for username [{username}]");
 
-        let user = self.auth_repo.fetch_by_username(conn, username).await?;
+        let user = fetch_by_username(&self.auth_repo, conn, &self.auth_config, username).await?;
 
         match &user.data.status {
             AccountStatus::Active => {}
@@ -279,12 +925,50 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             }
         };
 
-        let token =
-            self.token_service.generate_and_save_token_with_conn(conn, username, TokenType::ResetPassword).await?;
+        let token = self
+            .token_service
+            .generate_and_save_token_with_conn(conn, &user.data.username, TokenType::ResetPassword)
+            .await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &PasswordResetRequested {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                    sealed_reset_token: self.token_service.seal_token(&token.data.token)?,
+                },
+            )
+            .await?;
 
         Ok((user, token))
     }
 
+    pub async fn generate_reset_password_token_by_email(
+        &self,
+        email: &str,
+    ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.generate_reset_password_token_by_email_with_conn(conn, email).await)
+            .await
+    }
+
+    /// Same as `generate_reset_password_token_with_conn` for the account
+    /// with the given email. Unknown emails fail with `WrongCredentials`.
+    pub async fn generate_reset_password_token_by_email_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        email: &str,
+    ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
+        match fetch_by_email_optional(&self.auth_repo, conn, &self.auth_config, email).await? {
+            Some(user) => self.generate_reset_password_token_with_conn(conn, &user.data.username).await,
+            None => {
+                debug!("generate_reset_password_token: email [{email}] not found");
+                Err(LsAccountManagementError::WrongCredentials)
+            }
+        }
+    }
+
     pub async fn reset_password_by_token(
         &self,
         reset_password_dto: ResetPasswordDto,
@@ -292,16 +976,17 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         self.c3p0.transaction(async |conn| self.reset_password_by_token_with_conn(conn, reset_password_dto).await).await
     }
 
+    /// Sets the password of the user of the reset token and revokes its
+    /// sessions
     pub async fn reset_password_by_token_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         reset_password_dto: ResetPasswordDto,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
-        debug!("Reset password called with token [{}]", reset_password_dto.token);
-
         // Validate expiry: an expired reset-password token must not be usable
         // to reset the password.
         let token = self.token_service.fetch_by_token_with_conn(conn, &reset_password_dto.token, true).await?;
+        debug!("Reset password called with token [{}] of user [{}]", token.id, token.data.username);
 
         info!("Reset password of user [{}]", token.data.username);
 
@@ -310,7 +995,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             _ => return Err(LsAccountManagementError::TokenNotValid),
         };
 
-        let mut user = self.auth_repo.fetch_by_username(conn, &token.data.username).await?;
+        let mut user = fetch_by_username(&self.auth_repo, conn, &self.auth_config, &token.data.username).await?;
 
         match &user.data.status {
             AccountStatus::Active => {}
@@ -321,20 +1006,47 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
 
         self.token_service.delete_with_conn(conn, token).await?;
 
-        user.data.password = self.password_service.hash_password(&reset_password_dto.password).await?;
-        user.data.password_updated_date_epoch_seconds = current_epoch_seconds();
+        self.replace_password(&mut user.data, &reset_password_dto.password).await?;
         user = self.auth_repo.update(conn, user).await?;
+        self.revoke_sessions_with_conn(conn, user.id).await?;
+        self.audit_with_conn(
+            conn,
+            Some(account_actor(&user)),
+            AccountAuditAction::PasswordReset,
+            user.id,
+            (None, None),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::PasswordChanged, &user).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &PasswordReset {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                },
+            )
+            .await?;
         Ok(user)
     }
 
-    pub async fn change_password(&self, dto: ChangePasswordDto) -> Result<AuthAccountModel, LsAccountManagementError> {
-        self.c3p0.transaction(async |conn| self.change_password_with_conn(conn, dto).await).await
+    pub async fn change_password(
+        &self,
+        dto: ChangePasswordDto,
+        current_session_id: Option<&str>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.change_password_with_conn(conn, dto, current_session_id).await).await
     }
 
+    /// Changes the password and revokes the other sessions of the account,
+    /// keeping `current_session_id`, the session of the caller; with `None`
+    /// every session is revoked.
     pub async fn change_password_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         dto: ChangePasswordDto,
+        current_session_id: Option<&str>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         info!("Reset password of user_id [{:?}]", dto.user_id);
 
@@ -352,13 +1064,76 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             return Err(LsAccountManagementError::WrongCredentials);
         }
 
-        user.data.password = self.password_service.hash_password(&dto.new_password).await?;
-        user.data.password_updated_date_epoch_seconds = current_epoch_seconds();
+        self.replace_password(&mut user.data, &dto.new_password).await?;
 
         user = self.auth_repo.update(conn, user).await?;
+        self.revoke_other_sessions_with_conn(conn, user.id, current_session_id).await?;
+        self.audit_with_conn(
+            conn,
+            Some(account_actor(&user)),
+            AccountAuditAction::PasswordChanged,
+            user.id,
+            (None, None),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::PasswordChanged, &user).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &PasswordChanged {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                },
+            )
+            .await?;
         Ok(user)
     }
 
+    /// Sets the new password of the account, moving the current one to the
+    /// password history. Fails with `PasswordReused` if the new password
+    /// matches one of the last `password_history_size` passwords.
+    async fn replace_password(
+        &self,
+        data: &mut AccountData,
+        new_password: &str,
+    ) -> Result<(), LsAccountManagementError> {
+        self.check_not_breached(new_password).await?;
+
+        let history_size = self.auth_config.password_history_size as usize;
+        if history_size > 0 {
+            let recent_hashes = std::iter::once(&data.password).chain(data.password_history.iter()).take(history_size);
+            for hash in recent_hashes {
+                if self.password_service.verify_match(new_password, hash).await? {
+                    return Err(LsAccountManagementError::PasswordReused);
+                }
+            }
+        }
+
+        let old_hash = std::mem::replace(&mut data.password, self.password_service.hash_password(new_password).await?);
+        data.password_history.insert(0, old_hash);
+        data.password_history.truncate(history_size.saturating_sub(1));
+        data.password_updated_date_epoch_seconds = current_epoch_seconds();
+        Ok(())
+    }
+
+    /// Fails with `BreachedPassword` if the password appears in at least
+    /// `breached_password_min_count` known data breaches
+    async fn check_not_breached(&self, password: &str) -> Result<(), LsAccountManagementError> {
+        let checker = self.breached_password_checker.clone();
+        let plain = password.to_owned();
+        let breach_count =
+            tokio::task::spawn_blocking(move || checker.breach_count(&plain)).await.map_err(|err| {
+                LsAccountManagementError::BreachedPasswordCheckError { message: format!("task join error: {err:?}") }
+            })??;
+
+        if breach_count >= self.auth_config.breached_password_min_count.max(1) {
+            debug!("Password rejected, found in [{breach_count}] breaches");
+            return Err(LsAccountManagementError::BreachedPassword);
+        }
+        Ok(())
+    }
+
     pub async fn fetch_by_user_id(&self, user_id: i64) -> Result<AuthAccountModel, LsAccountManagementError> {
         self.c3p0.transaction(async |conn| self.fetch_by_user_id_with_conn(conn, user_id).await).await
     }
@@ -382,12 +1157,12 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         username: &str,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         debug!("Fetch user with username [{username}]");
-        self.auth_repo.fetch_by_username(conn, username).await
+        fetch_by_username(&self.auth_repo, conn, &self.auth_config, username).await
     }
 
     pub async fn fetch_all_by_status(
         &self,
-        status: AccountStatus,
+        status: AccountStatusKind,
         start_user_id: i64,
         limit: u32,
     ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
@@ -399,7 +1174,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
     pub async fn fetch_all_by_status_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
-        status: AccountStatus,
+        status: AccountStatusKind,
         start_user_id: i64,
         limit: u32,
     ) -> Result<Vec<AuthAccountModel>, LsAccountManagementError> {
@@ -407,12 +1182,49 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         self.auth_repo.fetch_all_by_status(conn, status, start_user_id, limit).await
     }
 
-    pub async fn add_roles(
+    pub async fn search(&self, query: &AccountQuery) -> Result<AccountPage, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.search_with_conn(conn, query).await).await
+    }
+
+    /// Returns the page of accounts selected by the query, together with the
+    /// number of all the accounts matching its filter
+    pub async fn search_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        query: &AccountQuery,
+    ) -> Result<AccountPage, LsAccountManagementError> {
+        debug!("Search accounts with query {query:?}");
+        let accounts = self.auth_repo.search(conn, query).await?;
+        let total = self.auth_repo.count(conn, &query.filter).await?;
+        Ok(AccountPage { accounts, total })
+    }
+
+    pub async fn stats(&self) -> Result<AccountStats, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.stats_with_conn(conn).await).await
+    }
+
+    pub async fn stats_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+    ) -> Result<AccountStats, LsAccountManagementError> {
+        let mut stats = AccountStats { total: 0, count_by_status: vec![] };
+        for (status, count) in self.auth_repo.count_by_status(conn).await? {
+            stats.total += count;
+            match status.parse::<AccountStatusKind>() {
+                Ok(status) => stats.count_by_status.push((status, count)),
+                Err(_) => warn!("Unknown status [{status}] of [{count}] accounts"),
+            }
+        }
+        Ok(stats)
+    }
+
+    pub async fn add_roles(
         &self,
         user_id: i64,
         roles: &[String],
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
-        self.c3p0.transaction(async |conn| self.add_roles_with_conn(conn, user_id, roles).await).await
+        self.c3p0.transaction(async |conn| self.add_roles_with_conn(conn, user_id, roles, actor).await).await
     }
 
     pub async fn add_roles_with_conn(
@@ -420,24 +1232,37 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         conn: &mut <RepoManager::DB as Database>::Connection,
         user_id: i64,
         roles: &[String],
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         info!("Add roles [{roles:?}] to user_id [{user_id:?}]");
 
         let mut account = self.fetch_by_user_id_with_conn(conn, user_id).await?;
+        let before = account.data.clone();
         for role in roles {
             if !account.data.roles.contains(role) {
                 account.data.roles.push(role.to_owned())
             }
         }
-        self.auth_repo.update(conn, account).await
+        let account = self.auth_repo.update(conn, account).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::RolesAdded,
+            user_id,
+            (Some(&before), Some(&account.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::RolesChanged, &account).await?;
+        Ok(account)
     }
 
     pub async fn delete_roles(
         &self,
         user_id: i64,
         roles: &[String],
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
-        self.c3p0.transaction(async |conn| self.delete_roles_with_conn(conn, user_id, roles).await).await
+        self.c3p0.transaction(async |conn| self.delete_roles_with_conn(conn, user_id, roles, actor).await).await
     }
 
     pub async fn delete_roles_with_conn(
@@ -445,14 +1270,26 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         conn: &mut <RepoManager::DB as Database>::Connection,
         user_id: i64,
         roles: &[String],
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         info!("delete roles [{roles:?}] to user_id [{user_id:?}]");
 
         let mut account = self.fetch_by_user_id_with_conn(conn, user_id).await?;
+        let before = account.data.clone();
         for role in roles {
             account.data.roles.retain(|r| r != role);
         }
-        self.auth_repo.update(conn, account).await
+        let account = self.auth_repo.update(conn, account).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::RolesDeleted,
+            user_id,
+            (Some(&before), Some(&account.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::RolesChanged, &account).await?;
+        Ok(account)
     }
 
     pub async fn change_user_data(
@@ -460,26 +1297,40 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         user_id: i64,
         new_username: Option<String>,
         new_email: Option<String>,
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         self.c3p0
-            .transaction(async |conn| self.change_user_data_with_conn(conn, user_id, new_username, new_email).await)
+            .transaction(async |conn| {
+                self.change_user_data_with_conn(conn, user_id, new_username, new_email, actor).await
+            })
             .await
     }
 
+    /// Changes username and email of an account immediately, e.g. by an
+    /// administrator. Users changing their own email must go through
+    /// `request_email_change_with_conn` instead.
     pub async fn change_user_data_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         user_id: i64,
         new_username: Option<String>,
         new_email: Option<String>,
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         info!(
             "Change user data of user_id [{user_id:?}]. New username: [{new_username:?}]. New email: [{new_email:?}]"
         );
 
         let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        let before = user.data.clone();
 
         if let Some(username) = new_username {
+            if let Some(existing) =
+                fetch_by_username_optional(&self.auth_repo, conn, &self.auth_config, &username).await?
+                && existing.id != user_id
+            {
+                return Err(LsAccountManagementError::UsernameAlreadyUsed);
+            }
             info!(
                 "Change user data of user_id [{:?}]. Old username: [{}] New username: [{}]",
                 user_id, user.data.username, username
@@ -488,6 +1339,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         }
 
         if let Some(email) = new_email {
+            self.check_email_not_used_with_conn(conn, user_id, &email).await?;
             info!(
                 "Change user data of user_id [{:?}]. Old email: [{}] New email: [{}]",
                 user_id, user.data.email, email
@@ -495,17 +1347,238 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             user.data.email = email;
         }
 
-        self.auth_repo.update(conn, user).await
+        self.set_normalized_identifiers(&mut user.data);
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::DataChanged,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        Ok(user)
+    }
+
+    pub async fn fetch_profile(&self, user_id: i64) -> Result<AccountProfile, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.fetch_profile_with_conn(conn, user_id).await).await
+    }
+
+    pub async fn fetch_profile_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<AccountProfile, LsAccountManagementError> {
+        debug!("Fetch profile of user_id [{user_id:?}]");
+        let user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        Ok(AccountProfile { language: user.data.language, attributes: user.data.profile })
+    }
+
+    pub async fn update_profile(
+        &self,
+        user_id: i64,
+        patch: AccountProfilePatch,
+        actor: Option<&Auth>,
+    ) -> Result<AccountProfile, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.update_profile_with_conn(conn, user_id, patch, actor).await).await
+    }
+
+    /// Applies `patch` to the profile of the account. The new attribute
+    /// values are validated before any change is saved.
+    pub async fn update_profile_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        patch: AccountProfilePatch,
+        actor: Option<&Auth>,
+    ) -> Result<AccountProfile, LsAccountManagementError> {
+        info!("Update profile of user_id [{user_id:?}]");
+        self.validate_profile(
+            patch.attributes.iter().filter_map(|(key, value)| value.as_ref().map(|value| (key, value))),
+        )?;
+
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        if user.data.is_anonymized() {
+            return Err(LsAccountManagementError::AnonymizedUser(user_id));
+        }
+        let before = user.data.clone();
+
+        if let Some(language) = patch.language {
+            user.data.language = Some(language);
+        }
+        for (key, value) in patch.attributes {
+            match value {
+                Some(value) => user.data.profile.insert(key, value),
+                None => user.data.profile.remove(&key),
+            };
+        }
+        self.check_profile_size(&user.data.profile)?;
+
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::ProfileChanged,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        Ok(AccountProfile { language: user.data.language, attributes: user.data.profile })
+    }
+
+    pub async fn request_email_change(
+        &self,
+        user_id: i64,
+        new_email: &str,
+    ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.request_email_change_with_conn(conn, user_id, new_email).await).await
     }
 
-    pub async fn disable_by_user_id(&self, user_id: i64) -> Result<AuthAccountModel, LsAccountManagementError> {
-        self.c3p0.transaction(async |conn| self.disable_by_user_id_with_conn(conn, user_id).await).await
+    /// Starts the change of the email of an account. The new email is stored
+    /// as pending and replaces the current one only once confirmed with the
+    /// returned token by `confirm_email_change_with_conn`. A new request
+    /// invalidates the previous ones.
+    pub async fn request_email_change_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        new_email: &str,
+    ) -> Result<(AuthAccountModel, TokenModel), LsAccountManagementError> {
+        info!("Request email change of user_id [{user_id}] to [{new_email}]");
+
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        match &user.data.status {
+            AccountStatus::Active => {}
+            _ => return Err(LsAccountManagementError::InactiveUser(user.data.username.to_string())),
+        };
+
+        self.check_email_not_used_with_conn(conn, user_id, new_email).await?;
+        if lookup_forms(&self.auth_config.identifier_normalization, new_email).contains(&user.data.normalized_email) {
+            return Err(LsAccountManagementError::EmailAlreadyUsed);
+        }
+
+        let existing_tokens = self.token_service.fetch_all_by_username_with_conn(conn, &user.data.username).await?;
+        for token in existing_tokens.into_iter().filter(|t| t.data.token_type == TokenType::EmailChange) {
+            self.token_service.delete_with_conn(conn, token).await?;
+        }
+
+        let before = user.data.clone();
+        user.data.pending_email = Some(new_email.to_owned());
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            Some(account_actor(&user)),
+            AccountAuditAction::EmailChangeRequested,
+            user.id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+
+        let token = self
+            .token_service
+            .generate_and_save_token_with_conn(conn, &user.data.username, TokenType::EmailChange)
+            .await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &EmailChangeRequested {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                    new_email: new_email.to_owned(),
+                    sealed_change_token: self.token_service.seal_token(&token.data.token)?,
+                },
+            )
+            .await?;
+        Ok((user, token))
+    }
+
+    pub async fn confirm_email_change(&self, change_token: &str) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.confirm_email_change_with_conn(conn, change_token).await).await
     }
 
+    /// Replaces the email of the account with the pending one. Fails with
+    /// `EmailAlreadyUsed` if, in the meantime, another account took it.
+    pub async fn confirm_email_change_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        change_token: &str,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        let token = self.token_service.fetch_by_token_with_conn(conn, change_token, true).await?;
+        debug!("Confirm email change called with token [{}] of user [{}]", token.id, token.data.username);
+        match &token.data.token_type {
+            TokenType::EmailChange => {}
+            _ => return Err(LsAccountManagementError::TokenNotValid),
+        };
+
+        let mut user = fetch_by_username(&self.auth_repo, conn, &self.auth_config, &token.data.username).await?;
+        match &user.data.status {
+            AccountStatus::Active => {}
+            _ => return Err(LsAccountManagementError::InactiveUser(user.data.username.to_string())),
+        };
+
+        self.token_service.delete_with_conn(conn, token).await?;
+
+        let Some(new_email) = user.data.pending_email.clone() else {
+            return Err(LsAccountManagementError::TokenNotValid);
+        };
+        self.check_email_not_used_with_conn(conn, user.id, &new_email).await?;
+
+        info!("Change email of user [{}] from [{}] to [{}]", user.data.username, user.data.email, new_email);
+        let before = user.data.clone();
+        user.data.email = new_email;
+        user.data.pending_email = None;
+        self.set_normalized_identifiers(&mut user.data);
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            Some(account_actor(&user)),
+            AccountAuditAction::EmailChanged,
+            user.id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &EmailChanged {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    old_email: before.email,
+                    email: user.data.email.clone(),
+                },
+            )
+            .await?;
+        Ok(user)
+    }
+
+    /// Fails with `EmailAlreadyUsed` if another account has the given email
+    async fn check_email_not_used_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        email: &str,
+    ) -> Result<(), LsAccountManagementError> {
+        match fetch_by_email_optional(&self.auth_repo, conn, &self.auth_config, email).await? {
+            Some(existing) if existing.id != user_id => Err(LsAccountManagementError::EmailAlreadyUsed),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn disable_by_user_id(
+        &self,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.disable_by_user_id_with_conn(conn, user_id, actor).await).await
+    }
+
+    /// Disables the account and revokes its sessions
     pub async fn disable_by_user_id_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         user_id: i64,
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         debug!("Disable user with user_id [{user_id:?}]");
         let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
@@ -517,16 +1590,35 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             }
         };
 
+        let before = user.data.clone();
         user.data.status = AccountStatus::Disabled;
-        self.auth_repo.update(conn, user).await
+        let user = self.auth_repo.update(conn, user).await?;
+        self.revoke_sessions_with_conn(conn, user_id).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Disabled,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Disabled, &user).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &AccountDisabled { user_id, username: user.data.username.clone(), email: user.data.email.clone() },
+            )
+            .await?;
+        Ok(user)
     }
 
     pub async fn reactivate_disabled_user_by_user_id(
         &self,
         user_id: i64,
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         self.c3p0
-            .transaction(async |conn| self.reactivate_disabled_user_by_user_id_with_conn(conn, user_id).await)
+            .transaction(async |conn| self.reactivate_disabled_user_by_user_id_with_conn(conn, user_id, actor).await)
             .await
     }
 
@@ -534,6 +1626,7 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         user_id: i64,
+        actor: Option<&Auth>,
     ) -> Result<AuthAccountModel, LsAccountManagementError> {
         debug!("Reactivate disabled user with user_id [{user_id:?}]");
         let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
@@ -542,21 +1635,677 @@ impl<RepoManager: AMRepositoryManager> LsAMAccountService<RepoManager> {
             AccountStatus::Disabled => {}
             _ => return Err(LsAccountManagementError::NotDisabledUser(user.data.username.to_string())),
         };
+        if user.data.is_anonymized() {
+            return Err(LsAccountManagementError::AnonymizedUser(user_id));
+        }
 
+        let before = user.data.clone();
         user.data.status = AccountStatus::Active;
-        self.auth_repo.update(conn, user).await
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Reactivated,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Reactivated, &user).await?;
+        Ok(user)
+    }
+
+    pub async fn suspend_by_user_id(
+        &self,
+        user_id: i64,
+        until_epoch_seconds: i64,
+        reason: &str,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| {
+                self.suspend_by_user_id_with_conn(conn, user_id, until_epoch_seconds, reason, actor).await
+            })
+            .await
+    }
+
+    /// Suspends the account until `until_epoch_seconds`, after which it can
+    /// log in again. A suspended account can be suspended again to change
+    /// the end or the reason of the suspension. Its sessions are revoked.
+    pub async fn suspend_by_user_id_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        until_epoch_seconds: i64,
+        reason: &str,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Suspend user with user_id [{user_id:?}] until [{until_epoch_seconds}]");
+        if until_epoch_seconds <= current_epoch_seconds() {
+            return Err(LsAccountManagementError::BadRequest {
+                message: format!("The suspension would end in the past: [{until_epoch_seconds}]"),
+                code: SUSPENSION_ALREADY_EXPIRED,
+            });
+        }
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+
+        match &user.data.status {
+            AccountStatus::Active | AccountStatus::Suspended { .. } => {}
+            _ => {
+                return Err(LsAccountManagementError::InactiveUser(user.data.username.to_string()));
+            }
+        };
+
+        let before = user.data.clone();
+        user.data.status = AccountStatus::Suspended { until_epoch_seconds, reason: reason.to_owned() };
+        let user = self.auth_repo.update(conn, user).await?;
+        self.revoke_sessions_with_conn(conn, user_id).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Suspended,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Suspended, &user).await?;
+        Ok(user)
+    }
+
+    pub async fn unsuspend_by_user_id(
+        &self,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.unsuspend_by_user_id_with_conn(conn, user_id, actor).await).await
+    }
+
+    /// Ends the suspension of the account before its time
+    pub async fn unsuspend_by_user_id_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Unsuspend user with user_id [{user_id:?}]");
+        let user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+
+        match &user.data.status {
+            AccountStatus::Suspended { .. } => {}
+            _ => return Err(LsAccountManagementError::NotSuspendedUser(user.data.username.to_string())),
+        };
+
+        self.unsuspend_with_conn(conn, user, actor).await
+    }
+
+    pub async fn lift_expired_suspensions(&self) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.lift_expired_suspensions_with_conn(conn).await).await
+    }
+
+    /// Reactivates the accounts whose suspension has ended and returns their
+    /// number. The suspension of an account is also lifted when it logs in.
+    pub async fn lift_expired_suspensions_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+    ) -> Result<u64, LsAccountManagementError> {
+        let now = current_epoch_seconds();
+        debug!("Lift the suspensions ended before [{now}]");
+
+        const PAGE_SIZE: u32 = 100;
+        let mut start_user_id = 0;
+        let mut lifted = 0;
+        loop {
+            let users = self
+                .auth_repo
+                .fetch_all_by_status(conn, AccountStatusKind::Suspended, start_user_id, PAGE_SIZE)
+                .await?;
+            let Some(last) = users.last() else {
+                break;
+            };
+            start_user_id = last.id + 1;
+            let page_len = users.len();
+            for user in users {
+                if matches!(user.data.status, AccountStatus::Suspended { until_epoch_seconds, .. } if until_epoch_seconds <= now)
+                {
+                    self.unsuspend_with_conn(conn, user, None).await?;
+                    lifted += 1;
+                }
+            }
+            if page_len < PAGE_SIZE as usize {
+                break;
+            }
+        }
+        Ok(lifted)
+    }
+
+    pub async fn lock_by_user_id(
+        &self,
+        user_id: i64,
+        reason: &str,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.lock_by_user_id_with_conn(conn, user_id, reason, actor).await).await
     }
 
-    pub async fn delete_by_user_id(&self, user_id: i64) -> Result<u64, LsAccountManagementError> {
-        self.c3p0.transaction(async |conn| self.delete_by_user_id_with_conn(conn, user_id).await).await
+    /// Locks the account until an administrator unlocks it with
+    /// `unlock_by_user_id`. A suspended account can be locked too. Its
+    /// sessions are revoked.
+    pub async fn lock_by_user_id_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        reason: &str,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Lock user with user_id [{user_id:?}]");
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+
+        match &user.data.status {
+            AccountStatus::Active | AccountStatus::Suspended { .. } | AccountStatus::Locked { .. } => {}
+            _ => {
+                return Err(LsAccountManagementError::InactiveUser(user.data.username.to_string()));
+            }
+        };
+
+        let before = user.data.clone();
+        user.data.status = AccountStatus::Locked { reason: reason.to_owned() };
+        let user = self.auth_repo.update(conn, user).await?;
+        self.revoke_sessions_with_conn(conn, user_id).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Locked,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Locked, &user).await?;
+        Ok(user)
+    }
+
+    pub async fn unlock_by_user_id(
+        &self,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.unlock_by_user_id_with_conn(conn, user_id, actor).await).await
     }
 
+    /// Clears the failed logins of the account, lifting its lockout, and
+    /// reactivates it if it was locked by an administrator
+    pub async fn unlock_by_user_id_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Unlock user with user_id [{user_id:?}]");
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+
+        let before = user.data.clone();
+        user.data.failed_login_attempts = 0;
+        user.data.last_failed_login_epoch_seconds = None;
+        let reactivated = matches!(user.data.status, AccountStatus::Locked { .. });
+        if reactivated {
+            user.data.status = AccountStatus::Active;
+        }
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Unlocked,
+            user_id,
+            (Some(&before), Some(&user.data)),
+        )
+        .await?;
+        if reactivated {
+            self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Reactivated, &user).await?;
+        }
+        Ok(user)
+    }
+
+    pub async fn delete_by_user_id(&self, user_id: i64, actor: Option<&Auth>) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.delete_by_user_id_with_conn(conn, user_id, actor).await).await
+    }
+
+    /// Deletes the account together with its tokens, sessions, external
+    /// identities and the ACL entries granted to it
     pub async fn delete_by_user_id_with_conn(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         user_id: i64,
+        actor: Option<&Auth>,
     ) -> Result<u64, LsAccountManagementError> {
         debug!("Delete user with user_id [{user_id:?}]");
-        self.auth_repo.delete_by_id(conn, user_id).await
+        let Some(user) = self.auth_repo.fetch_by_id_optional(conn, user_id).await? else {
+            return Ok(0);
+        };
+        self.delete_tokens_with_conn(conn, &user.data.username).await?;
+        self.revoke_sessions_with_conn(conn, user_id).await?;
+        self.delete_external_identities_with_conn(conn, user_id).await?;
+        self.acl_repo.delete_all_by_grantee(conn, &Grantee::User(user_id)).await?;
+        let deleted = self.auth_repo.delete_by_id(conn, user_id).await?;
+        self.audit_with_conn(
+            conn,
+            actor.map(AuditActor::from),
+            AccountAuditAction::Deleted,
+            user_id,
+            (Some(&user.data), None),
+        )
+        .await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Deleted, &user).await?;
+        Ok(deleted)
+    }
+
+    /// Returns the personal data held for the account, followed by the
+    /// sections of the registered data exporters
+    pub async fn export_account_data(&self, user_id: i64) -> Result<AccountDataExport, LsAccountManagementError> {
+        let (user, tokens) = self
+            .c3p0
+            .transaction(async |conn| {
+                let user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+                let tokens = self.token_service.fetch_all_by_username_with_conn(conn, &user.data.username).await?;
+                Ok::<_, LsAccountManagementError>((user, tokens))
+            })
+            .await?;
+
+        let exporters = self.data_exporters.read().clone();
+        let mut sections = BTreeMap::new();
+        for (section, exporter) in exporters {
+            sections.insert(section, exporter.export(user_id).await?);
+        }
+
+        Ok(AccountDataExport {
+            user_id: user.id,
+            username: user.data.username,
+            email: user.data.email,
+            pending_email: user.data.pending_email,
+            roles: user.data.roles,
+            status: user.data.status,
+            created_date_epoch_seconds: user.data.created_date_epoch_seconds,
+            password_updated_date_epoch_seconds: user.data.password_updated_date_epoch_seconds,
+            mfa_enabled: user.data.mfa.is_enabled(),
+            language: user.data.language,
+            profile: user.data.profile,
+            tokens: tokens
+                .into_iter()
+                .map(|token| TokenExport {
+                    token_type: token.data.token_type,
+                    expire_at_epoch_seconds: token.data.expire_at_epoch_seconds,
+                })
+                .collect(),
+            sections,
+        })
+    }
+
+    pub async fn anonymize_account(
+        &self,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.anonymize_account_with_conn(conn, user_id, actor).await).await
+    }
+
+    /// Scrubs the personal data of the account and disables it, keeping its
+    /// id. The account is deleted by `purge_anonymized_accounts` once the
+    /// grace period has passed.
+    pub async fn anonymize_account_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Anonymize user with user_id [{user_id:?}]");
+        let mut user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        if user.data.is_anonymized() {
+            return Ok(user);
+        }
+
+        self.delete_tokens_with_conn(conn, &user.data.username).await?;
+        self.revoke_sessions_with_conn(conn, user_id).await?;
+        self.delete_external_identities_with_conn(conn, user_id).await?;
+
+        let anonymous_id = new_hyphenated_uuid();
+        let now = current_epoch_seconds();
+        user.data.username = format!("anonymized-{anonymous_id}");
+        user.data.email = format!("{anonymous_id}@anonymized.invalid");
+        user.data.password = self.password_service.hash_password(&new_hyphenated_uuid()).await?;
+        user.data.password_updated_date_epoch_seconds = now;
+        user.data.password_history = vec![];
+        user.data.roles = vec![];
+        user.data.pending_email = None;
+        user.data.profile = BTreeMap::new();
+        user.data.mfa = MfaState::Disabled;
+        user.data.failed_login_attempts = 0;
+        user.data.last_failed_login_epoch_seconds = None;
+        user.data.status = AccountStatus::Disabled;
+        user.data.anonymized_date_epoch_seconds = Some(now);
+        self.set_normalized_identifiers(&mut user.data);
+        let user = self.auth_repo.update(conn, user).await?;
+
+        // The previous personal data must not survive in the audit trail
+        // and in the outbox
+        self.audit_service
+            .redact_with_conn(conn, ACCOUNT_AUDIT_ENTITY_TYPE, &user_id.to_string(), &account_actor(&user))
+            .await?;
+        self.scrub_outbox_with_conn(conn, &user).await?;
+
+        // Also the event of a user anonymizing itself must not hold the
+        // previous username
+        let actor = actor.map(|actor| if actor.id == user_id { account_actor(&user) } else { AuditActor::from(actor) });
+        self.audit_with_conn(conn, actor, AccountAuditAction::Anonymized, user_id, (None, Some(&user.data))).await?;
+        self.event_publisher.publish_with_conn(conn, &AccountAnonymized { user_id }).await?;
+        self.notify_listeners_with_conn(conn, AccountLifecycleEvent::Anonymized, &user).await?;
+        Ok(user)
     }
+
+    async fn delete_tokens_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        username: &str,
+    ) -> Result<(), LsAccountManagementError> {
+        for token in self.token_service.fetch_all_by_username_with_conn(conn, username).await? {
+            self.token_service.delete_with_conn(conn, token).await?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the sessions of the account, so that its tokens are rejected
+    async fn revoke_sessions_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<(), LsAccountManagementError> {
+        self.revoke_other_sessions_with_conn(conn, user_id, None).await
+    }
+
+    /// Deletes the sessions of the account but `kept_session_id`
+    async fn revoke_other_sessions_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        kept_session_id: Option<&str>,
+    ) -> Result<(), LsAccountManagementError> {
+        for session in self.session_repo.fetch_all_by_user_id(conn, user_id).await? {
+            if Some(session.data.session_id.as_str()) != kept_session_id {
+                self.session_repo.delete(conn, session).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_external_identities_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<(), LsAccountManagementError> {
+        for identity in self.external_identity_repo.fetch_all_by_user_id(conn, user_id).await? {
+            self.external_identity_repo.delete(conn, identity).await?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the username and the emails in the payloads of the events
+    /// of the account, whatever their delivery status, with those of the
+    /// anonymized `user`
+    async fn scrub_outbox_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        for mut event in self.outbox_repo.fetch_all_by_subject(conn, &user.id.to_string()).await? {
+            if !event.data.event_type.starts_with(AM_EVENT_TYPE_PREFIX) {
+                continue;
+            }
+            let Some(payload) = event.data.payload.as_object_mut() else {
+                continue;
+            };
+            for (fields, value) in
+                [(&EVENT_USERNAME_FIELDS[..], &user.data.username), (&EVENT_EMAIL_FIELDS[..], &user.data.email)]
+            {
+                for field in fields {
+                    if let Some(old_value) = payload.get_mut(*field) {
+                        *old_value = json!(value);
+                    }
+                }
+            }
+            self.outbox_repo.update(conn, event).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn purge_anonymized_accounts(&self) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.purge_anonymized_accounts_with_conn(conn).await).await
+    }
+
+    /// Deletes the accounts anonymized more than
+    /// `anonymized_account_grace_period_seconds` ago and returns their number
+    pub async fn purge_anonymized_accounts_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+    ) -> Result<u64, LsAccountManagementError> {
+        let deadline = current_epoch_seconds() - self.auth_config.anonymized_account_grace_period_seconds as i64;
+        debug!("Purge accounts anonymized before [{deadline}]");
+
+        const PAGE_SIZE: u32 = 100;
+        let mut start_user_id = 0;
+        let mut purged = 0;
+        loop {
+            let users =
+                self.auth_repo.fetch_all_by_status(conn, AccountStatusKind::Disabled, start_user_id, PAGE_SIZE).await?;
+            let Some(last) = users.last() else {
+                break;
+            };
+            start_user_id = last.id + 1;
+            for user in &users {
+                if user.data.anonymized_date_epoch_seconds.is_some_and(|anonymized| anonymized <= deadline) {
+                    purged += self.delete_by_user_id_with_conn(conn, user.id, None).await?;
+                }
+            }
+            if users.len() < PAGE_SIZE as usize {
+                break;
+            }
+        }
+        Ok(purged)
+    }
+
+    pub async fn purge_pending_accounts(&self) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.purge_pending_accounts_with_conn(conn).await).await
+    }
+
+    /// Deletes the accounts still pending activation
+    /// more than `pending_account_max_age_seconds` after their creation and
+    /// returns their number
+    pub async fn purge_pending_accounts_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+    ) -> Result<u64, LsAccountManagementError> {
+        let deadline = current_epoch_seconds() - self.auth_config.pending_account_max_age_seconds as i64;
+        debug!("Purge accounts pending activation created before [{deadline}]");
+
+        const PAGE_SIZE: u32 = 100;
+        let mut start_user_id = 0;
+        let mut purged = 0;
+        loop {
+            let users = self
+                .auth_repo
+                .fetch_all_by_status(conn, AccountStatusKind::PendingActivation, start_user_id, PAGE_SIZE)
+                .await?;
+            let Some(last) = users.last() else {
+                break;
+            };
+            start_user_id = last.id + 1;
+            for user in &users {
+                if user.data.created_date_epoch_seconds <= deadline {
+                    purged += self.delete_by_user_id_with_conn(conn, user.id, None).await?;
+                }
+            }
+            if users.len() < PAGE_SIZE as usize {
+                break;
+            }
+        }
+        Ok(purged)
+    }
+
+    pub async fn normalize_identifiers(&self) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.normalize_identifiers_with_conn(conn).await).await
+    }
+
+    /// Rewrites the normalized usernames and emails of the accounts stored
+    /// with a different normalization, e.g. by the migrations or before
+    /// `identifier_normalization` was changed, and returns their number.
+    /// It is a maintenance operation, never run by the module: it reads all
+    /// the accounts in one transaction. Nothing is rewritten if
+    /// different accounts would have the same normalized username or email:
+    /// it fails with `IDENTIFIER_CONFLICT` listing them.
+    pub async fn normalize_identifiers_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+    ) -> Result<u64, LsAccountManagementError> {
+        info!("Normalize the usernames and emails of the accounts");
+
+        const PAGE_SIZE: u32 = 100;
+        let mut query =
+            AccountQuery { sort: vec![(AccountSortField::Id, SortOrder::Asc)], limit: PAGE_SIZE, ..Default::default() };
+        let mut usernames = BTreeMap::<String, Vec<String>>::new();
+        let mut emails = BTreeMap::<String, Vec<String>>::new();
+        let mut changed = vec![];
+        loop {
+            let users = self.auth_repo.search(conn, &query).await?;
+            for mut user in users.iter().cloned() {
+                let before = (user.data.normalized_username.clone(), user.data.normalized_email.clone());
+                self.set_normalized_identifiers(&mut user.data);
+                usernames.entry(user.data.normalized_username.clone()).or_default().push(user.data.username.clone());
+                emails.entry(user.data.normalized_email.clone()).or_default().push(user.data.email.clone());
+                if before != (user.data.normalized_username.clone(), user.data.normalized_email.clone()) {
+                    changed.push(user);
+                }
+            }
+            if users.len() < PAGE_SIZE as usize {
+                break;
+            }
+            query.offset += PAGE_SIZE;
+        }
+
+        let conflicts: Vec<String> = usernames
+            .into_values()
+            .chain(emails.into_values())
+            .filter(|identifiers| identifiers.len() > 1)
+            .map(|identifiers| format!("{identifiers:?}"))
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(LsAccountManagementError::BadRequest {
+                message: format!("Accounts with the same normalized identifiers: {}", conflicts.join(", ")),
+                code: IDENTIFIER_CONFLICT,
+            });
+        }
+
+        let normalized = changed.len() as u64;
+        for user in changed {
+            self.auth_repo.update(conn, user).await?;
+        }
+        info!("Normalized the usernames and emails of [{normalized}] accounts");
+        Ok(normalized)
+    }
+
+    pub async fn purge_expired_tokens(&self) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.purge_expired_tokens_with_conn(conn).await).await
+    }
+
+    /// Deletes the expired tokens and returns their number
+    pub async fn purge_expired_tokens_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+    ) -> Result<u64, LsAccountManagementError> {
+        debug!("Purge expired tokens");
+        self.token_service.delete_expired_with_conn(conn, current_epoch_seconds()).await
+    }
+}
+
+/// Looks the account up by username, with the forms of `lookup_forms`
+pub(crate) async fn fetch_by_username_optional<AuthRepo: AccountRepository>(
+    auth_repo: &AuthRepo,
+    conn: &mut <AuthRepo::DB as Database>::Connection,
+    auth_config: &AMConfig,
+    username: &str,
+) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+    for form in lookup_forms(&auth_config.identifier_normalization, username) {
+        if let Some(user) = auth_repo.fetch_by_username_optional(conn, &form).await? {
+            return Ok(Some(user));
+        }
+    }
+    Ok(None)
+}
+
+/// Same as `fetch_by_username_optional`, failing if there is no account
+pub(crate) async fn fetch_by_username<AuthRepo: AccountRepository>(
+    auth_repo: &AuthRepo,
+    conn: &mut <AuthRepo::DB as Database>::Connection,
+    auth_config: &AMConfig,
+    username: &str,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    fetch_by_username_optional(auth_repo, conn, auth_config, username).await?.ok_or_else(|| {
+        LsAccountManagementError::BadRequest { message: format!("No user found with username [{username}]"), code: "" }
+    })
+}
+
+/// Looks the account up by email, with the forms of `lookup_forms`
+pub(crate) async fn fetch_by_email_optional<AuthRepo: AccountRepository>(
+    auth_repo: &AuthRepo,
+    conn: &mut <AuthRepo::DB as Database>::Connection,
+    auth_config: &AMConfig,
+    email: &str,
+) -> Result<Option<AuthAccountModel>, LsAccountManagementError> {
+    for form in lookup_forms(&auth_config.identifier_normalization, email) {
+        if let Some(user) = auth_repo.fetch_by_email_optional(conn, &form).await? {
+            return Ok(Some(user));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the epoch seconds until which the account is locked, if it is
+pub(crate) fn locked_until(auth_config: &AMConfig, data: &AccountData, now: i64) -> Option<i64> {
+    let max_attempts = auth_config.max_failed_login_attempts?.max(1);
+    let last_failure = data.last_failed_login_epoch_seconds?;
+    if data.failed_login_attempts < max_attempts {
+        return None;
+    }
+    let doublings = (data.failed_login_attempts - max_attempts).min(31);
+    let lockout_seconds = (auth_config.login_lockout_seconds as i64)
+        .saturating_mul(1 << doublings)
+        .min(auth_config.max_login_lockout_seconds as i64);
+    let until = last_failure + lockout_seconds;
+    (now < until).then_some(until)
+}
+
+/// Counts a failed verification of the credentials of the account towards
+/// its lockout
+pub(crate) fn count_failed_login(data: &mut AccountData, now: i64) {
+    data.failed_login_attempts = data.failed_login_attempts.saturating_add(1);
+    data.last_failed_login_epoch_seconds = Some(now);
+}
+
+/// Returns the session of an account, created at `creation_ts_seconds`
+pub(crate) fn new_auth(auth_config: &AMConfig, account: AuthAccountModel, creation_ts_seconds: i64) -> Auth {
+    let expiration_ts_seconds = creation_ts_seconds + (auth_config.auth_session_max_validity_minutes as i64 * 60);
+    Auth::new(account.id, account.data.username, account.data.roles, creation_ts_seconds, expiration_ts_seconds)
+}
+
+pub(crate) fn account_actor(account: &AuthAccountModel) -> AuditActor {
+    AuditActor { id: account.id, username: account.data.username.clone() }
+}
+
+pub(crate) fn audit_snapshot(data: &AccountData) -> Value {
+    json!({
+        "username": data.username,
+        "email": data.email,
+        "roles": data.roles,
+        "status": data.status.as_ref(),
+        "mfa_enabled": data.mfa.is_enabled(),
+        "failed_login_attempts": data.failed_login_attempts,
+        "pending_email": data.pending_email,
+        "password_login_disabled": data.password_login_disabled,
+        "language": data.language,
+        "profile": data.profile,
+    })
 }
@@ -0,0 +1,198 @@
+use crate::error::LsAccountManagementError;
+use crate::model::acl::{AclEntryData, AclEntryModel};
+use crate::repository::{AMRepositoryManager, AclRepository};
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::service::auth::{AccessEntry, AccessLevel, Auth, Grantee};
+use lightspeed_core::utils::current_epoch_seconds;
+use log::*;
+
+/// Grants, revokes and lists the shares of a resource.
+///
+/// The service does not check who is performing the operation; callers are
+/// expected to verify it first, e.g. with
+/// `auth_context.can(AccessLevel::Admin, &Shared::new(&resource, entries))`.
+#[derive(Clone)]
+pub struct LsAMAclService<RepoManager: AMRepositoryManager> {
+    c3p0: RepoManager::C3P0,
+    acl_repo: RepoManager::AclRepo,
+}
+
+impl<RepoManager: AMRepositoryManager> LsAMAclService<RepoManager> {
+    pub fn new(c3p0: RepoManager::C3P0, acl_repo: RepoManager::AclRepo) -> Self {
+        LsAMAclService { c3p0, acl_repo }
+    }
+
+    pub async fn grant(
+        &self,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: Grantee,
+        access_level: AccessLevel,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| {
+                self.grant_with_conn(conn, resource_type, resource_id, grantee, access_level).await
+            })
+            .await
+    }
+
+    /// Shares the resource with the grantee. If the resource is already
+    /// shared with the same grantee, its access level is replaced.
+    pub async fn grant_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: Grantee,
+        access_level: AccessLevel,
+    ) -> Result<AclEntryModel, LsAccountManagementError> {
+        info!("Grant [{access_level}] access on [{resource_type}:{resource_id}] to [{grantee:?}]");
+        match self.acl_repo.fetch_by_resource_and_grantee_optional(conn, resource_type, resource_id, &grantee).await? {
+            Some(mut entry) => {
+                if entry.data.access_level == access_level {
+                    Ok(entry)
+                } else {
+                    entry.data.access_level = access_level;
+                    self.acl_repo.update(conn, entry).await
+                }
+            }
+            None => {
+                self.acl_repo
+                    .save(
+                        conn,
+                        NewRecord::new(AclEntryData {
+                            resource_type: resource_type.to_owned(),
+                            resource_id: resource_id.to_owned(),
+                            grantee,
+                            access_level,
+                            created_date_epoch_seconds: current_epoch_seconds(),
+                        }),
+                    )
+                    .await
+            }
+        }
+    }
+
+    pub async fn revoke(
+        &self,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: &Grantee,
+    ) -> Result<bool, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.revoke_with_conn(conn, resource_type, resource_id, grantee).await).await
+    }
+
+    /// Removes the share of the resource with the grantee. Returns whether
+    /// a share existed.
+    pub async fn revoke_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+        grantee: &Grantee,
+    ) -> Result<bool, LsAccountManagementError> {
+        info!("Revoke access on [{resource_type}:{resource_id}] from [{grantee:?}]");
+        match self.acl_repo.fetch_by_resource_and_grantee_optional(conn, resource_type, resource_id, grantee).await? {
+            Some(entry) => {
+                self.acl_repo.delete(conn, entry).await?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub async fn fetch_all_by_resource(
+        &self,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.fetch_all_by_resource_with_conn(conn, resource_type, resource_id).await)
+            .await
+    }
+
+    pub async fn fetch_all_by_resource_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        debug!("Fetch shares of [{resource_type}:{resource_id}]");
+        self.acl_repo.fetch_all_by_resource(conn, resource_type, resource_id).await
+    }
+
+    pub async fn fetch_access_entries(
+        &self,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Vec<AccessEntry>, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.fetch_access_entries_with_conn(conn, resource_type, resource_id).await)
+            .await
+    }
+
+    /// Returns the access entries of the resource, ready to be wrapped in a
+    /// `Shared` and checked with `AuthContext::can`.
+    pub async fn fetch_access_entries_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<Vec<AccessEntry>, LsAccountManagementError> {
+        Ok(self
+            .fetch_all_by_resource_with_conn(conn, resource_type, resource_id)
+            .await?
+            .iter()
+            .map(|entry| entry.data.to_access_entry())
+            .collect())
+    }
+
+    pub async fn fetch_all_shared_with(
+        &self,
+        resource_type: &str,
+        auth: &Auth,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.fetch_all_shared_with_conn(conn, resource_type, auth).await).await
+    }
+
+    /// Returns the shares of resources of the given type with the user,
+    /// either directly or through one of the user's roles. A resource shared
+    /// both ways is returned once per share.
+    pub async fn fetch_all_shared_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        resource_type: &str,
+        auth: &Auth,
+    ) -> Result<Vec<AclEntryModel>, LsAccountManagementError> {
+        debug!("Fetch [{resource_type}] shares of user [{}]", auth.id);
+        let mut result = self.acl_repo.fetch_all_by_grantee(conn, resource_type, &Grantee::User(auth.id)).await?;
+        for role in &auth.roles {
+            result
+                .extend(self.acl_repo.fetch_all_by_grantee(conn, resource_type, &Grantee::Group(role.clone())).await?);
+        }
+        Ok(result)
+    }
+
+    pub async fn delete_all_by_resource(
+        &self,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<u64, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.delete_all_by_resource_with_conn(conn, resource_type, resource_id).await)
+            .await
+    }
+
+    /// Removes every share of the resource. To be called when the resource
+    /// is deleted.
+    pub async fn delete_all_by_resource_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        resource_type: &str,
+        resource_id: &str,
+    ) -> Result<u64, LsAccountManagementError> {
+        info!("Delete all shares of [{resource_type}:{resource_id}]");
+        self.acl_repo.delete_all_by_resource(conn, resource_type, resource_id).await
+    }
+}
@@ -0,0 +1,185 @@
+use crate::error::LsAccountManagementError;
+use crate::model::audit::{AuditActor, AuditEventData, AuditEventModel, AuditEventQuery};
+use crate::repository::{AMRepositoryManager, AuditRepository};
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::utils::current_epoch_seconds;
+use log::*;
+use serde_json::Value;
+
+/// Records and queries the changes applied to entities.
+///
+/// Events are written with the `_with_conn` methods so that they are
+/// committed, or rolled back, together with the change they describe.
+#[derive(Clone)]
+pub struct LsAMAuditService<RepoManager: AMRepositoryManager> {
+    c3p0: RepoManager::C3P0,
+    audit_repo: RepoManager::AuditRepo,
+}
+
+impl<RepoManager: AMRepositoryManager> LsAMAuditService<RepoManager> {
+    pub fn new(c3p0: RepoManager::C3P0, audit_repo: RepoManager::AuditRepo) -> Self {
+        LsAMAuditService { c3p0, audit_repo }
+    }
+
+    /// Saves an event for the given change. `before_and_after` holds the
+    /// state of the entity around the change; only their differing fields
+    /// are stored.
+    pub async fn record_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        actor: Option<AuditActor>,
+        action: &str,
+        entity_type: &str,
+        entity_id: &str,
+        before_and_after: (Option<Value>, Option<Value>),
+    ) -> Result<AuditEventModel, LsAccountManagementError> {
+        debug!("Audit [{action}] on [{entity_type}:{entity_id}] by [{actor:?}]");
+        let (before, after) = match before_and_after {
+            (Some(before), Some(after)) => {
+                let (before, after) = diff(before, after);
+                (Some(before), Some(after))
+            }
+            other => other,
+        };
+        self.audit_repo
+            .save(
+                conn,
+                NewRecord::new(AuditEventData {
+                    actor,
+                    action: action.to_owned(),
+                    entity_type: entity_type.to_owned(),
+                    entity_id: entity_id.to_owned(),
+                    before,
+                    after,
+                    created_date_epoch_seconds: current_epoch_seconds(),
+                }),
+            )
+            .await
+    }
+
+    pub async fn fetch_all(&self, query: &AuditEventQuery) -> Result<Vec<AuditEventModel>, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.fetch_all_with_conn(conn, query).await).await
+    }
+
+    pub async fn fetch_all_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        query: &AuditEventQuery,
+    ) -> Result<Vec<AuditEventModel>, LsAccountManagementError> {
+        debug!("Fetch audit events with query [{query:?}]");
+        self.audit_repo.fetch_all(conn, query).await
+    }
+
+    /// Removes the personal data of a user from the trail: the snapshots of
+    /// the entity `entity_type:entity_id` are dropped and `actor` replaces
+    /// the user, identified by its id, as the actor of the events it
+    /// triggered. Returns the number of the updated events.
+    pub async fn redact_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        entity_type: &str,
+        entity_id: &str,
+        actor: &AuditActor,
+    ) -> Result<u64, LsAccountManagementError> {
+        info!("Redact the audit events of [{entity_type}:{entity_id}] and of actor [{}]", actor.id);
+        const PAGE_SIZE: u32 = 100;
+        let queries = [
+            AuditEventQuery {
+                entity_type: Some(entity_type.to_owned()),
+                entity_id: Some(entity_id.to_owned()),
+                limit: PAGE_SIZE,
+                ..Default::default()
+            },
+            AuditEventQuery { actor_id: Some(actor.id), limit: PAGE_SIZE, ..Default::default() },
+        ];
+
+        let mut redacted = 0;
+        for mut query in queries {
+            loop {
+                let events = self.audit_repo.fetch_all(conn, &query).await?;
+                let Some(last) = events.last() else {
+                    break;
+                };
+                query.start_id = last.id + 1;
+                let page_len = events.len();
+                for mut event in events {
+                    if query.actor_id.is_some() {
+                        event.data.actor = Some(actor.clone());
+                    } else {
+                        event.data.before = None;
+                        event.data.after = None;
+                    }
+                    self.audit_repo.update(conn, event).await?;
+                    redacted += 1;
+                }
+                if page_len < PAGE_SIZE as usize {
+                    break;
+                }
+            }
+        }
+        Ok(redacted)
+    }
+}
+
+/// Removes from two JSON objects the top-level fields with the same value.
+/// Values that are not both objects are returned unchanged.
+fn diff(before: Value, after: Value) -> (Value, Value) {
+    match (before, after) {
+        (Value::Object(mut before), Value::Object(mut after)) => {
+            let unchanged: Vec<String> = before
+                .iter()
+                .filter(|(key, value)| after.get(*key) == Some(*value))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in &unchanged {
+                before.remove(key);
+                after.remove(key);
+            }
+            (Value::Object(before), Value::Object(after))
+        }
+        (before, after) => (before, after),
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_should_keep_only_changed_fields() {
+        let (before, after) = diff(
+            json!({ "username": "ugo", "email": "old@test.com", "roles": ["a"] }),
+            json!({ "username": "ugo", "email": "new@test.com", "roles": ["a", "b"] }),
+        );
+
+        assert_eq!(json!({ "email": "old@test.com", "roles": ["a"] }), before);
+        assert_eq!(json!({ "email": "new@test.com", "roles": ["a", "b"] }), after);
+    }
+
+    #[test]
+    fn diff_should_keep_added_and_removed_fields() {
+        let (before, after) = diff(json!({ "a": 1, "b": 2 }), json!({ "a": 1, "c": 3 }));
+
+        assert_eq!(json!({ "b": 2 }), before);
+        assert_eq!(json!({ "c": 3 }), after);
+    }
+
+    #[test]
+    fn diff_should_return_non_objects_unchanged() {
+        let (before, after) = diff(json!("a"), json!({ "a": 1 }));
+
+        assert_eq!(json!("a"), before);
+        assert_eq!(json!({ "a": 1 }), after);
+    }
+
+    #[test]
+    fn diff_of_equal_objects_should_be_empty() {
+        let (before, after) = diff(json!({ "a": 1 }), json!({ "a": 1 }));
+
+        assert_eq!(json!({}), before);
+        assert_eq!(json!({}), after);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::error::LsAccountManagementError;
+use data_encoding::HEXUPPER;
+use sha1::{Digest, Sha1};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+
+/// Tells how many times a password appears in known data breaches.
+/// The check runs on a blocking thread, so implementations can do I/O.
+pub trait BreachedPasswordChecker: Send + Sync {
+    fn breach_count(&self, password: &str) -> Result<u64, LsAccountManagementError>;
+}
+
+/// Considers every password as never breached
+pub struct NoBreachedPasswordChecker;
+
+impl BreachedPasswordChecker for NoBreachedPasswordChecker {
+    fn breach_count(&self, _password: &str) -> Result<u64, LsAccountManagementError> {
+        Ok(0)
+    }
+}
+
+/// Looks up the SHA-1 of the password in a local file in the format of the
+/// "Have I Been Pwned" password downloads ordered by hash: one
+/// `<SHA-1 uppercase hex>:<count>` entry per line, sorted by hash.
+///
+/// The file is never loaded: every lookup is a binary search over the byte
+/// offsets of the file, so it reads O(log n) lines.
+pub struct HibpFileBreachedPasswordChecker {
+    path: PathBuf,
+}
+
+impl HibpFileBreachedPasswordChecker {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Result<Self, LsAccountManagementError> {
+        let path = path.into();
+        if !path.is_file() {
+            return Err(LsAccountManagementError::BreachedPasswordCheckError {
+                message: format!("Breached passwords file [{}] not found", path.display()),
+            });
+        }
+        Ok(Self { path })
+    }
+
+    fn find(&self, hash: &str) -> std::io::Result<Option<u64>> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let (mut low, mut high) = (0, reader.get_ref().metadata()?.len());
+
+        // Smallest offset whose next entry is not lower than `hash`
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match next_entry(&mut reader, mid)? {
+                Some((entry_hash, _)) if entry_hash.as_str() < hash => low = mid + 1,
+                _ => high = mid,
+            }
+        }
+
+        Ok(next_entry(&mut reader, low)?.filter(|(entry_hash, _)| entry_hash == hash).map(|(_, count)| count))
+    }
+}
+
+impl BreachedPasswordChecker for HibpFileBreachedPasswordChecker {
+    fn breach_count(&self, password: &str) -> Result<u64, LsAccountManagementError> {
+        let hash = HEXUPPER.encode(&Sha1::digest(password.as_bytes()));
+        self.find(&hash).map(|count| count.unwrap_or(0)).map_err(|err| {
+            LsAccountManagementError::BreachedPasswordCheckError {
+                message: format!("Cannot read breached passwords file [{}]: {err:?}", self.path.display()),
+            }
+        })
+    }
+}
+
+/// Returns the first entry starting at or after `offset`
+fn next_entry(reader: &mut BufReader<File>, offset: u64) -> std::io::Result<Option<(String, u64)>> {
+    let mut line = String::new();
+    if offset == 0 {
+        reader.seek(SeekFrom::Start(0))?;
+    } else {
+        // Skip the rest of the entry containing the byte before `offset`
+        reader.seek(SeekFrom::Start(offset - 1))?;
+        reader.read_line(&mut line)?;
+        line.clear();
+    }
+
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    let (hash, count) = line.trim_end().split_once(':').unwrap_or((line.trim_end(), ""));
+    Ok(Some((hash.to_ascii_uppercase(), count.parse().unwrap_or(0))))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use std::io::Write;
+
+    fn sha1(password: &str) -> String {
+        HEXUPPER.encode(&Sha1::digest(password.as_bytes()))
+    }
+
+    fn hibp_file_checker(entries: &[(String, u64)]) -> (tempfile::NamedTempFile, HibpFileBreachedPasswordChecker) {
+        let mut entries = entries.to_vec();
+        entries.sort();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        for (hash, count) in entries {
+            write!(file, "{hash}:{count}\r\n").unwrap();
+        }
+        let checker = HibpFileBreachedPasswordChecker::new(file.path()).unwrap();
+        (file, checker)
+    }
+
+    #[test]
+    fn should_find_every_entry_of_the_file() {
+        let entries = (0..500).map(|i| (sha1(&format!("password_{i}")), i + 1)).collect::<Vec<_>>();
+        let (_file, checker) = hibp_file_checker(&entries);
+
+        for i in 0..500 {
+            assert_eq!(i + 1, checker.breach_count(&format!("password_{i}")).unwrap());
+        }
+        assert_eq!(0, checker.breach_count("password_500").unwrap());
+        assert_eq!(0, checker.breach_count("").unwrap());
+    }
+
+    #[test]
+    fn should_search_files_with_one_or_no_entry() {
+        let (_file, checker) = hibp_file_checker(&[(sha1("password"), 10)]);
+        assert_eq!(10, checker.breach_count("password").unwrap());
+        assert_eq!(0, checker.breach_count("other").unwrap());
+
+        let (_file, checker) = hibp_file_checker(&[]);
+        assert_eq!(0, checker.breach_count("password").unwrap());
+    }
+
+    #[test]
+    fn should_fail_if_the_file_does_not_exist() {
+        assert!(HibpFileBreachedPasswordChecker::new("./not_existing_file.txt").is_err());
+    }
+}
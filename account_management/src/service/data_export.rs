@@ -0,0 +1,36 @@
+use crate::error::LsAccountManagementError;
+use serde_json::Value;
+use std::future::Future;
+use std::pin::Pin;
+
+type BoxedFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Contributes the data held by another module (e.g. the files of the
+/// user) to the export of an account.
+///
+/// Implemented for every `Fn(i64) -> impl Future<Output = Result<Value, LsAccountManagementError>>`
+/// receiving the id of the user.
+pub trait AccountDataExporter: Send + Sync + 'static {
+    fn export(&self, user_id: i64) -> impl Future<Output = Result<Value, LsAccountManagementError>> + Send;
+}
+
+impl<F, Fut> AccountDataExporter for F
+where
+    F: Fn(i64) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Value, LsAccountManagementError>> + Send,
+{
+    fn export(&self, user_id: i64) -> impl Future<Output = Result<Value, LsAccountManagementError>> + Send {
+        self(user_id)
+    }
+}
+
+/// Object-safe view of an [`AccountDataExporter`]
+pub(crate) trait ErasedAccountDataExporter: Send + Sync + 'static {
+    fn export(&self, user_id: i64) -> BoxedFuture<'_, Result<Value, LsAccountManagementError>>;
+}
+
+impl<X: AccountDataExporter> ErasedAccountDataExporter for X {
+    fn export(&self, user_id: i64) -> BoxedFuture<'_, Result<Value, LsAccountManagementError>> {
+        Box::pin(AccountDataExporter::export(self, user_id))
+    }
+}
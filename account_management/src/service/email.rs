@@ -0,0 +1,259 @@
+use crate::config::{AMConfig, EmailTemplate, EmailTemplates};
+use crate::error::LsAccountManagementError;
+use crate::model::event::{
+    AccountCreated, AccountDisabled, ActivationTokenGenerated, PasswordChanged, PasswordReset, PasswordResetRequested,
+};
+use crate::repository::AMRepositoryManager;
+use crate::service::account::LsAMAccountService;
+use crate::service::token::LsTokenService;
+use lightspeed_email::model::email::EmailMessage;
+use lightspeed_email::service::LsEmailService;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::service::dispatcher::LsEventDispatcher;
+use log::*;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use std::sync::Arc;
+
+/// The emails sent on the account lifecycle events
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccountEmail {
+    Activation,
+    ResendActivation,
+    PasswordReset,
+    PasswordChanged,
+    AccountDisabled,
+}
+
+/// Sends to the users the emails of the account lifecycle events, rendered
+/// with the templates of `AMConfig::emails` in the language of the user.
+///
+/// The emails are sent by the handlers added to an `LsEventDispatcher` with
+/// [`AccountEmailHandlers::register_handlers`], so that they leave only once
+/// the change that triggered them is committed.
+#[derive(Clone)]
+pub struct LsAMEmailService<RepoManager: AMRepositoryManager> {
+    auth_config: AMConfig,
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+    token_service: Arc<LsTokenService<RepoManager>>,
+    email_service: Arc<LsEmailService>,
+    default_templates: Arc<EmailTemplates>,
+}
+
+impl<RepoManager: AMRepositoryManager> LsAMEmailService<RepoManager> {
+    pub fn new(
+        auth_config: AMConfig,
+        account_service: Arc<LsAMAccountService<RepoManager>>,
+        token_service: Arc<LsTokenService<RepoManager>>,
+        email_service: Arc<LsEmailService>,
+    ) -> Self {
+        LsAMEmailService {
+            auth_config,
+            account_service,
+            token_service,
+            email_service,
+            default_templates: Default::default(),
+        }
+    }
+
+    pub async fn on_account_created(&self, event: &AccountCreated) -> Result<(), LsAccountManagementError> {
+        let token = self.token_service.open_token(&event.sealed_activation_token)?;
+        self.send(AccountEmail::Activation, event.user_id, &event.username, &event.email, Some(&token)).await
+    }
+
+    pub async fn on_activation_token_generated(
+        &self,
+        event: &ActivationTokenGenerated,
+    ) -> Result<(), LsAccountManagementError> {
+        let token = self.token_service.open_token(&event.sealed_activation_token)?;
+        self.send(AccountEmail::ResendActivation, event.user_id, &event.username, &event.email, Some(&token)).await
+    }
+
+    pub async fn on_password_reset_requested(
+        &self,
+        event: &PasswordResetRequested,
+    ) -> Result<(), LsAccountManagementError> {
+        let token = self.token_service.open_token(&event.sealed_reset_token)?;
+        self.send(AccountEmail::PasswordReset, event.user_id, &event.username, &event.email, Some(&token)).await
+    }
+
+    pub async fn on_password_reset(&self, event: &PasswordReset) -> Result<(), LsAccountManagementError> {
+        self.send(AccountEmail::PasswordChanged, event.user_id, &event.username, &event.email, None).await
+    }
+
+    pub async fn on_password_changed(&self, event: &PasswordChanged) -> Result<(), LsAccountManagementError> {
+        self.send(AccountEmail::PasswordChanged, event.user_id, &event.username, &event.email, None).await
+    }
+
+    pub async fn on_account_disabled(&self, event: &AccountDisabled) -> Result<(), LsAccountManagementError> {
+        self.send(AccountEmail::AccountDisabled, event.user_id, &event.username, &event.email, None).await
+    }
+
+    /// Renders the email `kind` for the account `user_id` and sends it to
+    /// `email`. `token` fills the link of the activation and reset emails.
+    async fn send(
+        &self,
+        kind: AccountEmail,
+        user_id: i64,
+        username: &str,
+        email: &str,
+        token: Option<&str>,
+    ) -> Result<(), LsAccountManagementError> {
+        debug!("Send email [{kind:?}] to user_id [{user_id}]");
+        let emails = &self.auth_config.emails;
+        let language = self.account_service.fetch_by_user_id(user_id).await?.data.language;
+        let templates = language
+            .and_then(|language| emails.templates.get(&language))
+            .or_else(|| emails.templates.get(&emails.default_language))
+            .unwrap_or(&self.default_templates);
+
+        let (template, link) = match kind {
+            AccountEmail::Activation => (&templates.activation, Some(&emails.activation_link)),
+            AccountEmail::ResendActivation => (&templates.resend_activation, Some(&emails.activation_link)),
+            AccountEmail::PasswordReset => (&templates.password_reset, Some(&emails.reset_password_link)),
+            AccountEmail::PasswordChanged => (&templates.password_changed, None),
+            AccountEmail::AccountDisabled => (&templates.account_disabled, None),
+        };
+
+        let token = token.unwrap_or_default();
+        let link = link
+            .map(|link| {
+                render(link, &[("username", username), ("email", email), ("token", token)], |value| {
+                    utf8_percent_encode(value, NON_ALPHANUMERIC).to_string()
+                })
+            })
+            .unwrap_or_default();
+        let values = [("username", username), ("email", email), ("token", token), ("link", link.as_str())];
+
+        self.email_service.send(render_message(template, &values, &emails.from, email)).await?;
+        Ok(())
+    }
+}
+
+/// Adds to an `LsEventDispatcher` the handlers that send the emails of the
+/// account events.
+///
+/// It is implemented for the services of the enabled databases.
+pub trait AccountEmailHandlers {
+    fn register_handlers<EventRepoManager: EventRepositoryManager>(
+        &self,
+        dispatcher: &LsEventDispatcher<EventRepoManager>,
+    );
+}
+
+/// Implemented for the concrete repository managers, whose transaction
+/// futures are known to be `Send`.
+macro_rules! impl_account_email_handlers {
+    (@handler $service:expr, $dispatcher:expr, $event:ty, $method:ident) => {{
+        let service = $service.clone();
+        $dispatcher.add_handler(move |event: $event| {
+            let service = service.clone();
+            async move {
+                service
+                    .$method(&event)
+                    .await
+                    .map_err(|err| lightspeed_event::error::LsEventError::HandlerError { message: err.to_string() })
+            }
+        });
+    }};
+    ($repo_manager:ty) => {
+        impl AccountEmailHandlers for LsAMEmailService<$repo_manager> {
+            fn register_handlers<EventRepoManager: EventRepositoryManager>(
+                &self,
+                dispatcher: &LsEventDispatcher<EventRepoManager>,
+            ) {
+                impl_account_email_handlers!(@handler self, dispatcher, AccountCreated, on_account_created);
+                impl_account_email_handlers!(@handler self, dispatcher, ActivationTokenGenerated, on_activation_token_generated);
+                impl_account_email_handlers!(@handler self, dispatcher, PasswordResetRequested, on_password_reset_requested);
+                impl_account_email_handlers!(@handler self, dispatcher, PasswordReset, on_password_reset);
+                impl_account_email_handlers!(@handler self, dispatcher, PasswordChanged, on_password_changed);
+                impl_account_email_handlers!(@handler self, dispatcher, AccountDisabled, on_account_disabled);
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mysql")]
+impl_account_email_handlers!(crate::repository::mysql::MyAMSqlRepositoryManager);
+
+#[cfg(feature = "postgres")]
+impl_account_email_handlers!(crate::repository::postgres::PgAMRepositoryManager);
+
+#[cfg(feature = "sqlite")]
+impl_account_email_handlers!(crate::repository::sqlite::SqliteAMRepositoryManager);
+
+impl_account_email_handlers!(crate::repository::memory::MemoryAMRepositoryManager);
+
+fn render_message(template: &EmailTemplate, values: &[(&str, &str)], from: &str, to: &str) -> EmailMessage {
+    EmailMessage {
+        from: Some(from.to_owned()),
+        to: vec![to.to_owned()],
+        subject: Some(render(&template.subject, values, str::to_owned)),
+        text: Some(render(&template.text, values, str::to_owned)),
+        html: template.html.as_ref().map(|html| render(html, values, escape_html)),
+        ..Default::default()
+    }
+}
+
+/// Replaces the `{name}` placeholders of `template` with the escaped
+/// values. Unknown placeholders are left as they are, and the values are
+/// never rendered again.
+fn render(template: &str, values: &[(&str, &str)], escape: impl Fn(&str) -> String) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let placeholder = after
+            .find('}')
+            .and_then(|end| values.iter().find(|(name, _)| *name == &after[..end]).map(|(_, value)| (end, value)));
+        match placeholder {
+            Some((end, value)) => {
+                rendered.push_str(&escape(value));
+                rest = &after[end + 1..];
+            }
+            None => {
+                rendered.push('{');
+                rest = after;
+            }
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn should_render_the_placeholders() {
+        let values = [("username", "{link}"), ("link", "http://localhost/?token=1")];
+        assert_eq!(
+            "Hello {link}, open http://localhost/?token=1 {unknown} {",
+            render("Hello {username}, open {link} {unknown} {", &values, str::to_owned)
+        );
+    }
+
+    #[test]
+    fn should_escape_the_html_values() {
+        assert_eq!(
+            "<b>&lt;script&gt;&amp;&quot;&#39;</b>",
+            render("<b>{username}</b>", &[("username", "<script>&\"'")], escape_html)
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use crate::config::IdentifierNormalizationConfig;
+use caseless::default_case_fold_str;
+use unicode_normalization::UnicodeNormalization;
+
+/// Returns the form of a username or email with which it is looked up and
+/// kept unique
+pub fn normalize_identifier(config: &IdentifierNormalizationConfig, identifier: &str) -> String {
+    let mut normalized = if config.trim { identifier.trim() } else { identifier }.to_owned();
+    if config.unicode_nfkc {
+        normalized = normalized.nfkc().collect();
+    }
+    if config.case_fold {
+        normalized = default_case_fold_str(&normalized);
+        // The folding can produce non normalized sequences
+        if config.unicode_nfkc {
+            normalized = normalized.nfkc().collect();
+        }
+    }
+    normalized
+}
+
+/// Returns the forms with which the account of a username or email can be
+/// stored: the normalized form, then the forms written by the migrations,
+/// that only trimmed the spaces and lowercased it (only the ASCII letters on
+/// SQLite), for the accounts not rewritten yet by
+/// `LsAMAccountService::normalize_identifiers`
+pub(crate) fn lookup_forms(config: &IdentifierNormalizationConfig, identifier: &str) -> Vec<String> {
+    let mut forms = vec![normalize_identifier(config, identifier)];
+    let trimmed = identifier.trim_matches(' ');
+    for legacy_form in [trimmed.to_lowercase(), trimmed.to_ascii_lowercase()] {
+        if !forms.contains(&legacy_form) {
+            forms.push(legacy_form);
+        }
+    }
+    forms
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn should_normalize_the_identifiers() {
+        let config = IdentifierNormalizationConfig::default();
+        assert_eq!("alice", normalize_identifier(&config, "  Alice\t"));
+        assert_eq!("alice@email.fake", normalize_identifier(&config, "ALICE@Email.Fake"));
+        // Full width letters and the "fi" ligature
+        assert_eq!("alice", normalize_identifier(&config, "Ａｌｉｃｅ"));
+        assert_eq!("fiona", normalize_identifier(&config, "\u{FB01}ona"));
+        // Precomposed and combining accents
+        assert_eq!(normalize_identifier(&config, "Jos\u{E9}"), normalize_identifier(&config, "JOSE\u{301}"));
+        // Folding, not lowercasing
+        assert_eq!("strasse", normalize_identifier(&config, "Straße"));
+    }
+
+    #[test]
+    fn should_apply_only_the_configured_steps() {
+        let config = IdentifierNormalizationConfig { trim: false, unicode_nfkc: false, case_fold: false };
+        assert_eq!(" Ａlice ", normalize_identifier(&config, " Ａlice "));
+
+        let config = IdentifierNormalizationConfig { trim: true, unicode_nfkc: false, case_fold: false };
+        assert_eq!("Ａlice", normalize_identifier(&config, " Ａlice "));
+
+        let config = IdentifierNormalizationConfig { trim: true, unicode_nfkc: true, case_fold: false };
+        assert_eq!("Alice", normalize_identifier(&config, " Ａlice "));
+    }
+
+    #[test]
+    fn should_return_the_normalized_form_then_the_legacy_ones() {
+        let config = IdentifierNormalizationConfig::default();
+        assert_eq!(vec!["alice"], lookup_forms(&config, " Alice "));
+        assert_eq!(vec!["strasse", "straße"], lookup_forms(&config, "Straße"));
+        // SQLite lowercases only the ASCII letters
+        assert_eq!(vec!["àlice", "Àlice"], lookup_forms(&config, "ÀLICE"));
+    }
+}
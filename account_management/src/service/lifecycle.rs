@@ -0,0 +1,154 @@
+use crate::error::LsAccountManagementError;
+use crate::model::auth_account::AuthAccountModel;
+use c3p0::sqlx::Database;
+use std::future::Future;
+use std::pin::Pin;
+
+type BoxedFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Callbacks of the application on the changes of the accounts (e.g. to
+/// provision the default data of a new user or to sync it to a CRM).
+///
+/// They are invoked by `LsAMAccountService` in the transaction of the
+/// change, with its connection and the account as saved: an error rolls
+/// the change back. Every method does nothing by default.
+pub trait AccountLifecycleListener<DB: Database>: Send + Sync + 'static {
+    /// An account was created, either pending activation or, for the
+    /// external identities, already active
+    fn on_account_created(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn on_account_activated(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    fn on_account_disabled(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The account was suspended, or its suspension was changed
+    fn on_account_suspended(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The account was locked by an administrator
+    fn on_account_locked(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The account is active again after being disabled, suspended or
+    /// locked
+    fn on_account_reactivated(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The personal data of the account were scrubbed; `account` is the
+    /// anonymized version
+    fn on_account_anonymized(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The account was deleted, also by the purge of the anonymized and the
+    /// never activated accounts; `account` is its last version
+    fn on_account_deleted(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// Roles were added to or deleted from the account
+    fn on_roles_changed(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+
+    /// The password was changed by the user or reset with a token
+    fn on_password_changed(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> impl Future<Output = Result<(), LsAccountManagementError>> + Send {
+        async { Ok(()) }
+    }
+}
+
+/// The change notified to the listeners
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AccountLifecycleEvent {
+    Created,
+    Activated,
+    Disabled,
+    Suspended,
+    Locked,
+    Reactivated,
+    Anonymized,
+    Deleted,
+    RolesChanged,
+    PasswordChanged,
+}
+
+/// Object-safe view of an [`AccountLifecycleListener`]
+pub(crate) trait ErasedAccountLifecycleListener<DB: Database>: Send + Sync + 'static {
+    fn notify<'a>(
+        &'a self,
+        conn: &'a mut DB::Connection,
+        event: AccountLifecycleEvent,
+        account: &'a AuthAccountModel,
+    ) -> BoxedFuture<'a, Result<(), LsAccountManagementError>>;
+}
+
+impl<DB: Database, L: AccountLifecycleListener<DB>> ErasedAccountLifecycleListener<DB> for L {
+    fn notify<'a>(
+        &'a self,
+        conn: &'a mut DB::Connection,
+        event: AccountLifecycleEvent,
+        account: &'a AuthAccountModel,
+    ) -> BoxedFuture<'a, Result<(), LsAccountManagementError>> {
+        match event {
+            AccountLifecycleEvent::Created => Box::pin(self.on_account_created(conn, account)),
+            AccountLifecycleEvent::Activated => Box::pin(self.on_account_activated(conn, account)),
+            AccountLifecycleEvent::Disabled => Box::pin(self.on_account_disabled(conn, account)),
+            AccountLifecycleEvent::Suspended => Box::pin(self.on_account_suspended(conn, account)),
+            AccountLifecycleEvent::Locked => Box::pin(self.on_account_locked(conn, account)),
+            AccountLifecycleEvent::Reactivated => Box::pin(self.on_account_reactivated(conn, account)),
+            AccountLifecycleEvent::Anonymized => Box::pin(self.on_account_anonymized(conn, account)),
+            AccountLifecycleEvent::Deleted => Box::pin(self.on_account_deleted(conn, account)),
+            AccountLifecycleEvent::RolesChanged => Box::pin(self.on_roles_changed(conn, account)),
+            AccountLifecycleEvent::PasswordChanged => Box::pin(self.on_password_changed(conn, account)),
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::config::AMConfig;
+use crate::dto::totp_dto::TotpEnrollmentDto;
+use crate::error::LsAccountManagementError;
+use crate::model::auth_account::{AccountData, AccountStatus, AuthAccountModel, MfaState};
+use crate::model::token::TokenType;
+use crate::repository::{AMRepositoryManager, AccountRepository};
+use crate::service::account::{
+    ACCOUNT_AUDIT_ENTITY_TYPE, AccountAuditAction, account_actor, audit_snapshot, count_failed_login,
+    fetch_by_username, locked_until, new_auth,
+};
+use crate::service::audit::LsAMAuditService;
+use crate::service::password_codec::LsPasswordCodecService;
+use crate::service::token::LsTokenService;
+use crate::service::totp::LsTotpService;
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::service::auth::Auth;
+use lightspeed_core::utils::current_epoch_seconds;
+use log::*;
+use std::sync::Arc;
+
+pub const MFA_ALREADY_ENABLED: &str = "MFA_ALREADY_ENABLED";
+pub const MFA_NOT_PENDING: &str = "MFA_NOT_PENDING";
+pub const MFA_NOT_ENABLED: &str = "MFA_NOT_ENABLED";
+
+/// Returns the current epoch seconds. The TOTP codes are computed and
+/// verified against it.
+pub type LsClock = Arc<dyn Fn() -> i64 + Send + Sync>;
+
+/// TOTP second factor of the accounts: enrollment, login and disabling
+#[derive(Clone)]
+pub struct LsAMMfaService<RepoManager: AMRepositoryManager> {
+    c3p0: RepoManager::C3P0,
+    auth_config: AMConfig,
+    auth_repo: RepoManager::AccountRepo,
+    password_service: Arc<LsPasswordCodecService>,
+    token_service: Arc<LsTokenService<RepoManager>>,
+    audit_service: Arc<LsAMAuditService<RepoManager>>,
+    clock: LsClock,
+}
+
+impl<RepoManager: AMRepositoryManager> LsAMMfaService<RepoManager> {
+    pub fn new(
+        c3p0: RepoManager::C3P0,
+        auth_config: AMConfig,
+        token_service: Arc<LsTokenService<RepoManager>>,
+        password_service: Arc<LsPasswordCodecService>,
+        auth_repo: RepoManager::AccountRepo,
+        audit_service: Arc<LsAMAuditService<RepoManager>>,
+        clock: LsClock,
+    ) -> Self {
+        LsAMMfaService { c3p0, auth_config, auth_repo, password_service, token_service, audit_service, clock }
+    }
+
+    pub async fn start_totp_enrollment(&self, user_id: i64) -> Result<TotpEnrollmentDto, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.start_totp_enrollment_with_conn(conn, user_id).await).await
+    }
+
+    /// Generates a new TOTP secret for the account. It is enabled only after
+    /// a first code is confirmed with `confirm_totp_enrollment_with_conn`;
+    /// a new enrollment replaces a pending one.
+    pub async fn start_totp_enrollment_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<TotpEnrollmentDto, LsAccountManagementError> {
+        info!("Start TOTP enrollment of user_id [{user_id}]");
+        let mut user = self.fetch_active_user_with_conn(conn, user_id).await?;
+
+        if user.data.mfa.is_enabled() {
+            return Err(LsAccountManagementError::BadRequest {
+                message: format!("MFA already enabled for user [{}]", user.data.username),
+                code: MFA_ALREADY_ENABLED,
+            });
+        }
+
+        let secret = LsTotpService::generate_secret();
+        let otpauth_uri = LsTotpService::otpauth_uri(&self.auth_config.totp_issuer, &user.data.username, &secret);
+        user.data.mfa = MfaState::TotpPending { totp_secret: secret.clone() };
+        self.auth_repo.update(conn, user).await?;
+
+        Ok(TotpEnrollmentDto { secret, otpauth_uri })
+    }
+
+    pub async fn confirm_totp_enrollment(
+        &self,
+        user_id: i64,
+        code: &str,
+    ) -> Result<Vec<String>, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.confirm_totp_enrollment_with_conn(conn, user_id, code).await).await
+    }
+
+    /// Enables the pending TOTP secret of the account if `code` is valid.
+    /// Returns the recovery codes; only their hashes are stored, so they
+    /// cannot be retrieved again.
+    pub async fn confirm_totp_enrollment_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        code: &str,
+    ) -> Result<Vec<String>, LsAccountManagementError> {
+        info!("Confirm TOTP enrollment of user_id [{user_id}]");
+        let mut user = self.fetch_active_user_with_conn(conn, user_id).await?;
+
+        let MfaState::TotpPending { totp_secret } = &user.data.mfa else {
+            return Err(LsAccountManagementError::BadRequest {
+                message: format!("No TOTP enrollment pending for user [{}]", user.data.username),
+                code: MFA_NOT_PENDING,
+            });
+        };
+
+        let Some(step) = LsTotpService::verify(totp_secret, code, (self.clock)(), None)? else {
+            return Err(LsAccountManagementError::WrongCredentials);
+        };
+
+        let recovery_codes = LsTotpService::generate_recovery_codes();
+        let before = user.data.clone();
+        user.data.mfa = MfaState::TotpEnabled {
+            totp_secret: totp_secret.clone(),
+            recovery_code_hashes: recovery_codes.iter().map(|code| LsTotpService::hash_recovery_code(code)).collect(),
+            last_used_step: step,
+        };
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(conn, &user, AccountAuditAction::MfaEnabled, &before, &user.data).await?;
+
+        Ok(recovery_codes)
+    }
+
+    pub async fn disable_totp(
+        &self,
+        user_id: i64,
+        password: &str,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        // The failed attempt counted on a wrong password must be committed
+        // even though the call fails.
+        self.c3p0
+            .transaction(async |conn| match self.disable_totp_with_conn(conn, user_id, password).await {
+                Err(LsAccountManagementError::WrongCredentials) => Ok(Err(LsAccountManagementError::WrongCredentials)),
+                result => result.map(Ok),
+            })
+            .await?
+    }
+
+    /// Disables the TOTP second factor, or cancels a pending enrollment,
+    /// after checking the password of the account.
+    ///
+    /// A wrong password counts as a failed login of the account, so the
+    /// caller must commit the transaction also when `WrongCredentials` is
+    /// returned; the password of a locked account is not checked.
+    pub async fn disable_totp_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        password: &str,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        info!("Disable TOTP of user_id [{user_id}]");
+        let mut user = self.fetch_active_user_with_conn(conn, user_id).await?;
+        let now = current_epoch_seconds();
+
+        if let Some(until) = locked_until(&self.auth_config, &user.data, now) {
+            return Err(LsAccountManagementError::AccountLocked { until });
+        }
+
+        if !self.password_service.verify_match(password, &user.data.password).await? {
+            count_failed_login(&mut user.data, now);
+            self.auth_repo.update(conn, user).await?;
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
+
+        if user.data.mfa == MfaState::Disabled {
+            return Err(LsAccountManagementError::BadRequest {
+                message: format!("MFA not enabled for user [{}]", user.data.username),
+                code: MFA_NOT_ENABLED,
+            });
+        }
+
+        let before = user.data.clone();
+        user.data.mfa = MfaState::Disabled;
+        let user = self.auth_repo.update(conn, user).await?;
+        self.audit_with_conn(conn, &user, AccountAuditAction::MfaDisabled, &before, &user.data).await?;
+
+        Ok(user)
+    }
+
+    /// Completes the login of an account with MFA enabled. `code` is either a
+    /// TOTP code or one of the unused recovery codes.
+    ///
+    /// The MFA token can be used only once: a wrong code invalidates it and
+    /// the login must start again from the password. A wrong code also
+    /// counts as a failed login of the account.
+    pub async fn login_with_mfa_code(&self, mfa_token: &str, code: &str) -> Result<Auth, LsAccountManagementError> {
+        // The invalidation of the MFA token and the failed attempt must be
+        // committed also when the code is wrong.
+        self.c3p0
+            .transaction(async |conn| match self.login_with_mfa_code_with_conn(conn, mfa_token, code).await {
+                Err(LsAccountManagementError::WrongCredentials) => Ok(Err(LsAccountManagementError::WrongCredentials)),
+                result => result.map(Ok),
+            })
+            .await?
+    }
+
+    pub async fn login_with_mfa_code_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        mfa_token: &str,
+        code: &str,
+    ) -> Result<Auth, LsAccountManagementError> {
+        let token = self.token_service.fetch_by_token_with_conn(conn, mfa_token, true).await?;
+        debug!("MFA login called with token [{}] of user [{}]", token.id, token.data.username);
+        match &token.data.token_type {
+            TokenType::MfaPending => {}
+            _ => return Err(LsAccountManagementError::TokenNotValid),
+        };
+
+        let mut user = fetch_by_username(&self.auth_repo, conn, &self.auth_config, &token.data.username).await?;
+        match &user.data.status {
+            AccountStatus::Active => {}
+            _ => return Err(LsAccountManagementError::InactiveUser(user.data.username.to_string())),
+        };
+        let now = current_epoch_seconds();
+
+        if let Some(until) = locked_until(&self.auth_config, &user.data, now) {
+            debug!("MFA login of user [{}] rejected: account locked until [{until}]", user.data.username);
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
+
+        self.token_service.delete_with_conn(conn, token).await?;
+
+        let MfaState::TotpEnabled { totp_secret, recovery_code_hashes, last_used_step } = &mut user.data.mfa else {
+            return Err(LsAccountManagementError::TokenNotValid);
+        };
+
+        let code_accepted =
+            if let Some(step) = LsTotpService::verify(totp_secret, code, (self.clock)(), Some(*last_used_step))? {
+                *last_used_step = step;
+                true
+            } else {
+                let code_hash = LsTotpService::hash_recovery_code(code);
+                match recovery_code_hashes.iter().position(|hash| *hash == code_hash) {
+                    Some(index) => {
+                        info!("Recovery code used by user [{}]", user.data.username);
+                        recovery_code_hashes.remove(index);
+                        true
+                    }
+                    None => false,
+                }
+            };
+
+        if !code_accepted {
+            debug!("Wrong MFA code for user [{}]", user.data.username);
+            count_failed_login(&mut user.data, now);
+            self.auth_repo.update(conn, user).await?;
+            return Err(LsAccountManagementError::WrongCredentials);
+        }
+
+        user.data.failed_login_attempts = 0;
+        user.data.last_failed_login_epoch_seconds = None;
+        let user = self.auth_repo.update(conn, user).await?;
+        Ok(new_auth(&self.auth_config, user, now))
+    }
+
+    async fn fetch_active_user_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<AuthAccountModel, LsAccountManagementError> {
+        let user = self.auth_repo.fetch_by_id(conn, user_id).await?;
+        match &user.data.status {
+            AccountStatus::Active => Ok(user),
+            _ => Err(LsAccountManagementError::InactiveUser(user.data.username.to_string())),
+        }
+    }
+
+    async fn audit_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user: &AuthAccountModel,
+        action: AccountAuditAction,
+        before: &AccountData,
+        after: &AccountData,
+    ) -> Result<(), LsAccountManagementError> {
+        self.audit_service
+            .record_with_conn(
+                conn,
+                Some(account_actor(user)),
+                action.as_ref(),
+                ACCOUNT_AUDIT_ENTITY_TYPE,
+                &user.id.to_string(),
+                (Some(audit_snapshot(before)), Some(audit_snapshot(after))),
+            )
+            .await?;
+        Ok(())
+    }
+}
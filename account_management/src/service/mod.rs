@@ -1,3 +1,18 @@
 pub mod account;
+pub mod acl;
+pub mod audit;
+pub mod breached_password;
+pub mod data_export;
+#[cfg(feature = "email")]
+pub mod email;
+pub mod identifier;
+pub mod lifecycle;
+pub mod mfa;
+#[cfg(feature = "oidc")]
+pub mod oidc;
 pub mod password_codec;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+pub mod session;
 pub mod token;
+pub mod totp;
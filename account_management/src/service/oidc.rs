@@ -0,0 +1,521 @@
+use crate::config::{AMConfig, OidcProviderConfig};
+use crate::error::LsAccountManagementError;
+use crate::model::audit::AuditActor;
+use crate::model::event::AccountProvisioned;
+use crate::model::external_identity::{ExternalIdentityData, ExternalIdentityModel};
+use crate::repository::{AMRepositoryManager, ExternalIdentityRepository};
+use crate::service::account::{ACCOUNT_AUDIT_ENTITY_TYPE, AccountAuditAction, LoginOutcome, LsAMAccountService};
+use crate::service::audit::LsAMAuditService;
+use c3p0::sqlx::Database;
+use c3p0::*;
+use data_encoding::BASE64URL_NOPAD;
+use jsonwebtoken::jwk::{Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use lightspeed_core::service::auth::Auth;
+use lightspeed_core::utils::current_epoch_seconds;
+use lightspeed_event::service::publisher::LsEventPublisher;
+use log::*;
+use parking_lot::RwLock;
+use rand::RngExt;
+use secrecy::ExposeSecret;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use url::Url;
+
+/// Bytes of randomness of the state, the nonce and the PKCE verifier
+const RANDOM_VALUE_BYTES: usize = 32;
+
+/// Signature algorithms accepted for the ID tokens. The HMAC ones are not
+/// allowed as the keys of the provider are public.
+const ID_TOKEN_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+/// The endpoints published by a provider in its discovery document
+#[derive(Debug, Clone, Deserialize)]
+pub struct OidcDiscoveryDocument {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+}
+
+/// An authorization started with `authorization_request`.
+///
+/// The user must be redirected to `authorization_url`; the request must be
+/// kept by the caller, e.g. in an encrypted cookie, until the provider
+/// redirects the user back with the authorization code.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcAuthorizationRequest {
+    pub provider: String,
+    pub authorization_url: String,
+    pub state: String,
+    pub nonce: String,
+    pub pkce_verifier: String,
+}
+
+/// The claims of a verified ID token used by the service
+#[derive(Debug, Clone, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub email_verified: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// The discovery document and the signing keys of a provider
+struct ProviderMetadata {
+    discovery: OidcDiscoveryDocument,
+    jwks: JwkSet,
+}
+
+type MetadataCache = Arc<RwLock<HashMap<String, Arc<ProviderMetadata>>>>;
+
+/// OpenID Connect relying party: logs in the users through the providers in
+/// `AMConfig::oidc_providers` with the authorization code flow and PKCE.
+///
+/// The identities of the providers are linked to the accounts by issuer and
+/// subject. When `jit_provisioning` is enabled for a provider, an unknown
+/// identity with a verified email gets a new active account at its first
+/// login.
+#[derive(Clone)]
+pub struct LsAMOidcService<RepoManager: AMRepositoryManager> {
+    c3p0: RepoManager::C3P0,
+    auth_config: AMConfig,
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+    external_identity_repo: RepoManager::ExternalIdentityRepo,
+    audit_service: Arc<LsAMAuditService<RepoManager>>,
+    event_publisher: Arc<LsEventPublisher<RepoManager::OutboxRepo>>,
+    http_client: reqwest::Client,
+    metadata: MetadataCache,
+}
+
+impl<RepoManager: AMRepositoryManager> LsAMOidcService<RepoManager> {
+    pub fn new(
+        c3p0: RepoManager::C3P0,
+        auth_config: AMConfig,
+        account_service: Arc<LsAMAccountService<RepoManager>>,
+        external_identity_repo: RepoManager::ExternalIdentityRepo,
+        audit_service: Arc<LsAMAuditService<RepoManager>>,
+        event_publisher: Arc<LsEventPublisher<RepoManager::OutboxRepo>>,
+    ) -> Self {
+        LsAMOidcService {
+            c3p0,
+            auth_config,
+            account_service,
+            external_identity_repo,
+            audit_service,
+            event_publisher,
+            http_client: reqwest::Client::new(),
+            metadata: Default::default(),
+        }
+    }
+
+    /// Starts a login, or a link, through the provider `provider_name`
+    pub async fn authorization_request(
+        &self,
+        provider_name: &str,
+    ) -> Result<OidcAuthorizationRequest, LsAccountManagementError> {
+        debug!("Start authorization with OIDC provider [{provider_name}]");
+        let provider = self.provider(provider_name)?;
+        let metadata = self.provider_metadata(provider, false).await?;
+
+        let state = random_value();
+        let nonce = random_value();
+        let pkce_verifier = random_value();
+        let pkce_challenge = BASE64URL_NOPAD.encode(&Sha256::digest(pkce_verifier.as_bytes()));
+
+        let mut authorization_url = Url::parse(&metadata.discovery.authorization_endpoint)
+            .map_err(|err| LsAccountManagementError::OidcProviderError { message: err.to_string() })?;
+        authorization_url
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &provider.redirect_uri)
+            .append_pair("scope", &provider.scopes.join(" "))
+            .append_pair("state", &state)
+            .append_pair("nonce", &nonce)
+            .append_pair("code_challenge", &pkce_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(OidcAuthorizationRequest {
+            provider: provider.name.clone(),
+            authorization_url: authorization_url.into(),
+            state,
+            nonce,
+            pkce_verifier,
+        })
+    }
+
+    /// Completes the login started by `request` with the `code` and `state`
+    /// received from the provider
+    pub async fn login(
+        &self,
+        request: &OidcAuthorizationRequest,
+        code: &str,
+        state: &str,
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
+        let provider = self.provider(&request.provider)?;
+        let claims = self.verified_claims(provider, request, code, state).await?;
+        self.c3p0.transaction(async |conn| self.login_with_conn(conn, provider, &claims).await).await
+    }
+
+    async fn login_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        provider: &OidcProviderConfig,
+        claims: &IdTokenClaims,
+    ) -> Result<LoginOutcome, LsAccountManagementError> {
+        debug!("OIDC login of subject [{}] of issuer [{}]", claims.sub, claims.iss);
+        let now = current_epoch_seconds();
+        let user_id = match self
+            .external_identity_repo
+            .fetch_by_issuer_and_subject_optional(conn, &claims.iss, &claims.sub)
+            .await?
+        {
+            Some(mut identity) => {
+                identity.data.email = claims.email.clone();
+                identity.data.last_login_epoch_seconds = Some(now);
+                self.external_identity_repo.update(conn, identity).await?.data.user_id
+            }
+            None if provider.jit_provisioning => self.provision_with_conn(conn, claims, now).await?,
+            None => {
+                debug!("Subject [{}] of issuer [{}] is not linked to any account", claims.sub, claims.iss);
+                return Err(LsAccountManagementError::ExternalIdentityNotLinked);
+            }
+        };
+        self.account_service.external_login_with_conn(conn, user_id).await
+    }
+
+    /// Creates the account of an unknown identity and returns its id
+    async fn provision_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        claims: &IdTokenClaims,
+        now: i64,
+    ) -> Result<i64, LsAccountManagementError> {
+        let email = match &claims.email {
+            Some(email) if claims.email_verified => email,
+            _ => {
+                return Err(LsAccountManagementError::OidcAuthenticationFailed {
+                    message: "A verified email is required to create an account".to_owned(),
+                });
+            }
+        };
+        let user = self.account_service.provision_account_with_conn(conn, email).await?;
+        self.link_with_conn(conn, user.id, claims, Some(now), None).await?;
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &AccountProvisioned {
+                    user_id: user.id,
+                    username: user.data.username.clone(),
+                    email: user.data.email.clone(),
+                    issuer: claims.iss.clone(),
+                },
+            )
+            .await?;
+        Ok(user.id)
+    }
+
+    /// Links to the account `user_id` the identity authenticated by the
+    /// provider with the authorization started by `request`
+    pub async fn link_identity(
+        &self,
+        user_id: i64,
+        request: &OidcAuthorizationRequest,
+        code: &str,
+        state: &str,
+        actor: Option<&Auth>,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        let provider = self.provider(&request.provider)?;
+        let claims = self.verified_claims(provider, request, code, state).await?;
+        self.c3p0
+            .transaction(async |conn| {
+                match self
+                    .external_identity_repo
+                    .fetch_by_issuer_and_subject_optional(conn, &claims.iss, &claims.sub)
+                    .await?
+                {
+                    Some(identity) if identity.data.user_id == user_id => Ok(identity),
+                    Some(_) => Err(LsAccountManagementError::ExternalIdentityAlreadyLinked),
+                    None => {
+                        self.account_service.fetch_by_user_id_with_conn(conn, user_id).await?;
+                        self.link_with_conn(conn, user_id, &claims, None, actor.map(AuditActor::from)).await
+                    }
+                }
+            })
+            .await
+    }
+
+    async fn link_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        claims: &IdTokenClaims,
+        last_login_epoch_seconds: Option<i64>,
+        actor: Option<AuditActor>,
+    ) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+        info!("Link subject [{}] of issuer [{}] to user_id [{user_id}]", claims.sub, claims.iss);
+        let identity = self
+            .external_identity_repo
+            .save(
+                conn,
+                NewRecord::new(ExternalIdentityData {
+                    user_id,
+                    issuer: claims.iss.clone(),
+                    subject: claims.sub.clone(),
+                    email: claims.email.clone(),
+                    created_date_epoch_seconds: current_epoch_seconds(),
+                    last_login_epoch_seconds,
+                }),
+            )
+            .await?;
+        self.audit_service
+            .record_with_conn(
+                conn,
+                actor,
+                AccountAuditAction::ExternalIdentityLinked.as_ref(),
+                ACCOUNT_AUDIT_ENTITY_TYPE,
+                &user_id.to_string(),
+                (None, Some(json!({ "issuer": identity.data.issuer, "subject": identity.data.subject }))),
+            )
+            .await?;
+        Ok(identity)
+    }
+
+    pub async fn fetch_external_identities(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<ExternalIdentityModel>, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.fetch_external_identities_with_conn(conn, user_id).await).await
+    }
+
+    pub async fn fetch_external_identities_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<Vec<ExternalIdentityModel>, LsAccountManagementError> {
+        debug!("Fetch external identities of user_id [{user_id}]");
+        self.external_identity_repo.fetch_all_by_user_id(conn, user_id).await
+    }
+
+    pub async fn unlink_identity(
+        &self,
+        user_id: i64,
+        identity_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<(), LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.unlink_identity_with_conn(conn, user_id, identity_id, actor).await)
+            .await
+    }
+
+    /// Removes the external identity `identity_id` from the account `user_id`
+    pub async fn unlink_identity_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        identity_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<(), LsAccountManagementError> {
+        info!("Unlink external identity [{identity_id}] from user_id [{user_id}]");
+        let identity = self
+            .external_identity_repo
+            .fetch_all_by_user_id(conn, user_id)
+            .await?
+            .into_iter()
+            .find(|identity| identity.id == identity_id)
+            .ok_or(LsAccountManagementError::ExternalIdentityNotLinked)?;
+        let identity = self.external_identity_repo.delete(conn, identity).await?;
+        self.audit_service
+            .record_with_conn(
+                conn,
+                actor.map(AuditActor::from),
+                AccountAuditAction::ExternalIdentityUnlinked.as_ref(),
+                ACCOUNT_AUDIT_ENTITY_TYPE,
+                &user_id.to_string(),
+                (Some(json!({ "issuer": identity.data.issuer, "subject": identity.data.subject })), None),
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn provider(&self, provider_name: &str) -> Result<&OidcProviderConfig, LsAccountManagementError> {
+        self.auth_config
+            .oidc_providers
+            .iter()
+            .find(|provider| provider.name == provider_name)
+            .ok_or_else(|| LsAccountManagementError::UnknownOidcProvider(provider_name.to_owned()))
+    }
+
+    /// Checks the `state` returned by the provider, exchanges the `code` for
+    /// an ID token and returns its verified claims
+    async fn verified_claims(
+        &self,
+        provider: &OidcProviderConfig,
+        request: &OidcAuthorizationRequest,
+        code: &str,
+        state: &str,
+    ) -> Result<IdTokenClaims, LsAccountManagementError> {
+        if !bool::from(request.state.as_bytes().ct_eq(state.as_bytes())) {
+            return Err(LsAccountManagementError::OidcAuthenticationFailed { message: "State mismatch".to_owned() });
+        }
+
+        let metadata = self.provider_metadata(provider, false).await?;
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", provider.redirect_uri.as_str()),
+            ("client_id", provider.client_id.as_str()),
+            ("code_verifier", request.pkce_verifier.as_str()),
+        ];
+        if let Some(client_secret) = &provider.client_secret {
+            form.push(("client_secret", client_secret.expose_secret()));
+        }
+        let response = self
+            .http_client
+            .post(&metadata.discovery.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(provider_error)?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(LsAccountManagementError::OidcAuthenticationFailed {
+                message: format!("Token endpoint answered [{status}]: {body}"),
+            });
+        }
+        let token: TokenResponse = response.json().await.map_err(provider_error)?;
+
+        self.verify_id_token(provider, &token.id_token, &request.nonce).await
+    }
+
+    async fn verify_id_token(
+        &self,
+        provider: &OidcProviderConfig,
+        id_token: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, LsAccountManagementError> {
+        let header = jsonwebtoken::decode_header(id_token).map_err(authentication_failed)?;
+        if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+            return Err(LsAccountManagementError::OidcAuthenticationFailed {
+                message: format!("ID token algorithm [{:?}] not allowed", header.alg),
+            });
+        }
+
+        let mut metadata = self.provider_metadata(provider, false).await?;
+        let jwk = match find_key(&metadata.jwks, header.kid.as_deref()) {
+            Some(jwk) => jwk.clone(),
+            None => {
+                // The provider may have rotated its keys
+                metadata = self.provider_metadata(provider, true).await?;
+                find_key(&metadata.jwks, header.kid.as_deref()).cloned().ok_or_else(|| {
+                    LsAccountManagementError::OidcAuthenticationFailed {
+                        message: format!("Unknown ID token key [{:?}]", header.kid),
+                    }
+                })?
+            }
+        };
+        let key = DecodingKey::from_jwk(&jwk).map_err(authentication_failed)?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.discovery.issuer]);
+        validation.set_audience(&[&provider.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims =
+            jsonwebtoken::decode::<IdTokenClaims>(id_token, &key, &validation).map_err(authentication_failed)?.claims;
+
+        match &claims.nonce {
+            Some(claimed) if bool::from(claimed.as_bytes().ct_eq(nonce.as_bytes())) => Ok(claims),
+            _ => Err(LsAccountManagementError::OidcAuthenticationFailed { message: "Nonce mismatch".to_owned() }),
+        }
+    }
+
+    /// Returns the discovery document and the keys of the provider, loading
+    /// them if not cached or if `reload` is set
+    async fn provider_metadata(
+        &self,
+        provider: &OidcProviderConfig,
+        reload: bool,
+    ) -> Result<Arc<ProviderMetadata>, LsAccountManagementError> {
+        if !reload && let Some(metadata) = self.metadata.read().get(&provider.name) {
+            return Ok(metadata.clone());
+        }
+
+        debug!("Load metadata of OIDC provider [{}]", provider.name);
+        let discovery: OidcDiscoveryDocument = self
+            .get_json(&format!("{}/.well-known/openid-configuration", provider.issuer_url.trim_end_matches('/')))
+            .await?;
+        if discovery.issuer != provider.issuer_url {
+            return Err(LsAccountManagementError::OidcProviderError {
+                message: format!(
+                    "Provider [{}] declares issuer [{}] instead of [{}]",
+                    provider.name, discovery.issuer, provider.issuer_url
+                ),
+            });
+        }
+        let jwks: JwkSet = self.get_json(&discovery.jwks_uri).await?;
+
+        let metadata = Arc::new(ProviderMetadata { discovery, jwks });
+        self.metadata.write().insert(provider.name.clone(), metadata.clone());
+        Ok(metadata)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, LsAccountManagementError> {
+        self.http_client
+            .get(url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(provider_error)?
+            .json()
+            .await
+            .map_err(provider_error)
+    }
+}
+
+/// Returns the key `kid`; a token without `kid` can only be verified when
+/// the provider publishes a single key
+fn find_key<'a>(jwks: &'a JwkSet, kid: Option<&str>) -> Option<&'a Jwk> {
+    match kid {
+        Some(kid) => jwks.find(kid),
+        None if jwks.keys.len() == 1 => jwks.keys.first(),
+        None => None,
+    }
+}
+
+fn random_value() -> String {
+    let mut value = [0u8; RANDOM_VALUE_BYTES];
+    rand::rng().fill(&mut value);
+    BASE64URL_NOPAD.encode(&value)
+}
+
+fn provider_error(err: reqwest::Error) -> LsAccountManagementError {
+    LsAccountManagementError::OidcProviderError { message: err.to_string() }
+}
+
+fn authentication_failed(err: jsonwebtoken::errors::Error) -> LsAccountManagementError {
+    LsAccountManagementError::OidcAuthenticationFailed { message: err.to_string() }
+}
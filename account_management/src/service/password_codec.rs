@@ -1,6 +1,11 @@
 use crate::error::LsAccountManagementError;
-use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng};
+use argon2::password_hash::{PasswordHash, PasswordHasher, SaltString, rand_core::OsRng};
 use argon2::{Algorithm, Argon2, Params, Version};
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+
+/// Prefixes of the bcrypt hashes, in the Modular Crypt Format
+const BCRYPT_PREFIXES: [&str; 4] = ["$2a$", "$2b$", "$2x$", "$2y$"];
 
 #[derive(Clone)]
 pub struct LsPasswordCodecService {
@@ -40,20 +45,46 @@ impl LsPasswordCodecService {
         Ok(LsPasswordCodecService { argon2, dummy_hash })
     }
 
+    /// Tells whether `hashed` was not computed by `hash_password`, i.e. it
+    /// uses another algorithm or other Argon2 parameters than the configured
+    /// ones, so it should be replaced at the next successful login.
+    pub fn needs_rehash(&self, hashed: &str) -> bool {
+        let Ok(parsed) = PasswordHash::new(hashed) else {
+            return true;
+        };
+        let Ok(params) = Params::try_from(&parsed) else {
+            return true;
+        };
+        let expected = self.argon2.params();
+        parsed.algorithm != Algorithm::Argon2id.ident()
+            || parsed.version != Some(Version::V0x13 as u32)
+            || params.m_cost() != expected.m_cost()
+            || params.t_cost() != expected.t_cost()
+            || params.p_cost() != expected.p_cost()
+    }
+
     pub fn dummy_hash(&self) -> &str {
         &self.dummy_hash
     }
 
+    /// Verifies the password against an Argon2, PBKDF2 or scrypt PHC string,
+    /// or a bcrypt hash. The last ones are accepted only to let the accounts
+    /// imported from other systems log in; see `needs_rehash`.
     pub async fn verify_match(&self, plain_password: &str, hashed: &str) -> Result<bool, LsAccountManagementError> {
         let plain = plain_password.to_owned();
         let hashed = hashed.to_owned();
         tokio::task::spawn_blocking(move || -> Result<bool, LsAccountManagementError> {
+            if BCRYPT_PREFIXES.iter().any(|prefix| hashed.starts_with(prefix)) {
+                return bcrypt::verify(plain.as_bytes(), &hashed).map_err(|err| {
+                    LsAccountManagementError::PasswordEncryptionError { message: format!("bcrypt verify: {err:?}") }
+                });
+            }
             let parsed = PasswordHash::new(&hashed).map_err(|err| {
-                LsAccountManagementError::PasswordEncryptionError { message: format!("argon2 parse: {err:?}") }
+                LsAccountManagementError::PasswordEncryptionError { message: format!("password hash parse: {err:?}") }
             })?;
             // verify_password uses the algorithm/params encoded in the hash,
             // so it works regardless of the verifier instance's settings.
-            Ok(Argon2::default().verify_password(plain.as_bytes(), &parsed).is_ok())
+            Ok(parsed.verify_password(&[&Argon2::default(), &Pbkdf2, &Scrypt], plain.as_bytes()).is_ok())
         })
         .await
         .map_err(|err| LsAccountManagementError::PasswordEncryptionError {
@@ -124,4 +155,63 @@ pub mod test {
         assert!(codec.verify_match(plain_pass, &h2).await?);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn should_verify_legacy_hashes() -> Result<(), LsAccountManagementError> {
+        let codec = fast_codec();
+        let plain_pass = "legacy-password";
+        let salt = SaltString::generate(&mut OsRng);
+
+        let pbkdf2_hash = Pbkdf2
+            .hash_password_customized(
+                plain_pass.as_bytes(),
+                Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+                None,
+                pbkdf2::Params { rounds: 1_000, output_length: 32 },
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        let scrypt_hash = Scrypt
+            .hash_password_customized(
+                plain_pass.as_bytes(),
+                None,
+                None,
+                scrypt::Params::new(4, 8, 1, 32).unwrap(),
+                &salt,
+            )
+            .unwrap()
+            .to_string();
+        let bcrypt_hash = bcrypt::hash(plain_pass, 4).unwrap();
+
+        assert!(pbkdf2_hash.starts_with("$pbkdf2-sha256$"));
+        assert!(scrypt_hash.starts_with("$scrypt$"));
+        assert!(bcrypt_hash.starts_with("$2b$"));
+
+        for hash in [pbkdf2_hash, scrypt_hash, bcrypt_hash] {
+            assert!(codec.verify_match(plain_pass, &hash).await?);
+            assert!(!codec.verify_match("wrong-password", &hash).await?);
+            assert!(codec.needs_rehash(&hash));
+        }
+
+        // bcrypt hash generated by another implementation
+        assert!(codec.verify_match("password", "$2a$04$UuTkLRZZ6QofpDOlMz32MuuxEHA43WOemOYHPz6.SjsVsyO1tDU96").await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_need_rehash_if_the_parameters_change() -> Result<(), LsAccountManagementError> {
+        let codec = fast_codec();
+        let hash = codec.hash_password("password").await?;
+        assert!(!codec.needs_rehash(&hash));
+
+        let stronger_codec = LsPasswordCodecService::new(16, 2, 1)?;
+        assert!(stronger_codec.needs_rehash(&hash));
+        assert!(stronger_codec.verify_match("password", &hash).await?);
+        assert!(!stronger_codec.needs_rehash(&stronger_codec.hash_password("password").await?));
+
+        assert!(codec.needs_rehash("not a hash"));
+        Ok(())
+    }
 }
@@ -0,0 +1,123 @@
+use crate::config::AMScheduleConfig;
+use crate::error::LsAccountManagementError;
+use crate::repository::AMRepositoryManager;
+use crate::service::account::LsAMAccountService;
+use lightspeed_scheduler::{Job, JobExecutor, ScheduleRepository, ScheduledTask, SchedulerError};
+use std::sync::Arc;
+
+/// Group of the jobs added by [`register_scheduled_tasks`]
+pub const SCHEDULED_TASKS_GROUP: &str = "lightspeed_account_management";
+
+/// Runs [`LsAMAccountService::purge_anonymized_accounts`] when the job fires.
+/// The service uses its own transaction, independent of the one of the job.
+pub struct PurgeAnonymizedAccountsTask<RepoManager: AMRepositoryManager> {
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+}
+
+impl<RepoManager: AMRepositoryManager> PurgeAnonymizedAccountsTask<RepoManager> {
+    pub fn new(account_service: Arc<LsAMAccountService<RepoManager>>) -> Self {
+        Self { account_service }
+    }
+}
+
+/// Runs [`LsAMAccountService::purge_pending_accounts`] when the job fires.
+/// The service uses its own transaction, independent of the one of the job.
+pub struct PurgePendingAccountsTask<RepoManager: AMRepositoryManager> {
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+}
+
+impl<RepoManager: AMRepositoryManager> PurgePendingAccountsTask<RepoManager> {
+    pub fn new(account_service: Arc<LsAMAccountService<RepoManager>>) -> Self {
+        Self { account_service }
+    }
+}
+
+/// Runs [`LsAMAccountService::purge_expired_tokens`] when the job fires.
+/// The service uses its own transaction, independent of the one of the job.
+pub struct PurgeExpiredTokensTask<RepoManager: AMRepositoryManager> {
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+}
+
+impl<RepoManager: AMRepositoryManager> PurgeExpiredTokensTask<RepoManager> {
+    pub fn new(account_service: Arc<LsAMAccountService<RepoManager>>) -> Self {
+        Self { account_service }
+    }
+}
+
+/// Runs [`LsAMAccountService::lift_expired_suspensions`] when the job fires.
+/// The service uses its own transaction, independent of the one of the job.
+pub struct LiftExpiredSuspensionsTask<RepoManager: AMRepositoryManager> {
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+}
+
+impl<RepoManager: AMRepositoryManager> LiftExpiredSuspensionsTask<RepoManager> {
+    pub fn new(account_service: Arc<LsAMAccountService<RepoManager>>) -> Self {
+        Self { account_service }
+    }
+}
+
+/// Adds to `executor` the maintenance tasks with a cron expression in
+/// `schedules`. A job is identified by [`SCHEDULED_TASKS_GROUP`] and the
+/// name of the service method it runs.
+pub async fn register_scheduled_tasks<RepoManager, R>(
+    executor: &JobExecutor<R>,
+    account_service: Arc<LsAMAccountService<RepoManager>>,
+    schedules: &AMScheduleConfig,
+) -> Result<(), SchedulerError>
+where
+    RepoManager: AMRepositoryManager,
+    R: ScheduleRepository,
+    PurgeAnonymizedAccountsTask<RepoManager>: ScheduledTask<R>,
+    PurgePendingAccountsTask<RepoManager>: ScheduledTask<R>,
+    PurgeExpiredTokensTask<RepoManager>: ScheduledTask<R>,
+    LiftExpiredSuspensionsTask<RepoManager>: ScheduledTask<R>,
+{
+    if let Some(cron) = &schedules.purge_anonymized_accounts {
+        let task = PurgeAnonymizedAccountsTask::new(account_service.clone());
+        executor.add_job(cron, Job::new(SCHEDULED_TASKS_GROUP, "purge_anonymized_accounts", None, task)).await?;
+    }
+    if let Some(cron) = &schedules.purge_pending_accounts {
+        let task = PurgePendingAccountsTask::new(account_service.clone());
+        executor.add_job(cron, Job::new(SCHEDULED_TASKS_GROUP, "purge_pending_accounts", None, task)).await?;
+    }
+    if let Some(cron) = &schedules.purge_expired_tokens {
+        let task = PurgeExpiredTokensTask::new(account_service.clone());
+        executor.add_job(cron, Job::new(SCHEDULED_TASKS_GROUP, "purge_expired_tokens", None, task)).await?;
+    }
+    if let Some(cron) = &schedules.lift_expired_suspensions {
+        let task = LiftExpiredSuspensionsTask::new(account_service);
+        executor.add_job(cron, Job::new(SCHEDULED_TASKS_GROUP, "lift_expired_suspensions", None, task)).await?;
+    }
+    Ok(())
+}
+
+/// Implemented for the concrete repository managers, whose transaction
+/// futures are known to be `Send`.
+macro_rules! impl_scheduled_task {
+    (@task $task:ident, $method:ident, $repo_manager:ty) => {
+        impl<R: ScheduleRepository> ScheduledTask<R> for $task<$repo_manager> {
+            type Error = LsAccountManagementError;
+
+            async fn run(&self, _tx: &mut R::Tx) -> Result<(), Self::Error> {
+                self.account_service.$method().await.map(|_| ())
+            }
+        }
+    };
+    ($repo_manager:ty) => {
+        impl_scheduled_task!(@task PurgeAnonymizedAccountsTask, purge_anonymized_accounts, $repo_manager);
+        impl_scheduled_task!(@task PurgePendingAccountsTask, purge_pending_accounts, $repo_manager);
+        impl_scheduled_task!(@task PurgeExpiredTokensTask, purge_expired_tokens, $repo_manager);
+        impl_scheduled_task!(@task LiftExpiredSuspensionsTask, lift_expired_suspensions, $repo_manager);
+    };
+}
+
+#[cfg(feature = "mysql")]
+impl_scheduled_task!(crate::repository::mysql::MyAMSqlRepositoryManager);
+
+#[cfg(feature = "postgres")]
+impl_scheduled_task!(crate::repository::postgres::PgAMRepositoryManager);
+
+#[cfg(feature = "sqlite")]
+impl_scheduled_task!(crate::repository::sqlite::SqliteAMRepositoryManager);
+
+impl_scheduled_task!(crate::repository::memory::MemoryAMRepositoryManager);
@@ -0,0 +1,274 @@
+use crate::config::AMConfig;
+use crate::error::LsAccountManagementError;
+use crate::model::audit::AuditActor;
+use crate::model::session::{SessionData, SessionModel};
+use crate::repository::{AMRepositoryManager, SessionRepository};
+use crate::service::account::{ACCOUNT_AUDIT_ENTITY_TYPE, AccountAuditAction};
+use crate::service::audit::LsAMAuditService;
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::service::auth::Auth;
+use lightspeed_core::utils::current_epoch_seconds;
+use lightspeed_core::web::{Headers, WebAuthService};
+use log::*;
+use serde_json::json;
+use std::sync::Arc;
+
+/// Records the login sessions, so that the users can see where they are
+/// logged in and the sessions can be revoked.
+///
+/// A session is recorded by [`LsAMSessionService::create_session`] for the
+/// `Auth` returned by a login; the router of the module does it for its
+/// logins. For the enabled databases the service is a
+/// [`SessionValidator`](lightspeed_core::web::SessionValidator):
+/// set it on the `WebAuthService` with `with_session_validator` for the
+/// tokens of the revoked sessions to be rejected by
+/// `auth_from_request_with_session` and the tonic `AuthLayer`.
+#[derive(Clone)]
+pub struct LsAMSessionService<RepoManager: AMRepositoryManager> {
+    c3p0: RepoManager::C3P0,
+    auth_config: AMConfig,
+    session_repo: RepoManager::SessionRepo,
+    audit_service: Arc<LsAMAuditService<RepoManager>>,
+}
+
+impl<RepoManager: AMRepositoryManager> LsAMSessionService<RepoManager> {
+    pub fn new(
+        c3p0: RepoManager::C3P0,
+        auth_config: AMConfig,
+        session_repo: RepoManager::SessionRepo,
+        audit_service: Arc<LsAMAuditService<RepoManager>>,
+    ) -> Self {
+        LsAMSessionService { c3p0, auth_config, session_repo, audit_service }
+    }
+
+    pub async fn create_session(
+        &self,
+        auth: &Auth,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        self.c3p0
+            .transaction(async |conn| self.create_session_with_conn(conn, auth, user_agent, ip_address).await)
+            .await
+    }
+
+    /// Records the session of `auth`. The expired sessions of the user are
+    /// deleted at the same time.
+    pub async fn create_session_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        auth: &Auth,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+    ) -> Result<SessionModel, LsAccountManagementError> {
+        info!("Create session [{}] for user_id [{}]", auth.session_id, auth.id);
+        let now = current_epoch_seconds();
+        for session in self.session_repo.fetch_all_by_user_id(conn, auth.id).await? {
+            if session.data.expire_at_epoch_seconds < now {
+                self.session_repo.delete(conn, session).await?;
+            }
+        }
+
+        self.session_repo
+            .save(
+                conn,
+                NewRecord::new(SessionData {
+                    session_id: auth.session_id.clone(),
+                    user_id: auth.id,
+                    created_date_epoch_seconds: now,
+                    last_activity_epoch_seconds: now,
+                    expire_at_epoch_seconds: auth.expiration_ts_seconds,
+                    user_agent,
+                    ip_address,
+                }),
+            )
+            .await
+    }
+
+    pub async fn fetch_active_sessions(&self, user_id: i64) -> Result<Vec<SessionModel>, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.fetch_active_sessions_with_conn(conn, user_id).await).await
+    }
+
+    /// Returns the sessions of the user that are not expired
+    pub async fn fetch_active_sessions_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+    ) -> Result<Vec<SessionModel>, LsAccountManagementError> {
+        debug!("Fetch active sessions of user_id [{user_id}]");
+        let now = current_epoch_seconds();
+        let mut sessions = self.session_repo.fetch_all_by_user_id(conn, user_id).await?;
+        sessions.retain(|session| session.data.expire_at_epoch_seconds >= now);
+        Ok(sessions)
+    }
+
+    /// Parses the token and checks that its session is still valid
+    pub async fn auth_from_token_string(
+        &self,
+        web_auth_service: &WebAuthService,
+        token: &str,
+    ) -> Result<Auth, LsAccountManagementError> {
+        let auth = web_auth_service.auth_from_token_string(token)?.auth;
+        self.validate_session(&auth).await?;
+        Ok(auth)
+    }
+
+    /// Same as `auth_from_token_string` for the token of the request
+    pub async fn auth_from_request<H: Headers>(
+        &self,
+        web_auth_service: &WebAuthService,
+        req: &H,
+    ) -> Result<Auth, LsAccountManagementError> {
+        let token = web_auth_service.token_string_from_request(req)?;
+        self.auth_from_token_string(web_auth_service, token).await
+    }
+
+    pub async fn validate_session(&self, auth: &Auth) -> Result<(), LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.validate_session_with_conn(conn, auth).await).await
+    }
+
+    /// Fails with `SessionNotValid` if the session of `auth` was revoked or
+    /// is expired, otherwise updates its last activity
+    pub async fn validate_session_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        auth: &Auth,
+    ) -> Result<(), LsAccountManagementError> {
+        trace!("Validate session [{}] of user_id [{}]", auth.session_id, auth.id);
+        let now = current_epoch_seconds();
+        let mut session = match self.session_repo.fetch_by_session_id_optional(conn, &auth.session_id).await? {
+            Some(session) if session.data.user_id == auth.id && session.data.expire_at_epoch_seconds >= now => session,
+            _ => {
+                debug!("Session [{}] of user_id [{}] not valid", auth.session_id, auth.id);
+                return Err(LsAccountManagementError::SessionNotValid);
+            }
+        };
+
+        if now - session.data.last_activity_epoch_seconds >= self.auth_config.session_activity_update_seconds as i64 {
+            session.data.last_activity_epoch_seconds = now;
+            match self.session_repo.update(conn, session).await {
+                // A concurrent request has just updated it
+                Err(LsAccountManagementError::C3p0Error { source: C3p0Error::OptimisticLockError { .. } }) => {}
+                result => {
+                    result?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn revoke_session(
+        &self,
+        user_id: i64,
+        session_id: &str,
+        actor: Option<&Auth>,
+    ) -> Result<(), LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.revoke_session_with_conn(conn, user_id, session_id, actor).await).await
+    }
+
+    /// Revokes the session `session_id` of the user `user_id`; its tokens
+    /// are rejected from now on
+    pub async fn revoke_session_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        session_id: &str,
+        actor: Option<&Auth>,
+    ) -> Result<(), LsAccountManagementError> {
+        info!("Revoke session [{session_id}] of user_id [{user_id}]");
+        let session = self
+            .session_repo
+            .fetch_by_session_id_optional(conn, session_id)
+            .await?
+            .filter(|session| session.data.user_id == user_id)
+            .ok_or(LsAccountManagementError::SessionNotFound)?;
+        self.session_repo.delete(conn, session).await?;
+        self.audit_service
+            .record_with_conn(
+                conn,
+                actor.map(AuditActor::from),
+                AccountAuditAction::SessionRevoked.as_ref(),
+                ACCOUNT_AUDIT_ENTITY_TYPE,
+                &user_id.to_string(),
+                (Some(json!({ "session_id": session_id })), None),
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn revoke_all_sessions(
+        &self,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<u64, LsAccountManagementError> {
+        self.c3p0.transaction(async |conn| self.revoke_all_sessions_with_conn(conn, user_id, actor).await).await
+    }
+
+    /// Revokes every session of the user, logging it out everywhere, and
+    /// returns the number of the revoked sessions
+    pub async fn revoke_all_sessions_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        user_id: i64,
+        actor: Option<&Auth>,
+    ) -> Result<u64, LsAccountManagementError> {
+        info!("Revoke all the sessions of user_id [{user_id}]");
+        let mut revoked = 0;
+        for session in self.session_repo.fetch_all_by_user_id(conn, user_id).await? {
+            self.session_repo.delete(conn, session).await?;
+            revoked += 1;
+        }
+        self.audit_service
+            .record_with_conn(
+                conn,
+                actor.map(AuditActor::from),
+                AccountAuditAction::AllSessionsRevoked.as_ref(),
+                ACCOUNT_AUDIT_ENTITY_TYPE,
+                &user_id.to_string(),
+                (Some(json!({ "sessions": revoked })), None),
+            )
+            .await?;
+        Ok(revoked)
+    }
+}
+
+// Implemented for the concrete repository managers, as for them the
+// transaction futures are known to be `Send`. A session that is not valid
+// is an `UnauthenticatedError` for the `WebAuthService`.
+macro_rules! impl_session_validator {
+    ($repo_manager:ty) => {
+        impl lightspeed_core::web::SessionValidator for LsAMSessionService<$repo_manager> {
+            fn validate_session<'a>(
+                &'a self,
+                auth: &'a Auth,
+            ) -> std::pin::Pin<
+                Box<dyn std::future::Future<Output = Result<(), lightspeed_core::error::LsError>> + Send + 'a>,
+            > {
+                Box::pin(async move {
+                    LsAMSessionService::validate_session(self, auth).await.map_err(|err| match err {
+                        LsAccountManagementError::LsError { source } => source,
+                        LsAccountManagementError::C3p0Error { source } => {
+                            lightspeed_core::error::LsError::C3p0Error { source }
+                        }
+                        err => {
+                            debug!("Session [{}] rejected: {err:?}", auth.session_id);
+                            lightspeed_core::error::LsError::UnauthenticatedError
+                        }
+                    })
+                })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mysql")]
+impl_session_validator!(crate::repository::mysql::MyAMSqlRepositoryManager);
+
+#[cfg(feature = "postgres")]
+impl_session_validator!(crate::repository::postgres::PgAMRepositoryManager);
+
+#[cfg(feature = "sqlite")]
+impl_session_validator!(crate::repository::sqlite::SqliteAMRepositoryManager);
+
+impl_session_validator!(crate::repository::memory::MemoryAMRepositoryManager);
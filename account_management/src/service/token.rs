@@ -4,8 +4,24 @@ use crate::model::token::{TokenData, TokenModel, TokenType};
 use crate::repository::{AMRepositoryManager, TokenRepository};
 use c3p0::sqlx::Database;
 use c3p0::*;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use data_encoding::{BASE64URL_NOPAD, HEXLOWER};
+use hmac::{Hmac, KeyInit, Mac};
+use lightspeed_core::error::LsError;
 use lightspeed_core::utils::*;
 use log::*;
+use rand::RngExt;
+use secrecy::ExposeSecret;
+use sha2::Sha256;
+
+/// Minimum length, in bytes, of the `token_hash_secret`
+pub const MIN_TOKEN_HASH_SECRET_LENGTH: usize = 32;
+
+/// Input of the HMAC that derives from the `token_hash_secret` the key of
+/// the sealed tokens. It cannot be a generated token, which has no `:`.
+const SEALED_TOKEN_KEY_LABEL: &[u8] = b"ls_am:sealed_token_key";
+const SEALED_TOKEN_NONCE_LENGTH: usize = 12;
 
 #[derive(Clone)]
 pub struct LsTokenService<RepoManager: AMRepositoryManager> {
@@ -18,11 +34,105 @@ impl<RepoManager: AMRepositoryManager> LsTokenService<RepoManager> {
         LsTokenService { auth_config, token_repo }
     }
 
+    /// Fails if the `token_hash_secret` is shorter than
+    /// `MIN_TOKEN_HASH_SECRET_LENGTH` bytes, as the hashes of the tokens
+    /// could then be computed by whoever reads them
+    pub fn check_token_hash_secret(auth_config: &AMConfig) -> Result<(), LsAccountManagementError> {
+        if auth_config.token_hash_secret.expose_secret().len() < MIN_TOKEN_HASH_SECRET_LENGTH {
+            return Err(LsError::ConfigurationError {
+                message: format!("The token_hash_secret must be at least {MIN_TOKEN_HASH_SECRET_LENGTH} bytes long"),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// HMAC-SHA256, keyed by `token_hash_secret`, with which a token is
+    /// stored and looked up
+    pub fn hash_token(&self, token: &str) -> Result<String, LsAccountManagementError> {
+        Ok(HEXLOWER.encode(&self.mac(token.as_bytes())?))
+    }
+
+    /// Encrypts the token with a key derived from the `token_hash_secret`,
+    /// so that it can be carried by the events stored in the outbox without
+    /// being usable by whoever reads them. `open_token` gives it back.
+    pub fn seal_token(&self, token: &str) -> Result<String, LsAccountManagementError> {
+        let mut nonce = [0u8; SEALED_TOKEN_NONCE_LENGTH];
+        rand::rng().fill(&mut nonce);
+        let sealed = self.sealed_token_cipher()?.encrypt(&Nonce::from(nonce), token.as_bytes()).map_err(|err| {
+            LsAccountManagementError::TokenSealingError { message: format!("Cannot seal the token: {err:?}") }
+        })?;
+        Ok(BASE64URL_NOPAD.encode(&[nonce.as_slice(), &sealed].concat()))
+    }
+
+    /// Returns the token sealed by `seal_token`. Fails with `TokenNotValid`
+    /// if it was not sealed with the current `token_hash_secret`.
+    pub fn open_token(&self, sealed_token: &str) -> Result<String, LsAccountManagementError> {
+        let sealed =
+            BASE64URL_NOPAD.decode(sealed_token.as_bytes()).map_err(|_| LsAccountManagementError::TokenNotValid)?;
+        if sealed.len() < SEALED_TOKEN_NONCE_LENGTH {
+            return Err(LsAccountManagementError::TokenNotValid);
+        }
+        let (nonce, sealed) = sealed.split_at(SEALED_TOKEN_NONCE_LENGTH);
+        let nonce = Nonce::try_from(nonce).map_err(|_| LsAccountManagementError::TokenNotValid)?;
+        let token =
+            self.sealed_token_cipher()?.decrypt(&nonce, sealed).map_err(|_| LsAccountManagementError::TokenNotValid)?;
+        String::from_utf8(token).map_err(|_| LsAccountManagementError::TokenNotValid)
+    }
+
+    fn sealed_token_cipher(&self) -> Result<ChaCha20Poly1305, LsAccountManagementError> {
+        ChaCha20Poly1305::new_from_slice(&self.mac(SEALED_TOKEN_KEY_LABEL)?).map_err(|err| {
+            LsAccountManagementError::TokenSealingError {
+                message: format!("Cannot build the key of the sealed tokens: {err:?}"),
+            }
+        })
+    }
+
+    /// HMAC-SHA256 of `value` keyed by `token_hash_secret`
+    fn mac(&self, value: &[u8]) -> Result<Vec<u8>, LsAccountManagementError> {
+        Self::check_token_hash_secret(&self.auth_config)?;
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.auth_config.token_hash_secret.expose_secret().as_bytes())
+            .map_err(|err| LsAccountManagementError::TokenSealingError {
+                message: format!("Cannot hash the token: {err:?}"),
+            })?;
+        mac.update(value);
+        Ok(mac.finalize().into_bytes().to_vec())
+    }
+
+    /// Generates and saves a new token. Only its hash is stored: the
+    /// returned model is the only one holding the token itself, which must
+    /// be sent to the user now.
     pub async fn generate_and_save_token_with_conn<S: Into<String>>(
         &self,
         conn: &mut <RepoManager::DB as Database>::Connection,
         username: S,
         token_type: TokenType,
+    ) -> Result<TokenModel, LsAccountManagementError> {
+        let validity_seconds = self.auth_config.activation_token_validity_minutes as i64 * 60;
+        self.generate_and_save_token_valid_for_with_conn(conn, username, token_type, validity_seconds).await
+    }
+
+    /// Same as `generate_and_save_token_with_conn` for a token that expires
+    /// `validity_seconds` after its creation.
+    pub async fn generate_and_save_token_valid_for_with_conn<S: Into<String>>(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        username: S,
+        token_type: TokenType,
+        validity_seconds: i64,
+    ) -> Result<TokenModel, LsAccountManagementError> {
+        self.generate_and_save_device_token_with_conn(conn, username, token_type, validity_seconds, None).await
+    }
+
+    /// Same as `generate_and_save_token_valid_for_with_conn` for a token
+    /// bound to the device with the given fingerprint, if any.
+    pub async fn generate_and_save_device_token_with_conn<S: Into<String>>(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        username: S,
+        token_type: TokenType,
+        validity_seconds: i64,
+        device_fingerprint: Option<String>,
     ) -> Result<TokenModel, LsAccountManagementError> {
         let username = username.into();
         info!("Generate and save token of type [{token_type:?}] for username [{username}]");
@@ -36,14 +146,23 @@ impl<RepoManager: AMRepositoryManager> LsTokenService<RepoManager> {
         // concurrent fetches/deletes of still-valid tokens.
         self.delete_expired_with_conn(conn, issued_at).await?;
 
-        let expire_at_epoch = issued_at + (self.auth_config.activation_token_validity_minutes as i64 * 60);
-        let token = NewRecord::new(TokenData {
-            token: new_hyphenated_uuid(),
-            token_type,
-            username,
-            expire_at_epoch_seconds: expire_at_epoch,
-        });
-        self.token_repo.save(conn, token).await
+        let expire_at_epoch = issued_at + validity_seconds;
+        let token = new_hyphenated_uuid();
+        let mut token_model = self
+            .token_repo
+            .save(
+                conn,
+                NewRecord::new(TokenData {
+                    token: self.hash_token(&token)?,
+                    token_type,
+                    username,
+                    expire_at_epoch_seconds: expire_at_epoch,
+                    device_fingerprint,
+                }),
+            )
+            .await?;
+        token_model.data.token = token;
+        Ok(token_model)
     }
 
     pub async fn delete_expired_with_conn(
@@ -53,7 +172,7 @@ impl<RepoManager: AMRepositoryManager> LsTokenService<RepoManager> {
     ) -> Result<u64, LsAccountManagementError> {
         let deleted = self.token_repo.delete_expired(conn, threshold_epoch_seconds).await?;
         if deleted > 0 {
-            debug!("Lazy sweep removed [{deleted}] expired token(s)");
+            debug!("Removed [{deleted}] expired token(s)");
         }
         Ok(deleted)
     }
@@ -64,8 +183,15 @@ impl<RepoManager: AMRepositoryManager> LsTokenService<RepoManager> {
         token: &str,
         fail_if_expired: bool,
     ) -> Result<TokenModel, LsAccountManagementError> {
-        debug!("Fetch by token [{token}]");
-        let token_model = self.token_repo.fetch_by_token(conn, token).await?;
+        debug!("Fetch by token");
+        let token_model =
+            self.token_repo.fetch_by_token(conn, &self.hash_token(token)?).await.map_err(|err| match err {
+                LsAccountManagementError::SqlxError { source: c3p0::sqlx::Error::RowNotFound }
+                | LsAccountManagementError::C3p0Error {
+                    source: c3p0::error::C3p0Error::SqlxError(c3p0::sqlx::Error::RowNotFound),
+                } => LsAccountManagementError::TokenNotValid,
+                err => err,
+            })?;
 
         if fail_if_expired && current_epoch_seconds() > token_model.data.expire_at_epoch_seconds {
             Err(LsAccountManagementError::TokenExpired)
@@ -88,7 +214,7 @@ impl<RepoManager: AMRepositoryManager> LsTokenService<RepoManager> {
         conn: &mut <RepoManager::DB as Database>::Connection,
         token_model: TokenModel,
     ) -> Result<TokenModel, LsAccountManagementError> {
-        debug!("Delete token_model with id [{:?}] and token [{}]", token_model.id, token_model.data.token);
+        debug!("Delete token_model with id [{:?}]", token_model.id);
         self.token_repo.delete(conn, token_model).await
     }
 }
@@ -0,0 +1,189 @@
+use crate::error::LsAccountManagementError;
+use data_encoding::{BASE32_NOPAD, HEXLOWER};
+use hmac::{Hmac, KeyInit, Mac};
+use lightspeed_core::service::random::LsRandomService;
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use rand::RngExt;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+pub const TOTP_PERIOD_SECONDS: i64 = 30;
+pub const TOTP_DIGITS: u32 = 6;
+/// Number of time steps before and after the current one whose codes are
+/// accepted, to tolerate clock drift
+pub const TOTP_ALLOWED_SKEW_STEPS: u64 = 1;
+pub const TOTP_SECRET_BYTES: usize = 20;
+
+pub const RECOVERY_CODES_COUNT: usize = 10;
+pub const RECOVERY_CODE_LENGTH: usize = 10;
+
+pub const INVALID_TOTP_SECRET: &str = "INVALID_TOTP_SECRET";
+
+/// RFC 6238 time-based one-time passwords with the parameters supported by
+/// all the common authenticator apps: HMAC-SHA1, 6 digits, 30 seconds.
+pub struct LsTotpService {}
+
+impl LsTotpService {
+    /// Returns a new random secret, base32 encoded without padding
+    pub fn generate_secret() -> String {
+        let mut secret = [0u8; TOTP_SECRET_BYTES];
+        rand::rng().fill(&mut secret);
+        BASE32_NOPAD.encode(&secret)
+    }
+
+    /// Returns the `otpauth://` URI to be shown as QR code to the user
+    pub fn otpauth_uri(issuer: &str, account_name: &str, secret: &str) -> String {
+        let issuer = utf8_percent_encode(issuer, NON_ALPHANUMERIC).to_string();
+        let account_name = utf8_percent_encode(account_name, NON_ALPHANUMERIC);
+        format!(
+            "otpauth://totp/{issuer}:{account_name}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_PERIOD_SECONDS}"
+        )
+    }
+
+    /// Returns the time step of the given instant
+    pub fn time_step(epoch_seconds: i64) -> u64 {
+        (epoch_seconds.max(0) / TOTP_PERIOD_SECONDS) as u64
+    }
+
+    /// Returns the code of the time step of the given instant
+    pub fn code_at(secret: &str, epoch_seconds: i64) -> Result<String, LsAccountManagementError> {
+        hotp(&decode_secret(secret)?, Self::time_step(epoch_seconds))
+    }
+
+    /// Returns the time step matched by `code` at the given instant. Only the
+    /// steps after `last_used_step` are accepted, so a code cannot be reused.
+    pub fn verify(
+        secret: &str,
+        code: &str,
+        epoch_seconds: i64,
+        last_used_step: Option<u64>,
+    ) -> Result<Option<u64>, LsAccountManagementError> {
+        let key = decode_secret(secret)?;
+        let code = code.trim();
+        let current_step = Self::time_step(epoch_seconds);
+        let first_step = match last_used_step {
+            Some(last_used_step) => current_step.saturating_sub(TOTP_ALLOWED_SKEW_STEPS).max(last_used_step + 1),
+            None => current_step.saturating_sub(TOTP_ALLOWED_SKEW_STEPS),
+        };
+        for step in first_step..=current_step + TOTP_ALLOWED_SKEW_STEPS {
+            if bool::from(hotp(&key, step)?.as_bytes().ct_eq(code.as_bytes())) {
+                return Ok(Some(step));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns new recovery codes
+    pub fn generate_recovery_codes() -> Vec<String> {
+        (0..RECOVERY_CODES_COUNT)
+            .map(|_| {
+                let code = LsRandomService::random_string(RECOVERY_CODE_LENGTH).to_lowercase();
+                let (first, second) = code.split_at(RECOVERY_CODE_LENGTH / 2);
+                format!("{first}-{second}")
+            })
+            .collect()
+    }
+
+    /// Returns the hash of a recovery code. The codes are random, so a plain
+    /// SHA-256 is enough to store them. Case and separators are ignored.
+    pub fn hash_recovery_code(code: &str) -> String {
+        let normalized = code.chars().filter(|c| c.is_ascii_alphanumeric()).collect::<String>().to_lowercase();
+        HEXLOWER.encode(&Sha256::digest(normalized.as_bytes()))
+    }
+}
+
+fn decode_secret(secret: &str) -> Result<Vec<u8>, LsAccountManagementError> {
+    BASE32_NOPAD.decode(secret.as_bytes()).map_err(|err| invalid_secret(format!("Cannot decode TOTP secret: {err:?}")))
+}
+
+fn invalid_secret(message: String) -> LsAccountManagementError {
+    LsAccountManagementError::BadRequest { message, code: INVALID_TOTP_SECRET }
+}
+
+/// RFC 4226 HOTP value of `counter`
+fn hotp(key: &[u8], counter: u64) -> Result<String, LsAccountManagementError> {
+    let mut mac =
+        Hmac::<Sha1>::new_from_slice(key).map_err(|err| invalid_secret(format!("Invalid TOTP secret: {err:?}")))?;
+    mac.update(&counter.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([hash[offset] & 0x7f, hash[offset + 1], hash[offset + 2], hash[offset + 3]]);
+    Ok(format!("{:0width$}", binary % 10u32.pow(TOTP_DIGITS), width = TOTP_DIGITS as usize))
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    // The SHA1 secret of the RFC 6238 test vectors, "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn should_match_the_rfc_6238_test_vectors() {
+        // The RFC lists 8 digit codes; 6 digit codes are their last 6 digits
+        for (epoch_seconds, expected) in [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_111_111_111, "050471"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+        ] {
+            assert_eq!(expected, LsTotpService::code_at(RFC_SECRET, epoch_seconds).unwrap());
+        }
+    }
+
+    #[test]
+    fn should_accept_codes_within_the_allowed_skew() {
+        let now = 1_111_111_111;
+        let code = LsTotpService::code_at(RFC_SECRET, now).unwrap();
+        let step = LsTotpService::time_step(now);
+
+        assert_eq!(Some(step), LsTotpService::verify(RFC_SECRET, &code, now, None).unwrap());
+        assert_eq!(Some(step), LsTotpService::verify(RFC_SECRET, &code, now + TOTP_PERIOD_SECONDS, None).unwrap());
+        assert_eq!(Some(step), LsTotpService::verify(RFC_SECRET, &code, now - TOTP_PERIOD_SECONDS, None).unwrap());
+        assert_eq!(None, LsTotpService::verify(RFC_SECRET, &code, now + 2 * TOTP_PERIOD_SECONDS, None).unwrap());
+        assert_eq!(None, LsTotpService::verify(RFC_SECRET, "000000", now, None).unwrap());
+    }
+
+    #[test]
+    fn should_not_accept_used_steps() {
+        let now = 1_111_111_111;
+        let code = LsTotpService::code_at(RFC_SECRET, now).unwrap();
+        let step = LsTotpService::time_step(now);
+
+        assert_eq!(Some(step), LsTotpService::verify(RFC_SECRET, &code, now, Some(step - 1)).unwrap());
+        assert_eq!(None, LsTotpService::verify(RFC_SECRET, &code, now, Some(step)).unwrap());
+    }
+
+    #[test]
+    fn should_generate_decodable_secrets() {
+        let secret = LsTotpService::generate_secret();
+        assert_eq!(32, secret.len());
+        assert_eq!(6, LsTotpService::code_at(&secret, 0).unwrap().len());
+        assert!(LsTotpService::code_at("not base32!", 0).is_err());
+    }
+
+    #[test]
+    fn should_build_the_otpauth_uri() {
+        assert_eq!(
+            "otpauth://totp/My%20App:user%40email%2Ecom?secret=ABC&issuer=My%20App&algorithm=SHA1&digits=6&period=30",
+            LsTotpService::otpauth_uri("My App", "user@email.com", "ABC")
+        );
+    }
+
+    #[test]
+    fn should_hash_recovery_codes_ignoring_case_and_separators() {
+        let codes = LsTotpService::generate_recovery_codes();
+        assert_eq!(RECOVERY_CODES_COUNT, codes.len());
+        let code = &codes[0];
+        assert_eq!(RECOVERY_CODE_LENGTH + 1, code.len());
+        assert_eq!(
+            LsTotpService::hash_recovery_code(code),
+            LsTotpService::hash_recovery_code(&code.replace('-', "").to_uppercase())
+        );
+        assert_ne!(LsTotpService::hash_recovery_code(code), LsTotpService::hash_recovery_code(&codes[1]));
+    }
+}
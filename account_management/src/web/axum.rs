@@ -0,0 +1,556 @@
+use crate::LsAMModule;
+use crate::dto::activate_user_dto::ActivateUserDto;
+use crate::dto::change_password_dto::{ChangePasswordDto, ChangePasswordDtoValidable};
+use crate::dto::create_login_dto::{CreateLoginDto, CreateLoginDtoValidable};
+use crate::dto::error_response_dto::ErrorResponseDto;
+use crate::dto::login_dto::LoginDto;
+use crate::dto::login_response_dto::LoginResponseDto;
+use crate::dto::mfa_login_dto::{MfaLoginDto, MfaRequiredDto};
+use crate::dto::reset_password_dto::{ResetPasswordDto, ResetPasswordDtoValidable};
+use crate::dto::send_new_activation_token_dto::SendNewActivationTokenByUsernameAndEmailDto;
+use crate::dto::send_reset_password_dto::SendResetPasswordDto;
+use crate::dto::session_dto::SessionDto;
+use crate::dto::token_dto::TokenDto;
+use crate::dto::totp_dto::{ConfirmTotpDto, DisableTotpDto, RecoveryCodesDto, TotpEnrollmentDto};
+use crate::error::LsAccountManagementError;
+use crate::repository::AMRepositoryManager;
+use crate::service::account::LoginOutcome;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get, post};
+use axum::{Json, Router};
+use lightspeed_core::error::LsError;
+use lightspeed_core::service::auth::{Auth, AuthContext};
+use lightspeed_core::web::WebAuthService;
+use lightspeed_validator::ValidationError;
+use log::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Key of the `field_errors` entry with the errors not bound to a single field
+pub const GLOBAL_ERRORS_KEY: &str = "_global";
+
+/// Selects the endpoints exposed by [`router`]. `POST /login`,
+/// `POST /login/mfa` and `POST /activate` are always exposed.
+///
+/// The logins record a session with the `User-Agent` and the client IP
+/// taken from `X-Forwarded-For` or `X-Real-IP`; the authenticated endpoints
+/// reject the tokens of the revoked sessions.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AMRouterConfig {
+    /// Exposes `POST /signup` and `POST /activation/resend`
+    pub signup_enabled: bool,
+    /// Exposes `POST /password/reset/request` and `POST /password/reset`
+    pub password_reset_enabled: bool,
+    /// Exposes `POST /password/change`
+    pub password_change_enabled: bool,
+    /// Exposes `POST /mfa/totp/enroll`, `POST /mfa/totp/confirm` and
+    /// `POST /mfa/totp/disable`
+    pub mfa_enabled: bool,
+    /// Exposes `GET /sessions`, `DELETE /sessions` and
+    /// `DELETE /sessions/{session_id}`
+    pub sessions_enabled: bool,
+}
+
+impl Default for AMRouterConfig {
+    fn default() -> Self {
+        Self {
+            signup_enabled: true,
+            password_reset_enabled: true,
+            password_change_enabled: true,
+            mfa_enabled: true,
+            sessions_enabled: true,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct AMRouterState<RepoManager: AMRepositoryManager> {
+    am_module: LsAMModule<RepoManager>,
+    web_auth_service: WebAuthService,
+}
+
+/// Builds the REST endpoints of the account management module; mount them
+/// under a prefix with `Router::nest`.
+///
+/// The endpoints that start a flow for an account, i.e. signup, resend of
+/// the activation token and password reset request, answer `202 Accepted`
+/// whether or not the account exists, so they cannot be used to find out
+/// which accounts are registered. Their tokens are never returned: they
+/// reach the user through the events published by `LsAMAccountService`.
+///
+/// It is implemented for the modules of the enabled databases.
+pub trait LsAMRouter {
+    fn router(self, web_auth_service: WebAuthService, config: &AMRouterConfig) -> Router;
+}
+
+macro_rules! impl_router {
+    ($repo_manager:ty) => {
+        impl LsAMRouter for LsAMModule<$repo_manager> {
+            fn router(self, web_auth_service: WebAuthService, config: &AMRouterConfig) -> Router {
+                let mut router = Router::new()
+                    .route("/login", post(login::<$repo_manager>))
+                    .route("/login/mfa", post(login_mfa::<$repo_manager>))
+                    .route("/activate", post(activate::<$repo_manager>));
+
+                if config.signup_enabled {
+                    router = router
+                        .route("/signup", post(signup::<$repo_manager>))
+                        .route("/activation/resend", post(resend_activation::<$repo_manager>));
+                }
+                if config.password_reset_enabled {
+                    router = router
+                        .route("/password/reset/request", post(request_password_reset::<$repo_manager>))
+                        .route("/password/reset", post(reset_password::<$repo_manager>));
+                }
+                if config.password_change_enabled {
+                    router = router.route("/password/change", post(change_password::<$repo_manager>));
+                }
+                if config.mfa_enabled {
+                    router = router
+                        .route("/mfa/totp/enroll", post(enroll_totp::<$repo_manager>))
+                        .route("/mfa/totp/confirm", post(confirm_totp::<$repo_manager>))
+                        .route("/mfa/totp/disable", post(disable_totp::<$repo_manager>));
+                }
+                if config.sessions_enabled {
+                    router = router
+                        .route(
+                            "/sessions",
+                            get(fetch_sessions::<$repo_manager>).delete(revoke_all_sessions::<$repo_manager>),
+                        )
+                        .route("/sessions/{session_id}", delete(revoke_session::<$repo_manager>));
+                }
+
+                router.with_state(AMRouterState { am_module: self, web_auth_service })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "mysql")]
+impl_router!(crate::repository::mysql::MyAMSqlRepositoryManager);
+
+#[cfg(feature = "postgres")]
+impl_router!(crate::repository::postgres::PgAMRepositoryManager);
+
+#[cfg(feature = "sqlite")]
+impl_router!(crate::repository::sqlite::SqliteAMRepositoryManager);
+
+impl_router!(crate::repository::memory::MemoryAMRepositoryManager);
+
+/// The OpenAPI paths of the endpoints of [`LsAMRouter::router`], relative to
+/// the prefix the router is nested under.
+#[cfg(feature = "openapi")]
+pub(crate) fn openapi_paths() -> utoipa::openapi::Paths {
+    utoipa::openapi::path::PathsBuilder::new()
+        .path_from::<__path_login>()
+        .path_from::<__path_login_mfa>()
+        .path_from::<__path_activate>()
+        .path_from::<__path_signup>()
+        .path_from::<__path_resend_activation>()
+        .path_from::<__path_request_password_reset>()
+        .path_from::<__path_reset_password>()
+        .path_from::<__path_change_password>()
+        .path_from::<__path_enroll_totp>()
+        .path_from::<__path_confirm_totp>()
+        .path_from::<__path_disable_totp>()
+        .path_from::<__path_fetch_sessions>()
+        .path_from::<__path_revoke_all_sessions>()
+        .path_from::<__path_revoke_session>()
+        .build()
+}
+
+/// Answers `202 Accepted` with a [`MfaRequiredDto`] if the account has MFA
+/// enabled; the login is then completed by `POST /login/mfa`.
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/login", tag = "account_management", request_body = LoginDto,
+    responses(
+        (status = 200, body = LoginResponseDto),
+        (status = 202, description = "MFA required", body = MfaRequiredDto),
+        (status = 401, body = ErrorResponseDto),
+        (status = 403, body = ErrorResponseDto),
+    )
+))]
+async fn login<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+    body: Result<Json<LoginDto>, JsonRejection>,
+) -> Result<Response, LsAccountManagementError> {
+    let dto = json_body(body)?;
+    match state.am_module.auth_account_service.login(&dto.username, &dto.password).await? {
+        LoginOutcome::Authenticated(auth) => Ok(login_response(&state, &headers, auth).await?.into_response()),
+        LoginOutcome::MfaRequired { mfa_token, expire_at_epoch_seconds } => Ok((
+            StatusCode::ACCEPTED,
+            Json(MfaRequiredDto {
+                mfa_token: TokenDto { token: mfa_token, expiration_epoch_seconds: expire_at_epoch_seconds },
+            }),
+        )
+            .into_response()),
+    }
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/login/mfa", tag = "account_management", request_body = MfaLoginDto,
+    responses((status = 200, body = LoginResponseDto), (status = 401, body = ErrorResponseDto))
+))]
+async fn login_mfa<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+    body: Result<Json<MfaLoginDto>, JsonRejection>,
+) -> Result<Json<LoginResponseDto>, LsAccountManagementError> {
+    let dto = json_body(body)?;
+    let auth = state.am_module.mfa_service.login_with_mfa_code(&dto.mfa_token, &dto.code).await?;
+    login_response(&state, &headers, auth).await
+}
+
+/// Records the session of the login and returns its token
+async fn login_response<RepoManager: AMRepositoryManager>(
+    state: &AMRouterState<RepoManager>,
+    headers: &HeaderMap,
+    auth: Auth,
+) -> Result<Json<LoginResponseDto>, LsAccountManagementError> {
+    let (token, expiration_epoch_seconds) = state.web_auth_service.token_and_expiration_from_auth(&auth)?;
+    let user_agent = header_value(headers, "user-agent");
+    let ip_address = header_value(headers, "x-forwarded-for")
+        .and_then(|forwarded_for| forwarded_for.split(',').next().map(|ip| ip.trim().to_owned()))
+        .or_else(|| header_value(headers, "x-real-ip"));
+    state.am_module.session_service.create_session(&auth, user_agent, ip_address).await?;
+    Ok(Json(LoginResponseDto { auth, token: TokenDto { token, expiration_epoch_seconds } }))
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_owned)
+}
+
+/// Parses the token of the request and checks that its session is valid.
+/// The token is parsed without the `SessionValidator` of the
+/// `WebAuthService`, if any, so that the session is checked only once.
+async fn authenticated<'a, RepoManager: AMRepositoryManager>(
+    state: &'a AMRouterState<RepoManager>,
+    headers: &HeaderMap,
+) -> Result<AuthContext<'a>, LsAccountManagementError> {
+    let auth_context = state.web_auth_service.auth_from_request(headers)?;
+    state.am_module.session_service.validate_session(&auth_context.auth).await?;
+    Ok(auth_context)
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/signup", tag = "account_management", request_body = CreateLoginDto,
+    responses((status = 202), (status = 400, body = ErrorResponseDto))
+))]
+async fn signup<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    body: Result<Json<CreateLoginDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let dto = json_body(body)?.validate_dto()?;
+    match state.am_module.auth_account_service.create_user(dto).await {
+        Ok(_) | Err(LsAccountManagementError::EmailAlreadyUsed | LsAccountManagementError::UsernameAlreadyUsed) => {
+            Ok(StatusCode::ACCEPTED)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/activate", tag = "account_management", request_body = ActivateUserDto,
+    responses((status = 204), (status = 400, body = ErrorResponseDto))
+))]
+async fn activate<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    body: Result<Json<ActivateUserDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let dto = json_body(body)?;
+    state.am_module.auth_account_service.activate_user(&dto.token).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/activation/resend", tag = "account_management",
+    request_body = SendNewActivationTokenByUsernameAndEmailDto,
+    responses((status = 202), (status = 400, body = ErrorResponseDto))
+))]
+async fn resend_activation<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    body: Result<Json<SendNewActivationTokenByUsernameAndEmailDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let dto = json_body(body)?;
+    match state
+        .am_module
+        .auth_account_service
+        .generate_new_activation_token_by_username_and_email(&dto.username, &dto.email)
+        .await
+    {
+        Ok(_) | Err(LsAccountManagementError::WrongCredentials) => Ok(StatusCode::ACCEPTED),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/password/reset/request", tag = "account_management", request_body = SendResetPasswordDto,
+    responses((status = 202), (status = 400, body = ErrorResponseDto))
+))]
+async fn request_password_reset<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    body: Result<Json<SendResetPasswordDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let dto = json_body(body)?;
+    match state.am_module.auth_account_service.generate_reset_password_token_by_email(&dto.email).await {
+        Ok(_) | Err(LsAccountManagementError::WrongCredentials | LsAccountManagementError::InactiveUser(_)) => {
+            Ok(StatusCode::ACCEPTED)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/password/reset", tag = "account_management", request_body = ResetPasswordDto,
+    responses((status = 204), (status = 400, body = ErrorResponseDto))
+))]
+async fn reset_password<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    body: Result<Json<ResetPasswordDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let dto = json_body(body)?.validate_dto()?;
+    state.am_module.auth_account_service.reset_password_by_token(dto).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/password/change", tag = "account_management", request_body = ChangePasswordDto,
+    security(("bearer_auth" = [])),
+    responses((status = 204), (status = 400, body = ErrorResponseDto), (status = 401, body = ErrorResponseDto))
+))]
+async fn change_password<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+    body: Result<Json<ChangePasswordDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let dto = json_body(body)?;
+    let auth_context = authenticated(&state, &headers).await?;
+    auth_context.is_owner(&dto)?;
+    let dto = dto.validate_dto()?;
+    state.am_module.auth_account_service.change_password(dto, Some(&auth_context.auth.session_id)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/mfa/totp/enroll", tag = "account_management", security(("bearer_auth" = [])),
+    responses((status = 200, body = TotpEnrollmentDto), (status = 401, body = ErrorResponseDto))
+))]
+async fn enroll_totp<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+) -> Result<Json<TotpEnrollmentDto>, LsAccountManagementError> {
+    let user_id = authenticated(&state, &headers).await?.auth.id;
+    Ok(Json(state.am_module.mfa_service.start_totp_enrollment(user_id).await?))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/mfa/totp/confirm", tag = "account_management", request_body = ConfirmTotpDto,
+    security(("bearer_auth" = [])),
+    responses(
+        (status = 200, body = RecoveryCodesDto),
+        (status = 400, body = ErrorResponseDto),
+        (status = 401, body = ErrorResponseDto),
+    )
+))]
+async fn confirm_totp<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+    body: Result<Json<ConfirmTotpDto>, JsonRejection>,
+) -> Result<Json<RecoveryCodesDto>, LsAccountManagementError> {
+    let user_id = authenticated(&state, &headers).await?.auth.id;
+    let dto = json_body(body)?;
+    let recovery_codes = state.am_module.mfa_service.confirm_totp_enrollment(user_id, &dto.code).await?;
+    Ok(Json(RecoveryCodesDto { recovery_codes }))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    post, path = "/mfa/totp/disable", tag = "account_management", request_body = DisableTotpDto,
+    security(("bearer_auth" = [])),
+    responses((status = 204), (status = 401, body = ErrorResponseDto))
+))]
+async fn disable_totp<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+    body: Result<Json<DisableTotpDto>, JsonRejection>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let user_id = authenticated(&state, &headers).await?.auth.id;
+    let dto = json_body(body)?;
+    state.am_module.mfa_service.disable_totp(user_id, &dto.password).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    get, path = "/sessions", tag = "account_management", security(("bearer_auth" = [])),
+    responses((status = 200, body = Vec<SessionDto>), (status = 401, body = ErrorResponseDto))
+))]
+async fn fetch_sessions<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<SessionDto>>, LsAccountManagementError> {
+    let auth = authenticated(&state, &headers).await?.auth;
+    let sessions = state.am_module.session_service.fetch_active_sessions(auth.id).await?;
+    Ok(Json(
+        sessions
+            .into_iter()
+            .map(|session| SessionDto {
+                current: session.data.session_id == auth.session_id,
+                session_id: session.data.session_id,
+                created_date_epoch_seconds: session.data.created_date_epoch_seconds,
+                last_activity_epoch_seconds: session.data.last_activity_epoch_seconds,
+                expire_at_epoch_seconds: session.data.expire_at_epoch_seconds,
+                user_agent: session.data.user_agent,
+                ip_address: session.data.ip_address,
+            })
+            .collect(),
+    ))
+}
+
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete, path = "/sessions/{session_id}", tag = "account_management", security(("bearer_auth" = [])),
+    params(("session_id" = String, Path)),
+    responses(
+        (status = 204),
+        (status = 401, body = ErrorResponseDto),
+        (status = 404, body = ErrorResponseDto),
+    )
+))]
+async fn revoke_session<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let auth = authenticated(&state, &headers).await?.auth;
+    state.am_module.session_service.revoke_session(auth.id, &session_id, Some(&auth)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Logs the user out everywhere, the current session included
+#[cfg_attr(feature = "openapi", utoipa::path(
+    delete, path = "/sessions", tag = "account_management", security(("bearer_auth" = [])),
+    responses((status = 204), (status = 401, body = ErrorResponseDto))
+))]
+async fn revoke_all_sessions<RepoManager: AMRepositoryManager>(
+    State(state): State<AMRouterState<RepoManager>>,
+    headers: HeaderMap,
+) -> Result<StatusCode, LsAccountManagementError> {
+    let auth = authenticated(&state, &headers).await?.auth;
+    state.am_module.session_service.revoke_all_sessions(auth.id, Some(&auth)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn json_body<T>(body: Result<Json<T>, JsonRejection>) -> Result<T, LsAccountManagementError> {
+    body.map(|Json(value)| value)
+        .map_err(|err| LsAccountManagementError::BadRequest { message: err.body_text(), code: "INVALID_BODY" })
+}
+
+/// Runs the `Validable` validators of a request DTO.
+trait ValidateDto: Sized {
+    fn validate_dto(self) -> Result<Self, LsAccountManagementError>;
+}
+
+macro_rules! impl_validate_dto {
+    ($dto:ty, $validable:ty, [$($field:ident),*]) => {
+        impl ValidateDto for $dto {
+            fn validate_dto(self) -> Result<Self, LsAccountManagementError> {
+                <$validable>::new(self).validate().map_err(|validable| {
+                    let mut field_errors = BTreeMap::new();
+                    $(add_field_errors(&mut field_errors, stringify!($field), validable.$field.errors());)*
+                    add_field_errors(&mut field_errors, GLOBAL_ERRORS_KEY, validable.top_level_errors());
+                    LsAccountManagementError::ValidationError { field_errors }
+                })
+            }
+        }
+    };
+}
+
+impl_validate_dto!(CreateLoginDto, CreateLoginDtoValidable, [email, password, password_confirm, accept_privacy_policy]);
+impl_validate_dto!(ResetPasswordDto, ResetPasswordDtoValidable, [password, password_confirm]);
+impl_validate_dto!(ChangePasswordDto, ChangePasswordDtoValidable, [new_password, new_password_confirm]);
+
+fn add_field_errors(field_errors: &mut BTreeMap<String, Vec<String>>, field: &str, errors: &[ValidationError]) {
+    if !errors.is_empty() {
+        field_errors.insert(field.to_owned(), errors.iter().map(|err| err.to_string()).collect());
+    }
+}
+
+/// Only the error code is returned: the messages can contain data, like
+/// usernames, that must not be exposed.
+impl IntoResponse for LsAccountManagementError {
+    fn into_response(self) -> Response {
+        let mut field_errors = BTreeMap::new();
+        let (status, code) = match &self {
+            LsAccountManagementError::ValidationError { field_errors: errors } => {
+                field_errors = errors.clone();
+                (StatusCode::BAD_REQUEST, "VALIDATION_ERROR")
+            }
+            LsAccountManagementError::BadRequest { code, .. } => (StatusCode::BAD_REQUEST, *code),
+            LsAccountManagementError::WrongCredentials => (StatusCode::UNAUTHORIZED, "WRONG_CREDENTIALS"),
+            LsAccountManagementError::InactiveUser(_) => (StatusCode::FORBIDDEN, "INACTIVE_USER"),
+            LsAccountManagementError::ExpiredPassword(_) => (StatusCode::FORBIDDEN, "EXPIRED_PASSWORD"),
+            LsAccountManagementError::PasswordLoginDisabled(_) => (StatusCode::FORBIDDEN, "PASSWORD_LOGIN_DISABLED"),
+            LsAccountManagementError::PasswordReused => (StatusCode::BAD_REQUEST, "PASSWORD_REUSED"),
+            LsAccountManagementError::BreachedPassword => (StatusCode::BAD_REQUEST, "BREACHED_PASSWORD"),
+            LsAccountManagementError::AccountLocked { .. } => (StatusCode::FORBIDDEN, "ACCOUNT_LOCKED"),
+            LsAccountManagementError::AccountSuspended { .. } => (StatusCode::FORBIDDEN, "ACCOUNT_SUSPENDED"),
+            LsAccountManagementError::AccountLockedByAdmin { .. } => (StatusCode::FORBIDDEN, "ACCOUNT_LOCKED_BY_ADMIN"),
+            LsAccountManagementError::TokenExpired => (StatusCode::BAD_REQUEST, "TOKEN_EXPIRED"),
+            LsAccountManagementError::TokenNotValid => (StatusCode::BAD_REQUEST, "TOKEN_NOT_VALID"),
+            LsAccountManagementError::UserNotPendingActivation => {
+                (StatusCode::BAD_REQUEST, "USER_NOT_PENDING_ACTIVATION")
+            }
+            LsAccountManagementError::NotDisabledUser(_) => (StatusCode::BAD_REQUEST, "NOT_DISABLED_USER"),
+            LsAccountManagementError::NotSuspendedUser(_) => (StatusCode::BAD_REQUEST, "NOT_SUSPENDED_USER"),
+            LsAccountManagementError::AnonymizedUser(_) => (StatusCode::BAD_REQUEST, "ANONYMIZED_USER"),
+            LsAccountManagementError::UsernameAlreadyUsed => (StatusCode::CONFLICT, "USERNAME_ALREADY_USED"),
+            LsAccountManagementError::EmailAlreadyUsed => (StatusCode::CONFLICT, "EMAIL_ALREADY_USED"),
+            LsAccountManagementError::UnknownOidcProvider(_) => (StatusCode::BAD_REQUEST, "UNKNOWN_OIDC_PROVIDER"),
+            LsAccountManagementError::OidcProviderError { .. } => (StatusCode::BAD_GATEWAY, "OIDC_PROVIDER_ERROR"),
+            LsAccountManagementError::OidcAuthenticationFailed { .. } => {
+                (StatusCode::UNAUTHORIZED, "OIDC_AUTHENTICATION_FAILED")
+            }
+            LsAccountManagementError::ExternalIdentityNotLinked => {
+                (StatusCode::UNAUTHORIZED, "EXTERNAL_IDENTITY_NOT_LINKED")
+            }
+            LsAccountManagementError::ExternalIdentityAlreadyLinked => {
+                (StatusCode::CONFLICT, "EXTERNAL_IDENTITY_ALREADY_LINKED")
+            }
+            LsAccountManagementError::SessionNotValid => (StatusCode::UNAUTHORIZED, "SESSION_NOT_VALID"),
+            LsAccountManagementError::SessionNotFound => (StatusCode::NOT_FOUND, "SESSION_NOT_FOUND"),
+            LsAccountManagementError::C3p0Error { .. } | LsAccountManagementError::SqlxError { .. } => {
+                (StatusCode::BAD_REQUEST, "BAD_REQUEST")
+            }
+            LsAccountManagementError::LsError { source } => match source {
+                LsError::InvalidTokenError { .. }
+                | LsError::ExpiredTokenError { .. }
+                | LsError::MissingAuthTokenError
+                | LsError::ParseAuthHeaderError { .. }
+                | LsError::UnauthenticatedError => (StatusCode::UNAUTHORIZED, "UNAUTHORIZED"),
+                LsError::ForbiddenError { .. } => (StatusCode::FORBIDDEN, "FORBIDDEN"),
+                LsError::BadRequest { code, .. } => (StatusCode::BAD_REQUEST, *code),
+                LsError::C3p0Error { .. } | LsError::SqlxError { .. } => (StatusCode::BAD_REQUEST, "BAD_REQUEST"),
+                LsError::GenerateTokenError { .. }
+                | LsError::ModuleStartError { .. }
+                | LsError::ConfigurationError { .. } => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+            },
+            LsAccountManagementError::PasswordEncryptionError { .. }
+            | LsAccountManagementError::TokenSealingError { .. }
+            | LsAccountManagementError::BreachedPasswordCheckError { .. }
+            | LsAccountManagementError::EventError { .. } => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+            #[cfg(feature = "email")]
+            LsAccountManagementError::EmailError { .. } => (StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_ERROR"),
+        };
+
+        if status.is_server_error() {
+            error!("Account management request failed: {self:?}");
+        } else {
+            debug!("Account management request rejected: {self:?}");
+        }
+
+        (status, Json(ErrorResponseDto { code: code.to_owned(), field_errors })).into_response()
+    }
+}
@@ -0,0 +1,2 @@
+#[cfg(feature = "axum")]
+pub mod axum;
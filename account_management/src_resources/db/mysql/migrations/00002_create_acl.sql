@@ -0,0 +1,27 @@
+-- ---------------------------
+-- Begin - LS_AM_ACL -
+-- ---------------------------
+
+create table LS_AM_ACL (
+    id BIGINT PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    version INT NOT NULL,
+    create_time TIMESTAMP(3) NOT NULL,
+    update_time TIMESTAMP(3) NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_ACL_UNIQUE_RESOURCE_GRANTEE
+    ON LS_AM_ACL (
+        (JSON_VALUE(DATA, '$.resource_type' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.resource_id' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.grantee.type' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.grantee.id' RETURNING CHAR(255)))
+    );
+
+CREATE INDEX LS_AM_ACL_GRANTEE
+    ON LS_AM_ACL (
+        (JSON_VALUE(DATA, '$.grantee.type' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.grantee.id' RETURNING CHAR(255)))
+    );
+
+-- End - LS_AM_ACL -
@@ -0,0 +1,25 @@
+-- ---------------------------
+-- Begin - LS_AM_AUDIT_EVENT -
+-- ---------------------------
+
+create table LS_AM_AUDIT_EVENT (
+    id BIGINT PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    version INT NOT NULL,
+    create_time TIMESTAMP(3) NOT NULL,
+    update_time TIMESTAMP(3) NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_ENTITY
+    ON LS_AM_AUDIT_EVENT (
+        (JSON_VALUE(DATA, '$.entity_type' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.entity_id' RETURNING CHAR(255)))
+    );
+
+CREATE INDEX LS_AM_AUDIT_EVENT_ACTOR
+    ON LS_AM_AUDIT_EVENT ( (JSON_VALUE(DATA, '$.actor.id' RETURNING SIGNED)) );
+
+CREATE INDEX LS_AM_AUDIT_EVENT_CREATED
+    ON LS_AM_AUDIT_EVENT ( (JSON_VALUE(DATA, '$.created_date_epoch_seconds' RETURNING SIGNED)) );
+
+-- End - LS_AM_AUDIT_EVENT -
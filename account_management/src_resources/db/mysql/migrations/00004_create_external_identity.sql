@@ -0,0 +1,22 @@
+-- ----------------------------------
+-- Begin - LS_AM_EXTERNAL_IDENTITY -
+-- ----------------------------------
+
+create table LS_AM_EXTERNAL_IDENTITY (
+    id BIGINT PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    version INT NOT NULL,
+    create_time TIMESTAMP(3) NOT NULL,
+    update_time TIMESTAMP(3) NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_EXTERNAL_IDENTITY_UNIQUE_ISSUER_SUBJECT
+    ON LS_AM_EXTERNAL_IDENTITY (
+        (JSON_VALUE(DATA, '$.issuer' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.subject' RETURNING CHAR(255)))
+    );
+
+CREATE INDEX LS_AM_EXTERNAL_IDENTITY_USER_ID
+    ON LS_AM_EXTERNAL_IDENTITY ( (JSON_VALUE(DATA, '$.user_id' RETURNING SIGNED)) );
+
+-- End - LS_AM_EXTERNAL_IDENTITY -
@@ -0,0 +1,19 @@
+-- ------------------------
+-- Begin - LS_AM_SESSION -
+-- ------------------------
+
+create table LS_AM_SESSION (
+    id BIGINT PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    version INT NOT NULL,
+    create_time TIMESTAMP(3) NOT NULL,
+    update_time TIMESTAMP(3) NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_SESSION_UNIQUE_SESSION_ID
+    ON LS_AM_SESSION ( (JSON_VALUE(DATA, '$.session_id' RETURNING CHAR(255))) );
+
+CREATE INDEX LS_AM_SESSION_USER_ID
+    ON LS_AM_SESSION ( (JSON_VALUE(DATA, '$.user_id' RETURNING SIGNED)) );
+
+-- End - LS_AM_SESSION -
@@ -0,0 +1,47 @@
+-- The accounts are looked up and kept unique by the normalized forms of
+-- their username and email. They are initialized here with the trimmed
+-- lowercase form, which is the normalization of the application for the
+-- ASCII identifiers. The accounts are also looked up by this form until
+-- `LsAMAccountService::normalize_identifiers` rewrites them with the
+-- configured normalization.
+--
+-- The migration fails, listing them, if different accounts have the same
+-- identifiers once trimmed and lowercased: they must be renamed before
+-- running it. The MySQL error messages are cut at 128 characters.
+
+UPDATE LS_AM_ACCOUNT SET DATA = JSON_SET(
+    DATA,
+    '$.normalized_username', LOWER(TRIM(JSON_UNQUOTE(JSON_EXTRACT(DATA, '$.username')))),
+    '$.normalized_email', LOWER(TRIM(JSON_UNQUOTE(JSON_EXTRACT(DATA, '$.email'))))
+);
+
+DROP PROCEDURE IF EXISTS LS_AM_CHECK_IDENTIFIER_CONFLICTS;
+
+CREATE PROCEDURE LS_AM_CHECK_IDENTIFIER_CONFLICTS()
+BEGIN
+    DECLARE conflicts TEXT;
+    SELECT GROUP_CONCAT(identifier SEPARATOR ', ') INTO conflicts FROM (
+        SELECT JSON_UNQUOTE(JSON_EXTRACT(DATA, '$.normalized_username')) AS identifier FROM LS_AM_ACCOUNT
+            GROUP BY identifier HAVING COUNT(*) > 1
+        UNION ALL
+        SELECT JSON_UNQUOTE(JSON_EXTRACT(DATA, '$.normalized_email')) AS identifier FROM LS_AM_ACCOUNT
+            GROUP BY identifier HAVING COUNT(*) > 1
+    ) AS duplicated;
+    IF conflicts IS NOT NULL THEN
+        SET conflicts = LEFT(CONCAT('Accounts with the same normalized username or email: ', conflicts), 128);
+        SIGNAL SQLSTATE '45000' SET MESSAGE_TEXT = conflicts;
+    END IF;
+END;
+
+CALL LS_AM_CHECK_IDENTIFIER_CONFLICTS();
+
+DROP PROCEDURE LS_AM_CHECK_IDENTIFIER_CONFLICTS;
+
+DROP INDEX LS_AM_ACCOUNT_UNIQUE_USERNAME ON LS_AM_ACCOUNT;
+DROP INDEX LS_AM_ACCOUNT_UNIQUE_EMAIL ON LS_AM_ACCOUNT;
+
+CREATE UNIQUE INDEX LS_AM_ACCOUNT_UNIQUE_NORMALIZED_USERNAME
+    ON LS_AM_ACCOUNT ((JSON_VALUE(DATA, '$.normalized_username' RETURNING CHAR(255))));
+
+CREATE UNIQUE INDEX LS_AM_ACCOUNT_UNIQUE_NORMALIZED_EMAIL
+    ON LS_AM_ACCOUNT ((JSON_VALUE(DATA, '$.normalized_email' RETURNING CHAR(255))));
@@ -0,0 +1,10 @@
+-- The events of the module are about an account: its id is their subject,
+-- by which they are looked up when the account is anonymized. The outbox is
+-- migrated before the module.
+
+UPDATE LS_EVENT_OUTBOX SET DATA = JSON_SET(
+    DATA,
+    '$.subject', JSON_UNQUOTE(JSON_EXTRACT(DATA, '$.payload.user_id'))
+)
+WHERE LEFT(JSON_UNQUOTE(JSON_EXTRACT(DATA, '$.event_type')), 3) = 'AM_'
+    AND JSON_EXTRACT(DATA, '$.payload.user_id') IS NOT NULL;
@@ -0,0 +1,21 @@
+-- ---------------------------
+-- Begin - LS_AM_ACL -
+-- ---------------------------
+
+create table LS_AM_ACL (
+    ID bigserial primary key,
+    version bigint NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL,
+    update_time TIMESTAMPTZ NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_ACL_UNIQUE_RESOURCE_GRANTEE ON LS_AM_ACL(
+    (DATA->>'resource_type'), (DATA->>'resource_id'), (DATA->'grantee'->>'type'), (DATA->'grantee'->>'id')
+);
+
+CREATE INDEX LS_AM_ACL_GRANTEE ON LS_AM_ACL(
+    (DATA->>'resource_type'), (DATA->'grantee'->>'type'), (DATA->'grantee'->>'id')
+);
+
+-- End - LS_AM_ACL -
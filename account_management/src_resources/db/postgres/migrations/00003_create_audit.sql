@@ -0,0 +1,25 @@
+-- ---------------------------
+-- Begin - LS_AM_AUDIT_EVENT -
+-- ---------------------------
+
+create table LS_AM_AUDIT_EVENT (
+    ID bigserial primary key,
+    version bigint NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL,
+    update_time TIMESTAMPTZ NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_ENTITY ON LS_AM_AUDIT_EVENT(
+    (DATA->>'entity_type'), (DATA->>'entity_id')
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_ACTOR ON LS_AM_AUDIT_EVENT(
+    ((DATA->'actor'->>'id')::bigint)
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_CREATED ON LS_AM_AUDIT_EVENT(
+    ((DATA->>'created_date_epoch_seconds')::bigint)
+);
+
+-- End - LS_AM_AUDIT_EVENT -
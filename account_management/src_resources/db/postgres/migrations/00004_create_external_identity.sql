@@ -0,0 +1,21 @@
+-- ----------------------------------
+-- Begin - LS_AM_EXTERNAL_IDENTITY -
+-- ----------------------------------
+
+create table LS_AM_EXTERNAL_IDENTITY (
+    ID bigserial primary key,
+    version bigint NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL,
+    update_time TIMESTAMPTZ NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_EXTERNAL_IDENTITY_UNIQUE_ISSUER_SUBJECT ON LS_AM_EXTERNAL_IDENTITY(
+    (DATA->>'issuer'), (DATA->>'subject')
+);
+
+CREATE INDEX LS_AM_EXTERNAL_IDENTITY_USER_ID ON LS_AM_EXTERNAL_IDENTITY(
+    ((DATA->>'user_id')::bigint)
+);
+
+-- End - LS_AM_EXTERNAL_IDENTITY -
@@ -0,0 +1,19 @@
+-- ------------------------
+-- Begin - LS_AM_SESSION -
+-- ------------------------
+
+create table LS_AM_SESSION (
+    ID bigserial primary key,
+    version bigint NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL,
+    update_time TIMESTAMPTZ NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_SESSION_UNIQUE_SESSION_ID ON LS_AM_SESSION( (DATA->>'session_id') );
+
+CREATE INDEX LS_AM_SESSION_USER_ID ON LS_AM_SESSION(
+    ((DATA->>'user_id')::bigint)
+);
+
+-- End - LS_AM_SESSION -
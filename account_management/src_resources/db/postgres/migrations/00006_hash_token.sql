@@ -0,0 +1,4 @@
+-- The tokens are now stored as keyed hashes. The hash of the stored plain
+-- tokens cannot be computed here, as the key is known only to the
+-- application: the pending tokens are dropped and must be requested again.
+DELETE FROM LS_AM_TOKEN;
@@ -0,0 +1,37 @@
+-- The accounts are looked up and kept unique by the normalized forms of
+-- their username and email. They are initialized here with the trimmed
+-- lowercase form, which is the normalization of the application for the
+-- ASCII identifiers. The accounts are also looked up by this form until
+-- `LsAMAccountService::normalize_identifiers` rewrites them with the
+-- configured normalization.
+--
+-- The migration fails, listing them, if different accounts have the same
+-- identifiers once trimmed and lowercased: they must be renamed before
+-- running it.
+
+UPDATE LS_AM_ACCOUNT SET DATA = DATA || jsonb_build_object(
+    'normalized_username', lower(btrim(DATA->>'username')),
+    'normalized_email', lower(btrim(DATA->>'email'))
+);
+
+DO $$
+DECLARE
+    conflicts TEXT;
+BEGIN
+    SELECT string_agg(identifier, ', ') INTO conflicts FROM (
+        SELECT DATA->>'normalized_username' AS identifier FROM LS_AM_ACCOUNT
+            GROUP BY DATA->>'normalized_username' HAVING count(*) > 1
+        UNION ALL
+        SELECT DATA->>'normalized_email' AS identifier FROM LS_AM_ACCOUNT
+            GROUP BY DATA->>'normalized_email' HAVING count(*) > 1
+    ) AS duplicated;
+    IF conflicts IS NOT NULL THEN
+        RAISE EXCEPTION 'Accounts with the same normalized username or email: %', conflicts;
+    END IF;
+END $$;
+
+DROP INDEX LS_AM_ACCOUNT_UNIQUE_USERNAME;
+DROP INDEX LS_AM_ACCOUNT_UNIQUE_EMAIL;
+
+CREATE UNIQUE INDEX LS_AM_ACCOUNT_UNIQUE_NORMALIZED_USERNAME ON LS_AM_ACCOUNT( (DATA->>'normalized_username') );
+CREATE UNIQUE INDEX LS_AM_ACCOUNT_UNIQUE_NORMALIZED_EMAIL ON LS_AM_ACCOUNT( (DATA->>'normalized_email') );
@@ -0,0 +1,6 @@
+-- The events of the module are about an account: its id is their subject,
+-- by which they are looked up when the account is anonymized. The outbox is
+-- migrated before the module.
+
+UPDATE LS_EVENT_OUTBOX SET DATA = DATA || jsonb_build_object('subject', DATA->'payload'->>'user_id')
+WHERE left(DATA->>'event_type', 3) = 'AM_' AND DATA->'payload'->>'user_id' IS NOT NULL;
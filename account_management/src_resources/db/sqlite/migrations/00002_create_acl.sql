@@ -0,0 +1,21 @@
+-- ---------------------------
+-- Begin - LS_AM_ACL -
+-- ---------------------------
+
+create table LS_AM_ACL (
+    ID integer primary key autoincrement,
+    version INTEGER NOT NULL,
+    create_time TEXT NOT NULL,
+    update_time TEXT NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_ACL_UNIQUE_RESOURCE_GRANTEE ON LS_AM_ACL(
+    (DATA->>'$.resource_type'), (DATA->>'$.resource_id'), (DATA->>'$.grantee.type'), CAST(DATA->>'$.grantee.id' AS TEXT)
+);
+
+CREATE INDEX LS_AM_ACL_GRANTEE ON LS_AM_ACL(
+    (DATA->>'$.resource_type'), (DATA->>'$.grantee.type'), CAST(DATA->>'$.grantee.id' AS TEXT)
+);
+
+-- End - LS_AM_ACL -
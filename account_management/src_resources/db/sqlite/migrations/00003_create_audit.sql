@@ -0,0 +1,25 @@
+-- ---------------------------
+-- Begin - LS_AM_AUDIT_EVENT -
+-- ---------------------------
+
+create table LS_AM_AUDIT_EVENT (
+    ID integer primary key autoincrement,
+    version INTEGER NOT NULL,
+    create_time TEXT NOT NULL,
+    update_time TEXT NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_ENTITY ON LS_AM_AUDIT_EVENT(
+    (DATA->>'$.entity_type'), (DATA->>'$.entity_id')
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_ACTOR ON LS_AM_AUDIT_EVENT(
+    (DATA->>'$.actor.id')
+);
+
+CREATE INDEX LS_AM_AUDIT_EVENT_CREATED ON LS_AM_AUDIT_EVENT(
+    (DATA->>'$.created_date_epoch_seconds')
+);
+
+-- End - LS_AM_AUDIT_EVENT -
@@ -0,0 +1,21 @@
+-- ----------------------------------
+-- Begin - LS_AM_EXTERNAL_IDENTITY -
+-- ----------------------------------
+
+create table LS_AM_EXTERNAL_IDENTITY (
+    ID integer primary key autoincrement,
+    version INTEGER NOT NULL,
+    create_time TEXT NOT NULL,
+    update_time TEXT NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_EXTERNAL_IDENTITY_UNIQUE_ISSUER_SUBJECT ON LS_AM_EXTERNAL_IDENTITY(
+    (DATA->>'$.issuer'), (DATA->>'$.subject')
+);
+
+CREATE INDEX LS_AM_EXTERNAL_IDENTITY_USER_ID ON LS_AM_EXTERNAL_IDENTITY(
+    (DATA->>'$.user_id')
+);
+
+-- End - LS_AM_EXTERNAL_IDENTITY -
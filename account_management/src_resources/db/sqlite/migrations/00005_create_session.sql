@@ -0,0 +1,19 @@
+-- ------------------------
+-- Begin - LS_AM_SESSION -
+-- ------------------------
+
+create table LS_AM_SESSION (
+    ID integer primary key autoincrement,
+    version INTEGER NOT NULL,
+    create_time TEXT NOT NULL,
+    update_time TEXT NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE UNIQUE INDEX LS_AM_SESSION_UNIQUE_SESSION_ID ON LS_AM_SESSION( (DATA->>'$.session_id') );
+
+CREATE INDEX LS_AM_SESSION_USER_ID ON LS_AM_SESSION(
+    (DATA->>'$.user_id')
+);
+
+-- End - LS_AM_SESSION -
@@ -0,0 +1,42 @@
+-- The accounts are looked up and kept unique by the normalized forms of
+-- their username and email. They are initialized here with the trimmed
+-- lowercase form, which is the normalization of the application for the
+-- ASCII identifiers (SQLite lowercases only the ASCII letters). The
+-- accounts are also looked up by this form until
+-- `LsAMAccountService::normalize_identifiers` rewrites them with the
+-- configured normalization.
+--
+-- The migration fails, listing them, if different accounts have the same
+-- identifiers once trimmed and lowercased: they must be renamed before
+-- running it. SQLite raises errors only from triggers, hence the
+-- temporary table.
+
+UPDATE LS_AM_ACCOUNT SET DATA = json_set(
+    DATA,
+    '$.normalized_username', lower(trim(DATA->>'$.username')),
+    '$.normalized_email', lower(trim(DATA->>'$.email'))
+);
+
+CREATE TEMP TABLE LS_AM_IDENTIFIER_CONFLICT (CONFLICTS TEXT);
+
+CREATE TEMP TRIGGER LS_AM_IDENTIFIER_CONFLICT_REPORT BEFORE INSERT ON LS_AM_IDENTIFIER_CONFLICT
+    WHEN NEW.CONFLICTS IS NOT NULL
+BEGIN
+    SELECT RAISE(ABORT, 'Accounts with the same normalized username or email: ' || NEW.CONFLICTS);
+END;
+
+INSERT INTO LS_AM_IDENTIFIER_CONFLICT SELECT group_concat(identifier, ', ') FROM (
+    SELECT DATA->>'$.normalized_username' AS identifier FROM LS_AM_ACCOUNT
+        GROUP BY identifier HAVING count(*) > 1
+    UNION ALL
+    SELECT DATA->>'$.normalized_email' AS identifier FROM LS_AM_ACCOUNT
+        GROUP BY identifier HAVING count(*) > 1
+);
+
+DROP TABLE LS_AM_IDENTIFIER_CONFLICT;
+
+DROP INDEX LS_AM_ACCOUNT_UNIQUE_USERNAME;
+DROP INDEX LS_AM_ACCOUNT_UNIQUE_EMAIL;
+
+CREATE UNIQUE INDEX LS_AM_ACCOUNT_UNIQUE_NORMALIZED_USERNAME ON LS_AM_ACCOUNT( (DATA->>'$.normalized_username') );
+CREATE UNIQUE INDEX LS_AM_ACCOUNT_UNIQUE_NORMALIZED_EMAIL ON LS_AM_ACCOUNT( (DATA->>'$.normalized_email') );
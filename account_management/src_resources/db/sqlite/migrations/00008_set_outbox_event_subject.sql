@@ -0,0 +1,6 @@
+-- The events of the module are about an account: its id is their subject,
+-- by which they are looked up when the account is anonymized. The outbox is
+-- migrated before the module.
+
+UPDATE LS_EVENT_OUTBOX SET DATA = json_set(DATA, '$.subject', CAST(DATA->>'$.payload.user_id' AS TEXT))
+WHERE substr(DATA->>'$.event_type', 1, 3) = 'AM_' AND DATA->>'$.payload.user_id' IS NOT NULL;
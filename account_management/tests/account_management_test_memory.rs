@@ -0,0 +1,42 @@
+use std::sync::OnceLock;
+
+use maybe_once::tokio::*;
+
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::AMConfig;
+use lightspeed_account_management::repository::memory::MemoryAMRepositoryManager;
+use lightspeed_core::module::LsModule;
+
+mod tests;
+
+pub type RepoManager = MemoryAMRepositoryManager;
+
+/// Whether a failed transaction undoes its changes
+pub const ROLLS_BACK: bool = false;
+
+pub type MaybeType = (LsAMModule<RepoManager>, ());
+
+async fn init() -> MaybeType {
+    let repo_manager = RepoManager::new();
+
+    // Argon2 spec minimum (memory=8 KiB, t=1, p=1) — fast for tests.
+    let auth_config = AMConfig {
+        argon2_memory_kib: 8,
+        argon2_iterations: 1,
+        argon2_parallelism: 1,
+        token_hash_secret: "a_token_hash_secret_of_at_least_32_bytes".into(),
+        ..Default::default()
+    };
+
+    let mut auth_module = LsAMModule::new(repo_manager, auth_config).unwrap();
+    {
+        auth_module.start().await.unwrap();
+    }
+
+    (auth_module, ())
+}
+
+pub async fn data(serial: bool) -> Data<'static, MaybeType> {
+    static DATA: OnceLock<MaybeOnceAsync<MaybeType>> = OnceLock::new();
+    DATA.get_or_init(|| MaybeOnceAsync::new(|| Box::pin(init()))).data(serial).await
+}
@@ -16,6 +16,9 @@ mod tests;
 
 pub type RepoManager = PgAMRepositoryManager;
 
+/// Whether a failed transaction undoes its changes
+pub const ROLLS_BACK: bool = true;
+
 pub type MaybeType = (LsAMModule<RepoManager>, ContainerAsync<Postgres>);
 
 async fn init() -> MaybeType {
@@ -24,8 +27,13 @@ async fn init() -> MaybeType {
     let repo_manager = RepoManager::new(c3p0.clone());
 
     // Argon2 spec minimum (memory=8 KiB, t=1, p=1) — fast for tests.
-    let auth_config =
-        AMConfig { argon2_memory_kib: 8, argon2_iterations: 1, argon2_parallelism: 1, ..Default::default() };
+    let auth_config = AMConfig {
+        argon2_memory_kib: 8,
+        argon2_iterations: 1,
+        argon2_parallelism: 1,
+        token_hash_secret: "a_token_hash_secret_of_at_least_32_bytes".into(),
+        ..Default::default()
+    };
 
     let mut auth_module = LsAMModule::new(repo_manager, auth_config).unwrap();
     {
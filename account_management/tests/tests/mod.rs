@@ -1,2 +1,4 @@
 pub mod service;
 pub mod util;
+#[cfg(feature = "axum")]
+pub mod web;
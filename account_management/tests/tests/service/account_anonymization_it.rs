@@ -0,0 +1,204 @@
+use crate::data;
+use crate::tests::util::{
+    auth_account_service_with_config, create_user, create_user_with_password, external_identities,
+    link_external_identity, login_with_session, pending_events,
+};
+use c3p0::*;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::audit::AuditEventQuery;
+use lightspeed_account_management::model::auth_account::{AccountStatus, MfaState};
+use lightspeed_account_management::model::event::AccountAnonymized;
+use lightspeed_account_management::model::token::TokenType;
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
+use lightspeed_account_management::service::account::ACCOUNT_AUDIT_ENTITY_TYPE;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use lightspeed_event::model::OutboxEventStatus;
+use lightspeed_event::repository::OutboxRepository;
+use maybe_once::tokio_shared;
+use serde_json::json;
+
+#[tokio_shared::test]
+async fn should_export_the_account_data_with_the_exporter_sections() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, false).await?;
+
+    // A dedicated service, so that the exporter is not called by the other tests
+    let account_service = auth_account_service_with_config(auth_module, auth_module.auth_config.clone());
+    account_service.add_data_exporter("files", |user_id: i64| async move { Ok(json!({ "owner": user_id })) });
+
+    let export = account_service.export_account_data(user.id).await?;
+    assert_eq!(user.id, export.user_id);
+    assert_eq!(user.data.username, export.username);
+    assert_eq!(user.data.email, export.email);
+    assert_eq!(AccountStatus::PendingActivation, export.status);
+    assert_eq!(1, export.tokens.len());
+    assert_eq!(TokenType::AccountActivation, export.tokens[0].token_type);
+    assert_eq!(Some(&json!({ "owner": user.id })), export.sections.get("files"));
+
+    let serialized = serde_json::to_string(&export).unwrap();
+    assert!(!serialized.contains(&user.data.password));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_fail_the_export_if_an_exporter_fails() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+
+    let account_service = auth_account_service_with_config(auth_module, auth_module.auth_config.clone());
+    account_service
+        .add_data_exporter("files", |_user_id: i64| async { Err(LsAccountManagementError::WrongCredentials) });
+
+    assert!(matches!(
+        account_service.export_account_data(user.id).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_anonymize_an_account_keeping_its_id() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, token) = create_user(auth_module, false).await?;
+    let user = auth_module.auth_account_service.add_roles(user.id, &["admin".to_owned()], None).await?;
+
+    let anonymized = auth_module.auth_account_service.anonymize_account(user.id, None).await?;
+    assert_eq!(user.id, anonymized.id);
+    assert!(anonymized.data.is_anonymized());
+    assert_ne!(user.data.username, anonymized.data.username);
+    assert_ne!(user.data.email, anonymized.data.email);
+    assert_ne!(user.data.password, anonymized.data.password);
+    assert!(anonymized.data.roles.is_empty());
+    assert!(anonymized.data.password_history.is_empty());
+    assert_eq!(MfaState::Disabled, anonymized.data.mfa);
+    assert_eq!(AccountStatus::Disabled, anonymized.data.status);
+
+    assert!(auth_module.auth_account_service.fetch_by_username(&user.data.username).await.is_err());
+    assert!(auth_module.auth_account_service.activate_user(&token.data.token).await.is_err());
+    assert!(matches!(
+        auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id, None).await,
+        Err(LsAccountManagementError::AnonymizedUser(id)) if id == user.id
+    ));
+
+    assert_eq!(
+        vec![AccountAnonymized { user_id: user.id }],
+        pending_events::<AccountAnonymized, _>(auth_module, user.id).await?
+    );
+
+    // Anonymizing again changes nothing
+    let again = auth_module.auth_account_service.anonymize_account(user.id, None).await?;
+    assert_eq!(anonymized.version, again.version);
+    assert_eq!(anonymized.data.username, again.data.username);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_scrub_the_personal_data_of_the_account_everywhere() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    link_external_identity(auth_module, user.id).await?;
+    // An event triggered by the user
+    let (other_user, _) = create_user(auth_module, true).await?;
+    auth_module.auth_account_service.add_roles(other_user.id, &["admin".to_owned()], Some(&auth)).await?;
+
+    let anonymized = auth_module.auth_account_service.anonymize_account(user.id, Some(&auth)).await?;
+
+    // Audit trail
+    let account_events = auth_module
+        .audit_service
+        .fetch_all(&AuditEventQuery {
+            entity_type: Some(ACCOUNT_AUDIT_ENTITY_TYPE.to_owned()),
+            entity_id: Some(user.id.to_string()),
+            ..Default::default()
+        })
+        .await?;
+    let actor_events =
+        auth_module.audit_service.fetch_all(&AuditEventQuery { actor_id: Some(user.id), ..Default::default() }).await?;
+    assert!(account_events.len() > 1);
+    assert!(!actor_events.is_empty());
+    for event in account_events.iter().chain(&actor_events) {
+        let serialized = serde_json::to_string(&event.data).unwrap();
+        assert!(!serialized.contains(&user.data.username));
+        assert!(!serialized.contains(&user.data.email));
+    }
+    assert!(
+        actor_events.iter().all(|event| event
+            .data
+            .actor
+            .as_ref()
+            .is_some_and(|actor| actor.username == anonymized.data.username))
+    );
+
+    // Outbox
+    let outbox_repo = auth_module.repo_manager.outbox_repo();
+    let outbox_events = auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            Ok::<_, LsAccountManagementError>(
+                outbox_repo.fetch_all_by_status(conn, OutboxEventStatus::Pending, 0, u32::MAX).await?,
+            )
+        })
+        .await?;
+    let outbox_events: Vec<_> =
+        outbox_events.into_iter().filter(|event| event.data.payload["user_id"] == user.id).collect();
+    assert!(outbox_events.len() > 1);
+    for event in &outbox_events {
+        let serialized = event.data.payload.to_string();
+        assert!(!serialized.contains(&user.data.username));
+        assert!(!serialized.contains(&user.data.email));
+    }
+
+    // Sessions and external identities
+    assert!(auth_module.session_service.fetch_active_sessions(user.id).await?.is_empty());
+    assert!(matches!(
+        auth_module.session_service.validate_session(&auth).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+    assert!(external_identities(auth_module, user.id).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_purge_the_anonymized_accounts_after_the_grace_period() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (expired_user, _) = create_user(auth_module, true).await?;
+    let (recent_user, _) = create_user(auth_module, true).await?;
+    let (disabled_user, _) = create_user(auth_module, true).await?;
+
+    auth_module.auth_account_service.anonymize_account(recent_user.id, None).await?;
+    auth_module.auth_account_service.disable_by_user_id(disabled_user.id, None).await?;
+    let mut expired_user = auth_module.auth_account_service.anonymize_account(expired_user.id, None).await?;
+
+    // Move the anonymization before the grace period
+    let grace_period_seconds = auth_module.auth_config.anonymized_account_grace_period_seconds as i64;
+    expired_user.data.anonymized_date_epoch_seconds = Some(current_epoch_seconds() - grace_period_seconds - 1);
+    let account_repo = auth_module.repo_manager.account_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| account_repo.update(conn, expired_user.clone()).await)
+        .await?;
+
+    assert!(auth_module.auth_account_service.purge_anonymized_accounts().await? >= 1);
+
+    assert!(matches!(
+        auth_module.auth_account_service.fetch_by_user_id(expired_user.id).await,
+        Err(LsAccountManagementError::C3p0Error { .. })
+    ));
+    assert!(auth_module.auth_account_service.fetch_by_user_id(recent_user.id).await.is_ok());
+    assert!(auth_module.auth_account_service.fetch_by_user_id(disabled_user.id).await.is_ok());
+
+    Ok(())
+}
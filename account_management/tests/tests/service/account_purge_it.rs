@@ -0,0 +1,113 @@
+use crate::data;
+use crate::tests::util::{create_user, expire_token};
+use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::AuthAccountModel;
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository, TokenRepository};
+use lightspeed_core::utils::current_epoch_seconds;
+use maybe_once::tokio_shared;
+
+#[tokio_shared::test]
+async fn should_purge_the_accounts_pending_activation_for_too_long() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (expired_user, expired_user_token) = create_user(auth_module, false).await?;
+    let (recent_user, _) = create_user(auth_module, false).await?;
+    let (active_user, _) = create_user(auth_module, true).await?;
+
+    // Move the creation before the maximum age
+    let max_age_seconds = auth_module.auth_config.pending_account_max_age_seconds as i64;
+    let expired_user = backdate(auth_module, expired_user, current_epoch_seconds() - max_age_seconds - 1).await?;
+    let active_user = backdate(auth_module, active_user, current_epoch_seconds() - max_age_seconds - 1).await?;
+
+    assert!(auth_module.auth_account_service.purge_pending_accounts().await? >= 1);
+
+    assert!(matches!(
+        auth_module.auth_account_service.fetch_by_user_id(expired_user.id).await,
+        Err(LsAccountManagementError::C3p0Error { .. })
+    ));
+    assert!(!token_exists(auth_module, &expired_user_token.data.token).await?);
+    assert!(auth_module.auth_account_service.fetch_by_user_id(recent_user.id).await.is_ok());
+    assert!(auth_module.auth_account_service.fetch_by_user_id(active_user.id).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_purge_the_expired_tokens() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (_, expired_token) = create_user(auth_module, false).await?;
+    let (_, valid_token) = create_user(auth_module, false).await?;
+    let expired_token = expire_token(auth_module, expired_token).await?;
+
+    assert!(token_exists(auth_module, &expired_token.data.token).await?);
+    assert!(auth_module.auth_account_service.purge_expired_tokens().await? >= 1);
+
+    assert!(!token_exists(auth_module, &expired_token.data.token).await?);
+    assert!(token_exists(auth_module, &valid_token.data.token).await?);
+
+    Ok(())
+}
+
+#[cfg(feature = "scheduler")]
+#[tokio_shared::test]
+async fn should_register_the_scheduled_tasks() -> Result<(), LsAccountManagementError> {
+    use lightspeed_account_management::config::AMScheduleConfig;
+    use lightspeed_account_management::service::scheduler::{PurgeExpiredTokensTask, register_scheduled_tasks};
+    use lightspeed_scheduler::{
+        JobExecutor, MemoryScheduleRepository, ScheduleRepository, ScheduledTask, SchedulerError,
+    };
+
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let repo = MemoryScheduleRepository::init();
+    let executor = JobExecutor::new_with_utc_tz(repo.clone());
+
+    register_scheduled_tasks(&executor, auth_module.auth_account_service.clone(), &AMScheduleConfig::default())
+        .await
+        .unwrap();
+
+    let schedules = AMScheduleConfig {
+        purge_pending_accounts: None,
+        purge_expired_tokens: Some("not a cron".to_owned()),
+        ..Default::default()
+    };
+    assert!(matches!(
+        register_scheduled_tasks(&executor, auth_module.auth_account_service.clone(), &schedules).await,
+        Err(SchedulerError::ScheduleDefinitionError { .. })
+    ));
+
+    let (_, token) = create_user(auth_module, false).await?;
+    let token = expire_token(auth_module, token).await?;
+    let mut tx = repo.begin().await.unwrap();
+    let task = PurgeExpiredTokensTask::new(auth_module.auth_account_service.clone());
+    ScheduledTask::<MemoryScheduleRepository>::run(&task, &mut tx).await?;
+    assert!(!token_exists(auth_module, &token.data.token).await?);
+
+    Ok(())
+}
+
+async fn backdate<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    mut user: AuthAccountModel,
+    created_date_epoch_seconds: i64,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    user.data.created_date_epoch_seconds = created_date_epoch_seconds;
+    let account_repo = auth_module.repo_manager.account_repo();
+    auth_module.repo_manager.c3p0().transaction(async |conn| account_repo.update(conn, user).await).await
+}
+
+async fn token_exists<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    token: &str,
+) -> Result<bool, LsAccountManagementError> {
+    let token_repo = auth_module.repo_manager.token_repo();
+    let token_hash = auth_module.token_service.hash_token(token)?;
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| Ok(token_repo.fetch_by_token(conn, &token_hash).await.is_ok()))
+        .await
+}
@@ -0,0 +1,97 @@
+use crate::tests::util::create_user;
+use crate::{ROLLS_BACK, data};
+use c3p0::*;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::AccountData;
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
+use lightspeed_core::utils::new_hyphenated_uuid;
+use maybe_once::tokio_shared;
+
+#[tokio_shared::test]
+async fn should_not_save_accounts_with_the_same_username_or_email() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+    let account_repo = auth_module.repo_manager.account_repo();
+
+    let new_username = new_hyphenated_uuid();
+    let new_email = format!("{new_username}@email.fake");
+    let same_username =
+        AccountData { email: new_email.clone(), normalized_email: new_email.clone(), ..user.data.clone() };
+    let same_email =
+        AccountData { username: new_username.clone(), normalized_username: new_username.clone(), ..user.data.clone() };
+
+    for data in [same_username.clone(), same_email.clone()] {
+        let result = auth_module
+            .repo_manager
+            .c3p0()
+            .transaction(async |conn| account_repo.save(conn, NewRecord::new(data)).await)
+            .await;
+        assert!(result.is_err());
+    }
+
+    for data in [same_username, same_email] {
+        let result = auth_module
+            .repo_manager
+            .c3p0()
+            .transaction(async |conn| account_repo.update(conn, Record { data, ..other_user.clone() }).await)
+            .await;
+        assert!(result.is_err());
+    }
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_update_or_delete_a_stale_account() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let account_repo = auth_module.repo_manager.account_repo();
+
+    let c3p0 = auth_module.repo_manager.c3p0();
+    let updated = c3p0.transaction(async |conn| account_repo.update(conn, user.clone()).await).await?;
+    assert_eq!(user.version + 1, updated.version);
+
+    assert!(matches!(
+        c3p0.transaction(async |conn| account_repo.update(conn, user.clone()).await).await,
+        Err(LsAccountManagementError::C3p0Error { source: C3p0Error::OptimisticLockError { .. } })
+    ));
+    assert!(matches!(
+        c3p0.transaction(async |conn| account_repo.delete(conn, user.clone()).await).await,
+        Err(LsAccountManagementError::C3p0Error { source: C3p0Error::OptimisticLockError { .. } })
+    ));
+
+    c3p0.transaction(async |conn| account_repo.delete(conn, updated.clone()).await).await?;
+    assert!(c3p0.transaction(async |conn| account_repo.fetch_by_id_optional(conn, user.id).await).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_discard_the_changes_of_a_failed_transaction() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let account_repo = auth_module.repo_manager.account_repo();
+    let c3p0 = auth_module.repo_manager.c3p0();
+
+    let result = c3p0
+        .transaction(async |conn| {
+            account_repo.delete(conn, user.clone()).await?;
+            Err::<(), _>(LsAccountManagementError::WrongCredentials)
+        })
+        .await;
+    assert!(matches!(result, Err(LsAccountManagementError::WrongCredentials)));
+
+    let stored = c3p0.transaction(async |conn| account_repo.fetch_by_id_optional(conn, user.id).await).await?;
+    if ROLLS_BACK {
+        assert_eq!(Some(user.version), stored.map(|stored| stored.version));
+    } else {
+        // The in-memory transactions keep the changes
+        assert!(stored.is_none());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use crate::data;
+use crate::tests::util::create_user;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::{
+    AccountFilter, AccountQuery, AccountSortField, AccountStatusKind, AuthAccountModel, SortOrder,
+};
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_core::model::language::Language;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use maybe_once::tokio_shared;
+use std::collections::HashMap;
+
+async fn create_user_with_username<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    username: &str,
+    email: &str,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    let (user, _) = auth_module
+        .auth_account_service
+        .create_user(CreateLoginDto {
+            username: Some(username.to_owned()),
+            email: email.to_owned(),
+            data: HashMap::new(),
+            accept_privacy_policy: true,
+            language: Language::En,
+            password: "Password_123!".to_owned(),
+            password_confirm: "Password_123!".to_owned(),
+        })
+        .await?;
+    Ok(user)
+}
+
+fn usernames(accounts: &[AuthAccountModel]) -> Vec<String> {
+    accounts.iter().map(|account| account.data.username.clone()).collect()
+}
+
+fn prefix_query(prefix: &str) -> AccountQuery {
+    AccountQuery {
+        filter: AccountFilter { username_or_email_prefix: Some(prefix.to_owned()), ..Default::default() },
+        ..Default::default()
+    }
+}
+
+#[tokio_shared::test]
+async fn should_search_by_username_or_email_prefix() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let prefix = new_hyphenated_uuid();
+
+    let user_a =
+        create_user_with_username(auth_module, &format!("{prefix}_a"), &format!("{prefix}a@email.fake")).await?;
+    let user_b =
+        create_user_with_username(auth_module, &format!("{prefix}xb"), &format!("b{prefix}@email.fake")).await?;
+    let user_c =
+        create_user_with_username(auth_module, &new_hyphenated_uuid(), &format!("{prefix}_c@email.fake")).await?;
+
+    let page = auth_module.auth_account_service.search(&prefix_query(&prefix)).await?;
+    assert_eq!(3, page.total);
+    assert_eq!(vec![user_a.id, user_b.id, user_c.id], page.accounts.iter().map(|a| a.id).collect::<Vec<_>>());
+
+    // `_` and `%` are matched literally, and the case is ignored
+    let page = auth_module.auth_account_service.search(&prefix_query(&format!("{}_", prefix.to_uppercase()))).await?;
+    assert_eq!(2, page.total);
+    assert_eq!(vec![user_a.id, user_c.id], page.accounts.iter().map(|a| a.id).collect::<Vec<_>>());
+
+    assert_eq!(0, auth_module.auth_account_service.search(&prefix_query(&format!("{prefix}%"))).await?.total);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_filter_by_role_status_and_created_date() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let prefix = new_hyphenated_uuid();
+    let role = new_hyphenated_uuid();
+
+    let pending = create_user_with_username(auth_module, &format!("{prefix}-1"), &new_email()).await?;
+    let active = create_user_with_username(auth_module, &format!("{prefix}-2"), &new_email()).await?;
+    let active_with_role = create_user_with_username(auth_module, &format!("{prefix}-3"), &new_email()).await?;
+    for user in [&active, &active_with_role] {
+        auth_module.auth_account_service.activate_user(&activation_token(auth_module, user).await?).await?;
+    }
+    auth_module.auth_account_service.add_roles(active_with_role.id, std::slice::from_ref(&role), None).await?;
+    auth_module.auth_account_service.add_roles(pending.id, &[role.clone(), "other".to_owned()], None).await?;
+
+    let search = async |filter: AccountFilter| {
+        let query = AccountQuery {
+            filter: AccountFilter { username_or_email_prefix: Some(prefix.clone()), ..filter },
+            ..Default::default()
+        };
+        auth_module.auth_account_service.search(&query).await.map(|page| usernames(&page.accounts))
+    };
+
+    assert_eq!(
+        vec![active.data.username.clone(), active_with_role.data.username.clone()],
+        search(AccountFilter { status: Some(AccountStatusKind::Active), ..Default::default() }).await?
+    );
+    assert_eq!(
+        vec![pending.data.username.clone(), active_with_role.data.username.clone()],
+        search(AccountFilter { role: Some(role.clone()), ..Default::default() }).await?
+    );
+    assert_eq!(
+        vec![active_with_role.data.username.clone()],
+        search(AccountFilter {
+            role: Some(role.clone()),
+            status: Some(AccountStatusKind::Active),
+            ..Default::default()
+        })
+        .await?
+    );
+
+    let now = current_epoch_seconds();
+    assert_eq!(
+        3,
+        search(AccountFilter {
+            created_from_epoch_seconds: Some(now - 60),
+            created_to_epoch_seconds: Some(now + 60),
+            ..Default::default()
+        })
+        .await?
+        .len()
+    );
+    assert!(
+        search(AccountFilter { created_from_epoch_seconds: Some(now + 60), ..Default::default() }).await?.is_empty()
+    );
+    assert!(search(AccountFilter { created_to_epoch_seconds: Some(now - 60), ..Default::default() }).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_sort_and_paginate() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let prefix = new_hyphenated_uuid();
+
+    let mut users = vec![];
+    for (suffix, email) in [("c", "a"), ("a", "b"), ("b", "b"), ("d", "a")] {
+        let email = format!("{prefix}-{email}{suffix}@email.fake");
+        users.push(create_user_with_username(auth_module, &format!("{prefix}-{suffix}"), &email).await?);
+    }
+
+    let query = AccountQuery { sort: vec![(AccountSortField::Username, SortOrder::Desc)], ..prefix_query(&prefix) };
+    let page = auth_module.auth_account_service.search(&query).await?;
+    assert_eq!(
+        vec![format!("{prefix}-d"), format!("{prefix}-c"), format!("{prefix}-b"), format!("{prefix}-a")],
+        usernames(&page.accounts)
+    );
+
+    // All the accounts are pending activation; the ties are sorted by the next key
+    let query = AccountQuery {
+        sort: vec![(AccountSortField::Status, SortOrder::Asc), (AccountSortField::Email, SortOrder::Desc)],
+        ..prefix_query(&prefix)
+    };
+    let page = auth_module.auth_account_service.search(&query).await?;
+    assert_eq!(
+        vec![users[2].id, users[1].id, users[3].id, users[0].id],
+        page.accounts.iter().map(|a| a.id).collect::<Vec<_>>()
+    );
+
+    let query = AccountQuery { offset: 1, limit: 2, ..prefix_query(&prefix) };
+    let page = auth_module.auth_account_service.search(&query).await?;
+    assert_eq!(4, page.total);
+    assert_eq!(vec![users[1].id, users[2].id], page.accounts.iter().map(|a| a.id).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_count_the_accounts_by_status() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    create_user(auth_module, false).await?;
+    create_user(auth_module, true).await?;
+
+    let stats = auth_module.auth_account_service.stats().await?;
+    assert_eq!(stats.total, stats.count_by_status.iter().map(|(_, count)| count).sum::<u64>());
+    for status in [AccountStatusKind::Active, AccountStatusKind::PendingActivation] {
+        assert!(stats.count_by_status.iter().any(|(counted, count)| *counted == status && *count >= 1));
+    }
+
+    Ok(())
+}
+
+fn new_email() -> String {
+    format!("{}@email.fake", new_hyphenated_uuid())
+}
+
+async fn activation_token<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user: &AuthAccountModel,
+) -> Result<String, LsAccountManagementError> {
+    let (_, token) = auth_module
+        .auth_account_service
+        .generate_new_activation_token_by_username_and_email(&user.data.username, &user.data.email)
+        .await?;
+    Ok(token.data.token)
+}
@@ -0,0 +1,209 @@
+use crate::data;
+use crate::tests::util::{create_user, create_user_with_password};
+use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::{
+    AccountData, AccountFilter, AccountQuery, AccountStatus, AccountStatusKind, AuthAccountModel,
+};
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
+use lightspeed_account_management::service::account::{LoginOutcome, SUSPENSION_ALREADY_EXPIRED};
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use maybe_once::tokio_shared;
+
+#[tokio_shared::test]
+async fn should_suspend_and_unsuspend_an_account() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = &auth_module.auth_account_service;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+
+    let until = current_epoch_seconds() + 3600;
+    let suspended = account_service.suspend_by_user_id(user.id, until, "spam", None).await?;
+    assert_eq!(
+        AccountStatus::Suspended { until_epoch_seconds: until, reason: "spam".to_owned() },
+        suspended.data.status
+    );
+
+    match account_service.login(&user.data.username, &password).await {
+        Err(LsAccountManagementError::AccountSuspended { until: suspended_until, reason }) => {
+            assert_eq!(until, suspended_until);
+            assert_eq!("spam", reason);
+        }
+        _ => panic!("the login of a suspended account should fail"),
+    }
+
+    // Listed and counted by the name of the status
+    assert!(
+        account_service
+            .fetch_all_by_status(AccountStatusKind::Suspended, user.id, 1)
+            .await?
+            .iter()
+            .any(|account| account.id == user.id)
+    );
+    assert!(
+        account_service
+            .fetch_all_by_status(AccountStatusKind::Active, user.id, 1)
+            .await?
+            .iter()
+            .all(|account| account.id != user.id)
+    );
+    let page = account_service
+        .search(&AccountQuery {
+            filter: AccountFilter {
+                username_or_email_prefix: Some(user.data.username.clone()),
+                status: Some(AccountStatusKind::Suspended),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .await?;
+    assert_eq!(vec![user.id], page.accounts.iter().map(|account| account.id).collect::<Vec<_>>());
+    let stats = account_service.stats().await?;
+    assert!(stats.count_by_status.iter().any(|(status, count)| *status == AccountStatusKind::Suspended && *count >= 1));
+
+    let unsuspended = account_service.unsuspend_by_user_id(user.id, None).await?;
+    assert_eq!(AccountStatus::Active, unsuspended.data.status);
+    assert!(matches!(account_service.login(&user.data.username, &password).await?, LoginOutcome::Authenticated(_)));
+
+    assert!(matches!(
+        account_service.unsuspend_by_user_id(user.id, None).await,
+        Err(LsAccountManagementError::NotSuspendedUser(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_suspend_only_active_accounts_until_a_future_time() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = &auth_module.auth_account_service;
+    let (user, _) = create_user(auth_module, true).await?;
+    let (pending_user, _) = create_user(auth_module, false).await?;
+    let until = current_epoch_seconds() + 3600;
+
+    assert!(matches!(
+        account_service.suspend_by_user_id(user.id, current_epoch_seconds() - 1, "spam", None).await,
+        Err(LsAccountManagementError::BadRequest { code: SUSPENSION_ALREADY_EXPIRED, .. })
+    ));
+    assert!(matches!(
+        account_service.suspend_by_user_id(pending_user.id, until, "spam", None).await,
+        Err(LsAccountManagementError::InactiveUser(_))
+    ));
+
+    // The suspension can be extended
+    account_service.suspend_by_user_id(user.id, until, "spam", None).await?;
+    let extended = account_service.suspend_by_user_id(user.id, until + 3600, "more spam", None).await?;
+    assert_eq!(
+        AccountStatus::Suspended { until_epoch_seconds: until + 3600, reason: "more spam".to_owned() },
+        extended.data.status
+    );
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_lift_an_expired_suspension_on_login() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = &auth_module.auth_account_service;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+
+    let user = expire_suspension(auth_module, user).await?;
+
+    match account_service.login(&user.data.username, &password).await? {
+        LoginOutcome::Authenticated(auth) => assert_eq!(user.id, auth.id),
+        _ => panic!("the login should not require MFA"),
+    }
+    assert_eq!(AccountStatus::Active, account_service.fetch_by_user_id(user.id).await?.data.status);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_lift_the_expired_suspensions() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = &auth_module.auth_account_service;
+    let (expired_user, _) = create_user(auth_module, true).await?;
+    let (suspended_user, _) = create_user(auth_module, true).await?;
+
+    let expired_user = expire_suspension(auth_module, expired_user).await?;
+    let suspended_user =
+        account_service.suspend_by_user_id(suspended_user.id, current_epoch_seconds() + 3600, "spam", None).await?;
+
+    assert!(account_service.lift_expired_suspensions().await? >= 1);
+
+    assert_eq!(AccountStatus::Active, account_service.fetch_by_user_id(expired_user.id).await?.data.status);
+    assert_eq!(suspended_user.data.status, account_service.fetch_by_user_id(suspended_user.id).await?.data.status);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_lock_an_account_until_it_is_unlocked() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = &auth_module.auth_account_service;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let (pending_user, _) = create_user(auth_module, false).await?;
+
+    assert!(matches!(
+        account_service.lock_by_user_id(pending_user.id, "fraud", None).await,
+        Err(LsAccountManagementError::InactiveUser(_))
+    ));
+
+    // A suspended account can be locked
+    account_service.suspend_by_user_id(user.id, current_epoch_seconds() + 3600, "spam", None).await?;
+    let locked = account_service.lock_by_user_id(user.id, "fraud", None).await?;
+    assert_eq!(AccountStatus::Locked { reason: "fraud".to_owned() }, locked.data.status);
+
+    match account_service.login(&user.data.username, &password).await {
+        Err(LsAccountManagementError::AccountLockedByAdmin { reason }) => assert_eq!("fraud", reason),
+        _ => panic!("the login of a locked account should fail"),
+    }
+    assert!(matches!(
+        account_service.suspend_by_user_id(user.id, current_epoch_seconds() + 3600, "spam", None).await,
+        Err(LsAccountManagementError::InactiveUser(_))
+    ));
+    assert!(
+        account_service
+            .fetch_all_by_status(AccountStatusKind::Locked, user.id, 1)
+            .await?
+            .iter()
+            .any(|account| account.id == user.id)
+    );
+
+    let unlocked = account_service.unlock_by_user_id(user.id, None).await?;
+    assert_eq!(AccountStatus::Active, unlocked.data.status);
+    assert!(matches!(account_service.login(&user.data.username, &password).await?, LoginOutcome::Authenticated(_)));
+
+    Ok(())
+}
+
+/// Suspends the account and moves the end of the suspension to the past
+async fn expire_suspension<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user: AuthAccountModel,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    let suspended = auth_module
+        .auth_account_service
+        .suspend_by_user_id(user.id, current_epoch_seconds() + 3600, "spam", None)
+        .await?;
+    let expired = Record {
+        data: AccountData {
+            status: AccountStatus::Suspended {
+                until_epoch_seconds: current_epoch_seconds() - 1,
+                reason: "spam".to_owned(),
+            },
+            ..suspended.data.clone()
+        },
+        ..suspended
+    };
+    let account_repo = auth_module.repo_manager.account_repo();
+    auth_module.repo_manager.c3p0().transaction(async |conn| account_repo.update(conn, expired).await).await
+}
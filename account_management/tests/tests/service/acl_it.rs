@@ -0,0 +1,145 @@
+use crate::data;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_core::service::auth::{AccessLevel, Auth, Grantee, InMemoryRolesProvider, LsAuthService, Shared};
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use maybe_once::tokio_shared;
+
+const RESOURCE_TYPE: &str = "DOCUMENT";
+
+#[tokio_shared::test]
+async fn should_grant_and_list_shares() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let acl_service = &data.0.acl_service;
+
+    let resource_id = new_hyphenated_uuid();
+    let group = new_hyphenated_uuid();
+
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(1), AccessLevel::Read).await?;
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::Group(group.clone()), AccessLevel::Write).await?;
+    acl_service.grant(RESOURCE_TYPE, &new_hyphenated_uuid(), Grantee::User(1), AccessLevel::Admin).await?;
+
+    let shares = acl_service.fetch_all_by_resource(RESOURCE_TYPE, &resource_id).await?;
+    assert_eq!(2, shares.len());
+    assert_eq!(Grantee::User(1), shares[0].data.grantee);
+    assert_eq!(AccessLevel::Read, shares[0].data.access_level);
+    assert_eq!(Grantee::Group(group), shares[1].data.grantee);
+    assert_eq!(AccessLevel::Write, shares[1].data.access_level);
+
+    assert!(acl_service.fetch_all_by_resource("OTHER", &resource_id).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_replace_access_level_on_grant_to_the_same_grantee() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let acl_service = &data.0.acl_service;
+
+    let resource_id = new_hyphenated_uuid();
+
+    let first = acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(2), AccessLevel::Read).await?;
+    let second = acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(2), AccessLevel::Admin).await?;
+    assert_eq!(first.id, second.id);
+
+    let shares = acl_service.fetch_all_by_resource(RESOURCE_TYPE, &resource_id).await?;
+    assert_eq!(1, shares.len());
+    assert_eq!(AccessLevel::Admin, shares[0].data.access_level);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_revoke_share() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let acl_service = &data.0.acl_service;
+
+    let resource_id = new_hyphenated_uuid();
+
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(3), AccessLevel::Read).await?;
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(4), AccessLevel::Read).await?;
+
+    assert!(acl_service.revoke(RESOURCE_TYPE, &resource_id, &Grantee::User(3)).await?);
+    assert!(!acl_service.revoke(RESOURCE_TYPE, &resource_id, &Grantee::User(3)).await?);
+
+    let shares = acl_service.fetch_all_by_resource(RESOURCE_TYPE, &resource_id).await?;
+    assert_eq!(1, shares.len());
+    assert_eq!(Grantee::User(4), shares[0].data.grantee);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_delete_all_shares_of_a_resource() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let acl_service = &data.0.acl_service;
+
+    let resource_id = new_hyphenated_uuid();
+    let other_resource_id = new_hyphenated_uuid();
+
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(5), AccessLevel::Read).await?;
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::Group("G".to_owned()), AccessLevel::Read).await?;
+    acl_service.grant(RESOURCE_TYPE, &other_resource_id, Grantee::User(5), AccessLevel::Read).await?;
+
+    assert_eq!(2, acl_service.delete_all_by_resource(RESOURCE_TYPE, &resource_id).await?);
+
+    assert!(acl_service.fetch_all_by_resource(RESOURCE_TYPE, &resource_id).await?.is_empty());
+    assert_eq!(1, acl_service.fetch_all_by_resource(RESOURCE_TYPE, &other_resource_id).await?.len());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_fetch_all_shared_with_user_and_user_roles() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let acl_service = &data.0.acl_service;
+
+    let resource_type = new_hyphenated_uuid();
+    let user_id = current_epoch_seconds();
+    let role = new_hyphenated_uuid();
+
+    let by_user = acl_service.grant(&resource_type, "1", Grantee::User(user_id), AccessLevel::Read).await?;
+    let by_role = acl_service.grant(&resource_type, "2", Grantee::Group(role.clone()), AccessLevel::Write).await?;
+    acl_service.grant(&resource_type, "3", Grantee::User(user_id + 1), AccessLevel::Write).await?;
+    acl_service.grant(&resource_type, "4", Grantee::Group(new_hyphenated_uuid()), AccessLevel::Write).await?;
+
+    let auth = Auth::new(user_id, "user", vec![role], 0, i64::MAX);
+    let shared = acl_service.fetch_all_shared_with(&resource_type, &auth).await?;
+
+    assert_eq!(vec![by_user.id, by_role.id], shared.iter().map(|entry| entry.id).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_check_access_with_the_stored_entries() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let acl_service = &data.0.acl_service;
+    let auth_service = LsAuthService::new(InMemoryRolesProvider::new(vec![].into()));
+
+    let resource_id = new_hyphenated_uuid();
+    let owner_id = 10;
+    let editor_id = 11;
+    let stranger_id = 12;
+
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::User(editor_id), AccessLevel::Write).await?;
+    acl_service.grant(RESOURCE_TYPE, &resource_id, Grantee::Group("READERS".to_owned()), AccessLevel::Read).await?;
+
+    let entries = acl_service.fetch_access_entries(RESOURCE_TYPE, &resource_id).await?;
+    let shared = Shared::new(&owner_id, entries);
+
+    let owner = auth_service.auth(Auth::new(owner_id, "owner", vec![], 0, i64::MAX));
+    assert!(owner.can(AccessLevel::Admin, &shared).is_ok());
+
+    let editor = auth_service.auth(Auth::new(editor_id, "editor", vec![], 0, i64::MAX));
+    assert!(editor.can(AccessLevel::Write, &shared).is_ok());
+    assert!(editor.can(AccessLevel::Admin, &shared).is_err());
+
+    let reader = auth_service.auth(Auth::new(stranger_id, "reader", vec!["READERS".to_owned()], 0, i64::MAX));
+    assert!(reader.can(AccessLevel::Read, &shared).is_ok());
+    assert!(reader.can(AccessLevel::Write, &shared).is_err());
+
+    let stranger = auth_service.auth(Auth::new(stranger_id, "stranger", vec![], 0, i64::MAX));
+    assert!(stranger.can(AccessLevel::Read, &shared).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+use crate::data;
+use crate::tests::util::create_user;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::audit::{AuditActor, AuditEventQuery};
+use lightspeed_account_management::service::account::{ACCOUNT_AUDIT_ENTITY_TYPE, AccountAuditAction};
+use lightspeed_core::service::auth::Auth;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use maybe_once::tokio_shared;
+use serde_json::json;
+
+fn account_query(user_id: i64) -> AuditEventQuery {
+    AuditEventQuery {
+        entity_type: Some(ACCOUNT_AUDIT_ENTITY_TYPE.to_owned()),
+        entity_id: Some(user_id.to_string()),
+        ..Default::default()
+    }
+}
+
+fn new_admin() -> Auth {
+    Auth::new(1_000_000, new_hyphenated_uuid(), vec!["admin".to_owned()], 0, 0)
+}
+
+#[tokio_shared::test]
+async fn should_audit_account_creation_and_activation() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, _) = create_user(auth_module, true).await?;
+
+    let events = auth_module.audit_service.fetch_all(&account_query(user.id)).await?;
+    assert_eq!(2, events.len());
+
+    assert_eq!(AccountAuditAction::Created.as_ref(), events[0].data.action);
+    assert!(events[0].data.actor.is_none());
+    assert!(events[0].data.before.is_none());
+    let after = events[0].data.after.as_ref().unwrap();
+    assert_eq!(json!(user.data.username), after["username"]);
+    assert_eq!(json!("PendingActivation"), after["status"]);
+    assert!(after.get("password").is_none());
+
+    assert_eq!(AccountAuditAction::Activated.as_ref(), events[1].data.action);
+    assert_eq!(Some(AuditActor { id: user.id, username: user.data.username.clone() }), events[1].data.actor);
+    assert_eq!(Some(json!({ "status": "PendingActivation" })), events[1].data.before);
+    assert_eq!(Some(json!({ "status": "Active" })), events[1].data.after);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_audit_admin_changes_with_actor_and_diff() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let admin = new_admin();
+
+    let (user, _) = create_user(auth_module, true).await?;
+    let new_email = format!("{}@email.fake", new_hyphenated_uuid());
+
+    auth_module.auth_account_service.add_roles(user.id, &["one".to_owned()], Some(&admin)).await?;
+    auth_module.auth_account_service.change_user_data(user.id, None, Some(new_email.clone()), Some(&admin)).await?;
+    auth_module.auth_account_service.disable_by_user_id(user.id, Some(&admin)).await?;
+
+    let events = auth_module
+        .audit_service
+        .fetch_all(&AuditEventQuery { actor_id: Some(admin.id), ..account_query(user.id) })
+        .await?;
+    assert_eq!(3, events.len());
+
+    assert_eq!(AccountAuditAction::RolesAdded.as_ref(), events[0].data.action);
+    assert_eq!(Some(json!({ "roles": user.data.roles })), events[0].data.before);
+    assert_eq!(Some(json!({ "roles": ["one"] })), events[0].data.after);
+
+    assert_eq!(AccountAuditAction::DataChanged.as_ref(), events[1].data.action);
+    assert_eq!(Some(json!({ "email": user.data.email })), events[1].data.before);
+    assert_eq!(Some(json!({ "email": new_email })), events[1].data.after);
+
+    assert_eq!(AccountAuditAction::Disabled.as_ref(), events[2].data.action);
+    assert_eq!(Some(AuditActor::from(&admin)), events[2].data.actor);
+    assert_eq!(Some(json!({ "status": "Disabled" })), events[2].data.after);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_audit_account_deletion_only_if_deleted() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let admin = new_admin();
+
+    let (user, _) = create_user(auth_module, false).await?;
+
+    assert_eq!(1, auth_module.auth_account_service.delete_by_user_id(user.id, Some(&admin)).await?);
+    assert_eq!(0, auth_module.auth_account_service.delete_by_user_id(user.id, Some(&admin)).await?);
+
+    let events = auth_module.audit_service.fetch_all(&account_query(user.id)).await?;
+    assert_eq!(2, events.len());
+    assert_eq!(AccountAuditAction::Deleted.as_ref(), events[1].data.action);
+    assert_eq!(json!(user.data.username), events[1].data.before.as_ref().unwrap()["username"]);
+    assert!(events[1].data.after.is_none());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_audit_failed_changes() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, _) = create_user(auth_module, false).await?;
+
+    assert!(auth_module.auth_account_service.disable_by_user_id(user.id, Some(&new_admin())).await.is_err());
+
+    let events = auth_module.audit_service.fetch_all(&account_query(user.id)).await?;
+    assert_eq!(1, events.len());
+    assert_eq!(AccountAuditAction::Created.as_ref(), events[0].data.action);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_filter_audit_events_by_time_range_and_paginate() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let admin = new_admin();
+
+    let now = current_epoch_seconds();
+    let (user, _) = create_user(auth_module, true).await?;
+    auth_module.auth_account_service.add_roles(user.id, &["one".to_owned()], Some(&admin)).await?;
+
+    let all = auth_module.audit_service.fetch_all(&account_query(user.id)).await?;
+    assert_eq!(3, all.len());
+
+    let in_range = auth_module
+        .audit_service
+        .fetch_all(&AuditEventQuery { from_epoch_seconds: Some(now), ..account_query(user.id) })
+        .await?;
+    assert_eq!(3, in_range.len());
+
+    let in_the_future = auth_module
+        .audit_service
+        .fetch_all(&AuditEventQuery { from_epoch_seconds: Some(now + 3600), ..account_query(user.id) })
+        .await?;
+    assert!(in_the_future.is_empty());
+
+    let in_the_past = auth_module
+        .audit_service
+        .fetch_all(&AuditEventQuery { to_epoch_seconds: Some(now - 3600), ..account_query(user.id) })
+        .await?;
+    assert!(in_the_past.is_empty());
+
+    let page = auth_module
+        .audit_service
+        .fetch_all(&AuditEventQuery { start_id: all[1].id, limit: 1, ..account_query(user.id) })
+        .await?;
+    assert_eq!(1, page.len());
+    assert_eq!(all[1].id, page[0].id);
+
+    Ok(())
+}
@@ -1,16 +1,19 @@
 use crate::data;
-use crate::tests::util::{create_user, create_user_with_password};
+use crate::tests::util::{
+    auth_account_service_with_config, create_user, create_user_with_password, expire_token, external_identities,
+    link_external_identity, login_with_session,
+};
 use c3p0::*;
-use lightspeed_account_management::config::AMConfig;
 use lightspeed_account_management::dto::change_password_dto::ChangePasswordDto;
 use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
 use lightspeed_account_management::dto::reset_password_dto::ResetPasswordDto;
 use lightspeed_account_management::error::LsAccountManagementError;
-use lightspeed_account_management::model::auth_account::AccountStatus;
+use lightspeed_account_management::model::auth_account::{AccountStatus, AccountStatusKind};
 use lightspeed_account_management::model::token::TokenType;
 use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
-use lightspeed_account_management::service::account::LsAMAccountService;
+use lightspeed_account_management::service::account::LoginOutcome;
 use lightspeed_core::model::language::Language;
+use lightspeed_core::service::auth::{AccessLevel, Grantee};
 use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
 use maybe_once::tokio_shared;
 use std::collections::HashMap;
@@ -63,13 +66,7 @@ async fn should_assign_default_roles_at_account_creation() -> Result<(), LsAccou
     let mut auth_config = auth_module.auth_config.clone();
     auth_config.default_roles_on_account_creation = vec![new_hyphenated_uuid()];
 
-    let auth_account_service = LsAMAccountService::new(
-        auth_module.repo_manager.c3p0().clone(),
-        auth_config.clone(),
-        auth_module.token_service.clone(),
-        auth_module.password_codec.clone(),
-        auth_module.repo_manager.account_repo(),
-    );
+    let auth_account_service = auth_account_service_with_config(auth_module, auth_config.clone());
 
     let (user, _) = auth_account_service
         .create_user(CreateLoginDto {
@@ -379,11 +376,8 @@ async fn should_regenerate_activation_token_by_email_and_username_even_if_token_
     // delete this row mid-test.
     let data = data(true).await;
     let auth_module = &data.0;
-    let (user, mut token) = create_user(auth_module, false).await?;
-    token.data.expire_at_epoch_seconds = 0;
-
-    let token_model =
-        auth_module.repo_manager.c3p0().transaction(async |conn| conn.update(token.clone()).await).await?;
+    let (user, token) = create_user(auth_module, false).await?;
+    let token_model = expire_token(auth_module, token).await?;
 
     // The expired token cannot be used to activate or to fetch with validation.
     assert!(
@@ -425,7 +419,11 @@ async fn should_login_active_user() -> Result<(), LsAccountManagementError> {
     let auth_validity_seconds = auth_module.auth_config.auth_session_max_validity_minutes * 60;
     let before_login_ts_seconds = current_epoch_seconds();
 
-    let auth = auth_module.auth_account_service.login(&user.data.username, password).await?;
+    let LoginOutcome::Authenticated(auth) =
+        auth_module.auth_account_service.login(&user.data.username, password).await?
+    else {
+        panic!("expected Authenticated");
+    };
 
     let after_login_ts_seconds = current_epoch_seconds();
 
@@ -615,7 +613,7 @@ async fn should_fail_resetting_password_if_token_expired() -> Result<(), LsAccou
     let password = new_hyphenated_uuid();
     let (user, _) = create_user_with_password(auth_module, &password, true).await?;
 
-    let mut token = auth_module
+    let token = auth_module
         .repo_manager
         .c3p0()
         .transaction(async |conn| {
@@ -626,8 +624,7 @@ async fn should_fail_resetting_password_if_token_expired() -> Result<(), LsAccou
         })
         .await?;
 
-    token.data.expire_at_epoch_seconds = 0;
-    let token = auth_module.repo_manager.c3p0().transaction(async |conn| conn.update(token.clone()).await).await?;
+    let token = expire_token(auth_module, token).await?;
 
     let password_new = new_hyphenated_uuid();
 
@@ -758,12 +755,15 @@ async fn should_change_user_password() -> Result<(), LsAccountManagementError> {
 
     let updated_user = auth_module
         .auth_account_service
-        .change_password(ChangePasswordDto {
-            user_id: user.id,
-            old_password: password.clone(),
-            new_password: password_new.clone(),
-            new_password_confirm: password_new.clone(),
-        })
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: password.clone(),
+                new_password: password_new.clone(),
+                new_password_confirm: password_new.clone(),
+            },
+            None,
+        )
         .await?;
 
     assert_eq!(updated_user.id, user.id);
@@ -787,12 +787,15 @@ async fn should_not_change_user_password_if_wrong_old_password() -> Result<(), L
 
     let result = auth_module
         .auth_account_service
-        .change_password(ChangePasswordDto {
-            user_id: user.id,
-            old_password: format!("__{password}__"),
-            new_password: password_new.clone(),
-            new_password_confirm: password_new.clone(),
-        })
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: format!("__{password}__"),
+                new_password: password_new.clone(),
+                new_password_confirm: password_new.clone(),
+            },
+            None,
+        )
         .await;
 
     assert!(result.is_err());
@@ -816,12 +819,15 @@ async fn should_not_change_user_password_if_inactive_user() -> Result<(), LsAcco
 
     let result = auth_module
         .auth_account_service
-        .change_password(ChangePasswordDto {
-            user_id: user.id,
-            old_password: password,
-            new_password: password_new.clone(),
-            new_password_confirm: password_new.clone(),
-        })
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: password,
+                new_password: password_new.clone(),
+                new_password_confirm: password_new.clone(),
+            },
+            None,
+        )
         .await;
 
     assert!(result.is_err());
@@ -855,22 +861,22 @@ async fn should_add_and_remove_roles() -> Result<(), LsAccountManagementError> {
 
     assert!(user.data.roles.is_empty());
 
-    let user = auh_service.add_roles(user.id, &[]).await?;
+    let user = auh_service.add_roles(user.id, &[], None).await?;
     assert!(user.data.roles.is_empty());
 
-    let user = auh_service.delete_roles(user.id, &["one".to_owned()]).await?;
+    let user = auh_service.delete_roles(user.id, &["one".to_owned()], None).await?;
     assert!(user.data.roles.is_empty());
 
-    let user = auh_service.add_roles(user.id, &["one".to_owned()]).await?;
+    let user = auh_service.add_roles(user.id, &["one".to_owned()], None).await?;
     assert_eq!(vec!["one".to_owned()], user.data.roles);
 
-    let user = auh_service.add_roles(user.id, &["two".to_owned(), "three".to_owned()]).await?;
+    let user = auh_service.add_roles(user.id, &["two".to_owned(), "three".to_owned()], None).await?;
     assert_eq!(vec!["one".to_owned(), "two".to_owned(), "three".to_owned()], user.data.roles);
 
-    let user = auh_service.delete_roles(user.id, &["two".to_owned(), "four".to_owned()]).await?;
+    let user = auh_service.delete_roles(user.id, &["two".to_owned(), "four".to_owned()], None).await?;
     assert_eq!(vec!["one".to_owned(), "three".to_owned()], user.data.roles);
 
-    let user = auh_service.delete_roles(user.id, &["one".to_owned(), "three".to_owned()]).await?;
+    let user = auh_service.delete_roles(user.id, &["one".to_owned(), "three".to_owned()], None).await?;
     assert!(user.data.roles.is_empty());
 
     Ok(())
@@ -888,8 +894,11 @@ async fn should_change_username() -> Result<(), LsAccountManagementError> {
 
     // Act
     let new_username = new_hyphenated_uuid();
-    let updated_user =
-        auth_module.auth_account_service.change_user_data(user.id, Some(new_username.clone()), None).await.unwrap();
+    let updated_user = auth_module
+        .auth_account_service
+        .change_user_data(user.id, Some(new_username.clone()), None, None)
+        .await
+        .unwrap();
 
     // Assert
 
@@ -920,7 +929,7 @@ async fn should_change_email() -> Result<(), LsAccountManagementError> {
     // Act
     let new_email = format!("{}@test.com", new_hyphenated_uuid());
     let updated_user =
-        auth_module.auth_account_service.change_user_data(user.id, None, Some(new_email.clone())).await.unwrap();
+        auth_module.auth_account_service.change_user_data(user.id, None, Some(new_email.clone()), None).await.unwrap();
 
     // Assert
     assert_eq!(user.data.username, updated_user.data.username);
@@ -950,7 +959,7 @@ async fn should_change_username_and_email() -> Result<(), LsAccountManagementErr
     let new_email = format!("{new_username}@test.com");
     let updated_user = auth_module
         .auth_account_service
-        .change_user_data(user.id, Some(new_username.clone()), Some(new_email.clone()))
+        .change_user_data(user.id, Some(new_username.clone()), Some(new_email.clone()), None)
         .await
         .unwrap();
 
@@ -980,7 +989,7 @@ async fn should_disable_an_active_user() -> Result<(), LsAccountManagementError>
     assert!(auth_module.auth_account_service.login(&user.data.username, password).await.is_ok());
 
     // Act
-    let updated_user = auth_module.auth_account_service.disable_by_user_id(user.id).await.unwrap();
+    let updated_user = auth_module.auth_account_service.disable_by_user_id(user.id, None).await.unwrap();
 
     // Assert
     assert_eq!(AccountStatus::Disabled, updated_user.data.status);
@@ -1005,7 +1014,7 @@ async fn should_fail_disabling_a_pending_user() -> Result<(), LsAccountManagemen
     assert!(auth_module.auth_account_service.login(&user.data.username, password).await.is_err());
 
     // Act
-    let result = auth_module.auth_account_service.disable_by_user_id(user.id).await;
+    let result = auth_module.auth_account_service.disable_by_user_id(user.id, None).await;
 
     // Assert
     assert!(result.is_err());
@@ -1023,10 +1032,10 @@ async fn should_fail_disabling_a_disabled_user() -> Result<(), LsAccountManageme
 
     assert!(auth_module.auth_account_service.login(&user.data.username, password).await.is_ok());
 
-    auth_module.auth_account_service.disable_by_user_id(user.id).await.unwrap();
+    auth_module.auth_account_service.disable_by_user_id(user.id, None).await.unwrap();
 
     // Act
-    let result = auth_module.auth_account_service.disable_by_user_id(user.id).await;
+    let result = auth_module.auth_account_service.disable_by_user_id(user.id, None).await;
 
     // Assert
     assert!(result.is_err());
@@ -1042,10 +1051,11 @@ async fn should_activate_a_disabled_user() -> Result<(), LsAccountManagementErro
     let password = "123456789";
     let (user, _) = create_user_with_password(auth_module, password, true).await?;
 
-    assert!(auth_module.auth_account_service.disable_by_user_id(user.id).await.is_ok());
+    assert!(auth_module.auth_account_service.disable_by_user_id(user.id, None).await.is_ok());
 
     // Act
-    let updated_user = auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id).await.unwrap();
+    let updated_user =
+        auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id, None).await.unwrap();
 
     // Assert
     assert_eq!(AccountStatus::Active, updated_user.data.status);
@@ -1070,7 +1080,7 @@ async fn should_fail_reactivating_a_pending_user() -> Result<(), LsAccountManage
     assert!(auth_module.auth_account_service.login(&user.data.username, password).await.is_err());
 
     // Act
-    let result = auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id).await;
+    let result = auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id, None).await;
 
     // Assert
     assert!(result.is_err());
@@ -1089,7 +1099,7 @@ async fn should_fail_reactivating_an_active_user() -> Result<(), LsAccountManage
     assert!(auth_module.auth_account_service.login(&user.data.username, password).await.is_ok());
 
     // Act
-    let result = auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id).await;
+    let result = auth_module.auth_account_service.reactivate_disabled_user_by_user_id(user.id, None).await;
 
     // Assert
     assert!(result.is_err());
@@ -1106,7 +1116,7 @@ async fn should_delete_a_user() -> Result<(), LsAccountManagementError> {
     let (user, _) = create_user_with_password(auth_module, password, true).await?;
 
     // Act
-    let deleted_user_count = auth_module.auth_account_service.delete_by_user_id(user.id).await.unwrap();
+    let deleted_user_count = auth_module.auth_account_service.delete_by_user_id(user.id, None).await.unwrap();
 
     // Assert
     assert_eq!(1, deleted_user_count);
@@ -1118,6 +1128,41 @@ async fn should_delete_a_user() -> Result<(), LsAccountManagementError> {
     Ok(())
 }
 
+#[tokio_shared::test]
+async fn should_delete_the_rows_bound_to_a_deleted_user() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    link_external_identity(auth_module, user.id).await?;
+    let resource_id = new_hyphenated_uuid();
+    auth_module.acl_service.grant("document", &resource_id, Grantee::User(user.id), AccessLevel::Read).await?;
+    auth_module.acl_service.grant("document", &resource_id, Grantee::User(other_user.id), AccessLevel::Read).await?;
+
+    assert_eq!(1, auth_module.auth_account_service.delete_by_user_id(user.id, None).await?);
+
+    assert!(auth_module.session_service.fetch_active_sessions(user.id).await?.is_empty());
+    assert!(matches!(
+        auth_module.session_service.validate_session(&auth).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+    assert!(external_identities(auth_module, user.id).await?.is_empty());
+    assert_eq!(
+        vec![Grantee::User(other_user.id)],
+        auth_module
+            .acl_service
+            .fetch_all_by_resource("document", &resource_id)
+            .await?
+            .into_iter()
+            .map(|entry| entry.data.grantee)
+            .collect::<Vec<_>>()
+    );
+
+    Ok(())
+}
+
 #[tokio_shared::test]
 async fn should_not_fail_deleting_a_deleted_user() -> Result<(), LsAccountManagementError> {
     // Arrange
@@ -1126,10 +1171,10 @@ async fn should_not_fail_deleting_a_deleted_user() -> Result<(), LsAccountManage
     let password = "123456789";
     let (user, _) = create_user_with_password(auth_module, password, true).await?;
 
-    auth_module.auth_account_service.delete_by_user_id(user.id).await.unwrap();
+    auth_module.auth_account_service.delete_by_user_id(user.id, None).await.unwrap();
 
     // Act
-    let result = auth_module.auth_account_service.delete_by_user_id(user.id).await.unwrap();
+    let result = auth_module.auth_account_service.delete_by_user_id(user.id, None).await.unwrap();
 
     // Assert
     assert_eq!(0, result);
@@ -1149,20 +1194,20 @@ async fn should_return_users_by_status() -> Result<(), LsAccountManagementError>
     let (user_pending_2, _) = create_user(auth_module, false).await?;
     let (user_disabled_1, _) = create_user(auth_module, true).await?;
 
-    assert!(auth_module.auth_account_service.disable_by_user_id(user_disabled_1.id).await.is_ok());
+    assert!(auth_module.auth_account_service.disable_by_user_id(user_disabled_1.id, None).await.is_ok());
 
     // Act
     let all_active_users =
-        auth_module.auth_account_service.fetch_all_by_status(AccountStatus::Active, 0, u32::MAX).await.unwrap();
+        auth_module.auth_account_service.fetch_all_by_status(AccountStatusKind::Active, 0, u32::MAX).await.unwrap();
 
     let all_pending_users = auth_module
         .auth_account_service
-        .fetch_all_by_status(AccountStatus::PendingActivation, 0, u32::MAX)
+        .fetch_all_by_status(AccountStatusKind::PendingActivation, 0, u32::MAX)
         .await
         .unwrap();
 
     let all_disabled_users =
-        auth_module.auth_account_service.fetch_all_by_status(AccountStatus::Disabled, 0, u32::MAX).await.unwrap();
+        auth_module.auth_account_service.fetch_all_by_status(AccountStatusKind::Disabled, 0, u32::MAX).await.unwrap();
 
     // Assert
     assert!(!all_active_users.is_empty());
@@ -1200,14 +1245,20 @@ async fn should_return_users_by_status_with_offset_and_limit() -> Result<(), LsA
     let (_user_3, _) = create_user(auth_module, true).await?;
 
     // Act
-    let all_users =
-        auth_module.auth_account_service.fetch_all_by_status(AccountStatus::Active, user_1.id, u32::MAX).await.unwrap();
+    let all_users = auth_module
+        .auth_account_service
+        .fetch_all_by_status(AccountStatusKind::Active, user_1.id, u32::MAX)
+        .await
+        .unwrap();
 
-    let offset_one_users =
-        auth_module.auth_account_service.fetch_all_by_status(AccountStatus::Active, user_2.id, u32::MAX).await.unwrap();
+    let offset_one_users = auth_module
+        .auth_account_service
+        .fetch_all_by_status(AccountStatusKind::Active, user_2.id, u32::MAX)
+        .await
+        .unwrap();
 
     let limit_two_users =
-        auth_module.auth_account_service.fetch_all_by_status(AccountStatus::Active, user_2.id, 2).await.unwrap();
+        auth_module.auth_account_service.fetch_all_by_status(AccountStatusKind::Active, user_2.id, 2).await.unwrap();
 
     // Assert
     assert_eq!(all_users[1].id, offset_one_users[0].id);
@@ -1218,22 +1269,6 @@ async fn should_return_users_by_status_with_offset_and_limit() -> Result<(), LsA
     Ok(())
 }
 
-/// Build an `LsAMAccountService` that shares state with `auth_module` but
-/// uses a customized `AuthConfig`. Used by tests that need to flip
-/// `password_expiration_seconds` without rebuilding the whole module.
-fn auth_account_service_with_config<RepoManager: AMRepositoryManager>(
-    auth_module: &lightspeed_account_management::LsAMModule<RepoManager>,
-    auth_config: AMConfig,
-) -> LsAMAccountService<RepoManager> {
-    LsAMAccountService::new(
-        auth_module.repo_manager.c3p0().clone(),
-        auth_config,
-        auth_module.token_service.clone(),
-        auth_module.password_codec.clone(),
-        auth_module.repo_manager.account_repo(),
-    )
-}
-
 /// Overwrite a user's stored timestamps by going through the repo. Tests use
 /// this to simulate an aged password without actually waiting.
 async fn set_user_timestamps<RepoManager: AMRepositoryManager>(
@@ -1333,12 +1368,15 @@ async fn change_password_should_reset_password_age() -> Result<(), LsAccountMana
     let new_password = new_hyphenated_uuid();
     auth_module
         .auth_account_service
-        .change_password(ChangePasswordDto {
-            user_id: user.id,
-            old_password: password.clone(),
-            new_password: new_password.clone(),
-            new_password_confirm: new_password.clone(),
-        })
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: password.clone(),
+                new_password: new_password.clone(),
+                new_password_confirm: new_password.clone(),
+            },
+            None,
+        )
         .await?;
 
     // Login with the new password against the strict service must now succeed.
@@ -0,0 +1,128 @@
+use crate::data;
+use crate::tests::util::{auth_account_service_with_config, create_user_with_password};
+use data_encoding::HEXUPPER;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::AMConfig;
+use lightspeed_account_management::dto::change_password_dto::ChangePasswordDto;
+use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
+use lightspeed_account_management::dto::reset_password_dto::ResetPasswordDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::account::LsAMAccountService;
+use lightspeed_account_management::service::breached_password::HibpFileBreachedPasswordChecker;
+use lightspeed_core::model::language::Language;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use maybe_once::tokio_shared;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::io::Write;
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+
+const OFTEN_BREACHED_PASSWORD: &str = "often_breached_password";
+const RARELY_BREACHED_PASSWORD: &str = "rarely_breached_password";
+
+/// Returns a service that rejects the passwords found in at least
+/// `breached_password_min_count` breaches of a local HIBP file
+fn service_with_breached_passwords<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    breached_password_min_count: u64,
+) -> (NamedTempFile, LsAMAccountService<RepoManager>) {
+    let mut entries = [(OFTEN_BREACHED_PASSWORD, 100), (RARELY_BREACHED_PASSWORD, 2)]
+        .map(|(password, count)| (HEXUPPER.encode(&Sha1::digest(password.as_bytes())), count));
+    entries.sort();
+
+    let mut file = NamedTempFile::new().unwrap();
+    for (hash, count) in entries {
+        writeln!(file, "{hash}:{count}").unwrap();
+    }
+
+    let auth_config = AMConfig { breached_password_min_count, ..auth_module.auth_config.clone() };
+    let service = auth_account_service_with_config(auth_module, auth_config)
+        .with_breached_password_checker(Arc::new(HibpFileBreachedPasswordChecker::new(file.path()).unwrap()));
+    (file, service)
+}
+
+fn create_login_dto(password: &str) -> CreateLoginDto {
+    let username = new_hyphenated_uuid();
+    CreateLoginDto {
+        username: Some(username.clone()),
+        email: format!("{username}@email.fake"),
+        data: HashMap::new(),
+        accept_privacy_policy: true,
+        language: Language::En,
+        password: password.to_owned(),
+        password_confirm: password.to_owned(),
+    }
+}
+
+#[tokio_shared::test]
+async fn should_not_create_users_with_breached_passwords() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (_file, service) = service_with_breached_passwords(auth_module, 1);
+
+    for password in [OFTEN_BREACHED_PASSWORD, RARELY_BREACHED_PASSWORD] {
+        assert!(matches!(
+            service.create_user(create_login_dto(password)).await,
+            Err(LsAccountManagementError::BreachedPassword)
+        ));
+    }
+    service.create_user(create_login_dto(&new_hyphenated_uuid())).await?;
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_accept_passwords_breached_less_than_the_min_count() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (_file, service) = service_with_breached_passwords(auth_module, 10);
+
+    assert!(matches!(
+        service.create_user(create_login_dto(OFTEN_BREACHED_PASSWORD)).await,
+        Err(LsAccountManagementError::BreachedPassword)
+    ));
+    service.create_user(create_login_dto(RARELY_BREACHED_PASSWORD)).await?;
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_change_or_reset_to_breached_passwords() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (_file, service) = service_with_breached_passwords(auth_module, 1);
+    let (user, _) = create_user_with_password(auth_module, "password_1", true).await?;
+
+    assert!(matches!(
+        service
+            .change_password(
+                ChangePasswordDto {
+                    user_id: user.id,
+                    old_password: "password_1".to_owned(),
+                    new_password: OFTEN_BREACHED_PASSWORD.to_owned(),
+                    new_password_confirm: OFTEN_BREACHED_PASSWORD.to_owned(),
+                },
+                None
+            )
+            .await,
+        Err(LsAccountManagementError::BreachedPassword)
+    ));
+
+    let (_, token) = service.generate_reset_password_token(&user.data.username).await?;
+    assert!(matches!(
+        service
+            .reset_password_by_token(ResetPasswordDto {
+                token: token.data.token,
+                password: OFTEN_BREACHED_PASSWORD.to_owned(),
+                password_confirm: OFTEN_BREACHED_PASSWORD.to_owned(),
+            })
+            .await,
+        Err(LsAccountManagementError::BreachedPassword)
+    ));
+
+    assert!(service.login(&user.data.username, "password_1").await.is_ok());
+
+    Ok(())
+}
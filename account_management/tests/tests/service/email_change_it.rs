@@ -0,0 +1,139 @@
+use crate::data;
+use crate::tests::util::{create_user, pending_events};
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::event::{EmailChangeRequested, EmailChanged};
+use lightspeed_core::utils::new_hyphenated_uuid;
+use maybe_once::tokio_shared;
+
+fn new_email() -> String {
+    format!("{}@email.fake", new_hyphenated_uuid())
+}
+
+#[tokio_shared::test]
+async fn should_change_the_email_only_once_confirmed() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let email = new_email();
+
+    let (pending_user, token) = auth_module.auth_account_service.request_email_change(user.id, &email).await?;
+    assert_eq!(user.data.email, pending_user.data.email);
+    assert_eq!(Some(email.clone()), pending_user.data.pending_email);
+
+    let requested = pending_events::<EmailChangeRequested, _>(auth_module, user.id).await?;
+    assert_eq!(
+        vec![EmailChangeRequested {
+            user_id: user.id,
+            username: user.data.username.clone(),
+            email: user.data.email.clone(),
+            new_email: email.clone(),
+            sealed_change_token: requested[0].sealed_change_token.clone(),
+        }],
+        requested
+    );
+    assert_eq!(token.data.token, auth_module.token_service.open_token(&requested[0].sealed_change_token)?);
+
+    let changed_user = auth_module.auth_account_service.confirm_email_change(&token.data.token).await?;
+    assert_eq!(email, changed_user.data.email);
+    assert_eq!(None, changed_user.data.pending_email);
+
+    assert_eq!(
+        vec![EmailChanged {
+            user_id: user.id,
+            username: user.data.username.clone(),
+            old_email: user.data.email.clone(),
+            email: email.clone(),
+        }],
+        pending_events::<EmailChanged, _>(auth_module, user.id).await?
+    );
+
+    assert!(matches!(
+        auth_module.auth_account_service.confirm_email_change(&token.data.token).await,
+        Err(LsAccountManagementError::TokenNotValid)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_request_an_email_already_used() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+
+    for email in [&other_user.data.email, &user.data.email] {
+        assert!(matches!(
+            auth_module.auth_account_service.request_email_change(user.id, email).await,
+            Err(LsAccountManagementError::EmailAlreadyUsed)
+        ));
+    }
+    assert_eq!(None, auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.pending_email);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_confirm_an_email_taken_after_the_request() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+    let email = new_email();
+
+    let (_, token) = auth_module.auth_account_service.request_email_change(user.id, &email).await?;
+    auth_module.auth_account_service.change_user_data(other_user.id, None, Some(email.clone()), None).await?;
+
+    assert!(matches!(
+        auth_module.auth_account_service.confirm_email_change(&token.data.token).await,
+        Err(LsAccountManagementError::EmailAlreadyUsed)
+    ));
+    assert_eq!(user.data.email, auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.email);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_invalidate_previous_email_change_requests() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let second_email = new_email();
+
+    let (_, first_token) = auth_module.auth_account_service.request_email_change(user.id, &new_email()).await?;
+    let (_, second_token) = auth_module.auth_account_service.request_email_change(user.id, &second_email).await?;
+
+    assert!(matches!(
+        auth_module.auth_account_service.confirm_email_change(&first_token.data.token).await,
+        Err(LsAccountManagementError::TokenNotValid)
+    ));
+    let changed_user = auth_module.auth_account_service.confirm_email_change(&second_token.data.token).await?;
+    assert_eq!(second_email, changed_user.data.email);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_change_user_data_to_values_already_used() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+
+    assert!(matches!(
+        auth_module.auth_account_service.change_user_data(user.id, None, Some(other_user.data.email), None).await,
+        Err(LsAccountManagementError::EmailAlreadyUsed)
+    ));
+    assert!(matches!(
+        auth_module.auth_account_service.change_user_data(user.id, Some(other_user.data.username), None, None).await,
+        Err(LsAccountManagementError::UsernameAlreadyUsed)
+    ));
+
+    // Setting the current values is not a conflict
+    auth_module
+        .auth_account_service
+        .change_user_data(user.id, Some(user.data.username.clone()), Some(user.data.email.clone()), None)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+use crate::data;
+use crate::tests::util::{create_user, create_user_with_password, pending_events};
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::{AMConfig, AMEmailConfig, EmailTemplate, EmailTemplates};
+use lightspeed_account_management::dto::change_password_dto::ChangePasswordDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::AccountProfilePatch;
+use lightspeed_account_management::model::event::{
+    AccountCreated, AccountDisabled, PasswordChanged, PasswordResetRequested,
+};
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::email::LsAMEmailService;
+use lightspeed_core::model::language::Language;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use lightspeed_email::model::email::EmailMessage;
+use lightspeed_email::repository::email::EmailClient;
+use lightspeed_email::repository::in_memory_email::InMemoryEmailClient;
+use lightspeed_email::service::LsEmailService;
+use maybe_once::tokio_shared;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+#[tokio_shared::test]
+async fn should_send_the_activation_email() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (email_service, email_client) = email_service(auth_module, AMEmailConfig::default());
+    let (user, token) = create_user(auth_module, false).await?;
+
+    for event in pending_events::<AccountCreated, _>(auth_module, user.id).await? {
+        email_service.on_account_created(&event).await?;
+    }
+
+    let email = single_email(&email_client);
+    assert_eq!(Some("no-reply@localhost".to_owned()), email.from);
+    assert_eq!(vec![user.data.email.clone()], email.to);
+    assert_eq!(Some("Activate your account".to_owned()), email.subject);
+    let text = email.text.unwrap();
+    assert!(text.contains(&user.data.username));
+    assert!(text.contains(&format!("http://localhost/activate?token={}", token.data.token.replace('-', "%2D"))));
+    assert!(email.html.is_none());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_send_the_emails_in_the_language_of_the_user() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let italian = EmailTemplates {
+        password_reset: EmailTemplate {
+            subject: "Reimposta la password".to_owned(),
+            text: "Ciao {username}, apri {link}".to_owned(),
+            html: Some("<p>Ciao {username}, apri <a href=\"{link}\">il link</a></p>".to_owned()),
+        },
+        ..Default::default()
+    };
+    let (email_service, email_client) = email_service(
+        auth_module,
+        AMEmailConfig {
+            reset_password_link: "https://app.fake/reset/{token}".to_owned(),
+            templates: HashMap::from([(Language::It, italian)]),
+            ..Default::default()
+        },
+    );
+
+    let (user, _) = create_user(auth_module, true).await?;
+    auth_module
+        .auth_account_service
+        .update_profile(
+            user.id,
+            AccountProfilePatch { language: Some(Language::It), attributes: BTreeMap::new() },
+            None,
+        )
+        .await?;
+    let (_, token) = auth_module.auth_account_service.generate_reset_password_token(&user.data.username).await?;
+    for event in pending_events::<PasswordResetRequested, _>(auth_module, user.id).await? {
+        email_service.on_password_reset_requested(&event).await?;
+    }
+
+    let link = format!("https://app.fake/reset/{}", token.data.token.replace('-', "%2D"));
+    let email = single_email(&email_client);
+    assert_eq!(Some("Reimposta la password".to_owned()), email.subject);
+    assert_eq!(Some(format!("Ciao {}, apri {link}", user.data.username)), email.text);
+    assert_eq!(Some(format!("<p>Ciao {}, apri <a href=\"{link}\">il link</a></p>", user.data.username)), email.html);
+
+    // The users without templates in their language get the built-in ones
+    email_client.clear_emails().unwrap();
+    let (other_user, _) = create_user(auth_module, true).await?;
+    auth_module.auth_account_service.generate_reset_password_token(&other_user.data.username).await?;
+    for event in pending_events::<PasswordResetRequested, _>(auth_module, other_user.id).await? {
+        email_service.on_password_reset_requested(&event).await?;
+    }
+    assert_eq!(Some("Reset your password".to_owned()), single_email(&email_client).subject);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_notify_the_password_change_and_the_account_disabling() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (email_service, email_client) = email_service(auth_module, AMEmailConfig::default());
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+
+    let new_password = new_hyphenated_uuid();
+    auth_module
+        .auth_account_service
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: password,
+                new_password: new_password.clone(),
+                new_password_confirm: new_password,
+            },
+            None,
+        )
+        .await?;
+    let events = pending_events::<PasswordChanged, _>(auth_module, user.id).await?;
+    assert_eq!(1, events.len());
+    email_service.on_password_changed(&events[0]).await?;
+    assert_eq!(Some("Your password was changed".to_owned()), single_email(&email_client).subject);
+
+    email_client.clear_emails().unwrap();
+    auth_module.auth_account_service.disable_by_user_id(user.id, None).await?;
+    let events = pending_events::<AccountDisabled, _>(auth_module, user.id).await?;
+    assert_eq!(
+        vec![AccountDisabled {
+            user_id: user.id,
+            username: user.data.username.clone(),
+            email: user.data.email.clone()
+        }],
+        events
+    );
+    email_service.on_account_disabled(&events[0]).await?;
+    let email = single_email(&email_client);
+    assert_eq!(vec![user.data.email.clone()], email.to);
+    assert_eq!(Some("Your account was disabled".to_owned()), email.subject);
+
+    Ok(())
+}
+
+fn email_service<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    emails: AMEmailConfig,
+) -> (LsAMEmailService<RepoManager>, Arc<InMemoryEmailClient>) {
+    let email_client = Arc::new(InMemoryEmailClient::new());
+    let email_service = LsAMEmailService::new(
+        AMConfig { emails, ..auth_module.auth_config.clone() },
+        auth_module.auth_account_service.clone(),
+        auth_module.token_service.clone(),
+        Arc::new(LsEmailService::new(email_client.clone())),
+    );
+    (email_service, email_client)
+}
+
+fn single_email(email_client: &InMemoryEmailClient) -> EmailMessage {
+    let mut emails = email_client.get_emails().unwrap();
+    assert_eq!(1, emails.len());
+    emails.remove(0)
+}
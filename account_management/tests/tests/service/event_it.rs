@@ -0,0 +1,139 @@
+use crate::tests::util::{create_user, pending_events};
+use crate::{RepoManager, data};
+use c3p0::*;
+use lightspeed_account_management::config::AMConfig;
+use lightspeed_account_management::dto::reset_password_dto::ResetPasswordDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::event::{
+    AccountActivated, AccountCreated, ActivationTokenGenerated, PasswordReset, PasswordResetRequested,
+};
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::token::LsTokenService;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use lightspeed_event::model::{DomainEvent, OutboxEventStatus};
+use lightspeed_event::repository::OutboxRepository;
+use maybe_once::tokio_shared;
+
+#[tokio_shared::test]
+async fn should_publish_account_created_and_activated_events() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, token) = create_user(auth_module, true).await?;
+
+    let created = pending_events::<AccountCreated, _>(auth_module, user.id).await?;
+    assert_eq!(
+        vec![AccountCreated {
+            user_id: user.id,
+            username: user.data.username.clone(),
+            email: user.data.email.clone(),
+            sealed_activation_token: created[0].sealed_activation_token.clone(),
+        }],
+        created
+    );
+    assert_eq!(token.data.token, auth_module.token_service.open_token(&created[0].sealed_activation_token)?);
+    assert_eq!(
+        vec![AccountActivated {
+            user_id: user.id,
+            username: user.data.username.clone(),
+            email: user.data.email.clone()
+        }],
+        pending_events::<AccountActivated, _>(auth_module, user.id).await?
+    );
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_publish_event_when_activation_token_is_regenerated() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, _) = create_user(auth_module, false).await?;
+    let (_, token) = auth_module
+        .auth_account_service
+        .generate_new_activation_token_by_username_and_email(&user.data.username, &user.data.email)
+        .await?;
+
+    let events = pending_events::<ActivationTokenGenerated, _>(auth_module, user.id).await?;
+    assert_eq!(1, events.len());
+    assert_eq!(token.data.token, auth_module.token_service.open_token(&events[0].sealed_activation_token)?);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_publish_password_reset_events() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, _) = create_user(auth_module, true).await?;
+    let (_, token) = auth_module.auth_account_service.generate_reset_password_token(&user.data.username).await?;
+
+    let requested = pending_events::<PasswordResetRequested, _>(auth_module, user.id).await?;
+    assert_eq!(1, requested.len());
+    assert_eq!(token.data.token, auth_module.token_service.open_token(&requested[0].sealed_reset_token)?);
+    assert!(pending_events::<PasswordReset, _>(auth_module, user.id).await?.is_empty());
+
+    let password = new_hyphenated_uuid();
+    auth_module
+        .auth_account_service
+        .reset_password_by_token(ResetPasswordDto {
+            token: token.data.token,
+            password: password.clone(),
+            password_confirm: password,
+        })
+        .await?;
+
+    assert_eq!(1, pending_events::<PasswordReset, _>(auth_module, user.id).await?.len());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_publish_events_if_the_change_fails() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, _) = create_user(auth_module, false).await?;
+
+    assert!(auth_module.auth_account_service.generate_reset_password_token(&user.data.username).await.is_err());
+    assert!(pending_events::<PasswordResetRequested, _>(auth_module, user.id).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_keep_the_tokens_out_of_the_outbox() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let (user, token) = create_user(auth_module, false).await?;
+
+    let outbox_repo = auth_module.repo_manager.outbox_repo();
+    let created = auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            Ok::<_, LsAccountManagementError>(
+                outbox_repo.fetch_all_by_status(conn, OutboxEventStatus::Pending, 0, u32::MAX).await?,
+            )
+        })
+        .await?
+        .into_iter()
+        .find(|event| event.data.event_type == AccountCreated::EVENT_TYPE && event.data.payload["user_id"] == user.id)
+        .unwrap();
+    assert!(!created.data.payload.to_string().contains(&token.data.token));
+
+    // The sealed token is not a token and cannot be opened without the secret
+    let sealed_token = created.data.payload["sealed_activation_token"].as_str().unwrap();
+    assert!(auth_module.auth_account_service.activate_user(sealed_token).await.is_err());
+    let other_token_service = LsTokenService::<RepoManager>::new(
+        AMConfig { token_hash_secret: new_hyphenated_uuid().into(), ..auth_module.auth_config.clone() },
+        auth_module.repo_manager.token_repo(),
+    );
+    assert!(matches!(other_token_service.open_token(sealed_token), Err(LsAccountManagementError::TokenNotValid)));
+    assert_eq!(token.data.token, auth_module.token_service.open_token(sealed_token)?);
+
+    Ok(())
+}
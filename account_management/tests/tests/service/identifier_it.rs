@@ -0,0 +1,231 @@
+use crate::tests::util::create_user;
+use crate::{ROLLS_BACK, data};
+use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::{AccountData, AuthAccountModel};
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
+use lightspeed_account_management::service::account::{IDENTIFIER_CONFLICT, LoginOutcome};
+use lightspeed_account_management::service::identifier::normalize_identifier;
+use lightspeed_core::model::language::Language;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use maybe_once::tokio_shared;
+use std::collections::HashMap;
+
+fn create_login_dto(username: &str, email: &str, password: &str) -> CreateLoginDto {
+    CreateLoginDto {
+        username: Some(username.to_owned()),
+        email: email.to_owned(),
+        data: HashMap::new(),
+        accept_privacy_policy: true,
+        language: Language::En,
+        password: password.to_owned(),
+        password_confirm: password.to_owned(),
+    }
+}
+
+#[tokio_shared::test]
+async fn should_not_create_accounts_with_the_same_normalized_identifiers() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let username = format!("Alice-{}", new_hyphenated_uuid());
+    let email = format!("{username}@Email.Fake");
+
+    let (user, _) =
+        auth_module.auth_account_service.create_user(create_login_dto(&username, &email, &password)).await?;
+    assert_eq!(username, user.data.username);
+    assert_eq!(email, user.data.email);
+    assert_eq!(username.to_lowercase(), user.data.normalized_username);
+    assert_eq!(email.to_lowercase(), user.data.normalized_email);
+
+    let other_email = format!("{}@email.fake", new_hyphenated_uuid());
+    assert!(matches!(
+        auth_module
+            .auth_account_service
+            .create_user(create_login_dto(&format!(" {} ", username.to_lowercase()), &other_email, &password))
+            .await,
+        Err(LsAccountManagementError::UsernameAlreadyUsed)
+    ));
+
+    let other_username = new_hyphenated_uuid();
+    assert!(matches!(
+        auth_module
+            .auth_account_service
+            .create_user(create_login_dto(&other_username, &email.to_uppercase(), &password))
+            .await,
+        Err(LsAccountManagementError::EmailAlreadyUsed)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_look_up_the_accounts_by_the_normalized_identifiers() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let username = format!("Bob-{}", new_hyphenated_uuid());
+    let email = format!("{username}@Email.Fake");
+
+    let (user, token) =
+        auth_module.auth_account_service.create_user(create_login_dto(&username, &email, &password)).await?;
+    auth_module.auth_account_service.activate_user(&token.data.token).await?;
+
+    match auth_module.auth_account_service.login(&format!(" {} ", username.to_uppercase()), &password).await? {
+        LoginOutcome::Authenticated(auth) => {
+            assert_eq!(user.id, auth.id);
+            assert_eq!(username, auth.username);
+        }
+        _ => panic!("the login should not require MFA"),
+    }
+
+    assert_eq!(user.id, auth_module.auth_account_service.fetch_by_username(&username.to_lowercase()).await?.id);
+
+    let (reset_user, reset_token) =
+        auth_module.auth_account_service.generate_reset_password_token_by_email(&email.to_lowercase()).await?;
+    assert_eq!(user.id, reset_user.id);
+    assert_eq!(username, reset_token.data.username);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_rewrite_the_stale_normalized_identifiers() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+
+    // As stored with a normalization without case folding
+    let stale_username = user.data.username.to_uppercase();
+    let stale = update_account(
+        auth_module,
+        Record { data: AccountData { normalized_username: stale_username, ..user.data.clone() }, ..user.clone() },
+    )
+    .await?;
+
+    assert!(auth_module.auth_account_service.normalize_identifiers().await? >= 1);
+
+    let normalized = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+    assert_eq!(user.data.normalized_username, normalized.data.normalized_username);
+    assert!(normalized.version > stale.version);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_look_up_the_accounts_not_normalized_yet() -> Result<(), LsAccountManagementError> {
+    // Serial: the other tests normalize the accounts
+    let data = data(true).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+
+    // As stored by the migrations, which only trim and lowercase
+    let username = format!("Straße-{}", new_hyphenated_uuid());
+    let email = format!("{username}@Email.Fake");
+    update_account(
+        auth_module,
+        Record {
+            data: AccountData {
+                username: username.clone(),
+                email: email.clone(),
+                normalized_username: username.to_lowercase(),
+                normalized_email: email.to_lowercase(),
+                ..user.data.clone()
+            },
+            ..user.clone()
+        },
+    )
+    .await?;
+
+    assert_eq!(user.id, auth_module.auth_account_service.fetch_by_username(&username).await?.id);
+    let (reset_user, _) = auth_module.auth_account_service.generate_reset_password_token_by_email(&email).await?;
+    assert_eq!(user.id, reset_user.id);
+    assert!(matches!(
+        auth_module
+            .auth_account_service
+            .create_user(create_login_dto(&username.replacen("Stra", "STRA", 1), &email, &new_hyphenated_uuid()))
+            .await,
+        Err(LsAccountManagementError::UsernameAlreadyUsed)
+    ));
+
+    assert!(auth_module.auth_account_service.normalize_identifiers().await? >= 1);
+
+    let normalized = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+    assert_eq!(
+        normalize_identifier(&auth_module.auth_config.identifier_normalization, &username),
+        normalized.data.normalized_username
+    );
+    assert!(normalized.data.normalized_username.starts_with("strasse-"));
+    assert_eq!(user.id, auth_module.auth_account_service.fetch_by_username(&username.to_uppercase()).await?.id);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_rewrite_the_normalized_identifiers_on_conflicts() -> Result<(), LsAccountManagementError> {
+    // The in-memory transactions would keep the conflicting account
+    if !ROLLS_BACK {
+        return Ok(());
+    }
+
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+    let account_repo = auth_module.repo_manager.account_repo();
+
+    // Only the case of the usernames differs, but their keys were stored
+    // without case folding
+    let conflicting_username = user.data.username.to_uppercase();
+    let result = auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            account_repo
+                .update(
+                    conn,
+                    Record {
+                        data: AccountData {
+                            username: conflicting_username.clone(),
+                            normalized_username: conflicting_username.clone(),
+                            ..other_user.data.clone()
+                        },
+                        ..other_user.clone()
+                    },
+                )
+                .await?;
+            auth_module.auth_account_service.normalize_identifiers_with_conn(conn).await
+        })
+        .await;
+
+    match result {
+        Err(LsAccountManagementError::BadRequest { message, code }) => {
+            assert_eq!(IDENTIFIER_CONFLICT, code);
+            assert!(message.contains(&user.data.username));
+            assert!(message.contains(&conflicting_username));
+        }
+        _ => panic!("the conflict should be reported"),
+    }
+
+    // Rolled back
+    assert_eq!(
+        other_user.data.username,
+        auth_module.auth_account_service.fetch_by_user_id(other_user.id).await?.data.username
+    );
+    assert_eq!(
+        user.data.normalized_username,
+        auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.normalized_username
+    );
+
+    Ok(())
+}
+
+async fn update_account<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    account: AuthAccountModel,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    let account_repo = auth_module.repo_manager.account_repo();
+    auth_module.repo_manager.c3p0().transaction(async |conn| account_repo.update(conn, account).await).await
+}
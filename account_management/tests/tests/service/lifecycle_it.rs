@@ -0,0 +1,201 @@
+use crate::tests::util::auth_account_service_with_config;
+use crate::{ROLLS_BACK, data};
+use c3p0::sqlx::Database;
+use lightspeed_account_management::dto::change_password_dto::ChangePasswordDto;
+use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::{AccountStatus, AuthAccountModel};
+use lightspeed_account_management::service::lifecycle::AccountLifecycleListener;
+use lightspeed_core::model::language::Language;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use maybe_once::tokio_shared;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Records the notified changes as `(change, user_id)`
+#[derive(Clone, Default)]
+struct RecordingListener {
+    changes: Arc<Mutex<Vec<(&'static str, i64)>>>,
+}
+
+impl RecordingListener {
+    fn record(&self, change: &'static str, account: &AuthAccountModel) {
+        self.changes.lock().push((change, account.id));
+    }
+}
+
+impl<DB: Database> AccountLifecycleListener<DB> for RecordingListener {
+    async fn on_account_created(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("created", account);
+        Ok(())
+    }
+
+    async fn on_account_activated(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("activated", account);
+        Ok(())
+    }
+
+    async fn on_account_disabled(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("disabled", account);
+        Ok(())
+    }
+
+    async fn on_account_reactivated(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("reactivated", account);
+        Ok(())
+    }
+
+    async fn on_account_anonymized(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("anonymized", account);
+        Ok(())
+    }
+
+    async fn on_account_deleted(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("deleted", account);
+        Ok(())
+    }
+
+    async fn on_roles_changed(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("roles_changed", account);
+        Ok(())
+    }
+
+    async fn on_password_changed(
+        &self,
+        _conn: &mut DB::Connection,
+        account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        self.record("password_changed", account);
+        Ok(())
+    }
+}
+
+/// Rejects every disabling
+struct FailingListener;
+
+impl<DB: Database> AccountLifecycleListener<DB> for FailingListener {
+    async fn on_account_disabled(
+        &self,
+        _conn: &mut DB::Connection,
+        _account: &AuthAccountModel,
+    ) -> Result<(), LsAccountManagementError> {
+        Err(LsAccountManagementError::BadRequest { message: "not now".to_owned(), code: "CRM_UNAVAILABLE" })
+    }
+}
+
+fn create_login_dto(password: &str) -> CreateLoginDto {
+    let username = new_hyphenated_uuid();
+    CreateLoginDto {
+        email: format!("{username}@email.fake"),
+        username: Some(username),
+        data: HashMap::new(),
+        accept_privacy_policy: true,
+        language: Language::En,
+        password: password.to_owned(),
+        password_confirm: password.to_owned(),
+    }
+}
+
+#[tokio_shared::test]
+async fn should_notify_the_listeners_of_the_account_changes() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = auth_account_service_with_config(auth_module, auth_module.auth_config.clone());
+    let listener = RecordingListener::default();
+    account_service.add_lifecycle_listener(listener.clone());
+
+    let password = new_hyphenated_uuid();
+    let (user, token) = account_service.create_user(create_login_dto(&password)).await?;
+    account_service.activate_user(&token.data.token).await?;
+    account_service.add_roles(user.id, &["admin".to_owned()], None).await?;
+    account_service.delete_roles(user.id, &["admin".to_owned()], None).await?;
+    let new_password = new_hyphenated_uuid();
+    account_service
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: password,
+                new_password: new_password.clone(),
+                new_password_confirm: new_password,
+            },
+            None,
+        )
+        .await?;
+    account_service.disable_by_user_id(user.id, None).await?;
+    account_service.reactivate_disabled_user_by_user_id(user.id, None).await?;
+    account_service.anonymize_account(user.id, None).await?;
+    account_service.delete_by_user_id(user.id, None).await?;
+
+    assert_eq!(
+        vec![
+            ("created", user.id),
+            ("activated", user.id),
+            ("roles_changed", user.id),
+            ("roles_changed", user.id),
+            ("password_changed", user.id),
+            ("disabled", user.id),
+            ("reactivated", user.id),
+            ("anonymized", user.id),
+            ("deleted", user.id),
+        ],
+        *listener.changes.lock()
+    );
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_roll_back_the_change_rejected_by_a_listener() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = auth_account_service_with_config(auth_module, auth_module.auth_config.clone());
+    let listener = RecordingListener::default();
+    account_service.add_lifecycle_listener(listener.clone());
+    account_service.add_lifecycle_listener(FailingListener);
+
+    let (user, token) = account_service.create_user(create_login_dto(&new_hyphenated_uuid())).await?;
+    account_service.activate_user(&token.data.token).await?;
+
+    assert!(matches!(
+        account_service.disable_by_user_id(user.id, None).await,
+        Err(LsAccountManagementError::BadRequest { code: "CRM_UNAVAILABLE", .. })
+    ));
+    if ROLLS_BACK {
+        assert_eq!(AccountStatus::Active, account_service.fetch_by_user_id(user.id).await?.data.status);
+    }
+
+    // The listeners before the failing one were invoked in the rolled back
+    // transaction
+    assert_eq!(vec![("created", user.id), ("activated", user.id), ("disabled", user.id)], *listener.changes.lock());
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::data;
+use crate::tests::util::{auth_account_service_with_config, create_user_with_password};
+use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::AMConfig;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
+use lightspeed_account_management::service::account::LsAMAccountService;
+use maybe_once::tokio_shared;
+
+const PASSWORD: &str = "Password_123!";
+
+fn service_with_lockout<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    max_failed_login_attempts: Option<u32>,
+) -> LsAMAccountService<RepoManager> {
+    let auth_config = AMConfig {
+        max_failed_login_attempts,
+        login_lockout_seconds: 60,
+        max_login_lockout_seconds: 150,
+        ..auth_module.auth_config.clone()
+    };
+    auth_account_service_with_config(auth_module, auth_config)
+}
+
+/// Moves the last failed login of the user back in time, to simulate the
+/// passing of the lockout without waiting
+async fn backdate_last_failed_login<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user_id: i64,
+    seconds: i64,
+) -> Result<(), LsAccountManagementError> {
+    let repo = auth_module.repo_manager.account_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            let mut user = repo.fetch_by_id(conn, user_id).await?;
+            user.data.last_failed_login_epoch_seconds =
+                user.data.last_failed_login_epoch_seconds.map(|ts| ts - seconds);
+            repo.update(conn, user).await?;
+            Ok::<_, LsAccountManagementError>(())
+        })
+        .await
+}
+
+#[tokio_shared::test]
+async fn should_lock_the_account_after_too_many_failed_logins() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_lockout(auth_module, Some(3));
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    for _ in 0..3 {
+        assert!(matches!(
+            service.login(&user.data.username, "wrong").await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+    }
+
+    assert_eq!(3, auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.failed_login_attempts);
+
+    // Rejected also with the right password, and whatever the password is
+    for password in [PASSWORD, "wrong"] {
+        assert!(matches!(
+            service.login(&user.data.username, password).await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+    }
+
+    // Attempts rejected by the lockout are not counted
+    assert_eq!(3, auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.failed_login_attempts);
+
+    backdate_last_failed_login(auth_module, user.id, 60).await?;
+    assert!(service.login(&user.data.username, PASSWORD).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_double_the_lockout_on_every_further_failure() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_lockout(auth_module, Some(1));
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    for expected_lockout_seconds in [60, 120, 150, 150] {
+        backdate_last_failed_login(auth_module, user.id, 150).await?;
+        assert!(matches!(
+            service.login(&user.data.username, "wrong").await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+        // Still locked a couple of seconds before the end of the lockout
+        backdate_last_failed_login(auth_module, user.id, expected_lockout_seconds - 2).await?;
+        assert!(matches!(
+            service.login(&user.data.username, PASSWORD).await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+    }
+
+    backdate_last_failed_login(auth_module, user.id, 150).await?;
+    assert!(service.login(&user.data.username, PASSWORD).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reset_the_failed_logins_on_success() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_lockout(auth_module, Some(3));
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    for _ in 0..2 {
+        assert!(service.login(&user.data.username, "wrong").await.is_err());
+    }
+    assert!(service.login(&user.data.username, PASSWORD).await.is_ok());
+
+    let account = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+    assert_eq!(0, account.data.failed_login_attempts);
+    assert_eq!(None, account.data.last_failed_login_epoch_seconds);
+
+    for _ in 0..2 {
+        assert!(service.login(&user.data.username, "wrong").await.is_err());
+    }
+    assert!(service.login(&user.data.username, PASSWORD).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_unlock_an_account() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_lockout(auth_module, Some(1));
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    assert!(service.login(&user.data.username, "wrong").await.is_err());
+    assert!(matches!(
+        service.login(&user.data.username, PASSWORD).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    let account = service.unlock_by_user_id(user.id, None).await?;
+    assert_eq!(0, account.data.failed_login_attempts);
+    assert!(service.login(&user.data.username, PASSWORD).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_answer_wrong_credentials_to_the_magic_login_of_a_locked_account() -> Result<(), LsAccountManagementError>
+{
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_lockout(auth_module, Some(1));
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+    let (_, token) = service.request_magic_login(&user.data.email, None).await?;
+
+    assert!(service.login(&user.data.username, "wrong").await.is_err());
+    assert!(matches!(
+        service.login_with_magic_token(&token.data.token, None).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    // The token is not consumed by the rejected login
+    service.unlock_by_user_id(user.id, None).await?;
+    assert!(service.login_with_magic_token(&token.data.token, None).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_lock_if_the_lockout_is_disabled() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_lockout(auth_module, None);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    for _ in 0..10 {
+        assert!(matches!(
+            service.login(&user.data.username, "wrong").await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+    }
+    assert!(service.login(&user.data.username, PASSWORD).await.is_ok());
+
+    Ok(())
+}
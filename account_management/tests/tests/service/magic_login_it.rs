@@ -0,0 +1,148 @@
+use crate::data;
+use crate::tests::util::{create_user, create_user_with_password, expire_token, pending_events};
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::event::MagicLoginRequested;
+use lightspeed_account_management::model::token::TokenType;
+use lightspeed_account_management::service::account::LoginOutcome;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use maybe_once::tokio_shared;
+
+#[tokio_shared::test]
+async fn should_login_with_a_magic_token() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+
+    let (_, token) = auth_module.auth_account_service.request_magic_login(&user.data.email, None).await?;
+    assert_eq!(TokenType::MagicLogin, token.data.token_type);
+    assert_eq!(user.data.username, token.data.username);
+    let requested = pending_events::<MagicLoginRequested, _>(auth_module, user.id).await?;
+    assert_eq!(
+        vec![MagicLoginRequested {
+            user_id: user.id,
+            username: user.data.username.clone(),
+            email: user.data.email.clone(),
+            sealed_login_token: requested[0].sealed_login_token.clone(),
+        }],
+        requested
+    );
+    assert_eq!(token.data.token, auth_module.token_service.open_token(&requested[0].sealed_login_token)?);
+
+    match auth_module.auth_account_service.login_with_magic_token(&token.data.token, None).await? {
+        LoginOutcome::Authenticated(auth) => assert_eq!(user.id, auth.id),
+        outcome => panic!("unexpected outcome {outcome:?}"),
+    }
+
+    // The token can be used only once
+    assert!(matches!(
+        auth_module.auth_account_service.login_with_magic_token(&token.data.token, None).await,
+        Err(LsAccountManagementError::TokenNotValid)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_accept_only_the_last_magic_token() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+
+    let (_, first_token) = auth_module.auth_account_service.request_magic_login(&user.data.email, None).await?;
+    let (_, second_token) = auth_module.auth_account_service.request_magic_login(&user.data.email, None).await?;
+
+    assert!(auth_module.auth_account_service.login_with_magic_token(&first_token.data.token, None).await.is_err());
+    assert!(auth_module.auth_account_service.login_with_magic_token(&second_token.data.token, None).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_bind_the_magic_token_to_the_device() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user(auth_module, true).await?;
+    let device = new_hyphenated_uuid();
+
+    let (_, token) = auth_module.auth_account_service.request_magic_login(&user.data.email, Some(&device)).await?;
+    assert_eq!(Some(device.clone()), token.data.device_fingerprint);
+
+    for other_device in [None, Some("other-device")] {
+        assert!(matches!(
+            auth_module.auth_account_service.login_with_magic_token(&token.data.token, other_device).await,
+            Err(LsAccountManagementError::TokenNotValid)
+        ));
+    }
+    assert!(auth_module.auth_account_service.login_with_magic_token(&token.data.token, Some(&device)).await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_login_with_an_expired_or_wrong_type_token() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, activation_token) = create_user(auth_module, false).await?;
+
+    assert!(matches!(
+        auth_module.auth_account_service.login_with_magic_token(&activation_token.data.token, None).await,
+        Err(LsAccountManagementError::TokenNotValid)
+    ));
+
+    auth_module.auth_account_service.activate_user(&activation_token.data.token).await?;
+    let (_, token) = auth_module.auth_account_service.request_magic_login(&user.data.email, None).await?;
+    let token = expire_token(auth_module, token).await?;
+    assert!(matches!(
+        auth_module.auth_account_service.login_with_magic_token(&token.data.token, None).await,
+        Err(LsAccountManagementError::TokenExpired)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_reveal_the_accounts_not_eligible_for_a_magic_login() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (pending_user, _) = create_user(auth_module, false).await?;
+    let (disabled_user, _) = create_user(auth_module, true).await?;
+    auth_module.auth_account_service.disable_by_user_id(disabled_user.id, None).await?;
+
+    for email in [format!("{}@email.fake", new_hyphenated_uuid()), pending_user.data.email, disabled_user.data.email] {
+        assert!(matches!(
+            auth_module.auth_account_service.request_magic_login(&email, None).await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+    }
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_disable_the_password_login() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+
+    let user = auth_module.auth_account_service.set_password_login_enabled(user.id, false, None).await?;
+    assert!(user.data.password_login_disabled);
+    assert!(matches!(
+        auth_module.auth_account_service.login(&user.data.username, &password).await,
+        Err(LsAccountManagementError::PasswordLoginDisabled(_))
+    ));
+    // A wrong password is still reported as such
+    assert!(matches!(
+        auth_module.auth_account_service.login(&user.data.username, "wrong").await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    let (_, token) = auth_module.auth_account_service.request_magic_login(&user.data.email, None).await?;
+    assert!(auth_module.auth_account_service.login_with_magic_token(&token.data.token, None).await.is_ok());
+
+    let user = auth_module.auth_account_service.set_password_login_enabled(user.id, true, None).await?;
+    assert!(!user.data.password_login_disabled);
+    assert!(auth_module.auth_account_service.login(&user.data.username, &password).await.is_ok());
+
+    Ok(())
+}
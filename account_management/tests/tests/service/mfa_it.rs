@@ -0,0 +1,255 @@
+use crate::data;
+use crate::tests::util::create_user_with_password;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::MfaState;
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::account::LoginOutcome;
+use lightspeed_account_management::service::mfa::{LsAMMfaService, MFA_ALREADY_ENABLED, MFA_NOT_PENDING};
+use lightspeed_account_management::service::totp::{LsTotpService, TOTP_PERIOD_SECONDS};
+use maybe_once::tokio_shared;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+const PASSWORD: &str = "Password_123!";
+const START_EPOCH_SECONDS: i64 = 1_700_000_000;
+
+/// Returns a MFA service whose clock is set by the returned atomic
+fn mfa_service_with_fixed_clock<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+) -> (LsAMMfaService<RepoManager>, Arc<AtomicI64>) {
+    let now = Arc::new(AtomicI64::new(START_EPOCH_SECONDS));
+    let clock = now.clone();
+    let service = LsAMMfaService::new(
+        auth_module.repo_manager.c3p0().clone(),
+        auth_module.auth_config.clone(),
+        auth_module.token_service.clone(),
+        auth_module.password_codec.clone(),
+        auth_module.repo_manager.account_repo(),
+        auth_module.audit_service.clone(),
+        Arc::new(move || clock.load(Ordering::SeqCst)),
+    );
+    (service, now)
+}
+
+async fn mfa_token<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    username: &str,
+) -> Result<String, LsAccountManagementError> {
+    match auth_module.auth_account_service.login(username, PASSWORD).await? {
+        LoginOutcome::MfaRequired { mfa_token, .. } => Ok(mfa_token),
+        LoginOutcome::Authenticated(_) => panic!("expected MfaRequired"),
+    }
+}
+
+#[tokio_shared::test]
+async fn should_enroll_and_login_with_totp() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (mfa_service, now) = mfa_service_with_fixed_clock(auth_module);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    let enrollment = mfa_service.start_totp_enrollment(user.id).await?;
+    assert!(enrollment.otpauth_uri.starts_with("otpauth://totp/LightSpeed:"));
+    assert!(enrollment.otpauth_uri.contains(&format!("secret={}", enrollment.secret)));
+
+    // Until confirmed, the login does not require the second factor
+    assert!(matches!(
+        auth_module.auth_account_service.login(&user.data.username, PASSWORD).await?,
+        LoginOutcome::Authenticated(_)
+    ));
+
+    let code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS)?;
+    let recovery_codes = mfa_service.confirm_totp_enrollment(user.id, &code).await?;
+    assert_eq!(10, recovery_codes.len());
+
+    let account = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+    match &account.data.mfa {
+        MfaState::TotpEnabled { totp_secret, recovery_code_hashes, .. } => {
+            assert_eq!(&enrollment.secret, totp_secret);
+            assert!(!recovery_code_hashes.contains(&recovery_codes[0]));
+        }
+        _ => panic!("expected TotpEnabled"),
+    }
+
+    // The code used for the confirmation cannot be used again
+    let token = mfa_token(auth_module, &user.data.username).await?;
+    assert!(matches!(
+        mfa_service.login_with_mfa_code(&token, &code).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    now.fetch_add(TOTP_PERIOD_SECONDS, Ordering::SeqCst);
+    let code = LsTotpService::code_at(&enrollment.secret, now.load(Ordering::SeqCst))?;
+    let token = mfa_token(auth_module, &user.data.username).await?;
+    let auth = mfa_service.login_with_mfa_code(&token, &code).await?;
+    assert_eq!(user.id, auth.id);
+    assert_eq!(user.data.username, auth.username);
+
+    // The MFA token is single use
+    assert!(matches!(
+        mfa_service.login_with_mfa_code(&token, &code).await,
+        Err(LsAccountManagementError::TokenNotValid)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_confirm_enrollment_with_wrong_code() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (mfa_service, _) = mfa_service_with_fixed_clock(auth_module);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    assert!(matches!(
+        mfa_service.confirm_totp_enrollment(user.id, "123456").await,
+        Err(LsAccountManagementError::BadRequest { code: MFA_NOT_PENDING, .. })
+    ));
+
+    let enrollment = mfa_service.start_totp_enrollment(user.id).await?;
+    let expired_code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS - 2 * TOTP_PERIOD_SECONDS)?;
+    assert!(matches!(
+        mfa_service.confirm_totp_enrollment(user.id, &expired_code).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+    assert!(matches!(
+        auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.mfa,
+        MfaState::TotpPending { .. }
+    ));
+
+    let code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS)?;
+    mfa_service.confirm_totp_enrollment(user.id, &code).await?;
+
+    assert!(matches!(
+        mfa_service.start_totp_enrollment(user.id).await,
+        Err(LsAccountManagementError::BadRequest { code: MFA_ALREADY_ENABLED, .. })
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_invalidate_the_mfa_token_on_wrong_code() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (mfa_service, _) = mfa_service_with_fixed_clock(auth_module);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    let enrollment = mfa_service.start_totp_enrollment(user.id).await?;
+    let code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS)?;
+    mfa_service.confirm_totp_enrollment(user.id, &code).await?;
+
+    let token = mfa_token(auth_module, &user.data.username).await?;
+    let next_code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS + TOTP_PERIOD_SECONDS)?;
+    let wrong_code = if next_code == "000000" { "111111" } else { "000000" };
+
+    assert!(matches!(
+        mfa_service.login_with_mfa_code(&token, wrong_code).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+    assert!(matches!(
+        mfa_service.login_with_mfa_code(&token, &next_code).await,
+        Err(LsAccountManagementError::TokenNotValid)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_login_once_with_each_recovery_code() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (mfa_service, _) = mfa_service_with_fixed_clock(auth_module);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    let enrollment = mfa_service.start_totp_enrollment(user.id).await?;
+    let code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS)?;
+    let recovery_codes = mfa_service.confirm_totp_enrollment(user.id, &code).await?;
+
+    let token = mfa_token(auth_module, &user.data.username).await?;
+    assert_eq!(user.id, mfa_service.login_with_mfa_code(&token, &recovery_codes[3].to_uppercase()).await?.id);
+
+    let token = mfa_token(auth_module, &user.data.username).await?;
+    assert!(matches!(
+        mfa_service.login_with_mfa_code(&token, &recovery_codes[3]).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    match auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.mfa {
+        MfaState::TotpEnabled { recovery_code_hashes, .. } => assert_eq!(9, recovery_code_hashes.len()),
+        _ => panic!("expected TotpEnabled"),
+    }
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_disable_totp_only_with_the_password() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (mfa_service, _) = mfa_service_with_fixed_clock(auth_module);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    let enrollment = mfa_service.start_totp_enrollment(user.id).await?;
+    let code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS)?;
+    mfa_service.confirm_totp_enrollment(user.id, &code).await?;
+
+    assert!(matches!(
+        mfa_service.disable_totp(user.id, "wrong_password").await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+    assert!(auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.mfa.is_enabled());
+
+    let account = mfa_service.disable_totp(user.id, PASSWORD).await?;
+    assert_eq!(MfaState::Disabled, account.data.mfa);
+    assert!(matches!(
+        auth_module.auth_account_service.login(&user.data.username, PASSWORD).await?,
+        LoginOutcome::Authenticated(_)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_count_wrong_codes_and_passwords_as_failed_logins() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (mfa_service, _) = mfa_service_with_fixed_clock(auth_module);
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+    let max_attempts = auth_module.auth_config.max_failed_login_attempts.unwrap();
+
+    let enrollment = mfa_service.start_totp_enrollment(user.id).await?;
+    let code = LsTotpService::code_at(&enrollment.secret, START_EPOCH_SECONDS)?;
+    mfa_service.confirm_totp_enrollment(user.id, &code).await?;
+
+    let token = mfa_token(auth_module, &user.data.username).await?;
+    assert!(matches!(
+        mfa_service.login_with_mfa_code(&token, "not-a-code").await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+    assert_eq!(1, auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.failed_login_attempts);
+
+    for _ in 1..max_attempts {
+        assert!(matches!(
+            mfa_service.disable_totp(user.id, "wrong_password").await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+    }
+    assert_eq!(
+        max_attempts,
+        auth_module.auth_account_service.fetch_by_user_id(user.id).await?.data.failed_login_attempts
+    );
+
+    // The account is locked for the password and the second factor alike
+    assert!(matches!(
+        mfa_service.disable_totp(user.id, PASSWORD).await,
+        Err(LsAccountManagementError::AccountLocked { .. })
+    ));
+    assert!(matches!(
+        auth_module.auth_account_service.login(&user.data.username, PASSWORD).await,
+        Err(LsAccountManagementError::WrongCredentials)
+    ));
+
+    Ok(())
+}
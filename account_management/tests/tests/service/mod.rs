@@ -1,2 +1,25 @@
+pub mod account_anonymization_it;
+pub mod account_purge_it;
+pub mod account_repository_it;
+pub mod account_search_it;
+pub mod account_status_it;
+pub mod acl_it;
+pub mod audit_it;
 pub mod auth_account_it;
+pub mod breached_password_it;
+pub mod email_change_it;
+#[cfg(feature = "email")]
+pub mod email_it;
+pub mod event_it;
+pub mod identifier_it;
+pub mod lifecycle_it;
+pub mod lockout_it;
+pub mod magic_login_it;
+pub mod mfa_it;
+#[cfg(feature = "oidc")]
+pub mod oidc_it;
+pub mod password_history_it;
+pub mod profile_it;
+pub mod rehash_it;
+pub mod session_it;
 pub mod token_it;
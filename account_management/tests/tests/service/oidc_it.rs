@@ -0,0 +1,353 @@
+use crate::data;
+use crate::tests::util::{create_user, pending_events};
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Form, Json, Router};
+use data_encoding::{BASE64, BASE64URL_NOPAD};
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::{AMConfig, OidcProviderConfig};
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::AccountStatus;
+use lightspeed_account_management::model::event::AccountProvisioned;
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::account::LoginOutcome;
+use lightspeed_account_management::service::oidc::{LsAMOidcService, OidcAuthorizationRequest};
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use maybe_once::tokio_shared;
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use url::Url;
+
+const MOCK_PROVIDER: &str = "mock";
+const MOCK_CLIENT_ID: &str = "lightspeed";
+const MOCK_KEY_ID: &str = "mock-key";
+/// PKCS#8 P-256 private key, base64 encoded
+const MOCK_KEY_PKCS8: &str = "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgnx4snPRqaF+McZfBX9TF6GiO9/Yi2sB2u1+PRemRSQGhRANCAAQl8Y7pEBs6hvIE2CvzYH8e6mv5Bamjl/Li/YWDkLyPcRyTb13WKbhVEGkuYymcX4nc5ZD1GgbcrpgEK3DBhYC9";
+
+/// Coordinates of the public key
+const MOCK_KEY_X: &str = "JfGO6RAbOobyBNgr82B_Hupr-QWpo5fy4v2Fg5C8j3E";
+const MOCK_KEY_Y: &str = "HJNvXdYpuFUQaS5jKZxfidzlkPUaBtyumAQrcMGFgL0";
+
+#[tokio_shared::test]
+async fn should_provision_an_account_at_the_first_login() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let provider = MockProvider::start().await;
+    let oidc_service = oidc_service(auth_module, &provider, true);
+    let subject = new_hyphenated_uuid();
+    let email = format!("{subject}@email.fake");
+
+    let (request, code) =
+        provider.authorize(&oidc_service, json!({ "sub": subject, "email": email, "email_verified": true })).await?;
+    let user_id = match oidc_service.login(&request, &code, &request.state).await? {
+        LoginOutcome::Authenticated(auth) => auth.id,
+        outcome => panic!("unexpected outcome {outcome:?}"),
+    };
+
+    let user = auth_module.auth_account_service.fetch_by_user_id(user_id).await?;
+    assert_eq!(email, user.data.email);
+    assert_eq!(AccountStatus::Active, user.data.status);
+    assert_eq!(auth_module.auth_config.default_roles_on_account_creation, user.data.roles);
+    assert!(user.data.password_login_disabled);
+    assert_eq!(
+        vec![AccountProvisioned {
+            user_id,
+            username: user.data.username.clone(),
+            email: email.clone(),
+            issuer: provider.issuer.clone(),
+        }],
+        pending_events::<AccountProvisioned, _>(auth_module, user_id).await?
+    );
+
+    // The next login uses the linked account
+    let (request, code) =
+        provider.authorize(&oidc_service, json!({ "sub": subject, "email": email, "email_verified": true })).await?;
+    match oidc_service.login(&request, &code, &request.state).await? {
+        LoginOutcome::Authenticated(auth) => assert_eq!(user_id, auth.id),
+        outcome => panic!("unexpected outcome {outcome:?}"),
+    }
+
+    let identities = oidc_service.fetch_external_identities(user_id).await?;
+    assert_eq!(1, identities.len());
+    assert_eq!(provider.issuer, identities[0].data.issuer);
+    assert_eq!(subject, identities[0].data.subject);
+    assert!(identities[0].data.last_login_epoch_seconds.is_some());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_provision_an_account_without_a_verified_unused_email() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let provider = MockProvider::start().await;
+    let (user, _) = create_user(auth_module, true).await?;
+
+    let (request, code) = provider
+        .authorize(
+            &oidc_service(auth_module, &provider, false),
+            json!({ "sub": new_hyphenated_uuid(), "email": user.data.email, "email_verified": true }),
+        )
+        .await?;
+    assert!(matches!(
+        oidc_service(auth_module, &provider, false).login(&request, &code, &request.state).await,
+        Err(LsAccountManagementError::ExternalIdentityNotLinked)
+    ));
+
+    let oidc_service = oidc_service(auth_module, &provider, true);
+
+    let (request, code) = provider
+        .authorize(
+            &oidc_service,
+            json!({ "sub": new_hyphenated_uuid(), "email": format!("{}@email.fake", new_hyphenated_uuid()) }),
+        )
+        .await?;
+    assert!(matches!(
+        oidc_service.login(&request, &code, &request.state).await,
+        Err(LsAccountManagementError::OidcAuthenticationFailed { .. })
+    ));
+
+    // Accounts are never linked automatically by email
+    let (request, code) = provider
+        .authorize(
+            &oidc_service,
+            json!({ "sub": new_hyphenated_uuid(), "email": user.data.email, "email_verified": true }),
+        )
+        .await?;
+    assert!(matches!(
+        oidc_service.login(&request, &code, &request.state).await,
+        Err(LsAccountManagementError::EmailAlreadyUsed)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_link_and_unlink_an_identity() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let provider = MockProvider::start().await;
+    let oidc_service = oidc_service(auth_module, &provider, false);
+    let (user, _) = create_user(auth_module, true).await?;
+    let (other_user, _) = create_user(auth_module, true).await?;
+    let subject = new_hyphenated_uuid();
+
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": subject })).await?;
+    let identity = oidc_service.link_identity(user.id, &request, &code, &request.state, None).await?;
+    assert_eq!(user.id, identity.data.user_id);
+
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": subject })).await?;
+    match oidc_service.login(&request, &code, &request.state).await? {
+        LoginOutcome::Authenticated(auth) => assert_eq!(user.id, auth.id),
+        outcome => panic!("unexpected outcome {outcome:?}"),
+    }
+
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": subject })).await?;
+    assert!(matches!(
+        oidc_service.link_identity(other_user.id, &request, &code, &request.state, None).await,
+        Err(LsAccountManagementError::ExternalIdentityAlreadyLinked)
+    ));
+
+    assert!(matches!(
+        oidc_service.unlink_identity(other_user.id, identity.id, None).await,
+        Err(LsAccountManagementError::ExternalIdentityNotLinked)
+    ));
+    oidc_service.unlink_identity(user.id, identity.id, None).await?;
+    assert!(oidc_service.fetch_external_identities(user.id).await?.is_empty());
+
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": subject })).await?;
+    assert!(matches!(
+        oidc_service.login(&request, &code, &request.state).await,
+        Err(LsAccountManagementError::ExternalIdentityNotLinked)
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reject_a_login_of_an_inactive_account() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let provider = MockProvider::start().await;
+    let oidc_service = oidc_service(auth_module, &provider, false);
+    let (user, _) = create_user(auth_module, true).await?;
+    let subject = new_hyphenated_uuid();
+
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": subject })).await?;
+    oidc_service.link_identity(user.id, &request, &code, &request.state, None).await?;
+    auth_module.auth_account_service.disable_by_user_id(user.id, None).await?;
+
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": subject })).await?;
+    assert!(matches!(
+        oidc_service.login(&request, &code, &request.state).await,
+        Err(LsAccountManagementError::InactiveUser(_))
+    ));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reject_an_unverified_authentication() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let provider = MockProvider::start().await;
+    let oidc_service = oidc_service(auth_module, &provider, true);
+
+    assert!(matches!(
+        oidc_service.authorization_request("unknown").await,
+        Err(LsAccountManagementError::UnknownOidcProvider(_))
+    ));
+
+    // Wrong state
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": new_hyphenated_uuid() })).await?;
+    assert!(matches!(
+        oidc_service.login(&request, &code, &new_hyphenated_uuid()).await,
+        Err(LsAccountManagementError::OidcAuthenticationFailed { .. })
+    ));
+
+    // Unknown code
+    assert!(matches!(
+        oidc_service.login(&request, &new_hyphenated_uuid(), &request.state).await,
+        Err(LsAccountManagementError::OidcAuthenticationFailed { .. })
+    ));
+
+    // Wrong PKCE verifier
+    let (request, code) = provider.authorize(&oidc_service, json!({ "sub": new_hyphenated_uuid() })).await?;
+    let request = OidcAuthorizationRequest { pkce_verifier: new_hyphenated_uuid(), ..request };
+    assert!(matches!(
+        oidc_service.login(&request, &code, &request.state).await,
+        Err(LsAccountManagementError::OidcAuthenticationFailed { .. })
+    ));
+
+    for claims in [
+        json!({ "sub": new_hyphenated_uuid(), "nonce": new_hyphenated_uuid() }),
+        json!({ "sub": new_hyphenated_uuid(), "aud": "another-client" }),
+        json!({ "sub": new_hyphenated_uuid(), "iss": "https://another.issuer" }),
+        json!({ "sub": new_hyphenated_uuid(), "exp": current_epoch_seconds() - 3600 }),
+    ] {
+        let (request, code) = provider.authorize(&oidc_service, claims).await?;
+        assert!(matches!(
+            oidc_service.login(&request, &code, &request.state).await,
+            Err(LsAccountManagementError::OidcAuthenticationFailed { .. })
+        ));
+    }
+
+    Ok(())
+}
+
+fn oidc_service<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    provider: &MockProvider,
+    jit_provisioning: bool,
+) -> LsAMOidcService<RepoManager> {
+    LsAMOidcService::new(
+        auth_module.repo_manager.c3p0().clone(),
+        AMConfig {
+            oidc_providers: vec![OidcProviderConfig {
+                name: MOCK_PROVIDER.to_owned(),
+                issuer_url: provider.issuer.clone(),
+                client_id: MOCK_CLIENT_ID.to_owned(),
+                redirect_uri: "http://127.0.0.1/callback".to_owned(),
+                jit_provisioning,
+                ..Default::default()
+            }],
+            ..auth_module.auth_config.clone()
+        },
+        auth_module.auth_account_service.clone(),
+        auth_module.repo_manager.external_identity_repo(),
+        auth_module.audit_service.clone(),
+        auth_module.event_publisher.clone(),
+    )
+}
+
+/// A local OpenID provider issuing ES256 ID tokens with the claims
+/// registered for each authorization code
+#[derive(Clone)]
+struct MockProvider {
+    issuer: String,
+    /// The PKCE challenge and the ID token claims of each code
+    codes: Arc<Mutex<HashMap<String, (String, Value)>>>,
+}
+
+impl MockProvider {
+    async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let provider =
+            MockProvider { issuer: format!("http://{}", listener.local_addr().unwrap()), codes: Default::default() };
+        let router = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(provider.clone());
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        provider
+    }
+
+    /// Starts an authorization and returns the code the provider would send
+    /// back after authenticating a user with `claims`. The standard claims
+    /// not in `claims` are set to valid values.
+    async fn authorize<RepoManager: AMRepositoryManager>(
+        &self,
+        oidc_service: &LsAMOidcService<RepoManager>,
+        mut claims: Value,
+    ) -> Result<(OidcAuthorizationRequest, String), LsAccountManagementError> {
+        let request = oidc_service.authorization_request(MOCK_PROVIDER).await?;
+        let params: HashMap<String, String> =
+            Url::parse(&request.authorization_url).unwrap().query_pairs().into_owned().collect();
+        assert_eq!(request.state, params["state"]);
+        assert_eq!(MOCK_CLIENT_ID, params["client_id"]);
+        assert_eq!("S256", params["code_challenge_method"]);
+
+        let now = current_epoch_seconds();
+        let claims_map = claims.as_object_mut().unwrap();
+        claims_map.entry("iss").or_insert(json!(self.issuer));
+        claims_map.entry("aud").or_insert(json!(MOCK_CLIENT_ID));
+        claims_map.entry("iat").or_insert(json!(now));
+        claims_map.entry("exp").or_insert(json!(now + 300));
+        claims_map.entry("nonce").or_insert(json!(params["nonce"]));
+
+        let code = new_hyphenated_uuid();
+        self.codes.lock().unwrap().insert(code.clone(), (params["code_challenge"].clone(), claims));
+        Ok((request, code))
+    }
+}
+
+async fn discovery(State(provider): State<MockProvider>) -> Json<Value> {
+    Json(json!({
+        "issuer": provider.issuer,
+        "authorization_endpoint": format!("{}/authorize", provider.issuer),
+        "token_endpoint": format!("{}/token", provider.issuer),
+        "jwks_uri": format!("{}/jwks", provider.issuer),
+    }))
+}
+
+async fn jwks() -> Json<Value> {
+    Json(json!({
+        "keys": [{ "kty": "EC", "crv": "P-256", "x": MOCK_KEY_X, "y": MOCK_KEY_Y, "kid": MOCK_KEY_ID, "use": "sig", "alg": "ES256" }]
+    }))
+}
+
+async fn token(State(provider): State<MockProvider>, Form(form): Form<HashMap<String, String>>) -> Response {
+    let Some((code_challenge, claims)) = form.get("code").and_then(|code| provider.codes.lock().unwrap().remove(code))
+    else {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_grant" }))).into_response();
+    };
+    let code_verifier = form.get("code_verifier").map(String::as_str).unwrap_or_default();
+    if BASE64URL_NOPAD.encode(&Sha256::digest(code_verifier.as_bytes())) != code_challenge {
+        return (StatusCode::BAD_REQUEST, Json(json!({ "error": "invalid_grant" }))).into_response();
+    }
+
+    let header = Header { kid: Some(MOCK_KEY_ID.to_owned()), ..Header::new(Algorithm::ES256) };
+    let id_token = jsonwebtoken::encode(
+        &header,
+        &claims,
+        &EncodingKey::from_ec_der(&BASE64.decode(MOCK_KEY_PKCS8.as_bytes()).unwrap()),
+    )
+    .unwrap();
+    Json(json!({ "access_token": new_hyphenated_uuid(), "token_type": "Bearer", "id_token": id_token })).into_response()
+}
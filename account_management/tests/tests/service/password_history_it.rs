@@ -0,0 +1,124 @@
+use crate::data;
+use crate::tests::util::{auth_account_service_with_config, create_user_with_password};
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::AMConfig;
+use lightspeed_account_management::dto::change_password_dto::ChangePasswordDto;
+use lightspeed_account_management::dto::reset_password_dto::ResetPasswordDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::AuthAccountModel;
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::account::LsAMAccountService;
+use maybe_once::tokio_shared;
+
+fn service_with_history<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    password_history_size: u32,
+) -> LsAMAccountService<RepoManager> {
+    let auth_config = AMConfig { password_history_size, ..auth_module.auth_config.clone() };
+    auth_account_service_with_config(auth_module, auth_config)
+}
+
+async fn change_password<RepoManager: AMRepositoryManager>(
+    service: &LsAMAccountService<RepoManager>,
+    user_id: i64,
+    old_password: &str,
+    new_password: &str,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    service
+        .change_password(
+            ChangePasswordDto {
+                user_id,
+                old_password: old_password.to_owned(),
+                new_password: new_password.to_owned(),
+                new_password_confirm: new_password.to_owned(),
+            },
+            None,
+        )
+        .await
+}
+
+#[tokio_shared::test]
+async fn should_not_change_the_password_to_a_recent_one() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_history(auth_module, 3);
+    let (user, _) = create_user_with_password(auth_module, "password_1", true).await?;
+
+    assert!(matches!(
+        change_password(&service, user.id, "password_1", "password_1").await,
+        Err(LsAccountManagementError::PasswordReused)
+    ));
+
+    change_password(&service, user.id, "password_1", "password_2").await?;
+    let user = change_password(&service, user.id, "password_2", "password_3").await?;
+    assert_eq!(2, user.data.password_history.len());
+
+    for reused in ["password_1", "password_2", "password_3"] {
+        assert!(matches!(
+            change_password(&service, user.id, "password_3", reused).await,
+            Err(LsAccountManagementError::PasswordReused)
+        ));
+    }
+    assert!(service.login(&user.data.username, "password_3").await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_prune_the_oldest_passwords() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_history(auth_module, 2);
+    let (user, _) = create_user_with_password(auth_module, "password_1", true).await?;
+
+    change_password(&service, user.id, "password_1", "password_2").await?;
+    let user = change_password(&service, user.id, "password_2", "password_3").await?;
+    assert_eq!(1, user.data.password_history.len());
+
+    assert!(matches!(
+        change_password(&service, user.id, "password_3", "password_2").await,
+        Err(LsAccountManagementError::PasswordReused)
+    ));
+    change_password(&service, user.id, "password_3", "password_1").await?;
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_reset_the_password_to_a_recent_one() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_history(auth_module, 3);
+    let (user, _) = create_user_with_password(auth_module, "password_1", true).await?;
+
+    let reset = async |password: &str| {
+        let (_, token) = service.generate_reset_password_token(&user.data.username).await?;
+        service
+            .reset_password_by_token(ResetPasswordDto {
+                token: token.data.token,
+                password: password.to_owned(),
+                password_confirm: password.to_owned(),
+            })
+            .await
+    };
+
+    assert!(matches!(reset("password_1").await, Err(LsAccountManagementError::PasswordReused)));
+    reset("password_2").await?;
+    assert!(matches!(reset("password_1").await, Err(LsAccountManagementError::PasswordReused)));
+    assert!(service.login(&user.data.username, "password_2").await.is_ok());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reuse_passwords_if_the_history_is_disabled() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = service_with_history(auth_module, 0);
+    let (user, _) = create_user_with_password(auth_module, "password_1", true).await?;
+
+    let user = change_password(&service, user.id, "password_1", "password_1").await?;
+    assert!(user.data.password_history.is_empty());
+
+    Ok(())
+}
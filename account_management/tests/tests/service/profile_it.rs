@@ -0,0 +1,198 @@
+use crate::data;
+use crate::tests::util::{auth_account_service_with_config, create_user};
+use lightspeed_account_management::config::AMConfig;
+use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::{AccountProfilePatch, AuthAccountModel};
+use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::service::account::LsAMAccountService;
+use lightspeed_core::model::language::Language;
+use lightspeed_core::utils::new_hyphenated_uuid;
+use lightspeed_validator::length::LengthValidator;
+use maybe_once::tokio_shared;
+use std::collections::{BTreeMap, HashMap};
+
+#[tokio_shared::test]
+async fn should_save_the_signup_data_in_the_profile() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let user = signup(
+        &auth_module.auth_account_service,
+        HashMap::from([("nickname".to_owned(), "Neo".to_owned()), ("city".to_owned(), "Zion".to_owned())]),
+    )
+    .await?;
+    assert_eq!(Some(Language::It), user.data.language);
+
+    let profile = auth_module.auth_account_service.fetch_profile(user.id).await?;
+    assert_eq!(Some(Language::It), profile.language);
+    assert_eq!(
+        BTreeMap::from([("city".to_owned(), "Zion".to_owned()), ("nickname".to_owned(), "Neo".to_owned())]),
+        profile.attributes
+    );
+
+    let export = auth_module.auth_account_service.export_account_data(user.id).await?;
+    assert_eq!(profile.attributes, export.profile);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_patch_the_profile() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let user = signup(
+        &auth_module.auth_account_service,
+        HashMap::from([("nickname".to_owned(), "Neo".to_owned()), ("city".to_owned(), "Zion".to_owned())]),
+    )
+    .await?;
+
+    let profile = auth_module
+        .auth_account_service
+        .update_profile(
+            user.id,
+            AccountProfilePatch {
+                language: Some(Language::Fr),
+                attributes: BTreeMap::from([
+                    ("nickname".to_owned(), Some("The One".to_owned())),
+                    ("city".to_owned(), None),
+                    ("ship".to_owned(), Some("Nebuchadnezzar".to_owned())),
+                ]),
+            },
+            None,
+        )
+        .await?;
+    assert_eq!(Some(Language::Fr), profile.language);
+    assert_eq!(
+        BTreeMap::from([
+            ("nickname".to_owned(), "The One".to_owned()),
+            ("ship".to_owned(), "Nebuchadnezzar".to_owned())
+        ]),
+        profile.attributes
+    );
+    assert_eq!(profile, auth_module.auth_account_service.fetch_profile(user.id).await?);
+
+    // An empty patch changes nothing
+    assert_eq!(
+        profile,
+        auth_module.auth_account_service.update_profile(user.id, AccountProfilePatch::default(), None).await?
+    );
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_validate_the_profile_attributes() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    // A dedicated service, so that the validator is not used by the other tests
+    let account_service = auth_account_service_with_config(auth_module, auth_module.auth_config.clone());
+    account_service.add_profile_validator("nickname", LengthValidator { min: Some(2), max: Some(8), equal: None });
+
+    let result = signup(&account_service, HashMap::from([("nickname".to_owned(), "N".to_owned())])).await;
+    assert_field_error(result.map(|_| ()), "data.nickname");
+
+    let user = signup(&account_service, HashMap::from([("nickname".to_owned(), "Neo".to_owned())])).await?;
+    let result = account_service
+        .update_profile(
+            user.id,
+            AccountProfilePatch {
+                language: None,
+                attributes: BTreeMap::from([
+                    ("nickname".to_owned(), Some("Thomas Anderson".to_owned())),
+                    ("city".to_owned(), Some("Zion".to_owned())),
+                ]),
+            },
+            None,
+        )
+        .await;
+    assert_field_error(result.map(|_| ()), "data.nickname");
+    assert!(!account_service.fetch_profile(user.id).await?.attributes.contains_key("city"));
+
+    // Removing an attribute is always allowed
+    account_service
+        .update_profile(
+            user.id,
+            AccountProfilePatch { language: None, attributes: BTreeMap::from([("nickname".to_owned(), None)]) },
+            None,
+        )
+        .await?;
+    assert!(account_service.fetch_profile(user.id).await?.attributes.is_empty());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_limit_the_profile_size() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let account_service = auth_account_service_with_config(
+        auth_module,
+        AMConfig { max_profile_attributes: 1, max_profile_attribute_length: 4, ..auth_module.auth_config.clone() },
+    );
+
+    let result = signup(
+        &account_service,
+        HashMap::from([("nickname".to_owned(), "Neo".to_owned()), ("city".to_owned(), "Zion".to_owned())]),
+    )
+    .await;
+    assert_field_error(result.map(|_| ()), "data");
+
+    let result = signup(&account_service, HashMap::from([("nickname".to_owned(), "Morpheus".to_owned())])).await;
+    assert_field_error(result.map(|_| ()), "data.nickname");
+
+    let (user, _) = create_user(auth_module, true).await?;
+    account_service
+        .update_profile(
+            user.id,
+            AccountProfilePatch {
+                language: None,
+                attributes: BTreeMap::from([("city".to_owned(), Some("Zion".to_owned()))]),
+            },
+            None,
+        )
+        .await?;
+    let result = account_service
+        .update_profile(
+            user.id,
+            AccountProfilePatch {
+                language: None,
+                attributes: BTreeMap::from([("ship".to_owned(), Some("Neb".to_owned()))]),
+            },
+            None,
+        )
+        .await;
+    assert_field_error(result.map(|_| ()), "data");
+
+    Ok(())
+}
+
+async fn signup<RepoManager: AMRepositoryManager>(
+    account_service: &LsAMAccountService<RepoManager>,
+    data: HashMap<String, String>,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    let username = new_hyphenated_uuid();
+    let password = new_hyphenated_uuid();
+    let (user, _) = account_service
+        .create_user(CreateLoginDto {
+            email: format!("{username}@email.fake"),
+            username: Some(username),
+            data,
+            accept_privacy_policy: true,
+            language: Language::It,
+            password: password.clone(),
+            password_confirm: password,
+        })
+        .await?;
+    Ok(user)
+}
+
+fn assert_field_error(result: Result<(), LsAccountManagementError>, field: &str) {
+    match result {
+        Err(LsAccountManagementError::ValidationError { field_errors }) => {
+            assert!(field_errors.contains_key(field), "missing error of [{field}] in {field_errors:?}")
+        }
+        other => panic!("expected a validation error of [{field}], got {other:?}"),
+    }
+}
@@ -0,0 +1,109 @@
+use crate::data;
+use crate::tests::util::create_user_with_password;
+use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::auth_account::AuthAccountModel;
+use lightspeed_account_management::repository::{AMRepositoryManager, AccountRepository};
+use lightspeed_account_management::service::account::{LoginOutcome, LsAMAccountService};
+use lightspeed_account_management::service::password_codec::LsPasswordCodecService;
+use maybe_once::tokio_shared;
+use pbkdf2::Pbkdf2;
+use pbkdf2::password_hash::{PasswordHasher, SaltString, rand_core::OsRng};
+use std::sync::Arc;
+
+const PASSWORD: &str = "Password_123!";
+
+/// Replaces the password hash of the user, as done by an import from
+/// another system
+async fn set_password_hash<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user_id: i64,
+    hash: &str,
+) -> Result<AuthAccountModel, LsAccountManagementError> {
+    let repo = auth_module.repo_manager.account_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            let mut user = repo.fetch_by_id(conn, user_id).await?;
+            user.data.password = hash.to_owned();
+            let user = repo.update(conn, user).await?;
+            Ok::<_, LsAccountManagementError>(user)
+        })
+        .await
+}
+
+fn pbkdf2_sha256_hash(password: &str) -> String {
+    Pbkdf2
+        .hash_password_customized(
+            password.as_bytes(),
+            Some(pbkdf2::Algorithm::Pbkdf2Sha256.ident()),
+            None,
+            pbkdf2::Params { rounds: 1_000, output_length: 32 },
+            &SaltString::generate(&mut OsRng),
+        )
+        .unwrap()
+        .to_string()
+}
+
+#[tokio_shared::test]
+async fn should_upgrade_imported_hashes_at_login() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    for legacy_hash in [bcrypt::hash(PASSWORD, 4).unwrap(), pbkdf2_sha256_hash(PASSWORD)] {
+        let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+        let user = set_password_hash(auth_module, user.id, &legacy_hash).await?;
+
+        assert!(matches!(
+            auth_module.auth_account_service.login(&user.data.username, "wrong").await,
+            Err(LsAccountManagementError::WrongCredentials)
+        ));
+        let not_upgraded = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+        assert_eq!(legacy_hash, not_upgraded.data.password);
+
+        assert!(matches!(
+            auth_module.auth_account_service.login(&user.data.username, PASSWORD).await?,
+            LoginOutcome::Authenticated(_)
+        ));
+        let upgraded = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+        assert!(upgraded.data.password.starts_with("$argon2id$"));
+        assert!(!auth_module.password_codec.needs_rehash(&upgraded.data.password));
+        assert_eq!(user.data.password_updated_date_epoch_seconds, upgraded.data.password_updated_date_epoch_seconds);
+
+        assert!(matches!(
+            auth_module.auth_account_service.login(&user.data.username, PASSWORD).await?,
+            LoginOutcome::Authenticated(_)
+        ));
+        let unchanged = auth_module.auth_account_service.fetch_by_user_id(user.id).await?;
+        assert_eq!(upgraded.data.password, unchanged.data.password);
+    }
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_rehash_when_the_argon2_parameters_are_raised() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+
+    let stronger_codec = Arc::new(LsPasswordCodecService::new(16, 2, 1)?);
+    let service = LsAMAccountService::new(
+        &auth_module.repo_manager,
+        auth_module.auth_config.clone(),
+        auth_module.token_service.clone(),
+        stronger_codec.clone(),
+        auth_module.audit_service.clone(),
+        auth_module.event_publisher.clone(),
+    );
+    assert!(stronger_codec.needs_rehash(&user.data.password));
+
+    assert!(matches!(service.login(&user.data.username, PASSWORD).await?, LoginOutcome::Authenticated(_)));
+    let upgraded = service.fetch_by_user_id(user.id).await?;
+    assert_ne!(user.data.password, upgraded.data.password);
+    assert!(!stronger_codec.needs_rehash(&upgraded.data.password));
+
+    Ok(())
+}
@@ -0,0 +1,328 @@
+use crate::data;
+use crate::tests::util::{create_user_with_password, login_with_session};
+use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::dto::change_password_dto::ChangePasswordDto;
+use lightspeed_account_management::dto::reset_password_dto::ResetPasswordDto;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::session::SessionModel;
+use lightspeed_account_management::repository::{AMRepositoryManager, SessionRepository};
+use lightspeed_account_management::service::account::LoginOutcome;
+use lightspeed_core::config::JwtConfig;
+use lightspeed_core::error::LsError;
+use lightspeed_core::service::auth::{Auth, InMemoryRolesProvider, LsAuthService};
+use lightspeed_core::service::jwt::LsJwtService;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use lightspeed_core::web::WebAuthService;
+use maybe_once::tokio_shared;
+use std::sync::Arc;
+
+#[tokio_shared::test]
+async fn should_record_and_list_the_sessions() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+
+    let first_auth = login(auth_module, &user.data.username, &password).await?;
+    let second_auth = login(auth_module, &user.data.username, &password).await?;
+    assert_ne!(first_auth.session_id, second_auth.session_id);
+
+    let session = auth_module
+        .session_service
+        .create_session(&first_auth, Some("Firefox".to_owned()), Some("10.0.0.1".to_owned()))
+        .await?;
+    assert_eq!(user.id, session.data.user_id);
+    assert_eq!(first_auth.expiration_ts_seconds, session.data.expire_at_epoch_seconds);
+    assert_eq!(Some("Firefox".to_owned()), session.data.user_agent);
+    assert_eq!(Some("10.0.0.1".to_owned()), session.data.ip_address);
+    auth_module.session_service.create_session(&second_auth, None, None).await?;
+
+    let mut session_ids: Vec<String> = auth_module
+        .session_service
+        .fetch_active_sessions(user.id)
+        .await?
+        .into_iter()
+        .map(|session| session.data.session_id)
+        .collect();
+    session_ids.sort();
+    let mut expected = vec![first_auth.session_id.clone(), second_auth.session_id.clone()];
+    expected.sort();
+    assert_eq!(expected, session_ids);
+
+    auth_module.session_service.validate_session(&first_auth).await?;
+    auth_module.session_service.validate_session(&second_auth).await?;
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reject_unknown_and_expired_sessions() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let (other_user, _) = create_user_with_password(auth_module, &password, true).await?;
+
+    // Never recorded
+    let auth = login(auth_module, &user.data.username, &password).await?;
+    assert!(matches!(
+        auth_module.session_service.validate_session(&auth).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+
+    // Recorded for another user
+    let other_auth = login(auth_module, &other_user.data.username, &password).await?;
+    auth_module.session_service.create_session(&other_auth, None, None).await?;
+    let forged_auth = Auth { session_id: other_auth.session_id.clone(), ..auth.clone() };
+    assert!(matches!(
+        auth_module.session_service.validate_session(&forged_auth).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+
+    // Expired
+    let expired_auth = Auth { expiration_ts_seconds: current_epoch_seconds() - 1, ..auth.clone() };
+    auth_module.session_service.create_session(&expired_auth, None, None).await?;
+    assert!(matches!(
+        auth_module.session_service.validate_session(&expired_auth).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+    assert!(auth_module.session_service.fetch_active_sessions(user.id).await?.is_empty());
+
+    // The expired sessions are deleted by the next login
+    let new_auth = login(auth_module, &user.data.username, &password).await?;
+    auth_module.session_service.create_session(&new_auth, None, None).await?;
+    assert!(fetch_session(auth_module, &expired_auth.session_id).await?.is_none());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_update_the_last_activity() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let auth = login(auth_module, &user.data.username, &password).await?;
+    let mut session = auth_module.session_service.create_session(&auth, None, None).await?;
+
+    // Recent activity is not updated
+    auth_module.session_service.validate_session(&auth).await?;
+    let recent_activity = fetch_session(auth_module, &auth.session_id).await?.unwrap();
+    assert_eq!(session.data.last_activity_epoch_seconds, recent_activity.data.last_activity_epoch_seconds);
+
+    let update_seconds = auth_module.auth_config.session_activity_update_seconds as i64;
+    session.data.last_activity_epoch_seconds = current_epoch_seconds() - update_seconds - 1;
+    let session_repo = auth_module.repo_manager.session_repo();
+    let session =
+        auth_module.repo_manager.c3p0().transaction(async |conn| session_repo.update(conn, session).await).await?;
+
+    auth_module.session_service.validate_session(&auth).await?;
+    let updated = fetch_session(auth_module, &auth.session_id).await?.unwrap();
+    assert!(updated.data.last_activity_epoch_seconds > session.data.last_activity_epoch_seconds);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_revoke_the_sessions() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let (other_user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let web_auth_service = WebAuthService::new(
+        Arc::new(LsAuthService::new(InMemoryRolesProvider::new(vec![].into()))),
+        Arc::new(LsJwtService::new(&JwtConfig { secret: "secret".into(), ..Default::default() }).unwrap()),
+    );
+
+    let mut tokens = vec![];
+    for _ in 0..3 {
+        let auth = login(auth_module, &user.data.username, &password).await?;
+        auth_module.session_service.create_session(&auth, None, None).await?;
+        tokens.push((auth.clone(), web_auth_service.token_from_auth(&auth)?));
+    }
+    let other_auth = login(auth_module, &other_user.data.username, &password).await?;
+    auth_module.session_service.create_session(&other_auth, None, None).await?;
+
+    let (revoked_auth, revoked_token) = &tokens[0];
+    assert_eq!(
+        revoked_auth.session_id,
+        auth_module.session_service.auth_from_token_string(&web_auth_service, revoked_token).await?.session_id
+    );
+
+    // The sessions of other users cannot be revoked
+    assert!(matches!(
+        auth_module.session_service.revoke_session(user.id, &other_auth.session_id, None).await,
+        Err(LsAccountManagementError::SessionNotFound)
+    ));
+    auth_module.session_service.validate_session(&other_auth).await?;
+
+    auth_module.session_service.revoke_session(user.id, &revoked_auth.session_id, None).await?;
+    assert!(matches!(
+        auth_module.session_service.auth_from_token_string(&web_auth_service, revoked_token).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+    assert!(matches!(
+        auth_module.session_service.revoke_session(user.id, &revoked_auth.session_id, None).await,
+        Err(LsAccountManagementError::SessionNotFound)
+    ));
+    auth_module.session_service.auth_from_token_string(&web_auth_service, &tokens[1].1).await?;
+
+    // Log out everywhere
+    assert_eq!(2, auth_module.session_service.revoke_all_sessions(user.id, None).await?);
+    for (auth, _) in &tokens {
+        assert!(matches!(
+            auth_module.session_service.validate_session(auth).await,
+            Err(LsAccountManagementError::SessionNotValid)
+        ));
+    }
+    assert!(auth_module.session_service.fetch_active_sessions(user.id).await?.is_empty());
+    auth_module.session_service.validate_session(&other_auth).await?;
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reject_the_revoked_sessions_when_parsing_the_tokens() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let web_auth_service = WebAuthService::new(
+        Arc::new(LsAuthService::new(InMemoryRolesProvider::new(vec![].into()))),
+        Arc::new(LsJwtService::new(&JwtConfig { secret: "secret".into(), ..Default::default() }).unwrap()),
+    )
+    .with_session_validator(auth_module.session_service.clone());
+
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    let token = web_auth_service.token_from_auth(&auth)?;
+    assert_eq!(auth.session_id, web_auth_service.auth_from_token_string_with_session(&token).await?.auth.session_id);
+
+    auth_module.session_service.revoke_session(user.id, &auth.session_id, None).await?;
+    assert!(matches!(
+        web_auth_service.auth_from_token_string_with_session(&token).await,
+        Err(LsError::UnauthenticatedError)
+    ));
+    // Without the session check the token is accepted until it expires
+    assert_eq!(auth.session_id, web_auth_service.auth_from_token_string(&token)?.auth.session_id);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_revoke_the_sessions_when_the_account_is_disabled_or_its_password_reset()
+-> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let service = &auth_module.auth_account_service;
+    let password = new_hyphenated_uuid();
+
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    service.disable_by_user_id(user.id, None).await?;
+    assert_session_not_valid(auth_module, &auth).await;
+
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    service.lock_by_user_id(user.id, "fraud", None).await?;
+    assert_session_not_valid(auth_module, &auth).await;
+
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    service.suspend_by_user_id(user.id, current_epoch_seconds() + 3600, "spam", None).await?;
+    assert_session_not_valid(auth_module, &auth).await;
+
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    let (_, token) = service.generate_reset_password_token(&user.data.username).await?;
+    let new_password = new_hyphenated_uuid();
+    service
+        .reset_password_by_token(ResetPasswordDto {
+            token: token.data.token,
+            password: new_password.clone(),
+            password_confirm: new_password,
+        })
+        .await?;
+    assert_session_not_valid(auth_module, &auth).await;
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_revoke_the_other_sessions_when_the_password_is_changed() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let password = new_hyphenated_uuid();
+    let (user, _) = create_user_with_password(auth_module, &password, true).await?;
+    let current_auth = login_with_session(auth_module, &user.data.username, &password).await?;
+    let other_auth = login_with_session(auth_module, &user.data.username, &password).await?;
+
+    let new_password = new_hyphenated_uuid();
+    auth_module
+        .auth_account_service
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: password,
+                new_password: new_password.clone(),
+                new_password_confirm: new_password.clone(),
+            },
+            Some(&current_auth.session_id),
+        )
+        .await?;
+
+    auth_module.session_service.validate_session(&current_auth).await?;
+    assert_session_not_valid(auth_module, &other_auth).await;
+
+    // Without a current session, every session is revoked
+    let newest_password = new_hyphenated_uuid();
+    auth_module
+        .auth_account_service
+        .change_password(
+            ChangePasswordDto {
+                user_id: user.id,
+                old_password: new_password,
+                new_password: newest_password.clone(),
+                new_password_confirm: newest_password,
+            },
+            None,
+        )
+        .await?;
+    assert_session_not_valid(auth_module, &current_auth).await;
+
+    Ok(())
+}
+
+async fn assert_session_not_valid<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    auth: &Auth,
+) {
+    assert!(matches!(
+        auth_module.session_service.validate_session(auth).await,
+        Err(LsAccountManagementError::SessionNotValid)
+    ));
+}
+
+async fn login<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    username: &str,
+    password: &str,
+) -> Result<Auth, LsAccountManagementError> {
+    match auth_module.auth_account_service.login(username, password).await? {
+        LoginOutcome::Authenticated(auth) => Ok(auth),
+        _ => panic!("the login should not require MFA"),
+    }
+}
+
+async fn fetch_session<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    session_id: &str,
+) -> Result<Option<SessionModel>, LsAccountManagementError> {
+    let session_repo = auth_module.repo_manager.session_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| session_repo.fetch_by_session_id_optional(conn, session_id).await)
+        .await
+}
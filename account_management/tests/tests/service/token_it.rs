@@ -1,8 +1,12 @@
-use crate::data;
+use crate::{RepoManager, data};
 use c3p0::*;
+use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::AMConfig;
 use lightspeed_account_management::error::LsAccountManagementError;
 use lightspeed_account_management::model::token::{TokenData, TokenType};
-use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::repository::{AMRepositoryManager, TokenRepository};
+use lightspeed_account_management::service::token::LsTokenService;
+use lightspeed_core::error::LsError;
 use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
 use maybe_once::tokio_shared;
 
@@ -12,6 +16,7 @@ async fn should_delete_token() -> Result<(), LsAccountManagementError> {
     let auth_module = &data.0;
 
     let c3p0 = auth_module.repo_manager.c3p0();
+    let token_repo = auth_module.repo_manager.token_repo();
 
     let token = NewRecord {
         data: TokenData {
@@ -19,15 +24,16 @@ async fn should_delete_token() -> Result<(), LsAccountManagementError> {
             expire_at_epoch_seconds: 9999999999999,
             token_type: TokenType::ResetPassword,
             username: "test@test.com".to_owned(),
+            device_fingerprint: None,
         },
     };
 
     c3p0.transaction(async |conn| {
-        let saved_token = conn.save(token).await?;
+        let saved_token = token_repo.save(conn, token).await?;
 
-        assert!(conn.exists_by_id::<TokenData>(saved_token.id).await?);
+        assert!(token_repo.fetch_by_token(conn, &saved_token.data.token).await.is_ok());
         assert!(auth_module.token_service.delete_with_conn(conn, saved_token.clone()).await.is_ok());
-        assert!(!conn.exists_by_id::<TokenData>(saved_token.id).await?);
+        assert!(token_repo.fetch_by_token(conn, &saved_token.data.token).await.is_err());
 
         Ok(())
     })
@@ -70,26 +76,87 @@ async fn should_generate_token() -> Result<(), LsAccountManagementError> {
     .await
 }
 
+#[tokio_shared::test]
+async fn should_store_only_the_hash_of_the_token() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    let c3p0 = auth_module.repo_manager.c3p0();
+    let token_repo = auth_module.repo_manager.token_repo();
+    c3p0.transaction(async |conn| {
+        let token = auth_module
+            .token_service
+            .generate_and_save_token_with_conn(conn, new_hyphenated_uuid(), TokenType::ResetPassword)
+            .await?;
+        let token_hash = auth_module.token_service.hash_token(&token.data.token)?;
+        assert_ne!(token.data.token, token_hash);
+
+        assert!(token_repo.fetch_by_token(conn, &token.data.token).await.is_err());
+        let stored_token = token_repo.fetch_by_token(conn, &token_hash).await?;
+        assert_eq!(token.id, stored_token.id);
+        assert_eq!(token_hash, stored_token.data.token);
+
+        let fetched_token = auth_module.token_service.fetch_by_token_with_conn(conn, &token.data.token, true).await?;
+        assert_eq!(token.id, fetched_token.id);
+        assert!(matches!(
+            auth_module.token_service.fetch_by_token_with_conn(conn, &token_hash, true).await,
+            Err(LsAccountManagementError::TokenNotValid)
+        ));
+        Ok(())
+    })
+    .await
+}
+
+#[tokio_shared::test]
+async fn should_require_a_token_hash_secret() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+
+    for token_hash_secret in ["", "a_short_secret"] {
+        let auth_config = AMConfig { token_hash_secret: token_hash_secret.into(), ..auth_module.auth_config.clone() };
+        assert!(matches!(
+            LsAMModule::new(auth_module.repo_manager.clone(), auth_config.clone()),
+            Err(LsAccountManagementError::LsError { source: LsError::ConfigurationError { .. } })
+        ));
+
+        let token_service = LsTokenService::<RepoManager>::new(auth_config, auth_module.repo_manager.token_repo());
+        assert!(matches!(
+            token_service.hash_token("a_token"),
+            Err(LsAccountManagementError::LsError { source: LsError::ConfigurationError { .. } })
+        ));
+    }
+
+    Ok(())
+}
+
 #[tokio_shared::test]
 async fn should_validate_token_on_fetch() -> Result<(), LsAccountManagementError> {
     let data = data(false).await;
     let auth_module = &data.0;
 
     let c3p0 = auth_module.repo_manager.c3p0();
+    let token_repo = auth_module.repo_manager.token_repo();
 
+    let token_string = new_hyphenated_uuid();
+    let token_hash = auth_module.token_service.hash_token(&token_string)?;
     c3p0.transaction(async |conn| {
         let token = NewRecord {
             data: TokenData {
-                token: new_hyphenated_uuid(),
+                token: token_hash,
                 expire_at_epoch_seconds: current_epoch_seconds() - 1,
                 token_type: TokenType::ResetPassword,
                 username: "test@test.com".to_owned(),
+                device_fingerprint: None,
             },
         };
 
-        let saved_token = conn.save(token).await?;
+        token_repo.save(conn, token).await?;
 
-        assert!(auth_module.token_service.fetch_by_token_with_conn(conn, &saved_token.data.token, true).await.is_err());
+        assert!(matches!(
+            auth_module.token_service.fetch_by_token_with_conn(conn, &token_string, true).await,
+            Err(LsAccountManagementError::TokenExpired)
+        ));
+        assert!(auth_module.token_service.fetch_by_token_with_conn(conn, &token_string, false).await.is_ok());
 
         Ok(())
     })
@@ -102,41 +169,54 @@ async fn generate_and_save_token_should_lazily_delete_all_expired_tokens() -> Re
     let auth_module = &data.0;
     let token_service = &auth_module.token_service;
     let c3p0 = auth_module.repo_manager.c3p0();
+    let token_repo = auth_module.repo_manager.token_repo();
 
     c3p0.transaction::<_, LsAccountManagementError, _>(async |conn| {
         // Two stale tokens belonging to two different usernames — the sweep
         // must hit both, not just rows for the user we are minting next.
-        let stale_a = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: 0,
-                    token_type: TokenType::ResetPassword,
-                    username: new_hyphenated_uuid(),
+        let stale_a = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: 0,
+                        token_type: TokenType::ResetPassword,
+                        username: new_hyphenated_uuid(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
-        let stale_b = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: 0,
-                    token_type: TokenType::AccountActivation,
-                    username: new_hyphenated_uuid(),
+        let stale_b = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: 0,
+                        token_type: TokenType::AccountActivation,
+                        username: new_hyphenated_uuid(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
 
         // A non-expired token must survive the sweep.
-        let live = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: current_epoch_seconds() + 3600,
-                    token_type: TokenType::AccountActivation,
-                    username: new_hyphenated_uuid(),
+        let live = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: current_epoch_seconds() + 3600,
+                        token_type: TokenType::AccountActivation,
+                        username: new_hyphenated_uuid(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
 
         // Minting a new token for an unrelated user triggers the sweep.
@@ -144,10 +224,10 @@ async fn generate_and_save_token_should_lazily_delete_all_expired_tokens() -> Re
             .generate_and_save_token_with_conn(conn, new_hyphenated_uuid(), TokenType::AccountActivation)
             .await?;
 
-        assert!(!conn.exists_by_id::<TokenData>(stale_a.id).await?);
-        assert!(!conn.exists_by_id::<TokenData>(stale_b.id).await?);
-        assert!(conn.exists_by_id::<TokenData>(live.id).await?);
-        assert!(conn.exists_by_id::<TokenData>(fresh.id).await?);
+        assert!(token_repo.fetch_by_token(conn, &stale_a.data.token).await.is_err());
+        assert!(token_repo.fetch_by_token(conn, &stale_b.data.token).await.is_err());
+        assert!(token_repo.fetch_by_token(conn, &live.data.token).await.is_ok());
+        assert!(token_service.fetch_by_token_with_conn(conn, &fresh.data.token, true).await.is_ok());
 
         Ok(())
     })
@@ -160,48 +240,61 @@ async fn delete_expired_with_conn_should_remove_only_rows_below_threshold() -> R
     let auth_module = &data.0;
     let token_service = &auth_module.token_service;
     let c3p0 = auth_module.repo_manager.c3p0();
+    let token_repo = auth_module.repo_manager.token_repo();
 
     c3p0.transaction::<_, LsAccountManagementError, _>(async |conn| {
         let now = current_epoch_seconds();
 
-        let below = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: now - 100,
-                    token_type: TokenType::AccountActivation,
-                    username: new_hyphenated_uuid(),
+        let below = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: now - 100,
+                        token_type: TokenType::AccountActivation,
+                        username: new_hyphenated_uuid(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
-        let at_threshold = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: now,
-                    token_type: TokenType::AccountActivation,
-                    username: new_hyphenated_uuid(),
+        let at_threshold = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: now,
+                        token_type: TokenType::AccountActivation,
+                        username: new_hyphenated_uuid(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
-        let above = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: now + 100,
-                    token_type: TokenType::ResetPassword,
-                    username: new_hyphenated_uuid(),
+        let above = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: now + 100,
+                        token_type: TokenType::ResetPassword,
+                        username: new_hyphenated_uuid(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
 
         let deleted = token_service.delete_expired_with_conn(conn, now).await?;
         assert!(deleted >= 1);
 
         // Strictly less-than threshold: row at exactly `now` survives.
-        assert!(!conn.exists_by_id::<TokenData>(below.id).await?);
-        assert!(conn.exists_by_id::<TokenData>(at_threshold.id).await?);
-        assert!(conn.exists_by_id::<TokenData>(above.id).await?);
+        assert!(token_repo.fetch_by_token(conn, &below.data.token).await.is_err());
+        assert!(token_repo.fetch_by_token(conn, &at_threshold.data.token).await.is_ok());
+        assert!(token_repo.fetch_by_token(conn, &above.data.token).await.is_ok());
 
         Ok(())
     })
@@ -215,6 +308,7 @@ async fn should_return_all_tokens_by_username() -> Result<(), LsAccountManagemen
     let token_service = &auth_module.token_service;
 
     let c3p0 = auth_module.repo_manager.c3p0();
+    let token_repo = auth_module.repo_manager.token_repo();
 
     let username_1 = new_hyphenated_uuid();
     let username_2 = new_hyphenated_uuid();
@@ -223,28 +317,36 @@ async fn should_return_all_tokens_by_username() -> Result<(), LsAccountManagemen
         assert_eq!(0, token_service.fetch_all_by_username_with_conn(conn, &username_1).await?.len());
         assert_eq!(0, token_service.fetch_all_by_username_with_conn(conn, &username_2).await?.len());
 
-        let token_1 = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: current_epoch_seconds() - 1,
-                    token_type: TokenType::ResetPassword,
-                    username: username_1.clone(),
+        let token_1 = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: current_epoch_seconds() - 1,
+                        token_type: TokenType::ResetPassword,
+                        username: username_1.clone(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
 
         assert_eq!(1, token_service.fetch_all_by_username_with_conn(conn, &username_1).await?.len());
 
-        let token_2 = conn
-            .save(NewRecord {
-                data: TokenData {
-                    token: new_hyphenated_uuid(),
-                    expire_at_epoch_seconds: current_epoch_seconds() - 1,
-                    token_type: TokenType::AccountActivation,
-                    username: username_1.clone(),
+        let token_2 = token_repo
+            .save(
+                conn,
+                NewRecord {
+                    data: TokenData {
+                        token: new_hyphenated_uuid(),
+                        expire_at_epoch_seconds: current_epoch_seconds() - 1,
+                        token_type: TokenType::AccountActivation,
+                        username: username_1.clone(),
+                        device_fingerprint: None,
+                    },
                 },
-            })
+            )
             .await?;
 
         assert_eq!(0, token_service.fetch_all_by_username_with_conn(conn, &username_2).await?.len());
@@ -1,11 +1,18 @@
+use c3p0::*;
 use lightspeed_account_management::LsAMModule;
+use lightspeed_account_management::config::AMConfig;
 use lightspeed_account_management::dto::create_login_dto::CreateLoginDto;
 use lightspeed_account_management::error::LsAccountManagementError;
 use lightspeed_account_management::model::auth_account::AuthAccountModel;
-use lightspeed_account_management::model::token::TokenModel;
-use lightspeed_account_management::repository::AMRepositoryManager;
+use lightspeed_account_management::model::external_identity::{ExternalIdentityData, ExternalIdentityModel};
+use lightspeed_account_management::model::token::{TokenData, TokenModel};
+use lightspeed_account_management::repository::{AMRepositoryManager, ExternalIdentityRepository, TokenRepository};
+use lightspeed_account_management::service::account::{LoginOutcome, LsAMAccountService};
 use lightspeed_core::model::language::Language;
-use lightspeed_core::utils::new_hyphenated_uuid;
+use lightspeed_core::service::auth::Auth;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use lightspeed_event::model::{DomainEvent, OutboxEventStatus};
+use lightspeed_event::repository::OutboxRepository;
 use std::collections::HashMap;
 
 pub async fn create_user<RepoManager: AMRepositoryManager>(
@@ -43,3 +50,123 @@ pub async fn create_user_with_password<RepoManager: AMRepositoryManager>(
         Ok((user, token))
     }
 }
+
+/// Logs the user in and records the session of the login
+pub async fn login_with_session<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    username: &str,
+    password: &str,
+) -> Result<Auth, LsAccountManagementError> {
+    let auth = match auth_module.auth_account_service.login(username, password).await? {
+        LoginOutcome::Authenticated(auth) => auth,
+        _ => panic!("the login should not require MFA"),
+    };
+    auth_module.session_service.create_session(&auth, None, None).await?;
+    Ok(auth)
+}
+
+/// Links to the user an identity of an external provider, as the OIDC
+/// logins do
+pub async fn link_external_identity<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user_id: i64,
+) -> Result<ExternalIdentityModel, LsAccountManagementError> {
+    let external_identity_repo = auth_module.repo_manager.external_identity_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            external_identity_repo
+                .save(
+                    conn,
+                    NewRecord::new(ExternalIdentityData {
+                        user_id,
+                        issuer: "https://issuer.fake".to_owned(),
+                        subject: new_hyphenated_uuid(),
+                        email: None,
+                        created_date_epoch_seconds: current_epoch_seconds(),
+                        last_login_epoch_seconds: None,
+                    }),
+                )
+                .await
+        })
+        .await
+}
+
+/// Returns the external identities linked to the user
+pub async fn external_identities<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user_id: i64,
+) -> Result<Vec<ExternalIdentityModel>, LsAccountManagementError> {
+    let external_identity_repo = auth_module.repo_manager.external_identity_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| external_identity_repo.fetch_all_by_user_id(conn, user_id).await)
+        .await
+}
+
+/// Returns the pending events of type `E` published for the given user
+pub async fn pending_events<E: DomainEvent, RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    user_id: i64,
+) -> Result<Vec<E>, LsAccountManagementError> {
+    let outbox_repo = auth_module.repo_manager.outbox_repo();
+    let events = auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            Ok::<_, LsAccountManagementError>(
+                outbox_repo.fetch_all_by_status(conn, OutboxEventStatus::Pending, 0, u32::MAX).await?,
+            )
+        })
+        .await?;
+    Ok(events
+        .into_iter()
+        .filter(|event| event.data.event_type == E::EVENT_TYPE && event.data.payload["user_id"] == user_id)
+        .map(|event| serde_json::from_value(event.data.payload).unwrap())
+        .collect())
+}
+
+/// Build an `LsAMAccountService` that shares state with `auth_module` but
+/// uses a customized `AMConfig`. Used by tests that need to change the
+/// login settings without rebuilding the whole module.
+pub fn auth_account_service_with_config<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    auth_config: AMConfig,
+) -> LsAMAccountService<RepoManager> {
+    LsAMAccountService::new(
+        &auth_module.repo_manager,
+        auth_config,
+        auth_module.token_service.clone(),
+        auth_module.password_codec.clone(),
+        auth_module.audit_service.clone(),
+        auth_module.event_publisher.clone(),
+    )
+    .with_breached_password_checker(auth_module.breached_password_checker.clone())
+}
+
+/// Replaces the token with an expired copy, with the same token string.
+/// The returned model holds the token itself, as the one passed in.
+pub async fn expire_token<RepoManager: AMRepositoryManager>(
+    auth_module: &LsAMModule<RepoManager>,
+    token: TokenModel,
+) -> Result<TokenModel, LsAccountManagementError> {
+    let token_repo = auth_module.repo_manager.token_repo();
+    auth_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            let token = token_repo.delete(conn, token).await?;
+            let token_hash = auth_module.token_service.hash_token(&token.data.token)?;
+            let mut expired_token = token_repo
+                .save(
+                    conn,
+                    NewRecord::new(TokenData { token: token_hash, expire_at_epoch_seconds: 0, ..token.data.clone() }),
+                )
+                .await?;
+            expired_token.data.token = token.data.token;
+            Ok(expired_token)
+        })
+        .await
+}
@@ -0,0 +1,454 @@
+use crate::data;
+use crate::tests::util::{create_user, create_user_with_password, pending_events};
+use axum::Router;
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use http_body_util::BodyExt;
+use lightspeed_account_management::error::LsAccountManagementError;
+use lightspeed_account_management::model::event::{AccountCreated, PasswordResetRequested};
+use lightspeed_account_management::service::totp::LsTotpService;
+use lightspeed_account_management::web::axum::{AMRouterConfig, LsAMRouter};
+use lightspeed_core::config::JwtConfig;
+use lightspeed_core::service::auth::{InMemoryRolesProvider, LsAuthService};
+use lightspeed_core::service::jwt::LsJwtService;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use lightspeed_core::web::{JWT_TOKEN_HEADER, JWT_TOKEN_HEADER_SUFFIX, WebAuthService};
+use maybe_once::tokio_shared;
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const PASSWORD: &str = "Password_123!";
+
+fn new_router(auth_module: &crate::MaybeType, config: &AMRouterConfig) -> Router {
+    let web_auth_service = WebAuthService::new(
+        Arc::new(LsAuthService::new(InMemoryRolesProvider::new(vec![].into()))),
+        Arc::new(LsJwtService::new(&JwtConfig { secret: "secret".into(), ..Default::default() }).unwrap()),
+    );
+    auth_module.0.clone().router(web_auth_service, config)
+}
+
+async fn call(router: &Router, uri: &str, body: Value, token: Option<&str>) -> (StatusCode, Value) {
+    call_with_method(router, Method::POST, uri, body, token).await
+}
+
+async fn call_with_method(
+    router: &Router,
+    method: Method,
+    uri: &str,
+    body: Value,
+    token: Option<&str>,
+) -> (StatusCode, Value) {
+    let mut request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("user-agent", "router_it")
+        .header("x-forwarded-for", "10.0.0.1, 10.0.0.2");
+    if let Some(token) = token {
+        request = request.header(JWT_TOKEN_HEADER, format!("{JWT_TOKEN_HEADER_SUFFIX}{token}"));
+    }
+    let response = router.clone().oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap();
+    let status = response.status();
+    let bytes = response.into_body().collect().await.unwrap().to_bytes();
+    let body = if bytes.is_empty() { Value::Null } else { serde_json::from_slice(&bytes).unwrap() };
+    (status, body)
+}
+
+fn signup_body(username: &str, password: &str) -> Value {
+    json!({
+        "username": username,
+        "email": format!("{username}@email.fake"),
+        "password": password,
+        "password_confirm": password,
+        "language": "En",
+        "data": {},
+        "accept_privacy_policy": true,
+    })
+}
+
+#[tokio_shared::test]
+async fn should_signup_activate_and_login() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let username = new_hyphenated_uuid();
+
+    let (status, body) = call(&router, "/signup", signup_body(&username, PASSWORD), None).await;
+    assert_eq!(StatusCode::ACCEPTED, status);
+    assert_eq!(Value::Null, body);
+
+    let user = auth_module.auth_account_service.fetch_by_username(&username).await?;
+    let created = pending_events::<AccountCreated, _>(auth_module, user.id).await?;
+
+    let (status, body) = call(&router, "/login", json!({ "username": username, "password": PASSWORD }), None).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    assert_eq!("INACTIVE_USER", body["code"]);
+
+    let (status, _) = call(
+        &router,
+        "/activate",
+        json!({ "token": auth_module.token_service.open_token(&created[0].sealed_activation_token)? }),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::NO_CONTENT, status);
+
+    let (status, body) = call(&router, "/login", json!({ "username": username, "password": PASSWORD }), None).await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(json!(user.id), body["auth"]["id"]);
+    assert!(!body["token"]["token"].as_str().unwrap().is_empty());
+    assert!(body["token"]["expiration_epoch_seconds"].as_i64().unwrap() > 0);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reject_the_login_of_suspended_and_locked_accounts() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(auth_module, PASSWORD, true).await?;
+    let login_body = json!({ "username": user.data.username, "password": PASSWORD });
+
+    auth_module.auth_account_service.suspend_by_user_id(user.id, current_epoch_seconds() + 3600, "spam", None).await?;
+    let (status, body) = call(&router, "/login", login_body.clone(), None).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    assert_eq!("ACCOUNT_SUSPENDED", body["code"]);
+
+    auth_module.auth_account_service.lock_by_user_id(user.id, "fraud", None).await?;
+    let (status, body) = call(&router, "/login", login_body, None).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    assert_eq!("ACCOUNT_LOCKED_BY_ADMIN", body["code"]);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_reveal_registered_accounts() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(&data.0, PASSWORD, true).await?;
+
+    // Signup with a registered email
+    let mut body = signup_body(&new_hyphenated_uuid(), PASSWORD);
+    body["email"] = json!(user.data.email);
+    assert_eq!(StatusCode::ACCEPTED, call(&router, "/signup", body, None).await.0);
+
+    // Login with wrong password or unknown user
+    let wrong_password =
+        call(&router, "/login", json!({ "username": user.data.username, "password": "wrong" }), None).await;
+    let unknown_user =
+        call(&router, "/login", json!({ "username": new_hyphenated_uuid(), "password": PASSWORD }), None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, wrong_password.0);
+    assert_eq!(wrong_password, unknown_user);
+    assert_eq!(json!({ "code": "WRONG_CREDENTIALS" }), wrong_password.1);
+
+    // Password reset and activation requests
+    for email in [user.data.email.clone(), format!("{}@email.fake", new_hyphenated_uuid())] {
+        let (status, body) =
+            call(&router, "/password/reset/request", json!({ "email": email, "language": "En" }), None).await;
+        assert_eq!(StatusCode::ACCEPTED, status);
+        assert_eq!(Value::Null, body);
+
+        let (status, _) = call(
+            &router,
+            "/activation/resend",
+            json!({ "username": user.data.username, "email": email, "language": "En" }),
+            None,
+        )
+        .await;
+        assert_eq!(StatusCode::ACCEPTED, status);
+    }
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_reveal_locked_accounts() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(&data.0, PASSWORD, true).await?;
+    let max_attempts = data.0.auth_config.max_failed_login_attempts.unwrap();
+
+    for _ in 0..max_attempts {
+        call(&router, "/login", json!({ "username": user.data.username, "password": "wrong" }), None).await;
+    }
+
+    let locked_user =
+        call(&router, "/login", json!({ "username": user.data.username, "password": PASSWORD }), None).await;
+    let unknown_user =
+        call(&router, "/login", json!({ "username": new_hyphenated_uuid(), "password": PASSWORD }), None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, locked_user.0);
+    assert_eq!(unknown_user, locked_user);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_reveal_registered_usernames_on_signup() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(&data.0, PASSWORD, true).await?;
+
+    let mut registered_username = signup_body(&new_hyphenated_uuid(), PASSWORD);
+    registered_username["username"] = json!(user.data.username);
+    let registered_username = call(&router, "/signup", registered_username, None).await;
+    let new_username = call(&router, "/signup", signup_body(&new_hyphenated_uuid(), PASSWORD), None).await;
+
+    assert_eq!(StatusCode::ACCEPTED, registered_username.0);
+    assert_eq!(new_username, registered_username);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_return_validation_errors() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+
+    let mut body = signup_body(&new_hyphenated_uuid(), PASSWORD);
+    body["email"] = json!("not_an_email");
+    body["password_confirm"] = json!("other");
+
+    let (status, body) = call(&router, "/signup", body, None).await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!("VALIDATION_ERROR", body["code"]);
+    assert!(body["field_errors"]["email"].is_array());
+    assert!(body["field_errors"]["password_confirm"].is_array());
+    assert!(body["field_errors"].get("username").is_none());
+
+    let (status, body) = call(&router, "/login", json!({ "username": 1 }), None).await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!(json!({ "code": "INVALID_BODY" }), body);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_reset_password() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let auth_module = &data.0;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user(auth_module, true).await?;
+
+    let (status, _) =
+        call(&router, "/password/reset/request", json!({ "email": user.data.email, "language": "En" }), None).await;
+    assert_eq!(StatusCode::ACCEPTED, status);
+
+    let requested = pending_events::<PasswordResetRequested, _>(auth_module, user.id).await?;
+    let token = &auth_module.token_service.open_token(&requested[0].sealed_reset_token)?;
+
+    let (status, body) = call(
+        &router,
+        "/password/reset",
+        json!({ "token": token, "password": PASSWORD, "password_confirm": PASSWORD }),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::NO_CONTENT, status, "{body}");
+
+    let (status, body) = call(
+        &router,
+        "/password/reset",
+        json!({ "token": token, "password": PASSWORD, "password_confirm": PASSWORD }),
+        None,
+    )
+    .await;
+    assert_eq!(StatusCode::BAD_REQUEST, status);
+    assert_eq!("TOKEN_NOT_VALID", body["code"]);
+
+    let (status, _) =
+        call(&router, "/login", json!({ "username": user.data.username, "password": PASSWORD }), None).await;
+    assert_eq!(StatusCode::OK, status);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_change_password_only_of_the_authenticated_user() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(&data.0, PASSWORD, true).await?;
+    let (other_user, _) = create_user(&data.0, true).await?;
+
+    let (_, body) =
+        call(&router, "/login", json!({ "username": user.data.username, "password": PASSWORD }), None).await;
+    let token = body["token"]["token"].as_str().unwrap().to_owned();
+
+    let new_password = "New_Password_123!";
+    let change_password = |user_id: i64| {
+        json!({
+            "user_id": user_id,
+            "old_password": PASSWORD,
+            "new_password": new_password,
+            "new_password_confirm": new_password,
+        })
+    };
+
+    let (status, body) = call(&router, "/password/change", change_password(user.id), None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+    assert_eq!("UNAUTHORIZED", body["code"]);
+
+    let (status, body) = call(&router, "/password/change", change_password(other_user.id), Some(&token)).await;
+    assert_eq!(StatusCode::FORBIDDEN, status);
+    assert_eq!("FORBIDDEN", body["code"]);
+
+    let (status, _) = call(&router, "/password/change", change_password(user.id), Some(&token)).await;
+    assert_eq!(StatusCode::NO_CONTENT, status);
+    // The session that changed the password is kept
+    let (status, _) = call_with_method(&router, Method::GET, "/sessions", json!({}), Some(&token)).await;
+    assert_eq!(StatusCode::OK, status);
+
+    let (status, _) =
+        call(&router, "/login", json!({ "username": user.data.username, "password": new_password }), None).await;
+    assert_eq!(StatusCode::OK, status);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_login_with_the_second_factor() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(&data.0, PASSWORD, true).await?;
+    let login = json!({ "username": user.data.username, "password": PASSWORD });
+
+    let (_, body) = call(&router, "/login", login.clone(), None).await;
+    let token = body["token"]["token"].as_str().unwrap().to_owned();
+
+    let (status, enrollment) = call(&router, "/mfa/totp/enroll", json!({}), Some(&token)).await;
+    assert_eq!(StatusCode::OK, status);
+    let secret = enrollment["secret"].as_str().unwrap();
+
+    let code = LsTotpService::code_at(secret, current_epoch_seconds())?;
+    let (status, body) = call(&router, "/mfa/totp/confirm", json!({ "code": code }), Some(&token)).await;
+    assert_eq!(StatusCode::OK, status);
+    let recovery_code = body["recovery_codes"][0].as_str().unwrap().to_owned();
+
+    let (status, body) = call(&router, "/login", login.clone(), None).await;
+    assert_eq!(StatusCode::ACCEPTED, status);
+    assert!(body.get("auth").is_none());
+    let mfa_token = body["mfa_token"]["token"].as_str().unwrap().to_owned();
+
+    let (status, body) = call(&router, "/login/mfa", json!({ "mfa_token": mfa_token, "code": "x" }), None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+    assert_eq!("WRONG_CREDENTIALS", body["code"]);
+
+    let (_, body) = call(&router, "/login", login, None).await;
+    let mfa_token = body["mfa_token"]["token"].as_str().unwrap().to_owned();
+    let (status, body) =
+        call(&router, "/login/mfa", json!({ "mfa_token": mfa_token, "code": recovery_code }), None).await;
+    assert_eq!(StatusCode::OK, status);
+    assert_eq!(json!(user.id), body["auth"]["id"]);
+
+    let (status, _) = call(&router, "/mfa/totp/disable", json!({ "password": PASSWORD }), None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+    let (status, _) = call(&router, "/mfa/totp/disable", json!({ "password": PASSWORD }), Some(&token)).await;
+    assert_eq!(StatusCode::NO_CONTENT, status);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_list_and_revoke_the_sessions() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(&data, &AMRouterConfig::default());
+    let (user, _) = create_user_with_password(&data.0, PASSWORD, true).await?;
+    let login = json!({ "username": user.data.username, "password": PASSWORD });
+
+    let mut logins = vec![];
+    for _ in 0..3 {
+        let (_, body) = call(&router, "/login", login.clone(), None).await;
+        logins.push((
+            body["auth"]["session_id"].as_str().unwrap().to_owned(),
+            body["token"]["token"].as_str().unwrap().to_owned(),
+        ));
+    }
+    let (current_session_id, current_token) = &logins[0];
+
+    let (status, sessions) = call_with_method(&router, Method::GET, "/sessions", json!({}), None).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status, "{sessions}");
+
+    let (status, sessions) = call_with_method(&router, Method::GET, "/sessions", json!({}), Some(current_token)).await;
+    assert_eq!(StatusCode::OK, status);
+    let sessions = sessions.as_array().unwrap();
+    assert_eq!(3, sessions.len());
+    for session in sessions {
+        assert_eq!(json!(&session["session_id"] == current_session_id), session["current"]);
+        assert_eq!("router_it", session["user_agent"]);
+        assert_eq!("10.0.0.1", session["ip_address"]);
+    }
+
+    // Revoke a single session
+    let (revoked_session_id, revoked_token) = &logins[1];
+    let (status, _) = call_with_method(
+        &router,
+        Method::DELETE,
+        &format!("/sessions/{revoked_session_id}"),
+        json!({}),
+        Some(current_token),
+    )
+    .await;
+    assert_eq!(StatusCode::NO_CONTENT, status);
+
+    let (status, body) = call_with_method(&router, Method::GET, "/sessions", json!({}), Some(revoked_token)).await;
+    assert_eq!(StatusCode::UNAUTHORIZED, status);
+    assert_eq!("SESSION_NOT_VALID", body["code"]);
+
+    let (status, body) = call_with_method(
+        &router,
+        Method::DELETE,
+        &format!("/sessions/{revoked_session_id}"),
+        json!({}),
+        Some(current_token),
+    )
+    .await;
+    assert_eq!(StatusCode::NOT_FOUND, status);
+    assert_eq!("SESSION_NOT_FOUND", body["code"]);
+
+    // Log out everywhere
+    let (status, _) = call_with_method(&router, Method::DELETE, "/sessions", json!({}), Some(current_token)).await;
+    assert_eq!(StatusCode::NO_CONTENT, status);
+    for (_, token) in &logins {
+        let (status, body) = call_with_method(&router, Method::GET, "/sessions", json!({}), Some(token)).await;
+        assert_eq!(StatusCode::UNAUTHORIZED, status);
+        assert_eq!("SESSION_NOT_VALID", body["code"]);
+    }
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_expose_disabled_endpoints() -> Result<(), LsAccountManagementError> {
+    let data = data(false).await;
+    let router = new_router(
+        &data,
+        &AMRouterConfig {
+            signup_enabled: false,
+            password_reset_enabled: false,
+            password_change_enabled: false,
+            mfa_enabled: false,
+            sessions_enabled: false,
+        },
+    );
+
+    for uri in [
+        "/signup",
+        "/activation/resend",
+        "/password/reset/request",
+        "/password/reset",
+        "/password/change",
+        "/mfa/totp/enroll",
+        "/mfa/totp/confirm",
+        "/mfa/totp/disable",
+    ] {
+        assert_eq!(StatusCode::NOT_FOUND, call(&router, uri, json!({}), None).await.0);
+    }
+    for uri in ["/sessions", "/sessions/any"] {
+        assert_eq!(StatusCode::NOT_FOUND, call_with_method(&router, Method::DELETE, uri, json!({}), None).await.0);
+    }
+    assert_eq!(StatusCode::BAD_REQUEST, call(&router, "/login", json!({}), None).await.0);
+
+    Ok(())
+}
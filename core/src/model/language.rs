@@ -4,6 +4,7 @@ use std::str::FromStr;
 use strum::{AsRefStr, Display, EnumIter};
 
 #[derive(Clone, Debug, Display, EnumIter, AsRefStr, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub enum Language {
     De,
     En,
@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use crate::web::types::MaybeWeb;
 
 #[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RecordDto<Data: MaybeWeb> {
     pub id: i64,
     pub version: i64,
@@ -5,8 +5,10 @@ use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use strum::{AsRefStr, Display};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Auth {
     pub id: i64,
     pub username: String,
@@ -51,6 +53,60 @@ impl Owned for i64 {
     }
 }
 
+/// The level of access granted on a shared resource. Levels are ordered:
+/// `Admin` implies `Write`, which implies `Read`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, AsRefStr, Display)]
+pub enum AccessLevel {
+    Read,
+    Write,
+    Admin,
+}
+
+/// Who a resource is shared with. A `Group` grantee matches every user
+/// that has a role with the same name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", content = "id")]
+pub enum Grantee {
+    User(i64),
+    Group(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessEntry {
+    pub grantee: Grantee,
+    pub access_level: AccessLevel,
+}
+
+/// A resource that, beside its owner, can be shared with other users and
+/// groups.
+pub trait AccessControlled: Owned {
+    fn get_access_entries(&self) -> &[AccessEntry];
+}
+
+/// Pairs an `Owned` resource with the access entries stored for it.
+pub struct Shared<'a, T: Owned> {
+    pub resource: &'a T,
+    pub access_entries: Vec<AccessEntry>,
+}
+
+impl<'a, T: Owned> Shared<'a, T> {
+    pub fn new(resource: &'a T, access_entries: Vec<AccessEntry>) -> Self {
+        Self { resource, access_entries }
+    }
+}
+
+impl<T: Owned> Owned for Shared<'_, T> {
+    fn get_owner_id(&self) -> i64 {
+        self.resource.get_owner_id()
+    }
+}
+
+impl<T: Owned> AccessControlled for Shared<'_, T> {
+    fn get_access_entries(&self) -> &[AccessEntry] {
+        &self.access_entries
+    }
+}
+
 #[derive(Clone)]
 pub struct LsAuthService {
     permission_roles_map: BTreeMap<String, Vec<String>>,
@@ -207,6 +263,56 @@ impl AuthContext<'_> {
         }
     }
 
+    /// Succeeds if the user is the owner of the resource or if the resource
+    /// is shared with the user, or with one of the user's roles, with at least
+    /// the requested access level.
+    pub fn can<T: AccessControlled>(&self, access_level: AccessLevel, obj: &T) -> Result<&AuthContext<'_>, LsError> {
+        if self.can_bool(access_level, obj) {
+            Ok(self)
+        } else {
+            Err(LsError::ForbiddenError {
+                message: format!(
+                    "User [{:?}] is not the owner and has no [{}] access. Owner id: [{:?}]",
+                    self.auth.id,
+                    access_level,
+                    obj.get_owner_id()
+                ),
+            })
+        }
+    }
+
+    pub fn can_or_has_permission<T: AccessControlled>(
+        &self,
+        access_level: AccessLevel,
+        obj: &T,
+        permission: &str,
+    ) -> Result<&AuthContext<'_>, LsError> {
+        if self.can_bool(access_level, obj) || self.has_permission_bool(permission) {
+            Ok(self)
+        } else {
+            Err(LsError::ForbiddenError {
+                message: format!(
+                    "User [{:?}] is not the owner, has no [{}] access and does not have permission [{}]. Owner id: [{:?}]",
+                    self.auth.id,
+                    access_level,
+                    permission,
+                    obj.get_owner_id()
+                ),
+            })
+        }
+    }
+
+    fn can_bool<T: AccessControlled>(&self, access_level: AccessLevel, obj: &T) -> bool {
+        self.auth.id == obj.get_owner_id()
+            || obj.get_access_entries().iter().any(|entry| {
+                entry.access_level >= access_level
+                    && match &entry.grantee {
+                        Grantee::User(user_id) => *user_id == self.auth.id,
+                        Grantee::Group(group) => self.has_role_bool(group),
+                    }
+            })
+    }
+
     fn has_role_bool(&self, role: &str) -> bool {
         self.auth.roles.iter().any(|x| x == role)
     }
@@ -830,6 +936,74 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn should_be_allowed_if_the_owner_even_if_not_shared() {
+        let auth_service = super::LsAuthService::new(super::InMemoryRolesProvider::new(vec![].into()));
+        let auth_context = auth_service.auth(new_auth(0, vec![]));
+        let ownable = Ownable { owner_id: 0 };
+        assert!(auth_context.can(AccessLevel::Admin, &Shared::new(&ownable, vec![])).is_ok());
+    }
+
+    #[test]
+    fn should_be_allowed_if_shared_with_user_at_greater_or_equal_level() {
+        let auth_service = super::LsAuthService::new(super::InMemoryRolesProvider::new(vec![].into()));
+        let auth_context = auth_service.auth(new_auth(0, vec![]));
+        let ownable = Ownable { owner_id: 1 };
+        let shared =
+            Shared::new(&ownable, vec![AccessEntry { grantee: Grantee::User(0), access_level: AccessLevel::Write }]);
+        assert!(auth_context.can(AccessLevel::Read, &shared).is_ok());
+        assert!(auth_context.can(AccessLevel::Write, &shared).is_ok());
+        assert!(auth_context.can(AccessLevel::Admin, &shared).is_err());
+    }
+
+    #[test]
+    fn should_be_allowed_if_shared_with_a_group_of_the_user() {
+        let auth_service = super::LsAuthService::new(super::InMemoryRolesProvider::new(vec![].into()));
+        let auth_context = auth_service.auth(new_auth(0, vec!["EDITORS".to_string()]));
+        let ownable = Ownable { owner_id: 1 };
+        let shared = Shared::new(
+            &ownable,
+            vec![
+                AccessEntry { grantee: Grantee::Group("READERS".to_owned()), access_level: AccessLevel::Admin },
+                AccessEntry { grantee: Grantee::Group("EDITORS".to_owned()), access_level: AccessLevel::Write },
+            ],
+        );
+        assert!(auth_context.can(AccessLevel::Write, &shared).is_ok());
+        assert!(auth_context.can(AccessLevel::Admin, &shared).is_err());
+    }
+
+    #[test]
+    fn should_not_be_allowed_if_shared_with_another_user() {
+        let auth_service = super::LsAuthService::new(super::InMemoryRolesProvider::new(vec![].into()));
+        let auth_context = auth_service.auth(new_auth(0, vec![]));
+        let ownable = Ownable { owner_id: 1 };
+        let shared =
+            Shared::new(&ownable, vec![AccessEntry { grantee: Grantee::User(2), access_level: AccessLevel::Admin }]);
+        assert!(auth_context.can(AccessLevel::Read, &shared).is_err());
+    }
+
+    #[test]
+    fn should_be_allowed_if_not_shared_but_has_permission() {
+        let roles = vec![Role { name: "ROLE_1".to_string(), permissions: vec!["access_1".to_string()] }];
+        let auth_service = super::LsAuthService::new(super::InMemoryRolesProvider::new(roles.into()));
+        let auth_context = auth_service.auth(new_auth(0, vec!["ROLE_1".to_string()]));
+        let ownable = Ownable { owner_id: 1 };
+        let shared = Shared::new(&ownable, vec![]);
+        assert!(auth_context.can_or_has_permission(AccessLevel::Write, &shared, "access_1").is_ok());
+        assert!(auth_context.can_or_has_permission(AccessLevel::Write, &shared, "access_2").is_err());
+    }
+
+    fn new_auth(id: i64, roles: Vec<String>) -> Auth {
+        Auth {
+            id,
+            username: "name".to_string(),
+            session_id: "".to_string(),
+            roles,
+            creation_ts_seconds: 0,
+            expiration_ts_seconds: current_epoch_seconds() + 100,
+        }
+    }
+
     struct Ownable {
         owner_id: i64,
     }
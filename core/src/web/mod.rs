@@ -3,10 +3,16 @@ use crate::service::auth::{Auth, AuthContext, LsAuthService};
 use crate::service::jwt::LsJwtService;
 use ::http::{HeaderMap, Request};
 use log::*;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
 
 #[cfg(feature = "axum")]
 pub mod axum;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+#[cfg(feature = "tonic")]
+pub mod tonic;
 pub mod types;
 
 pub const JWT_TOKEN_HEADER: &str = "Authorization";
@@ -30,15 +36,32 @@ impl<T> Headers for Request<T> {
     }
 }
 
+/// Checks the session of the `Auth` parsed from a token, so that the tokens
+/// of the revoked sessions are rejected even if they are not expired yet.
+pub trait SessionValidator: Send + Sync {
+    /// Fails with `UnauthenticatedError` if the session of `auth` is not valid
+    fn validate_session<'a>(&'a self, auth: &'a Auth)
+    -> Pin<Box<dyn Future<Output = Result<(), LsError>> + Send + 'a>>;
+}
+
 #[derive(Clone)]
 pub struct WebAuthService {
     auth_service: Arc<LsAuthService>,
     jwt_service: Arc<LsJwtService>,
+    session_validator: Option<Arc<dyn SessionValidator>>,
 }
 
 impl WebAuthService {
     pub fn new(auth_service: Arc<LsAuthService>, jwt_service: Arc<LsJwtService>) -> Self {
-        Self { auth_service, jwt_service }
+        Self { auth_service, jwt_service, session_validator: None }
+    }
+
+    /// Checks the session of every token parsed by
+    /// `auth_from_request_with_session` and
+    /// `auth_from_token_string_with_session` with `session_validator`
+    pub fn with_session_validator(mut self, session_validator: Arc<dyn SessionValidator>) -> Self {
+        self.session_validator = Some(session_validator);
+        self
     }
 
     pub fn token_string_from_request<'a, H: Headers>(&self, req: &'a H) -> Result<&'a str, LsError> {
@@ -60,6 +83,13 @@ impl WebAuthService {
         Ok(self.jwt_service.generate_from_payload(auth)?.1)
     }
 
+    /// Returns the token for the given auth together with its expiration
+    /// epoch seconds.
+    pub fn token_and_expiration_from_auth(&self, auth: &Auth) -> Result<(String, i64), LsError> {
+        let (jwt, token) = self.jwt_service.generate_from_payload(auth)?;
+        Ok((token, jwt.exp))
+    }
+
     pub fn auth_from_request<H: Headers>(&self, req: &H) -> Result<AuthContext<'_>, LsError> {
         self.token_string_from_request(req).and_then(|token| self.auth_from_token_string(token))
     }
@@ -69,4 +99,27 @@ impl WebAuthService {
         trace!("Auth built from request: [{auth:?}]");
         Ok(self.auth_service.auth(auth?))
     }
+
+    /// Same as `auth_from_request`, but it also checks the session of the
+    /// token if a `SessionValidator` is set
+    pub async fn auth_from_request_with_session<H: Headers>(&self, req: &H) -> Result<AuthContext<'_>, LsError> {
+        let token = self.token_string_from_request(req)?;
+        self.auth_from_token_string_with_session(token).await
+    }
+
+    /// Same as `auth_from_token_string`, but it also checks the session of
+    /// the token if a `SessionValidator` is set
+    pub async fn auth_from_token_string_with_session(&self, token: &str) -> Result<AuthContext<'_>, LsError> {
+        let auth_context = self.auth_from_token_string(token)?;
+        self.validate_session(&auth_context.auth).await?;
+        Ok(auth_context)
+    }
+
+    /// Checks the session of `auth` if a `SessionValidator` is set
+    pub(crate) async fn validate_session(&self, auth: &Auth) -> Result<(), LsError> {
+        match &self.session_validator {
+            Some(session_validator) => session_validator.validate_session(auth).await,
+            None => Ok(()),
+        }
+    }
 }
@@ -0,0 +1,78 @@
+use crate::model::language::Language;
+use crate::service::auth::Auth;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::{ComponentsBuilder, Info, OpenApi, OpenApiBuilder, Paths};
+use utoipa::{PartialSchema, ToSchema};
+
+/// Name of the HTTP bearer (JWT) security scheme registered by [`build_openapi`].
+/// Operations protected by `WebAuthService` reference it in their `security`
+/// requirement.
+pub const BEARER_AUTH_SECURITY_SCHEME: &str = "bearer_auth";
+
+/// Implemented by the lightspeed modules that expose DTOs or routes. The
+/// returned document is a fragment (components and, when the module ships a
+/// router, paths); use [`build_openapi`] to merge fragments into a full spec.
+pub trait LsOpenApi {
+    fn openapi() -> OpenApi;
+}
+
+/// OpenAPI fragment for the core types shared by every module.
+pub struct LsCoreOpenApi;
+
+impl LsOpenApi for LsCoreOpenApi {
+    fn openapi() -> OpenApi {
+        OpenApiBuilder::new()
+            .paths(Paths::new())
+            .components(Some(
+                ComponentsBuilder::new()
+                    .schema(Auth::name(), Auth::schema())
+                    .schema(Language::name(), Language::schema())
+                    .build(),
+            ))
+            .build()
+    }
+}
+
+/// Assembles a full OpenAPI 3.1 document from the given fragments. Core
+/// schemas and the [`BEARER_AUTH_SECURITY_SCHEME`] are always included.
+///
+/// ```ignore
+/// let spec = build_openapi("my-app", "1.0.0", [LsAMOpenApi::openapi(), my_routes_openapi()]);
+/// ```
+pub fn build_openapi<I: IntoIterator<Item = OpenApi>>(title: &str, version: &str, parts: I) -> OpenApi {
+    let mut openapi = OpenApiBuilder::new().info(Info::new(title, version)).paths(Paths::new()).build();
+    openapi.merge(LsCoreOpenApi::openapi());
+    for part in parts {
+        openapi.merge(part);
+    }
+    openapi.components.get_or_insert_with(Default::default).add_security_scheme(
+        BEARER_AUTH_SECURITY_SCHEME,
+        SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).bearer_format("JWT").build()),
+    );
+    openapi
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn should_build_spec_with_core_schemas_and_bearer_auth() {
+        let fragment = OpenApiBuilder::new()
+            .components(Some(ComponentsBuilder::new().schema("Other", Auth::schema()).build()))
+            .build();
+
+        let spec = build_openapi("test", "1.2.3", [fragment]);
+        let json = serde_json::to_value(&spec).unwrap();
+
+        assert_eq!("3.1.0", json["openapi"]);
+        assert_eq!("test", json["info"]["title"]);
+        assert_eq!("1.2.3", json["info"]["version"]);
+        assert!(json["components"]["schemas"]["Auth"].is_object());
+        assert!(json["components"]["schemas"]["Language"].is_object());
+        assert!(json["components"]["schemas"]["Other"].is_object());
+        assert_eq!("bearer", json["components"]["securitySchemes"][BEARER_AUTH_SECURITY_SCHEME]["scheme"]);
+        assert_eq!("JWT", json["components"]["securitySchemes"][BEARER_AUTH_SECURITY_SCHEME]["bearerFormat"]);
+    }
+}
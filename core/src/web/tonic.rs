@@ -0,0 +1,414 @@
+use crate::error::LsError;
+use crate::service::auth::{Auth, AuthContext};
+use crate::web::{Headers, WebAuthService};
+use ::tonic::metadata::MetadataMap;
+use ::tonic::server::NamedService;
+use ::tonic::service::Interceptor;
+use ::tonic::{Code, Request, Status};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+
+impl Headers for MetadataMap {
+    fn get_as_str(&self, header_name: &str) -> Option<Result<&str, LsError>> {
+        self.get(header_name)
+            .map(|header| header.to_str().map_err(|err| LsError::ParseAuthHeaderError { message: format!("{err:?}") }))
+    }
+}
+
+impl<T> Headers for Request<T> {
+    fn get_as_str(&self, header_name: &str) -> Option<Result<&str, LsError>> {
+        self.metadata().get_as_str(header_name)
+    }
+}
+
+/// Maps an `LsError` to the gRPC status with the same meaning as the HTTP
+/// status returned by the axum integration. As for axum, no error detail is
+/// sent to the client.
+impl From<LsError> for Status {
+    fn from(err: LsError) -> Self {
+        let code = match err {
+            LsError::InvalidTokenError { .. }
+            | LsError::ExpiredTokenError { .. }
+            | LsError::GenerateTokenError { .. }
+            | LsError::MissingAuthTokenError
+            | LsError::ParseAuthHeaderError { .. }
+            | LsError::UnauthenticatedError => Code::Unauthenticated,
+            LsError::ForbiddenError { .. } => Code::PermissionDenied,
+
+            LsError::BadRequest { .. } => Code::InvalidArgument,
+            LsError::C3p0Error { .. } => Code::InvalidArgument,
+            LsError::SqlxError { .. } => Code::InvalidArgument,
+            LsError::ModuleStartError { .. } | LsError::ConfigurationError { .. } => Code::Internal,
+        };
+        Status::new(code, "")
+    }
+}
+
+/// A tonic [`Interceptor`] that validates the bearer token sent in the
+/// `authorization` metadata and inserts the resulting [`Auth`] into the
+/// request extensions. Requests without a valid token are rejected with
+/// `Unauthenticated`.
+///
+/// **The interceptor does not check the session of the token**: the
+/// `Interceptor` trait is synchronous, so the tokens of the revoked sessions
+/// are accepted until they expire. Use [`AuthLayer`] to also reject them.
+///
+/// ```ignore
+/// let interceptor = AuthInterceptor::new(web_auth_service);
+/// Server::builder().add_service(MyServiceServer::with_interceptor(my_service, interceptor.clone()));
+///
+/// // then, in the handler:
+/// let auth_context = interceptor.auth_from_extensions(&request)?;
+/// auth_context.has_role("admin")?;
+/// ```
+#[derive(Clone)]
+pub struct AuthInterceptor {
+    web_auth_service: WebAuthService,
+}
+
+impl AuthInterceptor {
+    pub fn new(web_auth_service: WebAuthService) -> Self {
+        Self { web_auth_service }
+    }
+
+    /// Builds the `AuthContext` from the [`Auth`] inserted by the interceptor.
+    /// Fails with `Unauthenticated` if the request was not intercepted.
+    pub fn auth_from_extensions<T>(&self, req: &Request<T>) -> Result<AuthContext<'_>, Status> {
+        let auth = req.extensions().get::<Auth>().ok_or(LsError::UnauthenticatedError)?;
+        Ok(self.web_auth_service.auth_service.auth(auth.clone()))
+    }
+}
+
+impl Interceptor for AuthInterceptor {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        let auth = self.web_auth_service.auth_from_request(req.metadata())?.auth;
+        req.extensions_mut().insert(auth);
+        Ok(req)
+    }
+}
+
+/// A tower [`Layer`] that authenticates the requests before they reach the
+/// wrapped gRPC service. As [`AuthInterceptor`], it validates the bearer
+/// token and inserts the resulting [`Auth`] into the request extensions; it
+/// also checks the session of the token with the `SessionValidator` of the
+/// `WebAuthService`, if one is set, so that the tokens of the revoked
+/// sessions are rejected with `Unauthenticated` before the handler runs.
+///
+/// ```ignore
+/// let auth_layer = AuthLayer::new(web_auth_service);
+/// Server::builder().add_service(auth_layer.layer(MyServiceServer::new(my_service)));
+///
+/// // then, in the handler:
+/// let auth_context = auth_layer.auth_from_extensions(&request)?;
+/// auth_context.has_role("admin")?;
+/// ```
+#[derive(Clone)]
+pub struct AuthLayer {
+    web_auth_service: WebAuthService,
+}
+
+impl AuthLayer {
+    pub fn new(web_auth_service: WebAuthService) -> Self {
+        Self { web_auth_service }
+    }
+
+    /// Builds the `AuthContext` from the [`Auth`] inserted by the service.
+    /// Fails with `Unauthenticated` if the request did not go through it.
+    pub fn auth_from_extensions<T>(&self, req: &Request<T>) -> Result<AuthContext<'_>, Status> {
+        let auth = req.extensions().get::<Auth>().ok_or(LsError::UnauthenticatedError)?;
+        Ok(self.web_auth_service.auth_service.auth(auth.clone()))
+    }
+}
+
+impl<S> Layer<S> for AuthLayer {
+    type Service = AuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthService { inner, web_auth_service: self.web_auth_service.clone() }
+    }
+}
+
+/// The service built by [`AuthLayer`]
+#[derive(Clone)]
+pub struct AuthService<S> {
+    inner: S,
+    web_auth_service: WebAuthService,
+}
+
+impl<S, ReqBody, ResBody> Service<::http::Request<ReqBody>> for AuthService<S>
+where
+    S: Service<::http::Request<ReqBody>, Response = ::http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ReqBody: Send + 'static,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: ::http::Request<ReqBody>) -> Self::Future {
+        // The service that was polled ready is the one to call
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let web_auth_service = self.web_auth_service.clone();
+        Box::pin(async move {
+            let auth = web_auth_service.auth_from_request_with_session(req.headers()).await.map(|context| context.auth);
+            match auth {
+                Ok(auth) => {
+                    req.extensions_mut().insert(auth);
+                    inner.call(req).await
+                }
+                Err(err) => Ok(Status::from(err).into_http()),
+            }
+        })
+    }
+}
+
+impl<S: NamedService> NamedService for AuthService<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use crate::config::JwtConfig;
+    use crate::service::auth::{InMemoryRolesProvider, LsAuthService, Role};
+    use crate::service::jwt::{JWT, LsJwtService};
+    use crate::web::{JWT_TOKEN_HEADER, JWT_TOKEN_HEADER_SUFFIX, SessionValidator};
+    use jsonwebtoken::Algorithm;
+    use std::sync::Arc;
+
+    #[test]
+    fn metadata_map_should_return_header_ignoring_case() {
+        // Arrange
+        let mut metadata = MetadataMap::new();
+        metadata.insert("authorization", "Bearer abc".parse().unwrap());
+
+        // Act
+        let header = metadata.get_as_str(JWT_TOKEN_HEADER);
+
+        // Assert
+        assert_eq!("Bearer abc", header.unwrap().unwrap());
+        assert!(metadata.get_as_str("missing").is_none());
+    }
+
+    #[test]
+    fn interceptor_should_return_unauthenticated_if_no_token() {
+        // Arrange
+        let mut interceptor = AuthInterceptor::new(new_service());
+
+        // Act
+        let status = interceptor.call(Request::new(())).unwrap_err();
+
+        // Assert
+        assert_eq!(Code::Unauthenticated, status.code());
+    }
+
+    #[test]
+    fn interceptor_should_return_unauthenticated_if_expired_token() {
+        // Arrange
+        let token = JWT { payload: new_auth(), exp: 0, iat: 0, sub: "".to_owned() };
+        let token = new_service().jwt_service.generate_from_token(&token).unwrap();
+        let mut interceptor = AuthInterceptor::new(new_service());
+
+        // Act
+        let status = interceptor.call(request_with_token(&token)).unwrap_err();
+
+        // Assert
+        assert_eq!(Code::Unauthenticated, status.code());
+    }
+
+    #[test]
+    fn interceptor_should_insert_auth_in_extensions_if_valid_token() {
+        // Arrange
+        let service = new_service();
+        let token = service.token_from_auth(&new_auth()).unwrap();
+        let mut interceptor = AuthInterceptor::new(service);
+
+        // Act
+        let req = interceptor.call(request_with_token(&token)).unwrap();
+
+        // Assert
+        assert_eq!("Amelia", req.extensions().get::<Auth>().unwrap().username);
+        let auth_context = interceptor.auth_from_extensions(&req).unwrap();
+        assert_eq!(100, auth_context.auth.id);
+    }
+
+    #[test]
+    fn auth_from_extensions_should_return_unauthenticated_if_not_intercepted() {
+        // Arrange
+        let interceptor = AuthInterceptor::new(new_service());
+
+        // Act
+        let status = interceptor.auth_from_extensions(&Request::new(())).err().unwrap();
+
+        // Assert
+        assert_eq!(Code::Unauthenticated, status.code());
+    }
+
+    #[test]
+    fn missing_role_should_map_to_permission_denied() {
+        // Arrange
+        let service = new_service();
+        let token = service.token_from_auth(&new_auth()).unwrap();
+        let mut interceptor = AuthInterceptor::new(service);
+        let req = interceptor.call(request_with_token(&token)).unwrap();
+
+        // Act
+        let auth_context = interceptor.auth_from_extensions(&req).unwrap();
+        let status: Status = auth_context.has_role("admin").err().unwrap().into();
+
+        // Assert
+        assert_eq!(Code::PermissionDenied, status.code());
+    }
+
+    #[test]
+    fn ls_errors_should_map_to_grpc_codes() {
+        assert_eq!(Code::Unauthenticated, Status::from(LsError::MissingAuthTokenError).code());
+        assert_eq!(Code::Unauthenticated, Status::from(LsError::UnauthenticatedError).code());
+        assert_eq!(Code::PermissionDenied, Status::from(LsError::ForbiddenError { message: "".to_owned() }).code());
+        assert_eq!(
+            Code::InvalidArgument,
+            Status::from(LsError::BadRequest { message: "".to_owned(), code: "" }).code()
+        );
+        assert_eq!(Code::Internal, Status::from(LsError::ConfigurationError { message: "".to_owned() }).code());
+    }
+
+    #[tokio::test]
+    async fn auth_layer_should_insert_auth_in_extensions_if_valid_token() {
+        // Arrange
+        let service = new_service().with_session_validator(Arc::new(RevokedSessions(vec![])));
+        let token = service.token_from_auth(&new_auth()).unwrap();
+        let mut auth_service = AuthLayer::new(service).layer(EchoUsername);
+
+        // Act
+        let response = auth_service.call(http_request_with_token(Some(&token))).await.unwrap();
+
+        // Assert
+        assert!(Status::from_header_map(response.headers()).is_none());
+        assert_eq!("Amelia", response.body());
+    }
+
+    #[tokio::test]
+    async fn auth_layer_should_return_unauthenticated_if_no_token() {
+        // Arrange
+        let mut auth_service = AuthLayer::new(new_service()).layer(EchoUsername);
+
+        // Act
+        let response = auth_service.call(http_request_with_token(None)).await.unwrap();
+
+        // Assert
+        assert_eq!(Code::Unauthenticated, Status::from_header_map(response.headers()).unwrap().code());
+        assert_eq!("", response.body());
+    }
+
+    #[tokio::test]
+    async fn auth_layer_should_return_unauthenticated_if_session_not_valid() {
+        // Arrange
+        let service = new_service().with_session_validator(Arc::new(RevokedSessions(vec!["a_0".to_owned()])));
+        let token = service.token_from_auth(&new_auth()).unwrap();
+        let mut auth_service = AuthLayer::new(service).layer(EchoUsername);
+
+        // Act
+        let response = auth_service.call(http_request_with_token(Some(&token))).await.unwrap();
+
+        // Assert
+        assert_eq!(Code::Unauthenticated, Status::from_header_map(response.headers()).unwrap().code());
+        assert_eq!("", response.body());
+    }
+
+    #[test]
+    fn auth_layer_should_build_the_auth_context_from_extensions() {
+        // Arrange
+        let auth_layer = AuthLayer::new(new_service());
+        let mut req = Request::new(());
+        req.extensions_mut().insert(new_auth());
+
+        // Act
+        let auth_context = auth_layer.auth_from_extensions(&req).unwrap();
+
+        // Assert
+        assert_eq!(100, auth_context.auth.id);
+        assert_eq!(Code::Unauthenticated, auth_layer.auth_from_extensions(&Request::new(())).err().unwrap().code());
+    }
+
+    struct RevokedSessions(Vec<String>);
+
+    impl SessionValidator for RevokedSessions {
+        fn validate_session<'a>(
+            &'a self,
+            auth: &'a Auth,
+        ) -> Pin<Box<dyn Future<Output = Result<(), LsError>> + Send + 'a>> {
+            Box::pin(async move {
+                if self.0.contains(&auth.session_id) { Err(LsError::UnauthenticatedError) } else { Ok(()) }
+            })
+        }
+    }
+
+    /// Answers with the username of the `Auth` in the request extensions
+    #[derive(Clone)]
+    struct EchoUsername;
+
+    impl Service<::http::Request<()>> for EchoUsername {
+        type Response = ::http::Response<String>;
+        type Error = Status;
+        type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: ::http::Request<()>) -> Self::Future {
+            let username = req.extensions().get::<Auth>().map(|auth| auth.username.clone()).unwrap_or_default();
+            Box::pin(async move { Ok(::http::Response::new(username)) })
+        }
+    }
+
+    fn http_request_with_token(token: Option<&str>) -> ::http::Request<()> {
+        let mut req = ::http::Request::new(());
+        if let Some(token) = token {
+            req.headers_mut().insert("authorization", format!("{JWT_TOKEN_HEADER_SUFFIX}{token}").parse().unwrap());
+        }
+        req
+    }
+
+    fn request_with_token(token: &str) -> Request<()> {
+        let mut req = Request::new(());
+        req.metadata_mut().insert("authorization", format!("{JWT_TOKEN_HEADER_SUFFIX}{token}").parse().unwrap());
+        req
+    }
+
+    fn new_auth() -> Auth {
+        Auth {
+            username: "Amelia".to_owned(),
+            id: 100,
+            session_id: "a_0".to_owned(),
+            roles: vec![],
+            creation_ts_seconds: 0,
+            expiration_ts_seconds: i64::MAX,
+        }
+    }
+
+    fn new_service() -> WebAuthService {
+        WebAuthService::new(
+            Arc::new(LsAuthService::new(InMemoryRolesProvider::new(
+                vec![Role { name: "admin".to_owned(), permissions: vec![] }].into(),
+            ))),
+            Arc::new(
+                LsJwtService::new(&JwtConfig {
+                    secret: "secret".into(),
+                    signature_algorithm: Algorithm::HS256,
+                    token_validity_minutes: 10,
+                })
+                .unwrap(),
+            ),
+        )
+    }
+}
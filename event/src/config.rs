@@ -0,0 +1,47 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct EventConfig {
+    /// Maximum number of delivery attempts of an event.
+    /// Once reached, the event is moved to the dead letters.
+    pub max_attempts: u32,
+
+    /// Delay, in seconds, before the first retry of a failed delivery.
+    /// The delay doubles at every further attempt.
+    pub retry_base_delay_seconds: u32,
+
+    /// Upper bound, in seconds, of the delay between two attempts
+    pub retry_max_delay_seconds: u32,
+
+    /// Maximum number of events delivered by a single dispatch run
+    pub dispatch_batch_size: u32,
+
+    /// Seconds for which an event being delivered is hidden from other
+    /// dispatchers. Should be longer than the slowest handler.
+    pub dispatch_lease_seconds: u32,
+}
+
+impl Default for EventConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            retry_base_delay_seconds: 10,
+            retry_max_delay_seconds: 3600,
+            dispatch_batch_size: 100,
+            dispatch_lease_seconds: 300,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn should_build_config() {
+        let config: EventConfig = config::Config::builder().build().unwrap().try_deserialize().unwrap();
+        assert_eq!(10, config.max_attempts);
+    }
+}
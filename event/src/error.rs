@@ -0,0 +1,26 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LsEventError {
+    #[error("SerializationError: {message}")]
+    SerializationError { message: String },
+
+    #[error("HandlerError: {message}")]
+    HandlerError { message: String },
+
+    /// The event misses the fields scrubbed from its payload
+    #[error("ScrubbedEventError: {message}")]
+    ScrubbedEventError { message: String },
+
+    #[error("C3p0Error: {source:?}")]
+    C3p0Error {
+        #[from]
+        source: c3p0::error::C3p0Error,
+    },
+
+    #[error("SqlxError: {source:?}")]
+    SqlxError {
+        #[from]
+        source: c3p0::sqlx::Error,
+    },
+}
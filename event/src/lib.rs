@@ -0,0 +1,53 @@
+#![doc = include_str!("../README.md")]
+// No `unsafe` in this crate.
+#![forbid(unsafe_code)]
+// `.unwrap()` and `.expect()` are banned in production code.
+#![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
+
+use crate::config::EventConfig;
+use crate::repository::EventRepositoryManager;
+use crate::service::dispatcher::LsEventDispatcher;
+use crate::service::publisher::LsEventPublisher;
+use lightspeed_core::error::LsError;
+use log::*;
+use std::sync::Arc;
+
+pub mod config;
+pub mod error;
+pub mod model;
+pub mod repository;
+pub mod service;
+
+#[derive(Clone)]
+pub struct LsEventModule<RepoManager: EventRepositoryManager> {
+    pub event_config: EventConfig,
+
+    pub repo_manager: RepoManager,
+
+    pub publisher: Arc<service::publisher::LsEventPublisher<RepoManager::OutboxRepo>>,
+    pub dispatcher: Arc<service::dispatcher::LsEventDispatcher<RepoManager>>,
+}
+
+impl<RepoManager: EventRepositoryManager> LsEventModule<RepoManager> {
+    pub fn new(repo_manager: RepoManager, event_config: EventConfig) -> Self {
+        println!("Creating LsEventModule");
+        info!("Creating LsEventModule");
+
+        let publisher = Arc::new(LsEventPublisher::new(repo_manager.outbox_repo()));
+        let dispatcher = Arc::new(LsEventDispatcher::new(
+            repo_manager.c3p0().clone(),
+            repo_manager.outbox_repo(),
+            event_config.clone(),
+        ));
+
+        LsEventModule { event_config, repo_manager, publisher, dispatcher }
+    }
+}
+
+impl<RepoManager: EventRepositoryManager> lightspeed_core::module::LsModule for LsEventModule<RepoManager> {
+    async fn start(&mut self) -> Result<(), LsError> {
+        info!("Starting LsEventModule");
+        self.repo_manager.start().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use c3p0::{Codec, DataType, Record};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use strum::{AsRefStr, Display};
+
+/// A typed event emitted by a service when something happened.
+///
+/// `EVENT_TYPE` identifies the event in the outbox and routes it to its
+/// handlers; it must be unique and must not change once events are stored.
+pub trait DomainEvent: Serialize + DeserializeOwned + Send + Sync + 'static {
+    const EVENT_TYPE: &'static str;
+
+    /// Fields of the payload removed from the outbox once the event is
+    /// delivered or moved to the dead letters, e.g. because they hold a
+    /// secret; such a dead letter cannot be requeued. The dispatcher knows
+    /// them from the handlers of the event, so they are kept if it has none.
+    const SCRUBBED_FIELDS: &'static [&'static str] = &[];
+
+    /// The entity the event is about, e.g. the id of an account, by which
+    /// its events are looked up in the outbox
+    fn subject(&self) -> Option<String> {
+        None
+    }
+}
+
+pub type OutboxEventModel = Record<OutboxEventData>;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OutboxEventData {
+    pub event_type: String,
+    pub payload: Value,
+    /// See [`DomainEvent::subject`]
+    #[serde(default)]
+    pub subject: Option<String>,
+    pub status: OutboxEventStatus,
+    /// Number of delivery attempts started so far
+    pub attempts: u32,
+    /// Epoch seconds after which the event can be delivered
+    pub next_attempt_epoch_seconds: i64,
+    pub last_error: Option<String>,
+    pub created_date_epoch_seconds: i64,
+    pub delivered_date_epoch_seconds: Option<i64>,
+}
+
+impl DataType for OutboxEventData {
+    const TABLE_NAME: &'static str = "LS_EVENT_OUTBOX";
+    type CODEC = OutboxEventDataCodec;
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize, AsRefStr, Display)]
+pub enum OutboxEventStatus {
+    Pending,
+    Delivered,
+    /// Delivery failed `max_attempts` times
+    DeadLetter,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "_codec_tag")]
+pub enum OutboxEventDataCodec {
+    V1(OutboxEventData),
+}
+
+impl Codec<OutboxEventData> for OutboxEventDataCodec {
+    fn encode(data: OutboxEventData) -> Self {
+        OutboxEventDataCodec::V1(data)
+    }
+
+    fn decode(data: Self) -> OutboxEventData {
+        match data {
+            OutboxEventDataCodec::V1(data) => data,
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! In-memory repositories for tests.
+//!
+//! The transactions of [`MemoryC3p0Pool`] are no-ops: the changes are
+//! applied immediately and are not undone when a transaction fails.
+//! [`MemoryOutboxRepository`] ignores the connection it receives, so it can
+//! be used with the in-memory transaction type of other crates too.
+
+use crate::error::LsEventError;
+use crate::model::{OutboxEventData, OutboxEventModel, OutboxEventStatus};
+use crate::repository::{EventRepositoryManager, OutboxRepository};
+use c3p0::sqlx::Database;
+use c3p0::*;
+use chrono::Utc;
+use lightspeed_core::error::LsError;
+use memory_db::{MemoryC3p0Pool, MemoryDb};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+pub mod memory_db;
+
+/// The records of a [`MemoryTable`], ordered by id
+pub struct MemoryRecords<DATA: DataType> {
+    last_id: i64,
+    records: BTreeMap<i64, Record<DATA>>,
+}
+
+impl<DATA: DataType + Clone> Clone for MemoryRecords<DATA> {
+    fn clone(&self) -> Self {
+        Self { last_id: self.last_id, records: self.records.clone() }
+    }
+}
+
+impl<DATA: DataType> Default for MemoryRecords<DATA> {
+    fn default() -> Self {
+        Self { last_id: 0, records: BTreeMap::new() }
+    }
+}
+
+impl<DATA: DataType + Clone> MemoryRecords<DATA> {
+    pub fn iter(&self) -> impl Iterator<Item = &Record<DATA>> {
+        self.records.values()
+    }
+
+    /// The records with id greater than or equal to `start_id`
+    pub fn iter_from(&self, start_id: i64) -> impl Iterator<Item = &Record<DATA>> {
+        self.records.range(start_id..).map(|(_, record)| record)
+    }
+
+    pub fn fetch_by_id_optional(&self, id: i64) -> Option<Record<DATA>> {
+        self.records.get(&id).cloned()
+    }
+
+    /// Fails with `RowNotFound`, like the SQL repositories
+    pub fn fetch_by_id(&self, id: i64) -> Result<Record<DATA>, C3p0Error> {
+        self.fetch_by_id_optional(id).ok_or(C3p0Error::SqlxError(c3p0::sqlx::Error::RowNotFound))
+    }
+
+    pub fn save(&mut self, model: NewRecord<DATA>) -> Record<DATA> {
+        self.last_id += 1;
+        let now = Utc::now();
+        let record = Record { id: self.last_id, version: 0, create_time: now, update_time: now, data: model.data };
+        self.records.insert(record.id, record.clone());
+        record
+    }
+
+    /// Fails with `OptimisticLockError` if the stored version is not the one
+    /// of `model`
+    pub fn update(&mut self, mut model: Record<DATA>) -> Result<Record<DATA>, C3p0Error> {
+        let stored = self.stored_version(&model, "update")?;
+        model.version += 1;
+        model.update_time = Utc::now();
+        *stored = model.clone();
+        Ok(model)
+    }
+
+    /// Fails with `OptimisticLockError` if the stored version is not the one
+    /// of `model`
+    pub fn delete(&mut self, model: Record<DATA>) -> Result<Record<DATA>, C3p0Error> {
+        self.stored_version(&model, "delete")?;
+        self.records.remove(&model.id);
+        Ok(model)
+    }
+
+    /// Deletes the records matching `predicate` and returns their number
+    pub fn delete_where(&mut self, predicate: impl Fn(&Record<DATA>) -> bool) -> u64 {
+        let count = self.records.len();
+        self.records.retain(|_, record| !predicate(record));
+        (count - self.records.len()) as u64
+    }
+
+    fn stored_version(&mut self, model: &Record<DATA>, operation: &str) -> Result<&mut Record<DATA>, C3p0Error> {
+        match self.records.get_mut(&model.id) {
+            Some(stored) if stored.version == model.version => Ok(stored),
+            _ => Err(C3p0Error::OptimisticLockError {
+                cause: format!(
+                    "Cannot {operation} data in table [{}] with id [{:?}], version [{}]: data was changed!",
+                    DATA::TABLE_NAME,
+                    model.id,
+                    model.version
+                ),
+            }),
+        }
+    }
+}
+
+/// A table kept in memory; the clones share the same records.
+pub struct MemoryTable<DATA: DataType> {
+    records: Arc<Mutex<MemoryRecords<DATA>>>,
+}
+
+impl<DATA: DataType> Clone for MemoryTable<DATA> {
+    fn clone(&self) -> Self {
+        Self { records: self.records.clone() }
+    }
+}
+
+impl<DATA: DataType> Default for MemoryTable<DATA> {
+    fn default() -> Self {
+        Self { records: Default::default() }
+    }
+}
+
+impl<DATA: DataType> MemoryTable<DATA> {
+    pub fn read<R>(&self, read: impl FnOnce(&MemoryRecords<DATA>) -> R) -> R {
+        read(&self.records.lock())
+    }
+
+    /// Applies `write` atomically, so it can check constraints before changing
+    /// the records
+    pub fn write<R>(&self, write: impl FnOnce(&mut MemoryRecords<DATA>) -> R) -> R {
+        write(&mut self.records.lock())
+    }
+}
+
+/// [`EventRepositoryManager`] that keeps the outbox in memory.
+#[derive(Clone, Default)]
+pub struct MemoryEventRepositoryManager {
+    c3p0: MemoryC3p0Pool,
+    outbox_repo: MemoryOutboxRepository<MemoryDb>,
+}
+
+impl MemoryEventRepositoryManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl EventRepositoryManager for MemoryEventRepositoryManager {
+    type DB = MemoryDb;
+    type C3P0 = MemoryC3p0Pool;
+    type OutboxRepo = MemoryOutboxRepository<MemoryDb>;
+
+    fn c3p0(&self) -> &Self::C3P0 {
+        &self.c3p0
+    }
+
+    async fn start(&self) -> Result<(), LsError> {
+        Ok(())
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        self.outbox_repo.clone()
+    }
+}
+
+pub struct MemoryOutboxRepository<DB> {
+    events: MemoryTable<OutboxEventData>,
+    phantom: PhantomData<fn() -> DB>,
+}
+
+impl<DB> Clone for MemoryOutboxRepository<DB> {
+    fn clone(&self) -> Self {
+        Self { events: self.events.clone(), phantom: PhantomData }
+    }
+}
+
+impl<DB> Default for MemoryOutboxRepository<DB> {
+    fn default() -> Self {
+        Self { events: Default::default(), phantom: PhantomData }
+    }
+}
+
+impl<DB> MemoryOutboxRepository<DB> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<DB: Database> OutboxRepository for MemoryOutboxRepository<DB> {
+    type DB = DB;
+
+    async fn fetch_due_for_update(
+        &self,
+        _tx: &mut DB::Connection,
+        now_epoch_seconds: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(self.events.read(|events| {
+            events
+                .iter()
+                .filter(|event| {
+                    event.data.status == OutboxEventStatus::Pending
+                        && event.data.next_attempt_epoch_seconds <= now_epoch_seconds
+                })
+                .take(limit as usize)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn fetch_by_id(&self, _tx: &mut DB::Connection, id: i64) -> Result<OutboxEventModel, LsEventError> {
+        Ok(self.events.read(|events| events.fetch_by_id(id))?)
+    }
+
+    async fn fetch_all_by_status(
+        &self,
+        _tx: &mut DB::Connection,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(self.events.read(|events| {
+            events
+                .iter_from(start_id)
+                .filter(|event| event.data.status == status)
+                .take(limit as usize)
+                .cloned()
+                .collect()
+        }))
+    }
+
+    async fn fetch_all_by_subject(
+        &self,
+        _tx: &mut DB::Connection,
+        subject: &str,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(self.events.read(|events| {
+            events.iter().filter(|event| event.data.subject.as_deref() == Some(subject)).cloned().collect()
+        }))
+    }
+
+    async fn save(
+        &self,
+        _tx: &mut DB::Connection,
+        model: NewRecord<OutboxEventData>,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(self.events.write(|events| events.save(model)))
+    }
+
+    async fn update(
+        &self,
+        _tx: &mut DB::Connection,
+        model: OutboxEventModel,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(self.events.write(|events| events.update(model))?)
+    }
+
+    async fn delete_delivered_before(&self, _tx: &mut DB::Connection, epoch_seconds: i64) -> Result<u64, LsEventError> {
+        Ok(self.events.write(|events| {
+            events.delete_where(|event| {
+                event.data.status == OutboxEventStatus::Delivered
+                    && event.data.delivered_date_epoch_seconds.is_some_and(|delivered| delivered < epoch_seconds)
+            })
+        }))
+    }
+}
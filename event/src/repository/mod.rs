@@ -0,0 +1,80 @@
+use crate::error::LsEventError;
+use crate::model::{OutboxEventData, OutboxEventModel, OutboxEventStatus};
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::error::LsError;
+
+pub mod memory;
+
+#[cfg(feature = "mysql")]
+pub mod mysql;
+
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+pub trait EventRepositoryManager: Clone + Send + Sync {
+    type DB: Database;
+    type C3P0: C3p0Pool<DB = Self::DB>;
+    type OutboxRepo: OutboxRepository<DB = Self::DB>;
+
+    fn c3p0(&self) -> &Self::C3P0;
+    fn start(&self) -> impl Future<Output = Result<(), LsError>> + Send;
+
+    fn outbox_repo(&self) -> Self::OutboxRepo;
+}
+
+pub trait OutboxRepository: Clone + Send + Sync {
+    type DB: Database;
+
+    /// Returns the `Pending` events whose next attempt is due, locking them
+    /// so that concurrent dispatchers skip them.
+    fn fetch_due_for_update(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        now_epoch_seconds: i64,
+        limit: u32,
+    ) -> impl Future<Output = Result<Vec<OutboxEventModel>, LsEventError>> + Send;
+
+    fn fetch_by_id(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        id: i64,
+    ) -> impl Future<Output = Result<OutboxEventModel, LsEventError>> + Send;
+
+    fn fetch_all_by_status(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> impl Future<Output = Result<Vec<OutboxEventModel>, LsEventError>> + Send;
+
+    /// Returns the events about `subject`, whatever their status
+    fn fetch_all_by_subject(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        subject: &str,
+    ) -> impl Future<Output = Result<Vec<OutboxEventModel>, LsEventError>> + Send;
+
+    fn save(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: NewRecord<OutboxEventData>,
+    ) -> impl Future<Output = Result<OutboxEventModel, LsEventError>> + Send;
+
+    fn update(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        model: OutboxEventModel,
+    ) -> impl Future<Output = Result<OutboxEventModel, LsEventError>> + Send;
+
+    /// Deletes the `Delivered` events delivered before the given time.
+    fn delete_delivered_before(
+        &self,
+        tx: &mut <Self::DB as Database>::Connection,
+        epoch_seconds: i64,
+    ) -> impl Future<Output = Result<u64, LsEventError>> + Send;
+}
@@ -0,0 +1,116 @@
+use crate::error::LsEventError;
+use crate::model::{OutboxEventData, OutboxEventModel, OutboxEventStatus};
+use crate::repository::OutboxRepository;
+use ::sqlx::AssertSqlSafe;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct MySqlOutboxRepository {}
+
+impl Default for MySqlOutboxRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MySqlOutboxRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OutboxRepository for MySqlOutboxRepository {
+    type DB = MySql;
+
+    async fn fetch_due_for_update(
+        &self,
+        tx: &mut MySqlConnection,
+        now_epoch_seconds: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where JSON_VALUE(data, '$.status' RETURNING CHAR(255)) = ? and JSON_VALUE(data, '$.next_attempt_epoch_seconds' RETURNING SIGNED) <= ?
+            order by id asc
+            limit ?
+            for update skip locked
+        "#,
+        )
+        .bind(OutboxEventStatus::Pending.as_ref())
+        .bind(now_epoch_seconds)
+        .bind(limit as i64)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_by_id(&self, tx: &mut MySqlConnection, id: i64) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.fetch_one_by_id::<OutboxEventData>(id).await?)
+    }
+
+    async fn fetch_all_by_status(
+        &self,
+        tx: &mut MySqlConnection,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where id >= ? and JSON_VALUE(data, '$.status' RETURNING CHAR(255)) = ?
+            order by id asc
+            limit ?
+        "#,
+        )
+        .bind(start_id)
+        .bind(status.as_ref())
+        .bind(limit as i64)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_subject(
+        &self,
+        tx: &mut MySqlConnection,
+        subject: &str,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where JSON_VALUE(data, '$.subject' RETURNING CHAR(255)) = ?
+            order by id asc
+        "#,
+        )
+        .bind(subject)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut MySqlConnection,
+        model: NewRecord<OutboxEventData>,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut MySqlConnection,
+        model: OutboxEventModel,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete_delivered_before(&self, tx: &mut MySqlConnection, epoch_seconds: i64) -> Result<u64, LsEventError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE JSON_VALUE(data, '$.status' RETURNING CHAR(255)) = ? AND JSON_VALUE(data, '$.delivered_date_epoch_seconds' RETURNING SIGNED) < ?",
+            <OutboxEventData as DataType>::TABLE_NAME
+        );
+        let res = query(AssertSqlSafe(sql))
+            .bind(OutboxEventStatus::Delivered.as_ref())
+            .bind(epoch_seconds)
+            .execute(tx)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
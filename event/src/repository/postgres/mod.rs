@@ -0,0 +1,48 @@
+use crate::repository::EventRepositoryManager;
+use crate::repository::postgres::pg_outbox::PgOutboxRepository;
+use c3p0::sqlx::{migrate::Migrator, *};
+use c3p0::*;
+use lightspeed_core::error::LsError;
+use std::sync::LazyLock;
+
+pub mod pg_outbox;
+
+/// The outbox table lives in the same database as the tables of the modules
+/// publishing events, so the migrations are tracked in a dedicated table to
+/// not clash with the ones of those modules.
+static MIGRATOR: LazyLock<Migrator> = LazyLock::new(|| {
+    let mut migrator = c3p0::sqlx::migrate!("src_resources/db/postgres/migrations");
+    migrator.dangerous_set_table_name("_ls_event_migrations");
+    migrator
+});
+
+#[derive(Clone)]
+pub struct PgEventRepositoryManager {
+    c3p0: PgC3p0Pool,
+}
+
+impl PgEventRepositoryManager {
+    pub fn new(c3p0: PgC3p0Pool) -> PgEventRepositoryManager {
+        PgEventRepositoryManager { c3p0 }
+    }
+}
+
+impl EventRepositoryManager for PgEventRepositoryManager {
+    type DB = Postgres;
+    type C3P0 = PgC3p0Pool;
+    type OutboxRepo = PgOutboxRepository;
+
+    fn c3p0(&self) -> &Self::C3P0 {
+        &self.c3p0
+    }
+
+    async fn start(&self) -> Result<(), LsError> {
+        MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
+            message: format!("PgEventRepositoryManager - db migration failed: {err:?}"),
+        })
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        PgOutboxRepository::new()
+    }
+}
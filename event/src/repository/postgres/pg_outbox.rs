@@ -0,0 +1,112 @@
+use crate::error::LsEventError;
+use crate::model::{OutboxEventData, OutboxEventModel, OutboxEventStatus};
+use crate::repository::OutboxRepository;
+use ::sqlx::AssertSqlSafe;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct PgOutboxRepository {}
+
+impl Default for PgOutboxRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PgOutboxRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OutboxRepository for PgOutboxRepository {
+    type DB = Postgres;
+
+    async fn fetch_due_for_update(
+        &self,
+        tx: &mut PgConnection,
+        now_epoch_seconds: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where data ->> 'status' = $1 and (data ->> 'next_attempt_epoch_seconds')::bigint <= $2
+            order by id asc
+            limit $3
+            for update skip locked
+        "#,
+        )
+        .bind(OutboxEventStatus::Pending.as_ref())
+        .bind(now_epoch_seconds)
+        .bind(limit as i64)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_by_id(&self, tx: &mut PgConnection, id: i64) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.fetch_one_by_id::<OutboxEventData>(id).await?)
+    }
+
+    async fn fetch_all_by_status(
+        &self,
+        tx: &mut PgConnection,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where id >= $1 and data ->> 'status' = $2
+            order by id asc
+            limit $3
+        "#,
+        )
+        .bind(start_id)
+        .bind(status.as_ref())
+        .bind(limit as i64)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_subject(
+        &self,
+        tx: &mut PgConnection,
+        subject: &str,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where data ->> 'subject' = $1
+            order by id asc
+        "#,
+        )
+        .bind(subject)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut PgConnection,
+        model: NewRecord<OutboxEventData>,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(&self, tx: &mut PgConnection, model: OutboxEventModel) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete_delivered_before(&self, tx: &mut PgConnection, epoch_seconds: i64) -> Result<u64, LsEventError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE data ->> 'status' = $1 AND (data ->> 'delivered_date_epoch_seconds')::bigint < $2",
+            <OutboxEventData as DataType>::TABLE_NAME
+        );
+        let res = query(AssertSqlSafe(sql))
+            .bind(OutboxEventStatus::Delivered.as_ref())
+            .bind(epoch_seconds)
+            .execute(tx)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
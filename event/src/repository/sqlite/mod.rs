@@ -0,0 +1,48 @@
+use crate::repository::EventRepositoryManager;
+use crate::repository::sqlite::sqlite_outbox::SqliteOutboxRepository;
+use c3p0::sqlx::{migrate::Migrator, *};
+use c3p0::*;
+use lightspeed_core::error::LsError;
+use std::sync::LazyLock;
+
+pub mod sqlite_outbox;
+
+/// The outbox table lives in the same database as the tables of the modules
+/// publishing events, so the migrations are tracked in a dedicated table to
+/// not clash with the ones of those modules.
+static MIGRATOR: LazyLock<Migrator> = LazyLock::new(|| {
+    let mut migrator = c3p0::sqlx::migrate!("src_resources/db/sqlite/migrations");
+    migrator.dangerous_set_table_name("_ls_event_migrations");
+    migrator
+});
+
+#[derive(Clone)]
+pub struct SqliteEventRepositoryManager {
+    c3p0: SqliteC3p0Pool,
+}
+
+impl SqliteEventRepositoryManager {
+    pub fn new(c3p0: SqliteC3p0Pool) -> SqliteEventRepositoryManager {
+        SqliteEventRepositoryManager { c3p0 }
+    }
+}
+
+impl EventRepositoryManager for SqliteEventRepositoryManager {
+    type DB = Sqlite;
+    type C3P0 = SqliteC3p0Pool;
+    type OutboxRepo = SqliteOutboxRepository;
+
+    fn c3p0(&self) -> &Self::C3P0 {
+        &self.c3p0
+    }
+
+    async fn start(&self) -> Result<(), LsError> {
+        MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
+            message: format!("SqliteEventRepositoryManager - db migration failed: {err:?}"),
+        })
+    }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        SqliteOutboxRepository::new()
+    }
+}
@@ -0,0 +1,119 @@
+use crate::error::LsEventError;
+use crate::model::{OutboxEventData, OutboxEventModel, OutboxEventStatus};
+use crate::repository::OutboxRepository;
+use ::sqlx::AssertSqlSafe;
+use c3p0::sqlx::*;
+use c3p0::*;
+
+#[derive(Clone)]
+pub struct SqliteOutboxRepository {}
+
+impl Default for SqliteOutboxRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqliteOutboxRepository {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl OutboxRepository for SqliteOutboxRepository {
+    type DB = Sqlite;
+
+    async fn fetch_due_for_update(
+        &self,
+        tx: &mut SqliteConnection,
+        now_epoch_seconds: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where data ->> '$.status' = ? and data ->> '$.next_attempt_epoch_seconds' <= ?
+            order by id asc
+            limit ?
+        "#,
+        )
+        .bind(OutboxEventStatus::Pending.as_ref())
+        .bind(now_epoch_seconds)
+        .bind(limit as i64)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_by_id(&self, tx: &mut SqliteConnection, id: i64) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.fetch_one_by_id::<OutboxEventData>(id).await?)
+    }
+
+    async fn fetch_all_by_status(
+        &self,
+        tx: &mut SqliteConnection,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where id >= ? and data ->> '$.status' = ?
+            order by id asc
+            limit ?
+        "#,
+        )
+        .bind(start_id)
+        .bind(status.as_ref())
+        .bind(limit as i64)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn fetch_all_by_subject(
+        &self,
+        tx: &mut SqliteConnection,
+        subject: &str,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        Ok(OutboxEventModel::query_with_tail(
+            r#"
+            where data ->> '$.subject' = ?
+            order by id asc
+        "#,
+        )
+        .bind(subject)
+        .fetch_all(tx)
+        .await?)
+    }
+
+    async fn save(
+        &self,
+        tx: &mut SqliteConnection,
+        model: NewRecord<OutboxEventData>,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.save(model).await?)
+    }
+
+    async fn update(
+        &self,
+        tx: &mut SqliteConnection,
+        model: OutboxEventModel,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        Ok(tx.update(model).await?)
+    }
+
+    async fn delete_delivered_before(
+        &self,
+        tx: &mut SqliteConnection,
+        epoch_seconds: i64,
+    ) -> Result<u64, LsEventError> {
+        let sql = format!(
+            "DELETE FROM {} WHERE data ->> '$.status' = ? AND data ->> '$.delivered_date_epoch_seconds' < ?",
+            <OutboxEventData as DataType>::TABLE_NAME
+        );
+        let res = query(AssertSqlSafe(sql))
+            .bind(OutboxEventStatus::Delivered.as_ref())
+            .bind(epoch_seconds)
+            .execute(tx)
+            .await?;
+        Ok(res.rows_affected())
+    }
+}
@@ -0,0 +1,333 @@
+use crate::config::EventConfig;
+use crate::error::LsEventError;
+use crate::model::{DomainEvent, OutboxEventModel, OutboxEventStatus};
+use crate::repository::{EventRepositoryManager, OutboxRepository};
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::utils::current_epoch_seconds;
+use log::*;
+use parking_lot::RwLock;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+
+type BoxedFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+type HandlersByEventType = HashMap<&'static str, Vec<Arc<dyn ErasedHandler>>>;
+
+/// Async handler of the events of type `E`.
+///
+/// Implemented for every `Fn(E) -> impl Future<Output = Result<(), LsEventError>>`.
+/// Delivery is at least once, so handlers should be idempotent.
+pub trait EventHandler<E: DomainEvent>: Send + Sync + 'static {
+    fn handle(&self, event: E) -> impl Future<Output = Result<(), LsEventError>> + Send;
+}
+
+impl<E, F, Fut> EventHandler<E> for F
+where
+    E: DomainEvent,
+    F: Fn(E) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), LsEventError>> + Send,
+{
+    fn handle(&self, event: E) -> impl Future<Output = Result<(), LsEventError>> + Send {
+        self(event)
+    }
+}
+
+/// Type-erased view of an [`EventHandler`] that decodes the stored payload.
+trait ErasedHandler: Send + Sync + 'static {
+    fn handle<'a>(&'a self, payload: &'a Value) -> BoxedFuture<'a, Result<(), LsEventError>>;
+
+    fn scrubbed_fields(&self) -> &'static [&'static str];
+}
+
+struct HandlerAdapter<E, H> {
+    handler: H,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: DomainEvent, H: EventHandler<E>> ErasedHandler for HandlerAdapter<E, H> {
+    fn handle<'a>(&'a self, payload: &'a Value) -> BoxedFuture<'a, Result<(), LsEventError>> {
+        Box::pin(async move {
+            let event: E = serde_json::from_value(payload.clone()).map_err(|err| LsEventError::SerializationError {
+                message: format!("Cannot deserialize event [{}]: {err:?}", E::EVENT_TYPE),
+            })?;
+            self.handler.handle(event).await
+        })
+    }
+
+    fn scrubbed_fields(&self) -> &'static [&'static str] {
+        E::SCRUBBED_FIELDS
+    }
+}
+
+/// Delivers the events stored in the outbox to the registered handlers.
+///
+/// Each [`dispatch_pending`](Self::dispatch_pending) run claims a batch of due
+/// events, runs their handlers outside of any transaction and then records
+/// the outcome. Failed deliveries are retried with an exponential backoff
+/// until `max_attempts` is reached; the event is then moved to the dead
+/// letters. Several dispatchers can run concurrently on the same database.
+///
+/// The `SCRUBBED_FIELDS` of an event are removed from its payload when it is
+/// delivered.
+#[derive(Clone)]
+pub struct LsEventDispatcher<RepoManager: EventRepositoryManager> {
+    c3p0: RepoManager::C3P0,
+    outbox_repo: RepoManager::OutboxRepo,
+    event_config: EventConfig,
+    handlers: Arc<RwLock<HandlersByEventType>>,
+}
+
+impl<RepoManager: EventRepositoryManager> LsEventDispatcher<RepoManager> {
+    pub fn new(c3p0: RepoManager::C3P0, outbox_repo: RepoManager::OutboxRepo, event_config: EventConfig) -> Self {
+        LsEventDispatcher { c3p0, outbox_repo, event_config, handlers: Default::default() }
+    }
+
+    /// Registers a handler for the events of type `E`. An event type can have
+    /// many handlers; an event is delivered once all of them succeed.
+    pub fn add_handler<E: DomainEvent, H: EventHandler<E>>(&self, handler: H) {
+        info!("Add handler for event [{}]", E::EVENT_TYPE);
+        self.handlers
+            .write()
+            .entry(E::EVENT_TYPE)
+            .or_default()
+            .push(Arc::new(HandlerAdapter { handler, _marker: PhantomData::<fn() -> E> }));
+    }
+
+    /// Delivers a batch of due events. Returns the number of events
+    /// delivered successfully.
+    pub async fn dispatch_pending(&self) -> Result<usize, LsEventError> {
+        let now = current_epoch_seconds();
+        let events = self.c3p0.transaction(async |conn| self.claim_due_with_conn(conn, now).await).await?;
+
+        let mut delivered = 0;
+        for event in events {
+            let result = self.deliver(&event).await;
+            if result.is_ok() {
+                delivered += 1;
+            }
+            let event_id = event.id;
+            // Once the handlers have run, failing to record the outcome only
+            // means that the event is delivered again after the lease expires.
+            if let Err(err) =
+                self.c3p0.transaction(async |conn| self.complete_with_conn(conn, event, result).await).await
+            {
+                warn!("Cannot record the delivery outcome of event [{event_id}]: {err:?}");
+            }
+        }
+        Ok(delivered)
+    }
+
+    /// Hides the due events from other dispatchers for `dispatch_lease_seconds`
+    /// and counts the new attempt.
+    async fn claim_due_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        now: i64,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        let mut claimed = vec![];
+        for mut event in self.outbox_repo.fetch_due_for_update(conn, now, self.event_config.dispatch_batch_size).await?
+        {
+            event.data.attempts += 1;
+            event.data.next_attempt_epoch_seconds = now + self.event_config.dispatch_lease_seconds as i64;
+            claimed.push(self.outbox_repo.update(conn, event).await?);
+        }
+        Ok(claimed)
+    }
+
+    async fn deliver(&self, event: &OutboxEventModel) -> Result<(), LsEventError> {
+        let handlers = self.handlers.read().get(event.data.event_type.as_str()).cloned().unwrap_or_default();
+        if handlers.is_empty() {
+            debug!("No handlers registered for event [{}] with id [{}]", event.data.event_type, event.id);
+        }
+
+        let mut errors = vec![];
+        for handler in handlers {
+            if let Err(err) = handler.handle(&event.data.payload).await {
+                errors.push(err.to_string());
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(LsEventError::HandlerError { message: errors.join("; ") }) }
+    }
+
+    fn scrubbed_fields(&self, event_type: &str) -> &'static [&'static str] {
+        self.handlers
+            .read()
+            .get(event_type)
+            .and_then(|handlers| handlers.first())
+            .map(|handler| handler.scrubbed_fields())
+            .unwrap_or_default()
+    }
+
+    /// Removes the `SCRUBBED_FIELDS` of the event from its payload
+    fn scrub(&self, event: &mut OutboxEventModel) {
+        let scrubbed_fields = self.scrubbed_fields(&event.data.event_type);
+        if let Some(payload) = event.data.payload.as_object_mut() {
+            for field in scrubbed_fields {
+                payload.remove(*field);
+            }
+        }
+    }
+
+    async fn complete_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        mut event: OutboxEventModel,
+        result: Result<(), LsEventError>,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        let now = current_epoch_seconds();
+        match result {
+            Ok(()) => {
+                debug!("Event [{}] with id [{}] delivered", event.data.event_type, event.id);
+                event.data.status = OutboxEventStatus::Delivered;
+                event.data.delivered_date_epoch_seconds = Some(now);
+                event.data.last_error = None;
+                self.scrub(&mut event);
+            }
+            Err(err) => {
+                event.data.last_error = Some(err.to_string());
+                if event.data.attempts >= self.event_config.max_attempts {
+                    warn!(
+                        "Event [{}] with id [{}] moved to the dead letters after [{}] attempts: {err}",
+                        event.data.event_type, event.id, event.data.attempts
+                    );
+                    event.data.status = OutboxEventStatus::DeadLetter;
+                    self.scrub(&mut event);
+                } else {
+                    info!(
+                        "Delivery of event [{}] with id [{}] failed at attempt [{}]: {err}",
+                        event.data.event_type, event.id, event.data.attempts
+                    );
+                    event.data.next_attempt_epoch_seconds =
+                        now + retry_delay_seconds(&self.event_config, event.data.attempts);
+                }
+            }
+        }
+        self.outbox_repo.update(conn, event).await
+    }
+
+    pub async fn fetch_all_by_status(
+        &self,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        self.c3p0
+            .transaction(async |conn| self.fetch_all_by_status_with_conn(conn, status, start_id, limit).await)
+            .await
+    }
+
+    pub async fn fetch_all_by_status_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        status: OutboxEventStatus,
+        start_id: i64,
+        limit: u32,
+    ) -> Result<Vec<OutboxEventModel>, LsEventError> {
+        debug!("Fetch all events with status [{status}], start_id {start_id:?}, limit {limit}");
+        self.outbox_repo.fetch_all_by_status(conn, status, start_id, limit).await
+    }
+
+    pub async fn requeue_dead_letter(&self, event_id: i64) -> Result<OutboxEventModel, LsEventError> {
+        self.c3p0.transaction(async |conn| self.requeue_dead_letter_with_conn(conn, event_id).await).await
+    }
+
+    /// Moves a dead letter back to the pending events, with a fresh attempts
+    /// count. Events that are not dead letters are returned unchanged. It
+    /// fails with `ScrubbedEventError` if the `SCRUBBED_FIELDS` of the
+    /// event were removed when it was moved to the dead letters.
+    pub async fn requeue_dead_letter_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        event_id: i64,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        let mut event = self.outbox_repo.fetch_by_id(conn, event_id).await?;
+        if event.data.status != OutboxEventStatus::DeadLetter {
+            return Ok(event);
+        }
+        if self.scrubbed_fields(&event.data.event_type).iter().any(|field| event.data.payload.get(*field).is_none()) {
+            return Err(LsEventError::ScrubbedEventError {
+                message: format!(
+                    "Cannot requeue the dead letter [{}] with id [{}]: its payload was scrubbed",
+                    event.data.event_type, event.id
+                ),
+            });
+        }
+        info!("Requeue dead letter [{}] with id [{}]", event.data.event_type, event.id);
+        event.data.status = OutboxEventStatus::Pending;
+        event.data.attempts = 0;
+        event.data.next_attempt_epoch_seconds = current_epoch_seconds();
+        self.outbox_repo.update(conn, event).await
+    }
+
+    pub async fn delete_delivered_before(&self, epoch_seconds: i64) -> Result<u64, LsEventError> {
+        self.c3p0.transaction(async |conn| self.delete_delivered_before_with_conn(conn, epoch_seconds).await).await
+    }
+
+    pub async fn delete_delivered_before_with_conn(
+        &self,
+        conn: &mut <RepoManager::DB as Database>::Connection,
+        epoch_seconds: i64,
+    ) -> Result<u64, LsEventError> {
+        info!("Delete events delivered before [{epoch_seconds}]");
+        self.outbox_repo.delete_delivered_before(conn, epoch_seconds).await
+    }
+}
+
+/// Runs [`LsEventDispatcher::dispatch_pending`] when the job fires. The
+/// dispatcher uses its own transactions, independent of the one of the job.
+///
+/// Implemented for the concrete repository managers, whose transaction
+/// futures are known to be `Send`.
+#[cfg(feature = "scheduler")]
+macro_rules! impl_scheduled_task {
+    ($repo_manager:ty) => {
+        impl<R: lightspeed_scheduler::ScheduleRepository> lightspeed_scheduler::ScheduledTask<R>
+            for LsEventDispatcher<$repo_manager>
+        {
+            type Error = LsEventError;
+
+            async fn run(&self, _tx: &mut R::Tx) -> Result<(), Self::Error> {
+                self.dispatch_pending().await.map(|_| ())
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "scheduler", feature = "mysql"))]
+impl_scheduled_task!(crate::repository::mysql::MySqlEventRepositoryManager);
+
+#[cfg(all(feature = "scheduler", feature = "postgres"))]
+impl_scheduled_task!(crate::repository::postgres::PgEventRepositoryManager);
+
+#[cfg(all(feature = "scheduler", feature = "sqlite"))]
+impl_scheduled_task!(crate::repository::sqlite::SqliteEventRepositoryManager);
+
+fn retry_delay_seconds(event_config: &EventConfig, attempts: u32) -> i64 {
+    let exponent = attempts.saturating_sub(1).min(31);
+    (event_config.retry_base_delay_seconds as i64)
+        .saturating_mul(1 << exponent)
+        .min(event_config.retry_max_delay_seconds as i64)
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+
+    #[test]
+    fn retry_delay_should_grow_exponentially_up_to_the_max() {
+        let config = EventConfig { retry_base_delay_seconds: 10, retry_max_delay_seconds: 100, ..Default::default() };
+
+        assert_eq!(10, retry_delay_seconds(&config, 1));
+        assert_eq!(20, retry_delay_seconds(&config, 2));
+        assert_eq!(40, retry_delay_seconds(&config, 3));
+        assert_eq!(80, retry_delay_seconds(&config, 4));
+        assert_eq!(100, retry_delay_seconds(&config, 5));
+        assert_eq!(100, retry_delay_seconds(&config, u32::MAX));
+    }
+}
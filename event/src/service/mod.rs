@@ -0,0 +1,2 @@
+pub mod dispatcher;
+pub mod publisher;
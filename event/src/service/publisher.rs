@@ -0,0 +1,51 @@
+use crate::error::LsEventError;
+use crate::model::{DomainEvent, OutboxEventData, OutboxEventModel, OutboxEventStatus};
+use crate::repository::OutboxRepository;
+use c3p0::sqlx::Database;
+use c3p0::*;
+use lightspeed_core::utils::current_epoch_seconds;
+use log::*;
+
+/// Stores domain events in the outbox.
+///
+/// Events must be published with the connection of the transaction that
+/// applies the change they describe: they are then delivered only if that
+/// transaction commits.
+#[derive(Clone)]
+pub struct LsEventPublisher<OutboxRepo: OutboxRepository> {
+    outbox_repo: OutboxRepo,
+}
+
+impl<OutboxRepo: OutboxRepository> LsEventPublisher<OutboxRepo> {
+    pub fn new(outbox_repo: OutboxRepo) -> Self {
+        LsEventPublisher { outbox_repo }
+    }
+
+    pub async fn publish_with_conn<E: DomainEvent>(
+        &self,
+        conn: &mut <OutboxRepo::DB as Database>::Connection,
+        event: &E,
+    ) -> Result<OutboxEventModel, LsEventError> {
+        debug!("Publish event [{}]", E::EVENT_TYPE);
+        let payload = serde_json::to_value(event).map_err(|err| LsEventError::SerializationError {
+            message: format!("Cannot serialize event [{}]: {err:?}", E::EVENT_TYPE),
+        })?;
+        let now = current_epoch_seconds();
+        self.outbox_repo
+            .save(
+                conn,
+                NewRecord::new(OutboxEventData {
+                    event_type: E::EVENT_TYPE.to_owned(),
+                    payload,
+                    subject: event.subject(),
+                    status: OutboxEventStatus::Pending,
+                    attempts: 0,
+                    next_attempt_epoch_seconds: now,
+                    last_error: None,
+                    created_date_epoch_seconds: now,
+                    delivered_date_epoch_seconds: None,
+                }),
+            )
+            .await
+    }
+}
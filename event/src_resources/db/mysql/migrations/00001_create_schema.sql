@@ -0,0 +1,19 @@
+-- ---------------------------
+-- Begin - LS_EVENT_OUTBOX -
+-- ---------------------------
+
+create table LS_EVENT_OUTBOX (
+    id BIGINT PRIMARY KEY NOT NULL AUTO_INCREMENT,
+    version INT NOT NULL,
+    create_time TIMESTAMP(3) NOT NULL,
+    update_time TIMESTAMP(3) NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE INDEX LS_EVENT_OUTBOX_STATUS_NEXT_ATTEMPT
+    ON LS_EVENT_OUTBOX (
+        (JSON_VALUE(DATA, '$.status' RETURNING CHAR(255))),
+        (JSON_VALUE(DATA, '$.next_attempt_epoch_seconds' RETURNING SIGNED))
+    );
+
+-- End - LS_EVENT_OUTBOX -
@@ -0,0 +1,6 @@
+-- The events about an entity are looked up by their subject
+
+CREATE INDEX LS_EVENT_OUTBOX_SUBJECT
+    ON LS_EVENT_OUTBOX (
+        (JSON_VALUE(DATA, '$.subject' RETURNING CHAR(255)))
+    );
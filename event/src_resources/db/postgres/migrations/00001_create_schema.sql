@@ -0,0 +1,17 @@
+-- ---------------------------
+-- Begin - LS_EVENT_OUTBOX -
+-- ---------------------------
+
+create table LS_EVENT_OUTBOX (
+    ID bigserial primary key,
+    version bigint NOT NULL,
+    create_time TIMESTAMPTZ NOT NULL,
+    update_time TIMESTAMPTZ NOT NULL,
+    data JSONB NOT NULL
+);
+
+CREATE INDEX LS_EVENT_OUTBOX_STATUS_NEXT_ATTEMPT ON LS_EVENT_OUTBOX(
+    (DATA->>'status'), ((DATA->>'next_attempt_epoch_seconds')::bigint)
+);
+
+-- End - LS_EVENT_OUTBOX -
@@ -0,0 +1,17 @@
+-- ---------------------------
+-- Begin - LS_EVENT_OUTBOX -
+-- ---------------------------
+
+create table LS_EVENT_OUTBOX (
+    ID integer primary key autoincrement,
+    version INTEGER NOT NULL,
+    create_time TEXT NOT NULL,
+    update_time TEXT NOT NULL,
+    data JSON NOT NULL
+);
+
+CREATE INDEX LS_EVENT_OUTBOX_STATUS_NEXT_ATTEMPT ON LS_EVENT_OUTBOX(
+    (DATA->>'$.status'), (DATA->>'$.next_attempt_epoch_seconds')
+);
+
+-- End - LS_EVENT_OUTBOX -
@@ -0,0 +1,33 @@
+use std::sync::OnceLock;
+
+use maybe_once::tokio::*;
+
+use lightspeed_core::module::LsModule;
+use lightspeed_event::LsEventModule;
+use lightspeed_event::config::EventConfig;
+use lightspeed_event::repository::memory::MemoryEventRepositoryManager;
+
+mod tests;
+
+pub type RepoManager = MemoryEventRepositoryManager;
+
+/// Whether a failed transaction undoes its changes
+pub const ROLLS_BACK: bool = false;
+
+pub type MaybeType = (LsEventModule<RepoManager>, ());
+
+async fn init() -> MaybeType {
+    let repo_manager = RepoManager::new();
+
+    let mut event_module = LsEventModule::new(repo_manager, EventConfig::default());
+    {
+        event_module.start().await.unwrap();
+    }
+
+    (event_module, ())
+}
+
+pub async fn data(serial: bool) -> Data<'static, MaybeType> {
+    static DATA: OnceLock<MaybeOnceAsync<MaybeType>> = OnceLock::new();
+    DATA.get_or_init(|| MaybeOnceAsync::new(|| Box::pin(init()))).data(serial).await
+}
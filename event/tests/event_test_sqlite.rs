@@ -0,0 +1,38 @@
+#![cfg(feature = "sqlite")]
+
+use std::sync::OnceLock;
+
+use maybe_once::tokio::*;
+
+use lightspeed_core::module::LsModule;
+use lightspeed_event::LsEventModule;
+use lightspeed_event::config::EventConfig;
+use lightspeed_event::repository::sqlite::SqliteEventRepositoryManager;
+use lightspeed_test_utils::sqlite::new_sqlite_db;
+
+mod tests;
+
+pub type RepoManager = SqliteEventRepositoryManager;
+
+/// Whether a failed transaction undoes its changes
+pub const ROLLS_BACK: bool = true;
+
+pub type MaybeType = (LsEventModule<RepoManager>, ());
+
+async fn init() -> MaybeType {
+    let c3p0 = new_sqlite_db().await;
+
+    let repo_manager = RepoManager::new(c3p0.clone());
+
+    let mut event_module = LsEventModule::new(repo_manager, EventConfig::default());
+    {
+        event_module.start().await.unwrap();
+    }
+
+    (event_module, ())
+}
+
+pub async fn data(serial: bool) -> Data<'static, MaybeType> {
+    static DATA: OnceLock<MaybeOnceAsync<MaybeType>> = OnceLock::new();
+    DATA.get_or_init(|| MaybeOnceAsync::new(|| Box::pin(init()))).data(serial).await
+}
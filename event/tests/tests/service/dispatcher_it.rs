@@ -0,0 +1,319 @@
+use crate::{ROLLS_BACK, RepoManager, data};
+use c3p0::*;
+use lightspeed_core::utils::{current_epoch_seconds, new_hyphenated_uuid};
+use lightspeed_event::LsEventModule;
+use lightspeed_event::config::EventConfig;
+use lightspeed_event::error::LsEventError;
+use lightspeed_event::model::{DomainEvent, OutboxEventModel, OutboxEventStatus};
+use lightspeed_event::repository::{EventRepositoryManager, OutboxRepository};
+use lightspeed_event::service::dispatcher::LsEventDispatcher;
+use maybe_once::tokio_shared;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Clone, Serialize, Deserialize)]
+struct TestEvent {
+    id: String,
+}
+
+impl DomainEvent for TestEvent {
+    const EVENT_TYPE: &'static str = "TestEvent";
+
+    fn subject(&self) -> Option<String> {
+        Some(self.id.clone())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SecretEvent {
+    id: String,
+    secret: String,
+}
+
+impl DomainEvent for SecretEvent {
+    const EVENT_TYPE: &'static str = "SecretEvent";
+    const SCRUBBED_FIELDS: &'static [&'static str] = &["secret"];
+}
+
+fn new_dispatcher(
+    event_module: &LsEventModule<RepoManager>,
+    event_config: EventConfig,
+) -> LsEventDispatcher<RepoManager> {
+    LsEventDispatcher::new(
+        event_module.repo_manager.c3p0().clone(),
+        event_module.repo_manager.outbox_repo(),
+        event_config,
+    )
+}
+
+async fn publish(
+    event_module: &LsEventModule<RepoManager>,
+    event: &TestEvent,
+) -> Result<OutboxEventModel, LsEventError> {
+    event_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| event_module.publisher.publish_with_conn(conn, event).await)
+        .await
+}
+
+async fn fetch(
+    event_module: &LsEventModule<RepoManager>,
+    status: OutboxEventStatus,
+    event_id: i64,
+) -> Result<Option<OutboxEventModel>, LsEventError> {
+    let events = event_module.dispatcher.fetch_all_by_status(status, event_id, 1).await?;
+    Ok(events.into_iter().find(|event| event.id == event_id))
+}
+
+#[tokio_shared::test]
+async fn should_deliver_published_events_to_all_handlers() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(event_module, EventConfig::default());
+
+    let received = Arc::new(Mutex::new(vec![]));
+    for _ in 0..2 {
+        let received = received.clone();
+        dispatcher.add_handler(move |event: TestEvent| {
+            let received = received.clone();
+            async move {
+                received.lock().push(event.id);
+                Ok(())
+            }
+        });
+    }
+
+    let event = TestEvent { id: new_hyphenated_uuid() };
+    let saved = publish(event_module, &event).await?;
+    assert_eq!(OutboxEventStatus::Pending, saved.data.status);
+    assert_eq!(TestEvent::EVENT_TYPE, saved.data.event_type);
+
+    assert!(dispatcher.dispatch_pending().await? >= 1);
+
+    assert_eq!(2, received.lock().iter().filter(|id| **id == event.id).count());
+    let delivered = fetch(event_module, OutboxEventStatus::Delivered, saved.id).await?.unwrap();
+    assert_eq!(1, delivered.data.attempts);
+    assert!(delivered.data.delivered_date_epoch_seconds.is_some());
+
+    // Delivered events are not dispatched again
+    dispatcher.dispatch_pending().await?;
+    assert_eq!(2, received.lock().iter().filter(|id| **id == event.id).count());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_scrub_the_fields_of_delivered_events() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(event_module, EventConfig::default());
+
+    let received = Arc::new(Mutex::new(vec![]));
+    {
+        let received = received.clone();
+        dispatcher.add_handler(move |event: SecretEvent| {
+            let received = received.clone();
+            async move {
+                received.lock().push(event.secret);
+                Ok(())
+            }
+        });
+    }
+
+    let event = SecretEvent { id: new_hyphenated_uuid(), secret: new_hyphenated_uuid() };
+    let saved = event_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| event_module.publisher.publish_with_conn(conn, &event).await)
+        .await?;
+    assert_eq!(event.secret.as_str(), saved.data.payload["secret"]);
+
+    dispatcher.dispatch_pending().await?;
+    assert!(received.lock().contains(&event.secret));
+    let delivered = fetch(event_module, OutboxEventStatus::Delivered, saved.id).await?.unwrap();
+    assert_eq!(event.id.as_str(), delivered.data.payload["id"]);
+    assert!(delivered.data.payload.get("secret").is_none());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_scrub_the_fields_of_dead_letters_and_refuse_to_requeue_them() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(event_module, EventConfig { max_attempts: 1, ..Default::default() });
+    dispatcher
+        .add_handler(async |_: SecretEvent| Err(LsEventError::HandlerError { message: "always fails".to_owned() }));
+
+    let event = SecretEvent { id: new_hyphenated_uuid(), secret: new_hyphenated_uuid() };
+    let saved = event_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| event_module.publisher.publish_with_conn(conn, &event).await)
+        .await?;
+
+    dispatcher.dispatch_pending().await?;
+    let dead_letter = fetch(event_module, OutboxEventStatus::DeadLetter, saved.id).await?.unwrap();
+    assert_eq!(event.id.as_str(), dead_letter.data.payload["id"]);
+    assert!(dead_letter.data.payload.get("secret").is_none());
+
+    assert!(matches!(dispatcher.requeue_dead_letter(saved.id).await, Err(LsEventError::ScrubbedEventError { .. })));
+    assert!(fetch(event_module, OutboxEventStatus::DeadLetter, saved.id).await?.is_some());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_fetch_the_events_by_subject() -> Result<(), LsEventError> {
+    let data = data(false).await;
+    let event_module = &data.0;
+
+    let event = TestEvent { id: new_hyphenated_uuid() };
+    let first = publish(event_module, &event).await?;
+    let second = publish(event_module, &event).await?;
+    publish(event_module, &TestEvent { id: new_hyphenated_uuid() }).await?;
+
+    let outbox_repo = event_module.repo_manager.outbox_repo();
+    let events = event_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| outbox_repo.fetch_all_by_subject(conn, &event.id).await)
+        .await?;
+    assert_eq!(vec![first.id, second.id], events.iter().map(|event| event.id).collect::<Vec<_>>());
+    assert_eq!(Some(event.id.as_str()), events[0].data.subject.as_deref());
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_store_events_if_the_transaction_rolls_back() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+
+    let event = TestEvent { id: new_hyphenated_uuid() };
+    let result: Result<(), LsEventError> = event_module
+        .repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            event_module.publisher.publish_with_conn(conn, &event).await?;
+            Err(LsEventError::HandlerError { message: "rollback".to_owned() })
+        })
+        .await;
+    assert!(result.is_err());
+
+    let pending = event_module.dispatcher.fetch_all_by_status(OutboxEventStatus::Pending, 0, u32::MAX).await?;
+    // The in-memory transactions keep the changes
+    assert_eq!(!ROLLS_BACK, pending.iter().any(|stored| stored.data.payload["id"] == event.id.as_str()));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_retry_failed_deliveries() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(event_module, EventConfig { retry_base_delay_seconds: 0, ..Default::default() });
+
+    let event = TestEvent { id: new_hyphenated_uuid() };
+    let calls = Arc::new(AtomicUsize::new(0));
+    {
+        let calls = calls.clone();
+        let event_id = event.id.clone();
+        dispatcher.add_handler(move |received: TestEvent| {
+            let calls = calls.clone();
+            let event_id = event_id.clone();
+            async move {
+                if received.id == event_id && calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return Err(LsEventError::HandlerError { message: "first attempt fails".to_owned() });
+                }
+                Ok(())
+            }
+        });
+    }
+
+    let saved = publish(event_module, &event).await?;
+
+    dispatcher.dispatch_pending().await?;
+    let pending = fetch(event_module, OutboxEventStatus::Pending, saved.id).await?.unwrap();
+    assert_eq!(1, pending.data.attempts);
+    assert!(pending.data.last_error.as_ref().unwrap().contains("first attempt fails"));
+
+    dispatcher.dispatch_pending().await?;
+    let delivered = fetch(event_module, OutboxEventStatus::Delivered, saved.id).await?.unwrap();
+    assert_eq!(2, delivered.data.attempts);
+    assert!(delivered.data.last_error.is_none());
+    assert_eq!(2, calls.load(Ordering::SeqCst));
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_not_retry_before_the_backoff_delay() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(event_module, EventConfig { retry_base_delay_seconds: 3600, ..Default::default() });
+    dispatcher.add_handler(async |_: TestEvent| Err(LsEventError::HandlerError { message: "fails".to_owned() }));
+
+    let saved = publish(event_module, &TestEvent { id: new_hyphenated_uuid() }).await?;
+
+    dispatcher.dispatch_pending().await?;
+    dispatcher.dispatch_pending().await?;
+
+    let pending = fetch(event_module, OutboxEventStatus::Pending, saved.id).await?.unwrap();
+    assert_eq!(1, pending.data.attempts);
+    assert!(pending.data.next_attempt_epoch_seconds >= current_epoch_seconds() + 3500);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_move_to_dead_letters_after_max_attempts_and_requeue() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(
+        event_module,
+        EventConfig { max_attempts: 2, retry_base_delay_seconds: 0, ..Default::default() },
+    );
+    dispatcher.add_handler(async |_: TestEvent| Err(LsEventError::HandlerError { message: "always fails".to_owned() }));
+
+    let saved = publish(event_module, &TestEvent { id: new_hyphenated_uuid() }).await?;
+
+    dispatcher.dispatch_pending().await?;
+    assert!(fetch(event_module, OutboxEventStatus::Pending, saved.id).await?.is_some());
+
+    dispatcher.dispatch_pending().await?;
+    let dead_letter = fetch(event_module, OutboxEventStatus::DeadLetter, saved.id).await?.unwrap();
+    assert_eq!(2, dead_letter.data.attempts);
+    assert!(dead_letter.data.last_error.as_ref().unwrap().contains("always fails"));
+
+    dispatcher.dispatch_pending().await?;
+    assert!(fetch(event_module, OutboxEventStatus::DeadLetter, saved.id).await?.is_some());
+
+    let requeued = dispatcher.requeue_dead_letter(saved.id).await?;
+    assert_eq!(OutboxEventStatus::Pending, requeued.data.status);
+    assert_eq!(0, requeued.data.attempts);
+
+    Ok(())
+}
+
+#[tokio_shared::test]
+async fn should_delete_delivered_events() -> Result<(), LsEventError> {
+    let data = data(true).await;
+    let event_module = &data.0;
+    let dispatcher = new_dispatcher(event_module, EventConfig::default());
+
+    let saved = publish(event_module, &TestEvent { id: new_hyphenated_uuid() }).await?;
+    dispatcher.dispatch_pending().await?;
+    assert!(fetch(event_module, OutboxEventStatus::Delivered, saved.id).await?.is_some());
+
+    dispatcher.delete_delivered_before(current_epoch_seconds() - 60).await?;
+    assert!(fetch(event_module, OutboxEventStatus::Delivered, saved.id).await?.is_some());
+
+    assert!(dispatcher.delete_delivered_before(current_epoch_seconds() + 1).await? >= 1);
+    assert!(fetch(event_module, OutboxEventStatus::Delivered, saved.id).await?.is_none());
+
+    Ok(())
+}
@@ -40,4 +40,10 @@ pub enum LsFileStoreError {
         #[from]
         source: c3p0::sqlx::Error,
     },
+
+    #[error("EventError: {source:?}")]
+    EventError {
+        #[from]
+        source: lightspeed_event::error::LsEventError,
+    },
 }
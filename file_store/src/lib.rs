@@ -14,6 +14,8 @@ pub mod config;
 pub mod dto;
 pub mod error;
 pub mod model;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 pub mod repository;
 pub mod service;
 pub mod web;
@@ -0,0 +1,26 @@
+use lightspeed_event::model::DomainEvent;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileSaved {
+    pub file_id: i64,
+    pub repository: String,
+    pub file_path: String,
+    pub filename: String,
+    pub content_type: String,
+}
+
+impl DomainEvent for FileSaved {
+    const EVENT_TYPE: &'static str = "FS_FILE_SAVED";
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDeleted {
+    pub file_id: i64,
+    pub repository: String,
+    pub file_path: String,
+}
+
+impl DomainEvent for FileDeleted {
+    const EVENT_TYPE: &'static str = "FS_FILE_DELETED";
+}
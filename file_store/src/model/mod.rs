@@ -5,6 +5,8 @@ use serde::{Deserialize, Serialize};
 use std::{borrow::Cow, sync::Arc};
 use tokio::sync::Mutex;
 
+pub mod event;
+
 pub type FileStoreDataModel = Record<FileStoreDataData>;
 
 /// `BinaryContent` is a wrapper around the source of a file's binary content.
@@ -15,6 +17,7 @@ pub enum BinaryContent<'a> {
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FileStoreDataData {
     pub filename: String,
     pub file_path: String,
@@ -0,0 +1,20 @@
+use crate::model::FileStoreDataData;
+use lightspeed_core::web::openapi::LsOpenApi;
+use utoipa::openapi::{ComponentsBuilder, OpenApi, OpenApiBuilder, Paths};
+use utoipa::{PartialSchema, ToSchema};
+
+/// OpenAPI fragment with the file store data types. It has no paths: the
+/// module exposes no router, the applications serve the files from their own
+/// handlers through `web::axum::into_response`.
+pub struct LsFileStoreOpenApi;
+
+impl LsOpenApi for LsFileStoreOpenApi {
+    fn openapi() -> OpenApi {
+        OpenApiBuilder::new()
+            .paths(Paths::new())
+            .components(Some(
+                ComponentsBuilder::new().schema(FileStoreDataData::name(), FileStoreDataData::schema()).build(),
+            ))
+            .build()
+    }
+}
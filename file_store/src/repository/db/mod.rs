@@ -3,6 +3,7 @@ use crate::model::{BinaryContent, FileStoreDataData, FileStoreDataModel};
 use c3p0::sqlx::Database;
 use c3p0::{sql::OrderBy, *};
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::OutboxRepository;
 
 #[cfg(feature = "mysql")]
 pub mod mysql;
@@ -18,12 +19,14 @@ pub trait DBFileStoreRepositoryManager: Clone + Send + Sync {
     type C3P0: C3p0Pool<DB = Self::DB>;
     type FileStoreBinaryRepo: DBFileStoreBinaryRepository<DB = Self::DB>;
     type FileStoreDataRepo: FileStoreDataRepository<DB = Self::DB>;
+    type OutboxRepo: OutboxRepository<DB = Self::DB>;
 
     fn c3p0(&self) -> &Self::C3P0;
     fn start(&self) -> impl Future<Output = Result<(), LsError>> + Send;
 
     fn file_store_binary_repo(&self) -> Self::FileStoreBinaryRepo;
     fn file_store_data_repo(&self) -> Self::FileStoreDataRepo;
+    fn outbox_repo(&self) -> Self::OutboxRepo;
 }
 
 pub trait DBFileStoreBinaryRepository: Clone + Send + Sync {
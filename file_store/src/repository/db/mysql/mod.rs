@@ -5,6 +5,9 @@ use ::sqlx::migrate::Migrator;
 use c3p0::sqlx::*;
 use c3p0::*;
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::repository::mysql::MySqlEventRepositoryManager;
+use lightspeed_event::repository::mysql::mysql_outbox::MySqlOutboxRepository;
 
 pub mod mysql_file_store_binary;
 pub mod mysql_file_store_data;
@@ -27,6 +30,7 @@ impl DBFileStoreRepositoryManager for MySqlFileStoreRepositoryManager {
     type C3P0 = MySqlC3p0Pool;
     type FileStoreBinaryRepo = MySqlFileStoreBinaryRepository;
     type FileStoreDataRepo = MySqlFileStoreDataRepository;
+    type OutboxRepo = MySqlOutboxRepository;
 
     fn c3p0(&self) -> &Self::C3P0 {
         &self.c3p0
@@ -35,7 +39,8 @@ impl DBFileStoreRepositoryManager for MySqlFileStoreRepositoryManager {
     async fn start(&self) -> Result<(), LsError> {
         MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
             message: format!("MySqlFileStoreRepositoryManager - db migration failed: {err:?}"),
-        })
+        })?;
+        MySqlEventRepositoryManager::new(self.c3p0.clone()).start().await
     }
 
     fn file_store_binary_repo(&self) -> Self::FileStoreBinaryRepo {
@@ -45,4 +50,8 @@ impl DBFileStoreRepositoryManager for MySqlFileStoreRepositoryManager {
     fn file_store_data_repo(&self) -> Self::FileStoreDataRepo {
         MySqlFileStoreDataRepository::default()
     }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        MySqlOutboxRepository::new()
+    }
 }
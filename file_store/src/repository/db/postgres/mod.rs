@@ -4,6 +4,9 @@ use crate::repository::db::postgres::pg_file_store_data::PgFileStoreDataReposito
 use c3p0::sqlx::{migrate::Migrator, *};
 use c3p0::*;
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::repository::postgres::PgEventRepositoryManager;
+use lightspeed_event::repository::postgres::pg_outbox::PgOutboxRepository;
 
 pub mod pg_file_store_binary;
 pub mod pg_file_store_data;
@@ -26,6 +29,7 @@ impl DBFileStoreRepositoryManager for PgFileStoreRepositoryManager {
     type C3P0 = PgC3p0Pool;
     type FileStoreBinaryRepo = PgFileStoreBinaryRepository;
     type FileStoreDataRepo = PgFileStoreDataRepository;
+    type OutboxRepo = PgOutboxRepository;
 
     fn c3p0(&self) -> &Self::C3P0 {
         &self.c3p0
@@ -34,7 +38,8 @@ impl DBFileStoreRepositoryManager for PgFileStoreRepositoryManager {
     async fn start(&self) -> Result<(), LsError> {
         MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
             message: format!("PgFileStoreRepositoryManager - db migration failed: {err:?}"),
-        })
+        })?;
+        PgEventRepositoryManager::new(self.c3p0.clone()).start().await
     }
 
     fn file_store_binary_repo(&self) -> Self::FileStoreBinaryRepo {
@@ -44,4 +49,8 @@ impl DBFileStoreRepositoryManager for PgFileStoreRepositoryManager {
     fn file_store_data_repo(&self) -> Self::FileStoreDataRepo {
         PgFileStoreDataRepository::default()
     }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        PgOutboxRepository::new()
+    }
 }
@@ -4,6 +4,9 @@ use crate::repository::db::sqlite::sqlite_file_store_data::SqliteFileStoreDataRe
 use c3p0::sqlx::{migrate::Migrator, *};
 use c3p0::*;
 use lightspeed_core::error::LsError;
+use lightspeed_event::repository::EventRepositoryManager;
+use lightspeed_event::repository::sqlite::SqliteEventRepositoryManager;
+use lightspeed_event::repository::sqlite::sqlite_outbox::SqliteOutboxRepository;
 
 pub mod sqlite_file_store_binary;
 pub mod sqlite_file_store_data;
@@ -26,6 +29,7 @@ impl DBFileStoreRepositoryManager for SqliteFileStoreRepositoryManager {
     type C3P0 = SqliteC3p0Pool;
     type FileStoreBinaryRepo = SqliteFileStoreBinaryRepository;
     type FileStoreDataRepo = SqliteFileStoreDataRepository;
+    type OutboxRepo = SqliteOutboxRepository;
 
     fn c3p0(&self) -> &Self::C3P0 {
         &self.c3p0
@@ -34,7 +38,8 @@ impl DBFileStoreRepositoryManager for SqliteFileStoreRepositoryManager {
     async fn start(&self) -> Result<(), LsError> {
         MIGRATOR.run(self.c3p0.pool()).await.map_err(|err| LsError::ModuleStartError {
             message: format!("SqliteFileStoreRepositoryManager - db migration failed: {err:?}"),
-        })
+        })?;
+        SqliteEventRepositoryManager::new(self.c3p0.clone()).start().await
     }
 
     fn file_store_binary_repo(&self) -> Self::FileStoreBinaryRepo {
@@ -44,4 +49,8 @@ impl DBFileStoreRepositoryManager for SqliteFileStoreRepositoryManager {
     fn file_store_data_repo(&self) -> Self::FileStoreDataRepo {
         SqliteFileStoreDataRepository::default()
     }
+
+    fn outbox_repo(&self) -> Self::OutboxRepo {
+        SqliteOutboxRepository::new()
+    }
 }
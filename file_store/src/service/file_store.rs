@@ -1,5 +1,6 @@
 use crate::config::RepositoryType;
 use crate::error::LsFileStoreError;
+use crate::model::event::{FileDeleted, FileSaved};
 use crate::model::{BinaryContent, FileStoreDataData, FileStoreDataModel};
 use crate::repository::db::{DBFileStoreBinaryRepository, DBFileStoreRepositoryManager, FileStoreDataRepository};
 use crate::repository::opendal::opendal_file_store_binary::OpendalFileStoreBinaryRepository;
@@ -7,6 +8,7 @@ use c3p0::sql::OrderBy;
 use c3p0::sqlx::Database;
 use c3p0::*;
 use lightspeed_core::utils::current_epoch_seconds;
+use lightspeed_event::service::publisher::LsEventPublisher;
 use log::*;
 use std::collections::HashMap;
 
@@ -15,6 +17,7 @@ pub struct LsFileStoreService<RepoManager: DBFileStoreRepositoryManager> {
     c3p0: RepoManager::C3P0,
     db_binary_repo: RepoManager::FileStoreBinaryRepo,
     db_data_repo: RepoManager::FileStoreDataRepo,
+    event_publisher: LsEventPublisher<RepoManager::OutboxRepo>,
     repositories: HashMap<String, RepositoryStoreType>,
     save_max_size_bytes: Option<usize>,
 }
@@ -35,6 +38,7 @@ impl<RepoManager: DBFileStoreRepositoryManager> LsFileStoreService<RepoManager>
             c3p0: repo_manager.c3p0().clone(),
             db_binary_repo: repo_manager.file_store_binary_repo(),
             db_data_repo: repo_manager.file_store_data_repo(),
+            event_publisher: LsEventPublisher::new(repo_manager.outbox_repo()),
             repositories: repositories
                 .into_iter()
                 .map(|(name, repo)| {
@@ -191,7 +195,8 @@ impl<RepoManager: DBFileStoreRepositoryManager> LsFileStoreService<RepoManager>
             }
         };
 
-        self.db_data_repo
+        let file_data = self
+            .db_data_repo
             .save(
                 conn,
                 NewRecord::new(FileStoreDataData {
@@ -202,7 +207,22 @@ impl<RepoManager: DBFileStoreRepositoryManager> LsFileStoreService<RepoManager>
                     created_date_epoch_seconds: current_epoch_seconds(),
                 }),
             )
-            .await
+            .await?;
+
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &FileSaved {
+                    file_id: file_data.id,
+                    repository: file_data.data.repository.clone(),
+                    file_path: file_data.data.file_path.clone(),
+                    filename: file_data.data.filename.clone(),
+                    content_type: file_data.data.content_type.clone(),
+                },
+            )
+            .await?;
+
+        Ok(file_data)
     }
 
     pub async fn save_file<'a>(
@@ -235,6 +255,17 @@ impl<RepoManager: DBFileStoreRepositoryManager> LsFileStoreService<RepoManager>
 
         self.db_data_repo.delete_by_id(conn, id).await?;
 
+        self.event_publisher
+            .publish_with_conn(
+                conn,
+                &FileDeleted {
+                    file_id: id,
+                    repository: file_data.data.repository.clone(),
+                    file_path: file_data.data.file_path.clone(),
+                },
+            )
+            .await?;
+
         match self.get_repository(&file_data.data.repository)? {
             RepositoryStoreType::DB => self
                 .db_binary_repo
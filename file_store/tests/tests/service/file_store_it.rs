@@ -1,8 +1,11 @@
 use crate::data;
 use c3p0::sql::OrderBy;
 use c3p0::*;
+use lightspeed_event::model::{DomainEvent, OutboxEventStatus};
+use lightspeed_event::repository::OutboxRepository;
 use lightspeed_file_store::error::LsFileStoreError;
 use lightspeed_file_store::model::BinaryContent;
+use lightspeed_file_store::model::event::{FileDeleted, FileSaved};
 use lightspeed_file_store::repository::db::{DBFileStoreBinaryRepository, DBFileStoreRepositoryManager};
 use lightspeed_file_store::service::file_store::LsFileStoreService;
 use maybe_once::tokio_shared;
@@ -768,3 +771,51 @@ async fn save_file_should_reject_payload_above_save_max_size_bytes() -> Result<(
 
     Ok(())
 }
+
+#[tokio_shared::test]
+async fn should_publish_events_when_files_are_saved_and_deleted() -> Result<(), LsFileStoreError> {
+    let data = data(false).await;
+    let repo_manager = &data.0.repo_manager;
+    let file_store = &data.0.file_store_service;
+
+    let random: u32 = rand::random();
+    let file_name = format!("file_{random}");
+    let binary_content = BinaryContent::InMemory { content: Cow::Borrowed(b"hello") };
+
+    let saved = file_store
+        .save_file("DB_ONE".to_owned(), file_name.clone(), file_name.clone(), "text/plain".to_owned(), &binary_content)
+        .await?;
+    file_store.delete_file_by_id(saved.id).await?;
+
+    let outbox_repo = repo_manager.outbox_repo();
+    let events = repo_manager
+        .c3p0()
+        .transaction(async |conn| {
+            Ok::<_, LsFileStoreError>(
+                outbox_repo.fetch_all_by_status(conn, OutboxEventStatus::Pending, 0, u32::MAX).await?,
+            )
+        })
+        .await?;
+    let events: Vec<_> = events.into_iter().filter(|event| event.data.payload["file_id"] == saved.id).collect();
+    assert_eq!(2, events.len());
+
+    assert_eq!(FileSaved::EVENT_TYPE, events[0].data.event_type);
+    assert_eq!(
+        FileSaved {
+            file_id: saved.id,
+            repository: "DB_ONE".to_owned(),
+            file_path: file_name.clone(),
+            filename: file_name.clone(),
+            content_type: "text/plain".to_owned(),
+        },
+        serde_json::from_value(events[0].data.payload.clone()).unwrap()
+    );
+
+    assert_eq!(FileDeleted::EVENT_TYPE, events[1].data.event_type);
+    assert_eq!(
+        FileDeleted { file_id: saved.id, repository: "DB_ONE".to_owned(), file_path: file_name },
+        serde_json::from_value(events[1].data.payload.clone()).unwrap()
+    );
+
+    Ok(())
+}
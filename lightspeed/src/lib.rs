@@ -18,6 +18,9 @@ pub use lightspeed_core as core;
 #[cfg(feature = "email")]
 pub use lightspeed_email as email;
 
+#[cfg(feature = "event")]
+pub use lightspeed_event as event;
+
 #[cfg(feature = "file_store")]
 pub use lightspeed_file_store as file_store;
 
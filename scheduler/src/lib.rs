@@ -152,6 +152,7 @@ pub(crate) fn to_millis(t: SystemTime) -> i64 {
     t.duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as i64
 }
 
+#[cfg(feature = "c3p0")]
 #[inline]
 pub(crate) fn from_millis(ms: i64) -> SystemTime {
     UNIX_EPOCH + Duration::from_millis(ms as u64)
@@ -5,6 +5,8 @@
 #![cfg_attr(not(test), deny(clippy::unwrap_used, clippy::expect_used))]
 
 mod error;
+#[cfg(feature = "openapi")]
+pub mod openapi;
 mod validation;
 
 pub use error::*;
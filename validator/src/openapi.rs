@@ -0,0 +1,254 @@
+//! OpenAPI support for [`Validable`](crate::Validable) structs.
+//!
+//! With the `openapi` feature enabled, `#[derive(Validable)]` also emits an
+//! impl of [`ValidableSchema`] that lists, per field, the JSON Schema
+//! keywords implied by its `#[validate(...)]` attributes. Combine it with a
+//! `utoipa::ToSchema` derive and [`constrained_schema`] to obtain a schema
+//! whose properties carry `minLength`, `maximum`, `format`, `pattern`, etc.
+//!
+//! Validators without a JSON Schema counterpart (`contains`, `isTrue`,
+//! `custom`, ...) are not reflected in the schema.
+
+use utoipa::openapi::schema::{KnownFormat, Schema, SchemaFormat};
+use utoipa::openapi::{RefOr, Type};
+use utoipa::{Number, ToSchema};
+
+/// A single JSON Schema keyword derived from a field validator.
+#[derive(Clone, PartialEq)]
+pub enum SchemaConstraint {
+    /// `minLength` for strings, `minItems` for arrays.
+    MinLength(usize),
+    /// `maxLength` for strings, `maxItems` for arrays.
+    MaxLength(usize),
+    Minimum(f64),
+    Maximum(f64),
+    ExclusiveMinimum(f64),
+    ExclusiveMaximum(f64),
+    Pattern(String),
+    Format(SchemaFormat),
+}
+
+/// Implemented by `#[derive(Validable)]` when the `openapi` feature is on.
+pub trait ValidableSchema {
+    /// `(field_name, constraint)` pairs in field declaration order.
+    fn schema_constraints() -> Vec<(&'static str, SchemaConstraint)>;
+}
+
+/// Converts a `range` bound to the `f64` used by [`SchemaConstraint`].
+/// Implemented for the numeric primitives accepted by `RangeValidator`.
+pub trait SchemaNumber {
+    fn to_schema_number(&self) -> f64;
+}
+
+macro_rules! impl_schema_number {
+    ( $( $ty:ty ),* ) => {
+        $(
+        impl SchemaNumber for $ty {
+            fn to_schema_number(&self) -> f64 {
+                *self as f64
+            }
+        }
+        )*
+    };
+}
+
+impl_schema_number!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+/// Helpers referenced by the code generated by `#[derive(Validable)]`.
+#[doc(hidden)]
+pub mod formats {
+    use super::*;
+
+    pub fn email() -> SchemaFormat {
+        SchemaFormat::KnownFormat(KnownFormat::Email)
+    }
+
+    pub fn password() -> SchemaFormat {
+        SchemaFormat::KnownFormat(KnownFormat::Password)
+    }
+
+    pub fn ipv4() -> SchemaFormat {
+        SchemaFormat::KnownFormat(KnownFormat::Ipv4)
+    }
+
+    pub fn ipv6() -> SchemaFormat {
+        SchemaFormat::KnownFormat(KnownFormat::Ipv6)
+    }
+
+    pub fn ip() -> SchemaFormat {
+        SchemaFormat::Custom("ip".to_owned())
+    }
+
+    pub fn uri() -> SchemaFormat {
+        SchemaFormat::Custom("uri".to_owned())
+    }
+
+    pub fn credit_card() -> SchemaFormat {
+        SchemaFormat::Custom("credit-card".to_owned())
+    }
+}
+
+/// Returns `(T::name(), schema)` where the schema generated by
+/// `utoipa::ToSchema` has been enriched with the constraints declared by the
+/// `#[validate(...)]` attributes of `T`. The tuple can be passed as-is to
+/// `ComponentsBuilder::schema`.
+pub fn constrained_schema<T: ToSchema + ValidableSchema>() -> (String, RefOr<Schema>) {
+    let mut schema = T::schema();
+    apply_schema_constraints(&mut schema, &T::schema_constraints());
+    (T::name().into_owned(), schema)
+}
+
+/// Applies `constraints` to the properties of an object `schema`.
+///
+/// When several validators set the same bound (e.g. two `length(min = ..)`)
+/// the most restrictive value wins. References (`$ref`) and properties that
+/// are not found in the schema are left untouched.
+pub fn apply_schema_constraints(schema: &mut RefOr<Schema>, constraints: &[(&'static str, SchemaConstraint)]) {
+    let RefOr::T(Schema::Object(object)) = schema else {
+        return;
+    };
+    for (field, constraint) in constraints {
+        if let Some(RefOr::T(property)) = object.properties.get_mut(*field) {
+            apply_constraint(property, constraint);
+        }
+    }
+}
+
+fn apply_constraint(schema: &mut Schema, constraint: &SchemaConstraint) {
+    match schema {
+        Schema::Object(object) => match constraint {
+            SchemaConstraint::MinLength(min) => {
+                object.min_length = Some(object.min_length.map_or(*min, |current| current.max(*min)))
+            }
+            SchemaConstraint::MaxLength(max) => {
+                object.max_length = Some(object.max_length.map_or(*max, |current| current.min(*max)))
+            }
+            SchemaConstraint::Minimum(min) => object.minimum = Some(most_restrictive(&object.minimum, *min, true)),
+            SchemaConstraint::Maximum(max) => object.maximum = Some(most_restrictive(&object.maximum, *max, false)),
+            SchemaConstraint::ExclusiveMinimum(min) => {
+                object.exclusive_minimum = Some(most_restrictive(&object.exclusive_minimum, *min, true))
+            }
+            SchemaConstraint::ExclusiveMaximum(max) => {
+                object.exclusive_maximum = Some(most_restrictive(&object.exclusive_maximum, *max, false))
+            }
+            SchemaConstraint::Pattern(pattern) => object.pattern = Some(pattern.clone()),
+            SchemaConstraint::Format(format) => {
+                // Only strings carry a meaningful `format` for these validators.
+                if is_string(&object.schema_type) {
+                    object.format = Some(format.clone())
+                }
+            }
+        },
+        Schema::Array(array) => match constraint {
+            SchemaConstraint::MinLength(min) => {
+                array.min_items = Some(array.min_items.map_or(*min, |current| current.max(*min)))
+            }
+            SchemaConstraint::MaxLength(max) => {
+                array.max_items = Some(array.max_items.map_or(*max, |current| current.min(*max)))
+            }
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+fn is_string(schema_type: &utoipa::openapi::schema::SchemaType) -> bool {
+    use utoipa::openapi::schema::SchemaType;
+    match schema_type {
+        SchemaType::Type(t) => *t == Type::String,
+        SchemaType::Array(types) => types.contains(&Type::String),
+        SchemaType::AnyValue => false,
+    }
+}
+
+fn most_restrictive(current: &Option<Number>, new: f64, is_lower_bound: bool) -> Number {
+    let current = current.as_ref().map(|n| match n {
+        Number::Int(v) => *v as f64,
+        Number::UInt(v) => *v as f64,
+        Number::Float(v) => *v,
+    });
+    let value = match current {
+        Some(current) if is_lower_bound => current.max(new),
+        Some(current) => current.min(new),
+        None => new,
+    };
+    to_number(value)
+}
+
+/// Keeps integral bounds integral so they serialize as `8` rather than `8.0`.
+fn to_number(value: f64) -> Number {
+    if value.fract() == 0.0 && value >= isize::MIN as f64 && value <= isize::MAX as f64 {
+        Number::Int(value as isize)
+    } else {
+        Number::Float(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+
+    use super::*;
+    use utoipa::openapi::schema::{ArrayBuilder, ObjectBuilder};
+
+    fn object_with(name: &str, property: impl Into<RefOr<Schema>>) -> RefOr<Schema> {
+        ObjectBuilder::new().property(name, property).into()
+    }
+
+    fn property<'a>(schema: &'a RefOr<Schema>, name: &str) -> &'a Schema {
+        match schema {
+            RefOr::T(Schema::Object(object)) => match object.properties.get(name) {
+                Some(RefOr::T(schema)) => schema,
+                _ => panic!("property [{name}] not found"),
+            },
+            _ => panic!("not an object schema"),
+        }
+    }
+
+    #[test]
+    fn should_keep_the_most_restrictive_length() {
+        let mut schema = object_with("name", ObjectBuilder::new().schema_type(Type::String));
+        apply_schema_constraints(
+            &mut schema,
+            &[
+                ("name", SchemaConstraint::MinLength(3)),
+                ("name", SchemaConstraint::MinLength(8)),
+                ("name", SchemaConstraint::MinLength(5)),
+                ("name", SchemaConstraint::MaxLength(20)),
+                ("name", SchemaConstraint::MaxLength(10)),
+            ],
+        );
+
+        let Schema::Object(name) = property(&schema, "name") else { panic!() };
+        assert_eq!(Some(8), name.min_length);
+        assert_eq!(Some(10), name.max_length);
+    }
+
+    #[test]
+    fn should_map_length_to_items_for_arrays() {
+        let mut schema = object_with("tags", ArrayBuilder::new().items(ObjectBuilder::new().schema_type(Type::String)));
+        apply_schema_constraints(
+            &mut schema,
+            &[("tags", SchemaConstraint::MinLength(1)), ("tags", SchemaConstraint::MaxLength(4))],
+        );
+
+        let Schema::Array(tags) = property(&schema, "tags") else { panic!() };
+        assert_eq!(Some(1), tags.min_items);
+        assert_eq!(Some(4), tags.max_items);
+    }
+
+    #[test]
+    fn should_keep_integral_bounds_integral() {
+        assert!(Number::Int(8) == to_number(8.0));
+        assert!(Number::Int(-3) == to_number(-3.0));
+        assert!(Number::Float(0.5) == to_number(0.5));
+    }
+
+    #[test]
+    fn should_not_set_format_on_non_string_properties() {
+        let mut schema = object_with("age", ObjectBuilder::new().schema_type(Type::Integer));
+        apply_schema_constraints(&mut schema, &[("age", SchemaConstraint::Format(formats::email()))]);
+
+        let Schema::Object(age) = property(&schema, "age") else { panic!() };
+        assert!(age.format.is_none());
+    }
+}
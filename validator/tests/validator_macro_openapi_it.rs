@@ -0,0 +1,98 @@
+#![cfg(feature = "openapi")]
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde_json::{Value, json};
+use utoipa::ToSchema;
+
+use lightspeed_validator::Validable;
+use lightspeed_validator::openapi::{SchemaConstraint, ValidableSchema, constrained_schema};
+
+static CODE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z]{3}$").unwrap());
+
+#[derive(ToSchema, Validable)]
+pub struct SignupForm {
+    #[validate(email)]
+    pub email: String,
+    #[validate(password)]
+    #[validate(length(min = 8, max = 64))]
+    pub password: String,
+    #[validate(range(min = 18, exclusive_max = 130))]
+    pub age: u32,
+    #[validate(range(exclusive_min = 0.5))]
+    pub score: f64,
+    #[validate(regex(pattern = r"^\d{5}$"))]
+    pub zip: String,
+    #[validate(regex(path = &CODE_RE))]
+    pub country: String,
+    #[validate(url)]
+    pub homepage: String,
+    #[validate(length(equal = 2))]
+    pub tags: Vec<String>,
+    #[validate(isTrue)]
+    pub accept: bool,
+    pub untouched: String,
+}
+
+fn schema_json() -> Value {
+    let (name, schema) = constrained_schema::<SignupForm>();
+    assert_eq!("SignupForm", name);
+    serde_json::to_value(schema).unwrap()
+}
+
+#[test]
+fn should_list_constraints_in_field_order() {
+    let constraints = SignupForm::schema_constraints();
+    let fields: Vec<&str> = constraints.iter().map(|(field, _)| *field).collect();
+    assert_eq!(
+        vec![
+            "email", "password", "password", "password", "age", "age", "score", "zip", "country", "homepage", "tags",
+            "tags"
+        ],
+        fields
+    );
+    assert!(constraints.contains(&("country", SchemaConstraint::Pattern(r"^[A-Z]{3}$".to_owned()))));
+}
+
+#[test]
+fn should_map_string_constraints() {
+    let schema = schema_json();
+    let properties = &schema["properties"];
+
+    assert_eq!(json!("email"), properties["email"]["format"]);
+    assert_eq!(json!("password"), properties["password"]["format"]);
+    assert_eq!(json!(8), properties["password"]["minLength"]);
+    assert_eq!(json!(64), properties["password"]["maxLength"]);
+    assert_eq!(json!(r"^\d{5}$"), properties["zip"]["pattern"]);
+    assert_eq!(json!(r"^[A-Z]{3}$"), properties["country"]["pattern"]);
+    assert_eq!(json!("uri"), properties["homepage"]["format"]);
+}
+
+#[test]
+fn should_map_numeric_constraints() {
+    let schema = schema_json();
+    let properties = &schema["properties"];
+
+    assert_eq!(json!(18), properties["age"]["minimum"]);
+    assert_eq!(json!(130), properties["age"]["exclusiveMaximum"]);
+    assert_eq!(json!(0.5), properties["score"]["exclusiveMinimum"]);
+}
+
+#[test]
+fn should_map_length_to_array_items() {
+    let schema = schema_json();
+    let tags = &schema["properties"]["tags"];
+
+    assert_eq!(json!(2), tags["minItems"]);
+    assert_eq!(json!(2), tags["maxItems"]);
+}
+
+#[test]
+fn should_leave_unconstrained_fields_untouched() {
+    let schema = schema_json();
+    let properties = &schema["properties"];
+
+    assert_eq!(json!({ "type": "string" }), properties["untouched"]);
+    assert_eq!(json!({ "type": "boolean" }), properties["accept"]);
+}
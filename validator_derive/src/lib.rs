@@ -119,6 +119,12 @@ const ERRORS_KEYWORD: &str = "errors";
 ///   number by the [`card_validate`](https://docs.rs/card-validate) crate
 ///   (Luhn + brand-specific length + IIN range matching for the major
 ///   issuers).
+///
+/// ## OpenAPI
+/// With the `openapi` feature the macro also implements
+/// `lightspeed_validator::openapi::ValidableSchema` for the struct, mapping
+/// `length`, `range`, `regex`, `email`, `url`, `ip*`, `password` and
+/// `credit_card` to the matching JSON Schema keywords.
 #[proc_macro_derive(Validable, attributes(validate))]
 pub fn derive_validable(item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemStruct);
@@ -215,6 +221,11 @@ pub fn derive_validable(item: TokenStream) -> TokenStream {
         FieldErrorStrategy::Shared | FieldErrorStrategy::Custom(_) => quote! {},
     };
 
+    let schema_impl = match generate_schema_impl(name, named_fields, &field_validators) {
+        Ok(v) => v,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
     let expanded = quote! {
         #field_enum_defs
 
@@ -225,6 +236,8 @@ pub fn derive_validable(item: TokenStream) -> TokenStream {
         #validate_fn
 
         #struct_validator_impls
+
+        #schema_impl
     };
 
     expanded.into()
@@ -647,6 +660,27 @@ fn generate_struct_validator_impls(
     quote! { #( #items )* }
 }
 
+/// Emits the `ValidableSchema` impl consumed by
+/// `lightspeed_validator::openapi`. Expands to nothing unless the `openapi`
+/// feature is enabled.
+fn generate_schema_impl(
+    name: &Ident,
+    fields: &FieldsNamed,
+    field_validators: &[(Ident, Vec<FieldValidator>)],
+) -> syn::Result<TokenStream2> {
+    #[cfg(feature = "openapi")]
+    {
+        let fields: Vec<(&Ident, &Type)> =
+            fields.named.iter().map(|f| Ok((named_ident(f)?, &f.ty))).collect::<syn::Result<Vec<_>>>()?;
+        Ok(validation::schema::generate_validable_schema_impl(name, &fields, field_validators))
+    }
+    #[cfg(not(feature = "openapi"))]
+    {
+        let _ = (name, fields, field_validators);
+        Ok(quote! {})
+    }
+}
+
 fn struct_validator_unit_ident(validable_name: &Ident, idx: usize) -> Ident {
     format_ident!("__{}StructValidator{}", validable_name, idx)
 }
@@ -4,7 +4,8 @@
 //!  1. add it to [`FieldValidator`];
 //!  2. recognize its keyword in [`parse_field_validators`];
 //!  3. declare its accepted field types via the per-validator `ensure_*` helper;
-//!  4. emit its validator-instance tokens in [`generate_validator_instance`].
+//!  4. emit its validator-instance tokens in [`generate_validator_instance`];
+//!  5. map it to JSON Schema keywords in `schema::field_constraints` (`openapi` feature).
 
 pub mod boolean;
 pub mod contains;
@@ -17,6 +18,8 @@ pub mod length;
 pub mod password;
 pub mod range;
 pub mod regex;
+#[cfg(feature = "openapi")]
+pub mod schema;
 pub mod string_field;
 pub mod struct_fields_match;
 pub mod url;
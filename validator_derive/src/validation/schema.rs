@@ -0,0 +1,102 @@
+//! Macro support for the `openapi` feature: emits an impl of
+//! `lightspeed_validator::openapi::ValidableSchema` listing the JSON Schema
+//! keywords implied by each field's validators.
+//!
+//! Validators without a JSON Schema counterpart (`isTrue`, `contains`,
+//! `custom`, ...) contribute nothing.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Expr, Ident, Type};
+
+use super::FieldValidator;
+use super::regex::RegexSpec;
+
+/// Emits `impl ValidableSchema for #name`.
+pub fn generate_validable_schema_impl(
+    name: &Ident,
+    fields: &[(&Ident, &Type)],
+    field_validators: &[(Ident, Vec<FieldValidator>)],
+) -> TokenStream2 {
+    let constraints = fields.iter().zip(field_validators.iter()).flat_map(|((field_ident, field_ty), (_, vs))| {
+        let field_name = field_ident.to_string();
+        vs.iter().flat_map(move |v| field_constraints(v, field_ty)).map(move |c| {
+            quote! { (#field_name, #c) }
+        })
+    });
+
+    quote! {
+        impl ::lightspeed_validator::openapi::ValidableSchema for #name {
+            fn schema_constraints() -> ::std::vec::Vec<(&'static str, ::lightspeed_validator::openapi::SchemaConstraint)> {
+                ::std::vec![ #( #constraints ),* ]
+            }
+        }
+    }
+}
+
+/// Tokens building every `SchemaConstraint` implied by `validator`.
+fn field_constraints(validator: &FieldValidator, field_ty: &Type) -> Vec<TokenStream2> {
+    let constraint = quote! { ::lightspeed_validator::openapi::SchemaConstraint };
+    let formats = quote! { ::lightspeed_validator::openapi::formats };
+    match validator {
+        FieldValidator::Length(args) => {
+            let mut out = Vec::new();
+            if let Some(equal) = &args.equal {
+                out.push(quote! { #constraint::MinLength((#equal) as ::core::primitive::usize) });
+                out.push(quote! { #constraint::MaxLength((#equal) as ::core::primitive::usize) });
+            }
+            if let Some(min) = &args.min {
+                out.push(quote! { #constraint::MinLength((#min) as ::core::primitive::usize) });
+            }
+            if let Some(max) = &args.max {
+                out.push(quote! { #constraint::MaxLength((#max) as ::core::primitive::usize) });
+            }
+            out
+        }
+        FieldValidator::Range(args) => {
+            let bound = |e: &Expr| {
+                quote! {{
+                    let bound: #field_ty = #e;
+                    ::lightspeed_validator::openapi::SchemaNumber::to_schema_number(&bound)
+                }}
+            };
+            let mut out = Vec::new();
+            if let Some(e) = &args.min {
+                let b = bound(e);
+                out.push(quote! { #constraint::Minimum(#b) });
+            }
+            if let Some(e) = &args.max {
+                let b = bound(e);
+                out.push(quote! { #constraint::Maximum(#b) });
+            }
+            if let Some(e) = &args.exclusive_min {
+                let b = bound(e);
+                out.push(quote! { #constraint::ExclusiveMinimum(#b) });
+            }
+            if let Some(e) = &args.exclusive_max {
+                let b = bound(e);
+                out.push(quote! { #constraint::ExclusiveMaximum(#b) });
+            }
+            out
+        }
+        FieldValidator::Regex(RegexSpec::Pattern(pattern)) => {
+            vec![quote! { #constraint::Pattern(::std::string::String::from(#pattern)) }]
+        }
+        FieldValidator::Regex(RegexSpec::Path(path)) => {
+            vec![quote! { #constraint::Pattern(::std::string::ToString::to_string((#path).as_str())) }]
+        }
+        FieldValidator::Email => vec![quote! { #constraint::Format(#formats::email()) }],
+        FieldValidator::Url => vec![quote! { #constraint::Format(#formats::uri()) }],
+        FieldValidator::Ip => vec![quote! { #constraint::Format(#formats::ip()) }],
+        FieldValidator::Ipv4 => vec![quote! { #constraint::Format(#formats::ipv4()) }],
+        FieldValidator::Ipv6 => vec![quote! { #constraint::Format(#formats::ipv6()) }],
+        FieldValidator::Password(_) => vec![quote! { #constraint::Format(#formats::password()) }],
+        #[cfg(feature = "credit_card")]
+        FieldValidator::CreditCard => vec![quote! { #constraint::Format(#formats::credit_card()) }],
+        FieldValidator::IsTrue
+        | FieldValidator::IsFalse
+        | FieldValidator::MustContain(_)
+        | FieldValidator::MustNotContain(_)
+        | FieldValidator::Custom(_) => vec![],
+    }
+}